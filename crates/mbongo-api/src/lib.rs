@@ -19,7 +19,8 @@
 
 /// REST API handlers, types, and router.
 pub mod rest;
-// pub mod ws;
+/// WebSocket subscriptions for new heads, pending transactions, and accounts.
+pub mod ws;
 // pub mod rpc;
 // pub mod sdk;
 
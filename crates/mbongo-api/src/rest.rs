@@ -1,9 +1,10 @@
 use async_trait::async_trait;
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     http::Method,
     response::IntoResponse,
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
@@ -15,16 +16,58 @@ use utoipa::OpenApi;
 /// Backend trait that REST handlers delegate to for chain data access.
 #[async_trait]
 pub trait ApiBackend: Clone + Send + Sync + 'static {
-    /// Returns the most recent blocks, up to `limit`.
-    async fn list_blocks(&self, limit: u32) -> Result<Vec<BlockSummary>, ApiError>;
-    /// Returns full details for the block identified by `hash`.
-    async fn get_block(&self, hash: String) -> Result<BlockDetail, ApiError>;
+    /// Returns up to `limit` blocks, newest first. With `before`, only
+    /// blocks below that height are listed; with `after`, the `limit` blocks
+    /// immediately above that height. At most one cursor is set.
+    async fn list_blocks(
+        &self,
+        before: Option<u64>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<BlockSummary>, ApiError>;
+    /// Returns full details for the block identified by `hash`, embedding up
+    /// to `tx_limit` of its transactions starting at index `tx_offset`.
+    async fn get_block(
+        &self,
+        hash: String,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError>;
+    /// Same as [`ApiBackend::get_block`], for the block at `height`.
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError>;
     /// Returns the transaction identified by `hash`.
     async fn get_transaction(&self, hash: String) -> Result<Transaction, ApiError>;
     /// Returns account state for the given `address`.
     async fn get_account(&self, address: String) -> Result<Account, ApiError>;
+    /// Returns the included transactions sent or received by `address`,
+    /// newest first, from blocks below height `before` (or from the tip).
+    /// At least `limit` are returned when that many exist; pages end on a
+    /// block boundary so the last `block_height` is the next `before` cursor.
+    async fn list_account_transactions(
+        &self,
+        address: String,
+        before: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<Transaction>, ApiError>;
+    /// Validates a signed transaction the same way as JSON-RPC submission
+    /// and adds it to the mempool. Returns the hex-encoded transaction hash.
+    async fn post_transaction(&self, tx: mbongo_core::Transaction) -> Result<String, ApiError>;
     /// Returns the current validator set.
     async fn list_validators(&self) -> Result<Vec<Validator>, ApiError>;
+    /// Returns up to `limit` pending transactions, skipping the first
+    /// `offset`, in mempool order. If `sender` is set, only that address's
+    /// transactions are listed and counted.
+    async fn list_mempool(
+        &self,
+        sender: Option<String>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<MempoolPage, ApiError>;
 }
 
 /// Errors produced by REST API operations.
@@ -52,7 +95,7 @@ pub struct BlockSummary {
     pub timestamp: u64,
 }
 
-/// Full block metadata returned by the detail endpoint.
+/// Full block returned by the detail endpoints.
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct BlockDetail {
     /// Hex-encoded block hash.
@@ -63,8 +106,27 @@ pub struct BlockDetail {
     pub timestamp: u64,
     /// Hex-encoded hash of the parent block.
     pub parent_hash: String,
+    /// Hex-encoded state root committed in the header.
+    pub state_root: String,
+    /// Hex-encoded Merkle root of the block's transactions.
+    pub transactions_root: String,
     /// Number of transactions included in this block.
     pub tx_count: u32,
+    /// The requested page of the block's transactions, in block order.
+    /// Empty in new-head notifications.
+    pub transactions: Vec<Transaction>,
+}
+
+/// Where a transaction is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Waiting in the mempool.
+    Pending,
+    /// Included in a committed block.
+    Included,
+    /// Not known to this node.
+    Unknown,
 }
 
 /// A single transaction with optional inclusion metadata.
@@ -82,6 +144,8 @@ pub struct Transaction {
     pub block_hash: Option<String>,
     /// Height of the block containing this transaction, if confirmed.
     pub block_height: Option<u64>,
+    /// Whether the transaction is pending or included.
+    pub status: TransactionStatus,
 }
 
 /// Account balance and nonce snapshot.
@@ -106,11 +170,202 @@ pub struct Validator {
     pub status: String,
 }
 
+/// A page of pending transactions returned by the mempool endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct MempoolPage {
+    /// Number of transactions in the mempool.
+    pub pending: u64,
+    /// Number of transactions matching the query, before pagination.
+    pub total: u64,
+    /// The requested page, in mempool order.
+    pub transactions: Vec<Transaction>,
+}
+
+/// Body of `POST /transactions`: a signed transaction, either in its JSON
+/// form or as hex-encoded SCALE bytes.
+///
+/// Neither form is wrapped in a tag; see [`SubmitTransactionRequest::parse`].
+#[derive(Debug)]
+pub enum SubmitTransactionRequest {
+    /// Hex-encoded SCALE bytes of the signed transaction.
+    Raw {
+        /// SCALE bytes, with or without a `0x` prefix.
+        raw: String,
+    },
+    /// The signed transaction in the JSON shape used by JSON-RPC.
+    Json(Box<mbongo_core::Transaction>),
+}
+
+/// Describes the body as `oneOf` the two untagged forms, which the derive
+/// would render as externally tagged objects.
+impl<'s> utoipa::ToSchema<'s> for SubmitTransactionRequest {
+    fn schema() -> (
+        &'s str,
+        utoipa::openapi::RefOr<utoipa::openapi::schema::Schema>,
+    ) {
+        use utoipa::openapi::schema::{OneOfBuilder, Schema};
+        use utoipa::openapi::Ref;
+
+        let one_of = OneOfBuilder::new()
+            .description(Some(
+                "A signed transaction, either in its JSON form or as hex-encoded SCALE bytes.",
+            ))
+            .item(Ref::from_schema_name("RawTransaction"))
+            .item(Ref::from_schema_name("SignedTransaction"))
+            .build();
+        ("SubmitTransactionRequest", Schema::OneOf(one_of).into())
+    }
+}
+
+/// Schemas of the two forms of [`SubmitTransactionRequest`] in the API
+/// docs. They mirror the JSON the request parser accepts and are never
+/// constructed.
+#[allow(dead_code)]
+mod submit_schema {
+    /// Hex-encoded SCALE bytes of a signed transaction.
+    #[derive(utoipa::ToSchema)]
+    pub struct RawTransaction {
+        /// SCALE bytes, with or without a `0x` prefix.
+        pub raw: String,
+    }
+
+    /// A signed transaction in the JSON shape used by JSON-RPC.
+    #[derive(utoipa::ToSchema)]
+    pub struct SignedTransaction {
+        /// Transaction type.
+        pub tx_type: TransactionType,
+        /// `0x`-prefixed hex address of the sender (its ed25519 public key).
+        pub sender: String,
+        /// `0x`-prefixed hex address of the receiver.
+        pub receiver: String,
+        /// Amount, as a JSON number that may exceed 64 bits.
+        pub amount: u128,
+        /// Sender nonce.
+        pub nonce: u64,
+        /// `0x`-prefixed hex ed25519 signature over the signing payload;
+        /// all zeros for a multisig sender.
+        pub signature: String,
+        /// Member signatures when `sender` is a multisig account.
+        pub multisig: Option<MultisigWitness>,
+        /// `0x`-prefixed hex contract code for `Deploy` or call payload for
+        /// `Call`; omitted for other types.
+        pub data: Option<String>,
+    }
+
+    /// Supported transaction types.
+    #[derive(utoipa::ToSchema)]
+    pub enum TransactionType {
+        Transfer,
+        ComputeTask,
+        Stake,
+        Deploy,
+        Call,
+    }
+
+    /// The multisig configuration and member signatures authorizing a
+    /// transaction.
+    #[derive(utoipa::ToSchema)]
+    pub struct MultisigWitness {
+        /// Configuration whose address is the transaction sender.
+        pub config: Multisig,
+        /// Member signatures, sorted by signer.
+        pub signatures: Vec<MultisigSignature>,
+    }
+
+    /// An M-of-N multisig configuration.
+    #[derive(utoipa::ToSchema)]
+    pub struct Multisig {
+        /// Number of member signatures required.
+        pub threshold: u8,
+        /// `0x`-prefixed hex member public keys, sorted ascending.
+        pub members: Vec<String>,
+    }
+
+    /// One member's signature.
+    #[derive(utoipa::ToSchema)]
+    pub struct MultisigSignature {
+        /// `0x`-prefixed hex address of the member that signed.
+        pub signer: String,
+        /// `0x`-prefixed hex ed25519 signature over the signing payload.
+        pub signature: String,
+    }
+}
+
+impl SubmitTransactionRequest {
+    /// Parses a request body, trying the `{"raw": ...}` form first.
+    ///
+    /// Not an untagged serde enum: those buffer numbers in a form that
+    /// cannot hold the `u128` amount.
+    fn parse(body: &[u8]) -> Result<Self, String> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Raw {
+            raw: String,
+        }
+        if let Ok(Raw { raw }) = serde_json::from_slice(body) {
+            return Ok(Self::Raw { raw });
+        }
+        serde_json::from_slice(body)
+            .map(|tx| Self::Json(Box::new(tx)))
+            .map_err(|e| format!("invalid body: {e}"))
+    }
+
+    /// Decodes the transaction carried by the request.
+    fn into_transaction(self) -> Result<mbongo_core::Transaction, String> {
+        match self {
+            Self::Json(tx) => Ok(*tx),
+            Self::Raw { raw } => {
+                mbongo_core::Transaction::decode_hex(&raw).map_err(|e| format!("invalid raw: {e}"))
+            }
+        }
+    }
+}
+
+/// Response of `POST /transactions`.
+#[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SubmitTransactionResponse {
+    /// Hex-encoded hash of the accepted transaction.
+    pub hash: String,
+}
+
 /// Query parameters for the block-list endpoint.
 #[derive(Debug, Deserialize, utoipa::IntoParams)]
 pub struct BlocksQuery {
     /// Maximum number of blocks to return.
     pub limit: Option<u32>,
+    /// Only list blocks below this height.
+    pub before: Option<u64>,
+    /// Only list blocks above this height.
+    pub after: Option<u64>,
+}
+
+/// Query parameters for the block-detail endpoints.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct BlockQuery {
+    /// Index of the first transaction to embed.
+    pub tx_offset: Option<u32>,
+    /// Maximum number of transactions to embed.
+    pub tx_limit: Option<u32>,
+}
+
+/// Query parameters for the account history endpoint.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct AccountTransactionsQuery {
+    /// Only list transactions from blocks below this height.
+    pub before: Option<u64>,
+    /// Minimum number of transactions to return.
+    pub limit: Option<u32>,
+}
+
+/// Query parameters for the mempool endpoint.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+pub struct MempoolQuery {
+    /// Only list transactions sent from this address.
+    pub sender: Option<String>,
+    /// Number of matching transactions to skip.
+    pub offset: Option<u32>,
+    /// Maximum number of transactions to return.
+    pub limit: Option<u32>,
 }
 
 #[derive(Clone)]
@@ -123,15 +378,23 @@ struct AppState<B: ApiBackend> {
     path = "/blocks",
     params(BlocksQuery),
     responses(
-        (status = 200, description = "Recent blocks", body = [BlockSummary])
+        (status = 200, description = "Blocks, newest first", body = [BlockSummary]),
+        (status = 400, description = "Both cursors given")
     )
 )]
 async fn get_blocks<B: ApiBackend>(
     State(state): State<AppState<B>>,
     Query(q): Query<BlocksQuery>,
 ) -> impl IntoResponse {
+    if q.before.is_some() && q.after.is_some() {
+        return (
+            axum::http::StatusCode::BAD_REQUEST,
+            Json(json!({"error": "before and after are mutually exclusive"})),
+        )
+            .into_response();
+    }
     let limit = q.limit.unwrap_or(10).min(1000);
-    match state.backend.list_blocks(limit).await {
+    match state.backend.list_blocks(q.before, q.after, limit).await {
         Ok(list) => (axum::http::StatusCode::OK, Json(list)).into_response(),
         Err(ApiError::Invalid(msg)) => (
             axum::http::StatusCode::BAD_REQUEST,
@@ -154,7 +417,7 @@ async fn get_blocks<B: ApiBackend>(
 #[utoipa::path(
     get,
     path = "/blocks/{hash}",
-    params(("hash" = String, Path, description = "Block hash")),
+    params(("hash" = String, Path, description = "Block hash"), BlockQuery),
     responses(
         (status = 200, description = "Block details", body = BlockDetail),
         (status = 404, description = "Block not found")
@@ -163,8 +426,40 @@ async fn get_blocks<B: ApiBackend>(
 async fn get_block<B: ApiBackend>(
     State(state): State<AppState<B>>,
     Path(hash): Path<String>,
+    Query(q): Query<BlockQuery>,
 ) -> impl IntoResponse {
-    match state.backend.get_block(hash).await {
+    let (tx_offset, tx_limit) = tx_page(&q);
+    block_response(state.backend.get_block(hash, tx_offset, tx_limit).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/blocks/by-height/{height}",
+    params(("height" = u64, Path, description = "Block height"), BlockQuery),
+    responses(
+        (status = 200, description = "Block details", body = BlockDetail),
+        (status = 404, description = "Block not found")
+    )
+)]
+async fn get_block_by_height<B: ApiBackend>(
+    State(state): State<AppState<B>>,
+    Path(height): Path<u64>,
+    Query(q): Query<BlockQuery>,
+) -> impl IntoResponse {
+    let (tx_offset, tx_limit) = tx_page(&q);
+    block_response(state.backend.get_block_by_height(height, tx_offset, tx_limit).await)
+}
+
+/// Embedded transaction page requested by `q`, with defaults applied.
+fn tx_page(q: &BlockQuery) -> (u32, u32) {
+    (
+        q.tx_offset.unwrap_or(0),
+        q.tx_limit.unwrap_or(100).min(1000),
+    )
+}
+
+fn block_response(result: Result<BlockDetail, ApiError>) -> axum::response::Response {
+    match result {
         Ok(block) => (axum::http::StatusCode::OK, Json(block)).into_response(),
         Err(ApiError::NotFound) => (
             axum::http::StatusCode::NOT_FOUND,
@@ -217,6 +512,55 @@ async fn get_transaction<B: ApiBackend>(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/transactions",
+    request_body = SubmitTransactionRequest,
+    responses(
+        (status = 202, description = "Transaction accepted into the mempool", body = SubmitTransactionResponse),
+        (status = 400, description = "Malformed or rejected transaction")
+    )
+)]
+async fn post_transaction<B: ApiBackend>(
+    State(state): State<AppState<B>>,
+    body: Bytes,
+) -> impl IntoResponse {
+    let tx =
+        SubmitTransactionRequest::parse(&body).and_then(SubmitTransactionRequest::into_transaction);
+    let tx = match tx {
+        Ok(tx) => tx,
+        Err(msg) => {
+            return (
+                axum::http::StatusCode::BAD_REQUEST,
+                Json(json!({"error": msg})),
+            )
+                .into_response()
+        }
+    };
+    match state.backend.post_transaction(tx).await {
+        Ok(hash) => (
+            axum::http::StatusCode::ACCEPTED,
+            Json(SubmitTransactionResponse { hash }),
+        )
+            .into_response(),
+        Err(ApiError::Invalid(msg)) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+        Err(ApiError::NotFound) => (
+            axum::http::StatusCode::NOT_FOUND,
+            Json(json!({"error": "not found"})),
+        )
+            .into_response(),
+        Err(ApiError::Internal(msg)) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/accounts/{address}",
@@ -250,6 +594,41 @@ async fn get_account<B: ApiBackend>(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/accounts/{address}/transactions",
+    params(("address" = String, Path, description = "Account address"), AccountTransactionsQuery),
+    responses(
+        (status = 200, description = "Included transactions, newest first", body = [Transaction]),
+        (status = 400, description = "Invalid address")
+    )
+)]
+async fn get_account_transactions<B: ApiBackend>(
+    State(state): State<AppState<B>>,
+    Path(address): Path<String>,
+    Query(q): Query<AccountTransactionsQuery>,
+) -> impl IntoResponse {
+    let limit = q.limit.unwrap_or(100).min(1000);
+    match state.backend.list_account_transactions(address, q.before, limit).await {
+        Ok(list) => (axum::http::StatusCode::OK, Json(list)).into_response(),
+        Err(ApiError::Invalid(msg)) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+        Err(ApiError::NotFound) => (
+            axum::http::StatusCode::NOT_FOUND,
+            Json(json!({"error": "not found"})),
+        )
+            .into_response(),
+        Err(ApiError::Internal(msg)) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+    }
+}
+
 #[utoipa::path(
     get,
     path = "/validators",
@@ -278,10 +657,71 @@ async fn get_validators<B: ApiBackend>(State(state): State<AppState<B>>) -> impl
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/mempool",
+    params(MempoolQuery),
+    responses(
+        (status = 200, description = "Pending transactions", body = MempoolPage),
+        (status = 400, description = "Invalid sender address")
+    )
+)]
+async fn get_mempool<B: ApiBackend>(
+    State(state): State<AppState<B>>,
+    Query(q): Query<MempoolQuery>,
+) -> impl IntoResponse {
+    let limit = q.limit.unwrap_or(100).min(1000);
+    let offset = q.offset.unwrap_or(0);
+    match state.backend.list_mempool(q.sender, offset, limit).await {
+        Ok(page) => (axum::http::StatusCode::OK, Json(page)).into_response(),
+        Err(ApiError::Invalid(msg)) => (
+            axum::http::StatusCode::BAD_REQUEST,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+        Err(ApiError::NotFound) => (
+            axum::http::StatusCode::NOT_FOUND,
+            Json(json!({"error": "not found"})),
+        )
+            .into_response(),
+        Err(ApiError::Internal(msg)) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"error": msg})),
+        )
+            .into_response(),
+    }
+}
+
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_blocks, get_block, get_transaction, get_account, get_validators),
-    components(schemas(BlockSummary, BlockDetail, Transaction, Account, Validator)),
+    paths(
+        get_blocks,
+        get_block,
+        get_block_by_height,
+        get_transaction,
+        post_transaction,
+        get_account,
+        get_account_transactions,
+        get_validators,
+        get_mempool
+    ),
+    components(schemas(
+        BlockSummary,
+        BlockDetail,
+        Transaction,
+        TransactionStatus,
+        SubmitTransactionRequest,
+        submit_schema::RawTransaction,
+        submit_schema::SignedTransaction,
+        submit_schema::TransactionType,
+        submit_schema::MultisigWitness,
+        submit_schema::Multisig,
+        submit_schema::MultisigSignature,
+        SubmitTransactionResponse,
+        Account,
+        Validator,
+        MempoolPage
+    )),
     tags((name = "mbongo-api", description = "Mbongo REST API"))
 )]
 struct ApiDoc;
@@ -290,7 +730,7 @@ struct ApiDoc;
 pub fn router<B: ApiBackend>(backend: B) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
-        .allow_methods([Method::GET])
+        .allow_methods([Method::GET, Method::POST])
         .allow_headers(Any);
 
     let state = AppState { backend };
@@ -301,9 +741,16 @@ pub fn router<B: ApiBackend>(backend: B) -> Router {
     Router::new()
         .route("/blocks", get(get_blocks::<B>))
         .route("/blocks/:hash", get(get_block::<B>))
+        .route("/blocks/by-height/:height", get(get_block_by_height::<B>))
+        .route("/transactions", post(post_transaction::<B>))
         .route("/transactions/:hash", get(get_transaction::<B>))
         .route("/accounts/:address", get(get_account::<B>))
+        .route(
+            "/accounts/:address/transactions",
+            get(get_account_transactions::<B>),
+        )
         .route("/validators", get(get_validators::<B>))
+        .route("/mempool", get(get_mempool::<B>))
         .merge(swagger)
         .with_state(state)
         .layer(cors)
@@ -318,7 +765,18 @@ pub async fn serve_on_addr<B: ApiBackend>(
     addr: std::net::SocketAddr,
     backend: B,
 ) -> anyhow::Result<()> {
-    let app = router(backend);
+    serve_router_on_addr(addr, router(backend)).await
+}
+
+/// Binds a TCP listener on `addr` and serves `app` until shutdown.
+///
+/// Use this to serve the REST router merged with other routers, such as
+/// [`crate::ws::router`].
+///
+/// # Errors
+///
+/// Returns an error if the TCP listener cannot bind or if the server fails.
+pub async fn serve_router_on_addr(addr: std::net::SocketAddr, app: Router) -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await.map_err(anyhow::Error::from)
 }
@@ -0,0 +1,440 @@
+//! WebSocket subscriptions for chain events.
+//!
+//! Clients connect to `/ws` and speak JSON-RPC 2.0. Each connection may hold
+//! several subscriptions:
+//!
+//! | Method                           | Params      | Notifies with   |
+//! |----------------------------------|-------------|-----------------|
+//! | `subscribe_new_heads`            | none        | [`BlockDetail`] |
+//! | `subscribe_pending_transactions` | none        | [`Transaction`] |
+//! | `subscribe_account`              | `[address]` | [`Account`]     |
+//! | `subscribe_logs`                 | `[filter]`  | [`LogEntry`]    |
+//! | `unsubscribe`                    | `[id]`      | —               |
+//!
+//! The `subscribe_logs` filter is a [`LogFilter`] whose `address` and
+//! `topics` select the logs of newly committed blocks; its block range is
+//! ignored, and omitting it subscribes to every log.
+//!
+//! A subscribe call returns a numeric subscription id. Matching events are
+//! pushed as `subscription` notifications:
+//!
+//! ```json
+//! {"jsonrpc":"2.0","method":"subscription","params":{"subscription":1,"result":{...}}}
+//! ```
+//!
+//! Events are published by the node on an [`EventBus`]. The bus keeps one
+//! bounded broadcast channel per [`EventKind`], and a connection only listens
+//! on the channels of kinds it holds subscriptions for, so a burst of logs
+//! never pushes new heads out of a head subscriber's buffer. Events of one
+//! kind arrive in the order they were published; events of different kinds
+//! may arrive interleaved differently. A connection that does not keep up
+//! with a kind's event rate skips its oldest events and receives a
+//! `subscription_lagged` notification carrying the number of events it
+//! missed and the affected subscription ids, so a slow client never stalls
+//! the node or other clients:
+//!
+//! ```json
+//! {"jsonrpc":"2.0","method":"subscription_lagged","params":{"skipped":3,"subscriptions":[1]}}
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::StatusCode,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use mbongo_core::{Address, LogEntry, LogFilter};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, OwnedSemaphorePermit, Semaphore};
+
+use crate::rest::{Account, BlockDetail, Transaction};
+
+/// A chain event delivered to WebSocket subscribers.
+#[derive(Debug, Clone)]
+pub enum ChainEvent {
+    /// A block was committed and is the new chain tip.
+    NewHead(BlockDetail),
+    /// A transaction was accepted into the mempool.
+    PendingTransaction(Transaction),
+    /// An account's balance or nonce changed in a committed block.
+    AccountChanged(Account),
+    /// A transaction in a committed block emitted a log.
+    Log(LogEntry),
+}
+
+impl ChainEvent {
+    /// Returns the kind of this event.
+    #[must_use]
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::NewHead(_) => EventKind::NewHead,
+            Self::PendingTransaction(_) => EventKind::PendingTransaction,
+            Self::AccountChanged(_) => EventKind::AccountChanged,
+            Self::Log(_) => EventKind::Log,
+        }
+    }
+}
+
+/// The kinds of [`ChainEvent`], each published on its own channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventKind {
+    /// [`ChainEvent::NewHead`].
+    NewHead,
+    /// [`ChainEvent::PendingTransaction`].
+    PendingTransaction,
+    /// [`ChainEvent::AccountChanged`].
+    AccountChanged,
+    /// [`ChainEvent::Log`].
+    Log,
+}
+
+impl EventKind {
+    /// Every kind, in declaration order.
+    pub const ALL: [Self; 4] = [
+        Self::NewHead,
+        Self::PendingTransaction,
+        Self::AccountChanged,
+        Self::Log,
+    ];
+}
+
+/// Fan-out channels the node publishes [`ChainEvent`]s on, one per
+/// [`EventKind`].
+///
+/// Cloning is cheap; all clones publish to the same subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    senders: [broadcast::Sender<ChainEvent>; 4],
+}
+
+impl EventBus {
+    /// Creates a bus that buffers up to `capacity` events of each kind per
+    /// subscriber before the slowest subscribers start skipping events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            senders: EventKind::ALL.map(|_| broadcast::channel(capacity).0),
+        }
+    }
+
+    /// Publishes `event` to every subscriber of its kind.
+    ///
+    /// Never blocks; publishing with no subscribers is a no-op.
+    pub fn publish(&self, event: ChainEvent) {
+        // An error only means nobody is listening right now.
+        let _ = self.senders[event.kind() as usize].send(event);
+    }
+
+    /// Returns a receiver for events of `kind` published after this call.
+    #[must_use]
+    pub fn subscribe(&self, kind: EventKind) -> broadcast::Receiver<ChainEvent> {
+        self.senders[kind as usize].subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(WsConfig::default().event_buffer)
+    }
+}
+
+/// Limits applied to the WebSocket endpoint.
+#[derive(Debug, Clone)]
+pub struct WsConfig {
+    /// Maximum concurrently open connections. Further upgrades get `503`.
+    pub max_connections: usize,
+    /// Maximum live subscriptions per connection.
+    pub max_subscriptions_per_connection: usize,
+    /// Maximum size in bytes of a single client message.
+    pub max_message_size: usize,
+    /// Events of each kind buffered per connection by [`EventBus::default`].
+    pub event_buffer: usize,
+}
+
+impl Default for WsConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 100,
+            max_subscriptions_per_connection: 16,
+            max_message_size: 16 * 1024,
+            event_buffer: 1024,
+        }
+    }
+}
+
+/// What a single subscription listens for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Subscription {
+    NewHeads,
+    PendingTransactions,
+    Account(Address),
+    Logs(LogFilter),
+}
+
+impl Subscription {
+    /// Returns the kind of event this subscription listens for.
+    fn kind(&self) -> EventKind {
+        match self {
+            Self::NewHeads => EventKind::NewHead,
+            Self::PendingTransactions => EventKind::PendingTransaction,
+            Self::Account(_) => EventKind::AccountChanged,
+            Self::Logs(_) => EventKind::Log,
+        }
+    }
+
+    /// Returns the notification payload for `event`, if this subscription
+    /// is interested in it.
+    fn filter(&self, event: &ChainEvent) -> Option<Value> {
+        match (self, event) {
+            (Self::NewHeads, ChainEvent::NewHead(head)) => serde_json::to_value(head).ok(),
+            (Self::PendingTransactions, ChainEvent::PendingTransaction(tx)) => {
+                serde_json::to_value(tx).ok()
+            }
+            (Self::Account(address), ChainEvent::AccountChanged(account))
+                if account.address.parse::<Address>().ok() == Some(*address) =>
+            {
+                serde_json::to_value(account).ok()
+            }
+            (Self::Logs(filter), ChainEvent::Log(entry)) if filter.matches(&entry.log) => {
+                serde_json::to_value(entry).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct WsState {
+    bus: EventBus,
+    config: Arc<WsConfig>,
+    connections: Arc<Semaphore>,
+}
+
+/// Builds an Axum [`Router`] serving subscriptions on `/ws`.
+pub fn router(bus: EventBus, config: WsConfig) -> Router {
+    let state = WsState {
+        bus,
+        connections: Arc::new(Semaphore::new(config.max_connections)),
+        config: Arc::new(config),
+    };
+    Router::new().route("/ws", get(handle_upgrade)).with_state(state)
+}
+
+async fn handle_upgrade(State(state): State<WsState>, ws: WebSocketUpgrade) -> impl IntoResponse {
+    let Ok(permit) = Arc::clone(&state.connections).try_acquire_owned() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "too many WebSocket connections",
+        )
+            .into_response();
+    };
+    let bus = state.bus.clone();
+    let config = Arc::clone(&state.config);
+    ws.max_message_size(config.max_message_size)
+        .on_upgrade(move |socket| run_connection(socket, bus, config, permit))
+}
+
+/// Per-connection subscription table.
+struct Connection {
+    bus: EventBus,
+    subscriptions: BTreeMap<u64, Subscription>,
+    /// Receivers of the kinds some subscription listens for, indexed by
+    /// [`EventKind`]. Kinds nobody listens for are not received at all.
+    receivers: [Option<broadcast::Receiver<ChainEvent>>; 4],
+    next_id: u64,
+    max_subscriptions: usize,
+}
+
+impl Connection {
+    /// Waits for the next event on any open receiver. Never completes while
+    /// the connection holds no subscriptions.
+    async fn recv(&mut self) -> (EventKind, Result<ChainEvent, broadcast::error::RecvError>) {
+        async fn recv_from(
+            receiver: &mut Option<broadcast::Receiver<ChainEvent>>,
+        ) -> Result<ChainEvent, broadcast::error::RecvError> {
+            match receiver {
+                Some(receiver) => receiver.recv().await,
+                None => std::future::pending().await,
+            }
+        }
+
+        let [heads, pending, accounts, logs] = &mut self.receivers;
+        tokio::select! {
+            event = recv_from(heads) => (EventKind::NewHead, event),
+            event = recv_from(pending) => (EventKind::PendingTransaction, event),
+            event = recv_from(accounts) => (EventKind::AccountChanged, event),
+            event = recv_from(logs) => (EventKind::Log, event),
+        }
+    }
+
+    /// Returns the notifications for `event` or for events of `kind` the
+    /// connection skipped.
+    fn notifications(
+        &self,
+        kind: EventKind,
+        event: Result<ChainEvent, broadcast::error::RecvError>,
+    ) -> Vec<Value> {
+        match event {
+            Ok(event) => self
+                .subscriptions
+                .iter()
+                .filter_map(|(id, sub)| sub.filter(&event).map(|result| notification(*id, &result)))
+                .collect(),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                log::warn!("WebSocket subscriber lagged; skipped {skipped} {kind:?} event(s)");
+                let ids: Vec<u64> = self
+                    .subscriptions
+                    .iter()
+                    .filter(|(_, sub)| sub.kind() == kind)
+                    .map(|(id, _)| *id)
+                    .collect();
+                vec![json!({
+                    "jsonrpc": "2.0",
+                    "method": "subscription_lagged",
+                    "params": { "skipped": skipped, "subscriptions": ids },
+                })]
+            }
+            Err(broadcast::error::RecvError::Closed) => Vec::new(),
+        }
+    }
+
+    /// Handles one client request and returns the JSON-RPC response.
+    fn handle_request(&mut self, text: &str) -> Value {
+        let Ok(request) = serde_json::from_str::<Value>(text) else {
+            return error_response(&Value::Null, -32700, "Parse error");
+        };
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let Some(method) = request.get("method").and_then(Value::as_str) else {
+            return error_response(&id, -32600, "Invalid Request");
+        };
+        let params = request.get("params");
+        let first_param =
+            params.and_then(|p| p.get(0).or_else(|| p.get("address")).or_else(|| p.get("id")));
+
+        let subscription = match method {
+            "subscribe_new_heads" => Subscription::NewHeads,
+            "subscribe_pending_transactions" => Subscription::PendingTransactions,
+            "subscribe_account" => {
+                match first_param.and_then(Value::as_str).map(str::parse::<Address>) {
+                    Some(Ok(address)) => Subscription::Account(address),
+                    Some(Err(e)) => {
+                        return error_response(&id, -32602, &format!("invalid address: {e}"))
+                    }
+                    None => return error_response(&id, -32602, "missing address"),
+                }
+            }
+            "subscribe_logs" => {
+                let filter = match params {
+                    Some(Value::Array(values)) => values.first().cloned(),
+                    other => other.cloned(),
+                };
+                match filter.filter(|f| !f.is_null()).map(serde_json::from_value) {
+                    None => Subscription::Logs(LogFilter::default()),
+                    Some(Ok(filter)) => Subscription::Logs(filter),
+                    Some(Err(e)) => {
+                        return error_response(&id, -32602, &format!("invalid log filter: {e}"))
+                    }
+                }
+            }
+            "unsubscribe" => {
+                let Some(sub_id) = first_param.and_then(Value::as_u64) else {
+                    return error_response(&id, -32602, "missing subscription id");
+                };
+                let Some(removed) = self.subscriptions.remove(&sub_id) else {
+                    return success_response(&id, &json!(false));
+                };
+                let kind = removed.kind();
+                if !self.subscriptions.values().any(|sub| sub.kind() == kind) {
+                    self.receivers[kind as usize] = None;
+                }
+                return success_response(&id, &json!(true));
+            }
+            _ => return error_response(&id, -32601, "Method not found"),
+        };
+
+        if self.subscriptions.len() >= self.max_subscriptions {
+            return error_response(
+                &id,
+                -32005,
+                &format!(
+                    "subscription limit of {} per connection reached",
+                    self.max_subscriptions
+                ),
+            );
+        }
+        let kind = subscription.kind();
+        self.receivers[kind as usize].get_or_insert_with(|| self.bus.subscribe(kind));
+        self.next_id += 1;
+        self.subscriptions.insert(self.next_id, subscription);
+        success_response(&id, &json!(self.next_id))
+    }
+}
+
+/// Drives one WebSocket connection until the client disconnects.
+///
+/// `_permit` is held for the connection's lifetime to enforce
+/// [`WsConfig::max_connections`].
+async fn run_connection(
+    mut socket: WebSocket,
+    bus: EventBus,
+    config: Arc<WsConfig>,
+    _permit: OwnedSemaphorePermit,
+) {
+    let mut conn = Connection {
+        bus,
+        subscriptions: BTreeMap::new(),
+        receivers: [None, None, None, None],
+        next_id: 0,
+        max_subscriptions: config.max_subscriptions_per_connection,
+    };
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => vec![conn.handle_request(&text)],
+                Some(Ok(Message::Binary(_))) => {
+                    vec![error_response(&Value::Null, -32600, "binary messages are not supported")]
+                }
+                Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+            },
+            (kind, event) = conn.recv() => match event {
+                Err(broadcast::error::RecvError::Closed) => return,
+                event => conn.notifications(kind, event),
+            },
+        };
+
+        for message in outgoing {
+            if socket.send(Message::Text(message.to_string())).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn notification(subscription: u64, result: &Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "subscription",
+        "params": { "subscription": subscription, "result": result },
+    })
+}
+
+fn success_response(id: &Value, result: &Value) -> Value {
+    json!({ "jsonrpc": "2.0", "result": result, "id": id })
+}
+
+fn error_response(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
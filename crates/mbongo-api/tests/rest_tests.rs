@@ -1,7 +1,8 @@
 use async_trait::async_trait;
 use axum::http::StatusCode;
 use mbongo_api::rest::{
-    self, Account, ApiBackend, ApiError, BlockDetail, BlockSummary, Transaction, Validator,
+    self, Account, ApiBackend, ApiError, BlockDetail, BlockSummary, MempoolPage, Transaction,
+    TransactionStatus, Validator,
 };
 use serde_json::json;
 use tower::ServiceExt;
@@ -11,26 +12,53 @@ struct MockBackend;
 
 #[async_trait]
 impl ApiBackend for MockBackend {
-    async fn list_blocks(&self, limit: u32) -> Result<Vec<BlockSummary>, ApiError> {
-        Ok((0..limit)
-            .map(|i| BlockSummary {
-                hash: format!("h{i}"),
-                height: i as u64,
-                timestamp: 1000 + i as u64,
+    async fn list_blocks(
+        &self,
+        before: Option<u64>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<BlockSummary>, ApiError> {
+        // Chain of 50 blocks; heights 0..=49.
+        let mut heights: Vec<u64> = match (before, after) {
+            (_, Some(a)) => (a + 1..50).take(limit as usize).collect(),
+            (b, None) => (0..b.unwrap_or(50).min(50)).rev().take(limit as usize).collect(),
+        };
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(heights
+            .into_iter()
+            .map(|h| BlockSummary {
+                hash: format!("h{h}"),
+                height: h,
+                timestamp: 1000 + h,
             })
             .collect())
     }
-    async fn get_block(&self, hash: String) -> Result<BlockDetail, ApiError> {
+    async fn get_block(
+        &self,
+        hash: String,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError> {
         if hash == "missing" {
             return Err(ApiError::NotFound);
         }
-        Ok(BlockDetail {
-            hash: hash.clone(),
-            height: 1,
-            timestamp: 1234,
-            parent_hash: "p".into(),
-            tx_count: 2,
-        })
+        Ok(block_detail(hash, 1, tx_offset, tx_limit))
+    }
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError> {
+        if height > 49 {
+            return Err(ApiError::NotFound);
+        }
+        Ok(block_detail(
+            format!("h{height}"),
+            height,
+            tx_offset,
+            tx_limit,
+        ))
     }
     async fn get_transaction(&self, hash: String) -> Result<Transaction, ApiError> {
         if hash == "missing" {
@@ -43,6 +71,7 @@ impl ApiBackend for MockBackend {
             value: "0x1".into(),
             block_hash: Some("bh".into()),
             block_height: Some(1),
+            status: TransactionStatus::Included,
         })
     }
     async fn get_account(&self, address: String) -> Result<Account, ApiError> {
@@ -55,6 +84,39 @@ impl ApiBackend for MockBackend {
             nonce: 7,
         })
     }
+    async fn list_account_transactions(
+        &self,
+        address: String,
+        before: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<Transaction>, ApiError> {
+        if address == "bad" {
+            return Err(ApiError::Invalid("bad address".into()));
+        }
+        // One transaction from `address` in each of blocks 0..=49.
+        Ok((0..before.unwrap_or(50).min(50))
+            .rev()
+            .take(limit as usize)
+            .map(|h| Transaction {
+                hash: format!("t{h}"),
+                from: address.clone(),
+                to: Some("b".into()),
+                value: "1".into(),
+                block_hash: Some(format!("h{h}")),
+                block_height: Some(h),
+                status: TransactionStatus::Included,
+            })
+            .collect())
+    }
+    async fn post_transaction(&self, tx: mbongo_core::Transaction) -> Result<String, ApiError> {
+        if tx.nonce != 0 {
+            return Err(ApiError::Invalid(format!(
+                "invalid nonce: expected 0, got {}",
+                tx.nonce
+            )));
+        }
+        Ok(format!("0x{:02x}", tx.amount))
+    }
     async fn list_validators(&self) -> Result<Vec<Validator>, ApiError> {
         Ok(vec![Validator {
             address: "v1".into(),
@@ -62,6 +124,60 @@ impl ApiBackend for MockBackend {
             status: "active".into(),
         }])
     }
+    async fn list_mempool(
+        &self,
+        sender: Option<String>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<MempoolPage, ApiError> {
+        if sender.as_deref() == Some("bad") {
+            return Err(ApiError::Invalid("bad address".into()));
+        }
+        let transactions = (offset..25)
+            .take(limit as usize)
+            .map(|i| Transaction {
+                hash: format!("t{i}"),
+                from: sender.clone().unwrap_or_else(|| "a".into()),
+                to: None,
+                value: "1".into(),
+                block_hash: None,
+                block_height: None,
+                status: TransactionStatus::Pending,
+            })
+            .collect();
+        Ok(MempoolPage {
+            pending: 25,
+            total: 25,
+            transactions,
+        })
+    }
+}
+
+/// Block with five transactions, of which `tx_offset..tx_offset + tx_limit`
+/// are embedded.
+fn block_detail(hash: String, height: u64, tx_offset: u32, tx_limit: u32) -> BlockDetail {
+    let transactions = (tx_offset..5)
+        .take(tx_limit as usize)
+        .map(|i| Transaction {
+            hash: format!("t{i}"),
+            from: "a".into(),
+            to: Some("b".into()),
+            value: "1".into(),
+            block_hash: Some(hash.clone()),
+            block_height: Some(height),
+            status: TransactionStatus::Included,
+        })
+        .collect();
+    BlockDetail {
+        hash,
+        height,
+        timestamp: 1234,
+        parent_hash: "p".into(),
+        state_root: "s".into(),
+        transactions_root: "r".into(),
+        tx_count: 5,
+        transactions,
+    }
 }
 
 #[tokio::test]
@@ -154,3 +270,248 @@ async fn test_validators_list() {
     assert!(v.is_array());
     assert_eq!(v.as_array().unwrap().len(), 1);
 }
+
+#[tokio::test]
+async fn test_mempool_pagination_and_filter() {
+    let get = |uri: &'static str| {
+        rest::router(MockBackend).oneshot(
+            axum::http::Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+    };
+
+    let response = get("/mempool").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["pending"], json!(25));
+    assert_eq!(v["transactions"].as_array().unwrap().len(), 25);
+    assert_eq!(v["transactions"][0]["status"], json!("pending"));
+
+    let response = get("/mempool?sender=s&offset=20&limit=3").await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let hashes: Vec<&str> = v["transactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["hash"].as_str().unwrap())
+        .collect();
+    assert_eq!(hashes, ["t20", "t21", "t22"]);
+    assert_eq!(v["transactions"][0]["from"], json!("s"));
+
+    let response = get("/mempool?sender=bad").await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_block_cursors_and_height_lookup() {
+    let get = |uri: &'static str| {
+        rest::router(MockBackend).oneshot(
+            axum::http::Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+    };
+    let heights = |v: &serde_json::Value| -> Vec<u64> {
+        v.as_array().unwrap().iter().map(|b| b["height"].as_u64().unwrap()).collect()
+    };
+
+    let response = get("/blocks?before=10&limit=3").await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(heights(&v), [9, 8, 7]);
+
+    let response = get("/blocks?after=46&limit=5").await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(heights(&v), [49, 48, 47]);
+
+    let response = get("/blocks?before=10&after=2").await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = get("/blocks/by-height/7?tx_offset=1&tx_limit=2").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["hash"], json!("h7"));
+    assert_eq!(v["state_root"], json!("s"));
+    assert_eq!(v["tx_count"], json!(5));
+    let txs = v["transactions"].as_array().unwrap();
+    assert_eq!(txs.len(), 2);
+    assert_eq!(txs[0]["hash"], json!("t1"));
+    assert_eq!(txs[0]["block_height"], json!(7));
+
+    let response = get("/blocks/0xabc").await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["transactions"].as_array().unwrap().len(), 5);
+
+    let response = get("/blocks/by-height/50").await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = get("/blocks/by-height/tip").await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_account_transactions_cursor() {
+    let get = |uri: &'static str| {
+        rest::router(MockBackend).oneshot(
+            axum::http::Request::builder()
+                .uri(uri)
+                .method("GET")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+    };
+    let heights = |v: &serde_json::Value| -> Vec<u64> {
+        v.as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["block_height"].as_u64().unwrap())
+            .collect()
+    };
+
+    let response = get("/accounts/a1/transactions").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v.as_array().unwrap().len(), 50);
+    assert_eq!(v[0]["from"], json!("a1"));
+    assert_eq!(v[0]["status"], json!("included"));
+
+    let response = get("/accounts/a1/transactions?before=20&limit=3").await.unwrap();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(heights(&v), [19, 18, 17]);
+
+    let response = get("/accounts/bad/transactions").await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_post_transaction_json_and_raw() {
+    use parity_scale_codec::Encode;
+
+    let post = |body: String| {
+        rest::router(MockBackend).oneshot(
+            axum::http::Request::builder()
+                .uri("/transactions")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body))
+                .unwrap(),
+        )
+    };
+    let tx = mbongo_core::Transaction {
+        tx_type: mbongo_core::TransactionType::Transfer,
+        sender: mbongo_core::Address([1u8; 32]),
+        receiver: mbongo_core::Address([2u8; 32]),
+        amount: 42,
+        nonce: 0,
+        signature: [3u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    };
+
+    let response = post(serde_json::to_string(&tx).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["hash"], json!("0x2a"));
+
+    let raw = format!("0x{}", hex::encode(tx.encode()));
+    let response = post(json!({ "raw": raw }).to_string()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    // Validation failures from the backend are reported as 400.
+    let stale = mbongo_core::Transaction { nonce: 5, ..tx };
+    let response = post(serde_json::to_string(&stale).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert!(v["error"].as_str().unwrap().contains("nonce"));
+
+    for body in [
+        json!({ "raw": "0x00" }).to_string(),
+        "{\"amount\": 1}".to_string(),
+    ] {
+        let response = post(body).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_openapi_describes_untagged_submit_body() {
+    let response = rest::router(MockBackend)
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/openapi.json")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    let schemas = &spec["components"]["schemas"];
+
+    assert_eq!(
+        spec["paths"]["/transactions"]["post"]["requestBody"]["content"]["application/json"]
+            ["schema"]["$ref"],
+        json!("#/components/schemas/SubmitTransactionRequest")
+    );
+    assert_eq!(
+        schemas["SubmitTransactionRequest"]["oneOf"],
+        json!([
+            {"$ref": "#/components/schemas/RawTransaction"},
+            {"$ref": "#/components/schemas/SignedTransaction"}
+        ])
+    );
+    assert_eq!(schemas["RawTransaction"]["required"], json!(["raw"]));
+
+    // The JSON form lists the fields of a serialized transaction.
+    let signed = &schemas["SignedTransaction"];
+    let tx = serde_json::to_value(mbongo_core::Transaction {
+        tx_type: mbongo_core::TransactionType::Call,
+        sender: mbongo_core::Address([1u8; 32]),
+        receiver: mbongo_core::Address([2u8; 32]),
+        amount: 42,
+        nonce: 0,
+        signature: [3u8; 64],
+        multisig: None,
+        data: vec![4],
+    })
+    .unwrap();
+    for field in tx.as_object().unwrap().keys() {
+        assert!(signed["properties"].get(field).is_some(), "{field} missing");
+    }
+    assert!(signed["properties"].get("multisig").is_some());
+    let required: Vec<&str> = signed["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        required,
+        [
+            "tx_type",
+            "sender",
+            "receiver",
+            "amount",
+            "nonce",
+            "signature"
+        ]
+    );
+    assert_eq!(
+        schemas["TransactionType"]["enum"],
+        json!(["Transfer", "ComputeTask", "Stake", "Deploy", "Call"])
+    );
+}
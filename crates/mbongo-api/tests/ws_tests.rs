@@ -0,0 +1,259 @@
+use std::net::SocketAddr;
+
+use futures::{SinkExt, StreamExt};
+use mbongo_api::rest::{Account, BlockDetail, Transaction, TransactionStatus};
+use mbongo_api::ws::{self, ChainEvent, EventBus, WsConfig};
+use mbongo_core::{Address, Hash, Log, LogEntry};
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn spawn_server(bus: EventBus, config: WsConfig) -> SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = ws::router(bus, config);
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> Client {
+    let (client, _) = connect_async(format!("ws://{addr}/ws")).await.unwrap();
+    client
+}
+
+async fn call(client: &mut Client, id: u64, method: &str, params: Value) -> Value {
+    let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+    client.send(Message::text(request.to_string())).await.unwrap();
+    next(client).await
+}
+
+async fn next(client: &mut Client) -> Value {
+    loop {
+        match client.next().await.unwrap().unwrap() {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Ping(_) | Message::Pong(_) => {}
+            other => panic!("unexpected message: {other:?}"),
+        }
+    }
+}
+
+fn head(height: u64) -> ChainEvent {
+    ChainEvent::NewHead(BlockDetail {
+        hash: format!("h{height}"),
+        height,
+        timestamp: 1000 + height,
+        parent_hash: "p".into(),
+        state_root: "s".into(),
+        transactions_root: "r".into(),
+        tx_count: 0,
+        transactions: Vec::new(),
+    })
+}
+
+fn account_changed(address: Address, balance: u128) -> ChainEvent {
+    ChainEvent::AccountChanged(Account {
+        address: address.to_string(),
+        balance: balance.to_string(),
+        nonce: 1,
+    })
+}
+
+#[tokio::test]
+async fn test_subscriptions_receive_matching_events() {
+    let bus = EventBus::new(16);
+    let addr = spawn_server(bus.clone(), WsConfig::default()).await;
+    let mut client = connect(addr).await;
+
+    let watched = Address([1u8; 32]);
+    let heads = call(&mut client, 1, "subscribe_new_heads", json!([])).await["result"].clone();
+    let account = call(
+        &mut client,
+        2,
+        "subscribe_account",
+        json!([watched.to_string()]),
+    )
+    .await["result"]
+        .clone();
+    assert_ne!(heads, account);
+
+    bus.publish(account_changed(Address([2u8; 32]), 5));
+    bus.publish(ChainEvent::PendingTransaction(Transaction {
+        hash: "t".into(),
+        from: "a".into(),
+        to: None,
+        value: "1".into(),
+        block_hash: None,
+        block_height: None,
+        status: TransactionStatus::Pending,
+    }));
+    bus.publish(account_changed(watched, 7));
+    bus.publish(head(3));
+
+    // Events of different kinds may arrive in either order.
+    let mut msgs = [next(&mut client).await, next(&mut client).await];
+    msgs.sort_by_key(|msg| msg["params"]["subscription"] == heads);
+    assert_eq!(msgs[0]["method"], "subscription");
+    assert_eq!(msgs[0]["params"]["subscription"], account);
+    assert_eq!(msgs[0]["params"]["result"]["balance"], "7");
+    assert_eq!(msgs[1]["params"]["subscription"], heads);
+    assert_eq!(msgs[1]["params"]["result"]["height"], 3);
+
+    let resp = call(&mut client, 3, "unsubscribe", json!([heads])).await;
+    assert_eq!(resp["result"], true);
+    let resp = call(&mut client, 4, "unsubscribe", json!([heads])).await;
+    assert_eq!(resp["result"], false);
+
+    bus.publish(head(4));
+    bus.publish(account_changed(watched, 8));
+    let msg = next(&mut client).await;
+    assert_eq!(msg["params"]["subscription"], account);
+    assert_eq!(msg["params"]["result"]["balance"], "8");
+}
+
+fn log(address: Address, topic: u8, height: u64) -> ChainEvent {
+    ChainEvent::Log(LogEntry {
+        block_height: height,
+        block_hash: Hash([1u8; 32]),
+        tx_hash: Hash([2u8; 32]),
+        tx_index: 0,
+        log_index: 0,
+        log: Log {
+            address,
+            topics: vec![Hash([topic; 32])],
+            data: Vec::new(),
+        },
+    })
+}
+
+#[tokio::test]
+async fn test_log_subscriptions_filter_by_address_and_topics() {
+    let bus = EventBus::new(16);
+    let addr = spawn_server(bus.clone(), WsConfig::default()).await;
+    let mut client = connect(addr).await;
+    let contract = Address([5u8; 32]);
+
+    let all = call(&mut client, 1, "subscribe_logs", json!([])).await["result"].clone();
+    let filtered = call(
+        &mut client,
+        2,
+        "subscribe_logs",
+        json!([{"address": contract, "topics": [Hash([9u8; 32])]}]),
+    )
+    .await["result"]
+        .clone();
+    let resp = call(&mut client, 3, "subscribe_logs", json!({"topics": "x"})).await;
+    assert_eq!(resp["error"]["code"], -32602);
+
+    bus.publish(log(Address([6u8; 32]), 9, 1));
+    bus.publish(log(contract, 9, 2));
+
+    let msg = next(&mut client).await;
+    assert_eq!(msg["params"]["subscription"], all);
+    assert_eq!(msg["params"]["result"]["block_height"], 1);
+    for expected in [&all, &filtered] {
+        let msg = next(&mut client).await;
+        assert_eq!(&msg["params"]["subscription"], expected);
+        assert_eq!(msg["params"]["result"]["address"], json!(contract));
+        assert_eq!(msg["params"]["result"]["block_height"], 2);
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_requests_and_subscription_limit() {
+    let config = WsConfig {
+        max_subscriptions_per_connection: 2,
+        ..WsConfig::default()
+    };
+    let addr = spawn_server(EventBus::new(16), config).await;
+    let mut client = connect(addr).await;
+
+    let resp = call(&mut client, 1, "subscribe_account", json!(["nope"])).await;
+    assert_eq!(resp["error"]["code"], -32602);
+    let resp = call(&mut client, 2, "subscribe_everything", json!([])).await;
+    assert_eq!(resp["error"]["code"], -32601);
+
+    client.send(Message::text("{not json")).await.unwrap();
+    assert_eq!(next(&mut client).await["error"]["code"], -32700);
+
+    assert!(call(&mut client, 3, "subscribe_new_heads", json!([])).await["result"].is_u64());
+    assert!(
+        call(&mut client, 4, "subscribe_pending_transactions", json!([])).await["result"].is_u64()
+    );
+    let resp = call(&mut client, 5, "subscribe_new_heads", json!([])).await;
+    assert_eq!(resp["error"]["code"], -32005);
+    assert_eq!(resp["id"], 5);
+}
+
+#[tokio::test]
+async fn test_connection_limit() {
+    let config = WsConfig {
+        max_connections: 1,
+        ..WsConfig::default()
+    };
+    let addr = spawn_server(EventBus::new(16), config).await;
+
+    let mut first = connect(addr).await;
+    assert!(connect_async(format!("ws://{addr}/ws")).await.is_err());
+
+    // Closing the first connection frees its slot.
+    first.close(None).await.unwrap();
+    while first.next().await.is_some() {}
+    for _ in 0..50 {
+        if connect_async(format!("ws://{addr}/ws")).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("connection slot was not released");
+}
+
+#[tokio::test]
+async fn test_slow_subscriber_is_told_how_many_events_it_missed() {
+    let bus = EventBus::new(2);
+    let addr = spawn_server(bus.clone(), WsConfig::default()).await;
+    let mut client = connect(addr).await;
+    let heads = call(&mut client, 1, "subscribe_new_heads", json!([])).await["result"].clone();
+
+    // The single-threaded test runtime cannot run the connection task while
+    // these are published, so its receiver overflows.
+    for height in 1..=5 {
+        bus.publish(head(height));
+    }
+
+    let msg = next(&mut client).await;
+    assert_eq!(msg["method"], "subscription_lagged");
+    assert_eq!(msg["params"]["skipped"], 3);
+    assert_eq!(msg["params"]["subscriptions"], json!([heads]));
+    assert_eq!(next(&mut client).await["params"]["result"]["height"], 4);
+    assert_eq!(next(&mut client).await["params"]["result"]["height"], 5);
+}
+
+#[tokio::test]
+async fn test_events_of_one_kind_do_not_crowd_out_another() {
+    let bus = EventBus::new(2);
+    let addr = spawn_server(bus.clone(), WsConfig::default()).await;
+    let mut heads_client = connect(addr).await;
+    let mut idle_client = connect(addr).await;
+    let heads =
+        call(&mut heads_client, 1, "subscribe_new_heads", json!([])).await["result"].clone();
+
+    // Far more logs than either connection buffers, published while
+    // neither connection task can run.
+    for height in 1..=5 {
+        bus.publish(log(Address([5u8; 32]), 9, height));
+    }
+    bus.publish(head(6));
+
+    let msg = next(&mut heads_client).await;
+    assert_eq!(msg["method"], "subscription");
+    assert_eq!(msg["params"]["subscription"], heads);
+    assert_eq!(msg["params"]["result"]["height"], 6);
+
+    // A connection without subscriptions is never told it lagged.
+    let resp = call(&mut idle_client, 1, "subscribe_new_heads", json!([])).await;
+    assert!(resp["result"].is_u64(), "{resp}");
+}
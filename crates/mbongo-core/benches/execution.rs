@@ -0,0 +1,88 @@
+//! Sequential versus parallel execution of a 1000-transaction block.
+//!
+//! Accounts are read from a lock-protected map of SCALE-encoded bytes, the
+//! way the node's in-memory storage serves them. The `independent` block
+//! has a distinct sender and receiver per transaction; the `hot_receiver`
+//! block pays everything to one account, so it forms a single conflict
+//! group and shows the cost of partitioning when nothing can run in
+//! parallel. Speedups scale with the number of cores available to rayon.
+//!
+//! Run with `cargo bench -p mbongo-core --bench execution`.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::RwLock;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mbongo_core::execution::{execute_parallel, execute_sequential};
+use mbongo_core::{Account, Address, Transaction, TransactionType};
+use parity_scale_codec::{Decode, Encode};
+
+const BLOCK_SIZE: u64 = 1000;
+
+fn address(i: u64, role: u8) -> Address {
+    let mut bytes = [role; 32];
+    bytes[..8].copy_from_slice(&i.to_le_bytes());
+    Address(bytes)
+}
+
+fn transfer(sender: Address, receiver: Address) -> Transaction {
+    Transaction {
+        tx_type: TransactionType::Transfer,
+        sender,
+        receiver,
+        amount: 1,
+        nonce: 0,
+        signature: [0u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    }
+}
+
+fn funded_state() -> RwLock<HashMap<Address, Vec<u8>>> {
+    let accounts = (0..BLOCK_SIZE)
+        .map(|i| {
+            let account = Account {
+                address: address(i, 1),
+                balance: 1_000_000,
+                nonce: 0,
+            };
+            (account.address, account.encode())
+        })
+        .collect();
+    RwLock::new(accounts)
+}
+
+fn execute_block(c: &mut Criterion) {
+    let state = funded_state();
+    let load = |address: &Address| -> Result<Option<Account>, Infallible> {
+        let accounts = state.read().expect("lock poisoned");
+        Ok(accounts
+            .get(address)
+            .map(|bytes| Account::decode(&mut bytes.as_slice()).expect("valid account")))
+    };
+
+    let independent: Vec<Transaction> =
+        (0..BLOCK_SIZE).map(|i| transfer(address(i, 1), address(i, 2))).collect();
+    let hot_receiver: Vec<Transaction> =
+        (0..BLOCK_SIZE).map(|i| transfer(address(i, 1), address(0, 2))).collect();
+
+    let mut group = c.benchmark_group("execute_block");
+    group.throughput(Throughput::Elements(BLOCK_SIZE));
+    for (name, block) in [
+        ("independent", &independent),
+        ("hot_receiver", &hot_receiver),
+    ] {
+        let txs: Vec<&Transaction> = block.iter().collect();
+        group.bench_with_input(BenchmarkId::new("sequential", name), &txs, |b, txs| {
+            b.iter(|| execute_sequential(&load, black_box(txs)));
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", name), &txs, |b, txs| {
+            b.iter(|| execute_parallel(&load, black_box(txs)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, execute_block);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+//! Sequential versus parallel block signature verification, at block sizes
+//! up to the node's `MAX_TX_PER_BLOCK` of 1000.
+//!
+//! Run with `cargo bench -p mbongo-core --bench signatures`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ed25519_dalek::{Signer, SigningKey};
+use mbongo_core::{verify_transaction_signatures, Address, Transaction, TransactionType};
+
+fn signed_transactions(count: u64) -> Vec<Transaction> {
+    (0..count)
+        .map(|i| {
+            let mut seed = [0u8; 32];
+            seed[..8].copy_from_slice(&i.to_le_bytes());
+            let key = SigningKey::from_bytes(&seed);
+            let mut tx = Transaction {
+                tx_type: TransactionType::Transfer,
+                sender: Address(key.verifying_key().to_bytes()),
+                receiver: Address([7u8; 32]),
+                amount: 1_000,
+                nonce: i,
+                signature: [0u8; 64],
+                multisig: None,
+                data: Vec::new(),
+            };
+            tx.signature = key.sign(&tx.signing_payload()).to_bytes();
+            tx
+        })
+        .collect()
+}
+
+fn verify_signatures(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_signatures");
+    for count in [1u64, 10, 100, 1000] {
+        let txs = signed_transactions(count);
+        group.throughput(Throughput::Elements(count));
+        group.bench_with_input(BenchmarkId::new("sequential", count), &txs, |b, txs| {
+            b.iter(|| black_box(txs).iter().all(Transaction::verify_signature));
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", count), &txs, |b, txs| {
+            b.iter(|| verify_transaction_signatures(black_box(txs)));
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify_signatures);
+criterion_main!(benches);
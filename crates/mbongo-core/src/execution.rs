@@ -0,0 +1,393 @@
+//! Native transaction execution over an account overlay.
+//!
+//! [`execute_transaction`] applies one transaction to an in-memory overlay,
+//! loading accounts it has not seen yet through a caller-supplied loader.
+//! [`execute_sequential`] and [`execute_parallel`] apply a whole block's
+//! worth of transactions and return the accounts they modified.
+//!
+//! # Parallel execution
+//!
+//! A transaction reads and writes exactly two accounts, its sender and its
+//! receiver. [`conflict_groups`] joins transactions that share an account,
+//! directly or through a chain of others, into one group. Groups touch
+//! disjoint accounts, so each can run on its own overlay without seeing the
+//! others, and the overlays are merged at the end.
+//!
+//! Within a group transactions run in block order and stop at the first
+//! failure. Because a transaction's outcome only depends on earlier
+//! transactions of its own group, the lowest failing index across all groups
+//! is exactly the transaction sequential execution would have stopped at,
+//! with the same error. Both functions therefore return identical results
+//! for every input:
+//!
+//! ```rust
+//! use std::convert::Infallible;
+//! use mbongo_core::execution::{execute_parallel, execute_sequential};
+//! use mbongo_core::{Account, Address, Transaction, TransactionType};
+//!
+//! let funded = |address: &Address| -> Result<Option<Account>, Infallible> {
+//!     Ok(Some(Account { address: *address, balance: 100, nonce: 0 }))
+//! };
+//! let transfer = |from: u8, to: u8| Transaction {
+//!     tx_type: TransactionType::Transfer,
+//!     sender: Address([from; 32]),
+//!     receiver: Address([to; 32]),
+//!     amount: 10,
+//!     nonce: 0,
+//!     signature: [0u8; 64],
+//!     multisig: None,
+//!     data: Vec::new(),
+//! };
+//! let block = [transfer(1, 2), transfer(3, 4), transfer(5, 6)];
+//! let txs: Vec<&Transaction> = block.iter().collect();
+//!
+//! let parallel = execute_parallel(&funded, &txs).unwrap();
+//! assert_eq!(parallel, execute_sequential(&funded, &txs).unwrap());
+//! assert_eq!(parallel[&Address([2; 32])].balance, 110);
+//! ```
+
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::{Account, AccountError, Address, Transaction};
+
+/// Why a transaction could not be executed.
+///
+/// `E` is the error type of the account loader.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExecutionError<E> {
+    /// The transaction nonce is not the sender's next nonce.
+    #[error("invalid nonce: expected {expected}, got {actual}")]
+    InvalidNonce {
+        /// Sender's next nonce.
+        expected: u64,
+        /// Nonce carried by the transaction.
+        actual: u64,
+    },
+    /// The sender does not exist or cannot cover the amount.
+    #[error("insufficient balance: have {balance}, need {required}")]
+    InsufficientBalance {
+        /// Sender's balance (0 if the account does not exist).
+        balance: u128,
+        /// Transaction amount.
+        required: u128,
+    },
+    /// Crediting the receiver would overflow its balance.
+    #[error("receiver balance overflow")]
+    BalanceOverflow,
+    /// The loader failed to read an account.
+    #[error("storage error: {0}")]
+    Storage(E),
+}
+
+/// The first transaction of a block that failed to execute.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("transaction {index} failed: {error}")]
+pub struct ExecutionFailure<E> {
+    /// Position of the transaction in the executed slice.
+    pub index: usize,
+    /// Why it failed.
+    pub error: ExecutionError<E>,
+}
+
+/// Executes `tx` against the account overlay `accounts`, loading accounts
+/// through `load` on first use. Signature and duplicate checks are the
+/// caller's responsibility.
+///
+/// Native transaction types move `amount` from sender to receiver and bump
+/// the sender nonce. Contract transactions need the contract runtime; for
+/// them this applies only the same nonce bump and transfer to `receiver`.
+/// On error `accounts` is left unchanged.
+///
+/// # Errors
+///
+/// Returns [`ExecutionError`] if the nonce is wrong, the sender cannot pay,
+/// the receiver would overflow, or `load` fails.
+pub fn execute_transaction<E, L, H>(
+    load: &L,
+    accounts: &mut HashMap<Address, Account, H>,
+    tx: &Transaction,
+) -> Result<(), ExecutionError<E>>
+where
+    L: Fn(&Address) -> Result<Option<Account>, E>,
+    H: BuildHasher,
+{
+    // Load sender (from overlay or loader).
+    let sender_addr = tx.sender;
+    let mut sender = match accounts.get(&sender_addr) {
+        Some(acc) => acc.clone(),
+        None => load(&sender_addr).map_err(ExecutionError::Storage)?.ok_or(
+            ExecutionError::InsufficientBalance {
+                balance: 0,
+                required: tx.amount,
+            },
+        )?,
+    };
+
+    let expected = sender.nonce;
+    sender
+        .validate_and_increment_nonce(tx.nonce)
+        .map_err(|_| ExecutionError::InvalidNonce {
+            expected,
+            actual: tx.nonce,
+        })?;
+
+    // Load receiver (from overlay or loader).
+    let receiver_addr = tx.receiver;
+    let mut receiver = match accounts.get(&receiver_addr) {
+        Some(acc) => acc.clone(),
+        None => load(&receiver_addr)
+            .map_err(ExecutionError::Storage)?
+            .unwrap_or_else(|| Account::new(receiver_addr)),
+    };
+
+    Account::transfer(&mut sender, &mut receiver, tx.amount).map_err(|e| match e {
+        AccountError::BalanceOverflow => ExecutionError::BalanceOverflow,
+        AccountError::InsufficientBalance | AccountError::InvalidNonce => {
+            ExecutionError::InsufficientBalance {
+                balance: sender.balance,
+                required: tx.amount,
+            }
+        }
+    })?;
+
+    accounts.insert(sender_addr, sender);
+    accounts.insert(receiver_addr, receiver);
+    Ok(())
+}
+
+/// Executes `txs` one after another and returns every account they touched.
+///
+/// # Errors
+///
+/// Returns the index and error of the first transaction that fails; later
+/// transactions are not executed.
+pub fn execute_sequential<E, L>(
+    load: &L,
+    txs: &[&Transaction],
+) -> Result<HashMap<Address, Account>, ExecutionFailure<E>>
+where
+    L: Fn(&Address) -> Result<Option<Account>, E>,
+{
+    let mut accounts = HashMap::new();
+    for (index, tx) in txs.iter().enumerate() {
+        execute_transaction(load, &mut accounts, tx)
+            .map_err(|error| ExecutionFailure { index, error })?;
+    }
+    Ok(accounts)
+}
+
+/// Executes `txs` with independent [`conflict_groups`] running in parallel.
+///
+/// Returns the same accounts, or the same failure, as
+/// [`execute_sequential`]; see the [module docs](self) for why. Falls back
+/// to sequential execution when everything lands in one group.
+///
+/// # Errors
+///
+/// Returns the index and error of the first transaction, in block order,
+/// that fails.
+pub fn execute_parallel<E, L>(
+    load: &L,
+    txs: &[&Transaction],
+) -> Result<HashMap<Address, Account>, ExecutionFailure<E>>
+where
+    E: Send,
+    L: Fn(&Address) -> Result<Option<Account>, E> + Sync,
+{
+    let groups = conflict_groups(txs);
+    if groups.len() < 2 {
+        return execute_sequential(load, txs);
+    }
+
+    // Each rayon task shares one overlay across the groups it runs; they
+    // touch disjoint accounts. A failed group leaves partial writes behind,
+    // which is harmless since any failure discards every overlay.
+    let overlays: Vec<Overlay<E>> = groups
+        .par_iter()
+        .with_min_len(MIN_GROUPS_PER_TASK)
+        .fold(
+            || (HashMap::new(), None),
+            |(mut accounts, mut failure): Overlay<E>, group| {
+                for &index in group {
+                    if let Err(error) = execute_transaction(load, &mut accounts, txs[index]) {
+                        failure = first(failure, Some(ExecutionFailure { index, error }));
+                        break;
+                    }
+                }
+                (accounts, failure)
+            },
+        )
+        .collect();
+
+    let mut merged =
+        HashMap::with_capacity(overlays.iter().map(|(accounts, _)| accounts.len()).sum());
+    let mut failure = None;
+    for (accounts, task_failure) in overlays {
+        failure = first(failure, task_failure);
+        merged.extend(accounts);
+    }
+    match failure {
+        Some(failure) => Err(failure),
+        None => Ok(merged),
+    }
+}
+
+/// Fewest groups a rayon task runs, so tiny groups do not each pay for a
+/// task and an overlay.
+const MIN_GROUPS_PER_TASK: usize = 32;
+
+/// Accounts written by a set of groups, and their earliest failure.
+type Overlay<E> = (HashMap<Address, Account>, Option<ExecutionFailure<E>>);
+
+fn first<E>(
+    a: Option<ExecutionFailure<E>>,
+    b: Option<ExecutionFailure<E>>,
+) -> Option<ExecutionFailure<E>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(if a.index <= b.index { a } else { b }),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Splits `txs` into groups of transaction indexes such that no account is
+/// touched by two groups.
+///
+/// Groups are ordered by their first transaction and list their indexes in
+/// ascending order, so the split is deterministic.
+#[must_use]
+pub fn conflict_groups(txs: &[&Transaction]) -> Vec<Vec<usize>> {
+    // Union-find over accounts, numbered in order of first appearance.
+    let mut ids: HashMap<Address, usize> = HashMap::with_capacity(2 * txs.len());
+    let mut parent: Vec<usize> = Vec::with_capacity(2 * txs.len());
+    let mut id_of = |address: Address, parent: &mut Vec<usize>| {
+        *ids.entry(address).or_insert_with(|| {
+            parent.push(parent.len());
+            parent.len() - 1
+        })
+    };
+    let edges: Vec<(usize, usize)> = txs
+        .iter()
+        .map(|tx| {
+            (
+                id_of(tx.sender, &mut parent),
+                id_of(tx.receiver, &mut parent),
+            )
+        })
+        .collect();
+    for &(a, b) in &edges {
+        let (a, b) = (find(&mut parent, a), find(&mut parent, b));
+        if a != b {
+            // Keep the smaller id as root so roots are stable.
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let mut group_of_root = vec![usize::MAX; parent.len()];
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (index, &(sender, _)) in edges.iter().enumerate() {
+        let root = find(&mut parent, sender);
+        if group_of_root[root] == usize::MAX {
+            group_of_root[root] = groups.len();
+            groups.push(Vec::new());
+        }
+        groups[group_of_root[root]].push(index);
+    }
+    groups
+}
+
+fn find(parent: &mut [usize], mut id: usize) -> usize {
+    while parent[id] != id {
+        parent[id] = parent[parent[id]];
+        id = parent[id];
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TransactionType;
+    use std::convert::Infallible;
+
+    fn transfer(from: u8, to: u8, amount: u128, nonce: u64) -> Transaction {
+        Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: Address([from; 32]),
+            receiver: Address([to; 32]),
+            amount,
+            nonce,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        }
+    }
+
+    /// Every account with a first byte below 100 starts with 1000 coins.
+    #[allow(clippy::unnecessary_wraps)]
+    fn genesis(address: &Address) -> Result<Option<Account>, Infallible> {
+        Ok((address.0[0] < 100).then_some(Account {
+            address: *address,
+            balance: 1000,
+            nonce: 0,
+        }))
+    }
+
+    #[test]
+    fn groups_join_transactions_through_shared_accounts() {
+        let block = [
+            transfer(1, 2, 1, 0),
+            transfer(3, 4, 1, 0),
+            transfer(5, 2, 1, 0),
+            transfer(6, 6, 1, 0),
+            transfer(4, 5, 1, 0),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+        assert_eq!(conflict_groups(&txs), vec![vec![0, 1, 2, 4], vec![3]]);
+        assert!(conflict_groups(&[]).is_empty());
+    }
+
+    #[test]
+    fn parallel_reports_the_first_failure_in_block_order() {
+        // Group {1,2} fails at index 3, group {3,4} at index 1.
+        let block = [
+            transfer(1, 2, 10, 0),
+            transfer(3, 4, 10, 5),
+            transfer(200, 7, 1, 0),
+            transfer(1, 2, 10, 0),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+        let expected = ExecutionFailure {
+            index: 1,
+            error: ExecutionError::InvalidNonce {
+                expected: 0,
+                actual: 5,
+            },
+        };
+        assert_eq!(execute_sequential(&genesis, &txs), Err(expected.clone()));
+        assert_eq!(execute_parallel(&genesis, &txs), Err(expected));
+    }
+
+    #[test]
+    fn loader_errors_are_reported_at_their_transaction() {
+        let load = |address: &Address| {
+            if address.0[0] == 9 {
+                Err("disk on fire")
+            } else {
+                Ok(Some(Account::new(*address)))
+            }
+        };
+        let block = [transfer(1, 2, 0, 0), transfer(3, 9, 0, 0)];
+        let txs: Vec<&Transaction> = block.iter().collect();
+        assert_eq!(
+            execute_parallel(&load, &txs),
+            Err(ExecutionFailure {
+                index: 1,
+                error: ExecutionError::Storage("disk on fire"),
+            })
+        );
+    }
+}
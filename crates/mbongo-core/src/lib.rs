@@ -4,6 +4,9 @@
 //! the Mbongo Chain blockchain, including:
 //! - Block and transaction primitives
 //! - Cryptographic helpers (hashing)
+//! - M-of-N multisig accounts
+//! - Event logs and bloom filters
+//! - Sequential and parallel transaction execution
 //!
 //! # Block Primitives
 //!
@@ -16,8 +19,8 @@
 //!
 //! // Build a simple block with two typed transactions (unsigned)
 //! let txs = vec![
-//!     Transaction { tx_type: TransactionType::Transfer, sender: Address::zero(), receiver: Address::zero(), amount: 1, nonce: 0, signature: [0u8; 64] },
-//!     Transaction { tx_type: TransactionType::Stake, sender: Address::zero(), receiver: Address::zero(), amount: 1000, nonce: 1, signature: [0u8; 64] },
+//!     Transaction { tx_type: TransactionType::Transfer, sender: Address::zero(), receiver: Address::zero(), amount: 1, nonce: 0, signature: [0u8; 64], multisig: None, data: Vec::new() },
+//!     Transaction { tx_type: TransactionType::Stake, sender: Address::zero(), receiver: Address::zero(), amount: 1000, nonce: 1, signature: [0u8; 64], multisig: None, data: Vec::new() },
 //! ];
 //! let header = BlockHeader {
 //!     parent_hash: Hash::zero(),
@@ -36,13 +39,20 @@
 
 pub mod account;
 pub mod crypto;
+pub mod execution;
+pub mod logs;
+pub mod multisig;
 mod primitives;
+pub mod receipt;
 
 pub use account::{Account, AccountError};
+pub use logs::{Bloom, Log, LogEntry, LogFilter};
+pub use multisig::{Multisig, MultisigError, MultisigWitness};
 pub use primitives::{
-    compute_transactions_root, Address, Block, BlockBody, BlockHeader, Hash, Transaction,
-    TransactionType,
+    compute_transactions_root, verify_transaction_signatures, Address, Block, BlockBody,
+    BlockHeader, Hash, Transaction, TransactionType,
 };
+pub use receipt::{Event, Receipt, ReceiptStatus};
 
 #[cfg(test)]
 mod tests {
@@ -76,6 +86,8 @@ mod tests {
                 amount: 10,
                 nonce: 1,
                 signature: [0u8; 64],
+                multisig: None,
+                data: Vec::new(),
             },
             Transaction {
                 tx_type: TransactionType::Stake,
@@ -84,6 +96,8 @@ mod tests {
                 amount: 1000,
                 nonce: 2,
                 signature: [0u8; 64],
+                multisig: None,
+                data: Vec::new(),
             },
         ];
         let header = BlockHeader {
@@ -130,6 +144,8 @@ mod tests {
             amount: 42,
             nonce: 7,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         let payload = tx.signing_payload();
         let sig = sk.sign(&payload);
@@ -151,6 +167,8 @@ mod tests {
             amount: 10,
             nonce: 1,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         let sig = sk.sign(&tx.signing_payload());
         let mut tampered = tx.clone();
@@ -175,6 +193,8 @@ mod tests {
                 amount: 1234,
                 nonce: 9,
                 signature: [5u8; 64],
+                multisig: None,
+                data: Vec::new(),
             };
             let enc = tx.encode();
             let dec = Transaction::decode(&mut &enc[..]).unwrap();
@@ -187,6 +207,204 @@ mod tests {
         }
     }
 
+    #[test]
+    fn transaction_decode_hex() {
+        let tx = Transaction {
+            tx_type: TransactionType::Stake,
+            sender: Address([3u8; 32]),
+            receiver: Address([4u8; 32]),
+            amount: 77,
+            nonce: 2,
+            signature: [5u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        let raw = hex::encode(tx.encode());
+        assert_eq!(tx.encode_hex(), format!("0x{raw}"));
+        assert_eq!(Transaction::decode_hex(&raw).unwrap(), tx);
+        assert_eq!(Transaction::decode_hex(&tx.encode_hex()).unwrap(), tx);
+        assert!(Transaction::decode_hex("0xzz").is_err());
+        assert!(Transaction::decode_hex(&raw[..raw.len() - 2]).is_err());
+        assert!(Transaction::decode_hex(&format!("{raw}00")).is_err());
+    }
+
+    #[test]
+    fn multisig_transactions_scale_roundtrip() {
+        let single = Transaction {
+            tx_type: TransactionType::Stake,
+            sender: Address([3u8; 32]),
+            receiver: Address([4u8; 32]),
+            amount: 77,
+            nonce: 2,
+            signature: [5u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        // Single-key transactions keep their original 153-byte encoding.
+        let legacy = single.encode();
+        assert_eq!(legacy.len(), 153);
+        assert_eq!(legacy[0], TransactionType::Stake.encode()[0]);
+
+        let config = Multisig::new(1, vec![Address([1u8; 32]), Address([2u8; 32])]).unwrap();
+        let mut witness = MultisigWitness::new(config.clone());
+        witness.add_signature(Address([2u8; 32]), [6u8; 64]).unwrap();
+        let multi = Transaction {
+            sender: config.address(),
+            signature: [0u8; 64],
+            multisig: Some(witness),
+            ..single.clone()
+        };
+        let encoded = multi.encode();
+        assert_eq!(encoded[0], 0xFF);
+        assert_eq!(Transaction::decode_hex(&multi.encode_hex()).unwrap(), multi);
+        let round: Transaction = json::from_str(&json::to_string(&multi).unwrap()).unwrap();
+        assert_eq!(round, multi);
+        assert!(!json::to_string(&single).unwrap().contains("multisig"));
+    }
+
+    #[test]
+    fn contract_data_scale_roundtrip() {
+        let key = SigningKey::from_bytes(&[8u8; 32]);
+        let mut plain = signed(&key, 0);
+        let legacy_payload = plain.signing_payload();
+
+        let mut call = Transaction {
+            tx_type: TransactionType::Call,
+            data: vec![1, 2, 3],
+            ..plain.clone()
+        };
+        let encoded = call.encode();
+        assert_eq!(encoded[0], 0xFE);
+        assert_eq!(encoded.len(), 153 + 1 + 4);
+        assert_eq!(Transaction::decode_hex(&call.encode_hex()).unwrap(), call);
+        let round: Transaction = json::from_str(&json::to_string(&call).unwrap()).unwrap();
+        assert_eq!(round, call);
+        assert!(json::to_string(&call).unwrap().contains("\"data\":\"0x010203\""));
+
+        // Data is signed; transactions without data sign the legacy payload.
+        assert!(!call.verify_signature());
+        call.signature = key.sign(&call.signing_payload()).to_bytes();
+        assert!(call.verify_signature());
+        plain.data.clear();
+        assert_eq!(plain.signing_payload(), legacy_payload);
+        assert!(!json::to_string(&plain).unwrap().contains("data"));
+
+        // The tag without a payload is not a canonical encoding.
+        let mut tagged_empty = vec![0xFE];
+        tagged_empty.extend(&plain.encode()[..]);
+        tagged_empty.push(0);
+        assert!(Transaction::decode(&mut tagged_empty.as_slice()).is_err());
+    }
+
+    #[test]
+    fn deployed_address_depends_on_sender_and_nonce() {
+        let key = SigningKey::from_bytes(&[8u8; 32]);
+        let a = signed(&key, 0).deployed_address();
+        assert_eq!(a, signed(&key, 0).deployed_address());
+        assert_ne!(a, signed(&key, 1).deployed_address());
+        assert_ne!(
+            a,
+            signed(&SigningKey::from_bytes(&[9u8; 32]), 0).deployed_address()
+        );
+    }
+
+    fn signed(key: &SigningKey, nonce: u64) -> Transaction {
+        let mut tx = Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: Address(key.verifying_key().to_bytes()),
+            receiver: Address([4u8; 32]),
+            amount: 10,
+            nonce,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        tx.signature = key.sign(&tx.signing_payload()).to_bytes();
+        tx
+    }
+
+    #[test]
+    fn signature_verification_finds_the_first_invalid_signature() {
+        let keys: Vec<SigningKey> = (1u8..=8).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let mut txs: Vec<Transaction> = keys.iter().zip(0..).map(|(k, n)| signed(k, n)).collect();
+        assert_eq!(verify_transaction_signatures(&[]), Ok(()));
+        assert_eq!(verify_transaction_signatures(&txs), Ok(()));
+
+        txs[6].amount += 1;
+        txs[3].signature[0] ^= 1;
+        assert_eq!(verify_transaction_signatures(&txs), Err(3));
+
+        txs[3] = signed(&keys[3], 3);
+        assert_eq!(verify_transaction_signatures(&txs), Err(6));
+
+        // A sender that is not a valid ed25519 point fails.
+        txs[6] = signed(&keys[6], 6);
+        txs[1].sender = Address([0xFF; 32]);
+        assert_eq!(verify_transaction_signatures(&txs), Err(1));
+    }
+
+    #[test]
+    fn block_and_mempool_agree_on_signatures_with_torsion() {
+        use curve25519_dalek::constants::EIGHT_TORSION;
+        use curve25519_dalek::{EdwardsPoint, Scalar};
+        use sha2::{Digest, Sha512};
+
+        // R carries an order-8 component T, so [s]B = R + [k]A fails by T.
+        // Cofactored or batched equations can accept such a signature;
+        // strict verification never does.
+        let a = Scalar::from(0x1234_5678u64);
+        let public = EdwardsPoint::mul_base(&a).compress();
+        let torsion_signed = |nonce: u64| {
+            let r = Scalar::from(0x9abc_def0u64 + nonce);
+            let big_r = (EdwardsPoint::mul_base(&r) + EIGHT_TORSION[1]).compress();
+            let mut tx = signed(&SigningKey::from_bytes(&[1u8; 32]), nonce);
+            tx.sender = Address(public.to_bytes());
+            let k = Scalar::from_hash(
+                Sha512::new()
+                    .chain_update(big_r.as_bytes())
+                    .chain_update(public.as_bytes())
+                    .chain_update(tx.signing_payload()),
+            );
+            tx.signature[..32].copy_from_slice(big_r.as_bytes());
+            tx.signature[32..].copy_from_slice((r + k * a).as_bytes());
+            tx
+        };
+        let txs: Vec<Transaction> = (0..8).map(torsion_signed).collect();
+
+        assert!(txs.iter().all(|tx| !tx.verify_signature()));
+        assert_eq!(verify_transaction_signatures(&txs), Err(0));
+        assert_eq!(verify_transaction_signatures(&txs[5..]), Err(0));
+    }
+
+    #[test]
+    fn signature_verification_checks_multisig_transactions() {
+        let members: Vec<SigningKey> =
+            (1u8..=2).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let config = Multisig::new(
+            2,
+            members.iter().map(|k| Address(k.verifying_key().to_bytes())).collect(),
+        )
+        .unwrap();
+        let mut multi = Transaction {
+            sender: config.address(),
+            signature: [0u8; 64],
+            multisig: Some(MultisigWitness::new(config)),
+            ..signed(&members[0], 0)
+        };
+        for key in &members {
+            let signature = key.sign(&multi.signing_payload()).to_bytes();
+            let witness = multi.multisig.as_mut().unwrap();
+            witness
+                .add_signature(Address(key.verifying_key().to_bytes()), signature)
+                .unwrap();
+        }
+        let mut txs = vec![signed(&members[0], 0), multi, signed(&members[1], 0)];
+        assert_eq!(verify_transaction_signatures(&txs), Ok(()));
+
+        txs[1].amount += 1;
+        assert_eq!(verify_transaction_signatures(&txs), Err(1));
+    }
+
     #[test]
     fn transactions_root_changes_with_body() {
         let a = vec![Transaction {
@@ -196,6 +414,8 @@ mod tests {
             amount: 1,
             nonce: 0,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         }];
         let b = vec![Transaction {
             tx_type: TransactionType::Transfer,
@@ -204,6 +424,8 @@ mod tests {
             amount: 2,
             nonce: 0,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         }];
         let ra = compute_transactions_root(&a);
         let rb = compute_transactions_root(&b);
@@ -0,0 +1,277 @@
+//! Event logs, the bloom filters that index them, and log queries.
+//!
+//! Every [`Event`](crate::Event) in a receipt is exposed to indexers as a
+//! [`Log`]: the address it concerns, up to [`MAX_TOPICS`] indexed topics and
+//! opaque data. The first topic of a native event is [`event_topic`] of its
+//! name; contracts choose their own topics.
+//!
+//! Each receipt carries a [`Bloom`] of its logs' addresses and topics, so a
+//! [`LogFilter`] can skip receipts that certainly hold no matching log
+//! without decoding them.
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::crypto::blake3_hash;
+use crate::primitives::serde_hex_bytes;
+use crate::{Address, Hash};
+
+/// Most topics a log may carry.
+pub const MAX_TOPICS: usize = 4;
+
+/// Size of a [`Bloom`] in bytes (2048 bits).
+pub const BLOOM_BYTES: usize = 256;
+
+/// Bits set in a [`Bloom`] per inserted item.
+const BLOOM_HASHES: usize = 3;
+
+/// Returns the topic identifying events named `name`.
+#[must_use]
+pub fn event_topic(name: &str) -> Hash {
+    Hash(blake3_hash(name.as_bytes()))
+}
+
+/// An indexed record of something a transaction did.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+pub struct Log {
+    /// Account or contract the log concerns.
+    pub address: Address,
+    /// Indexed topics, at most [`MAX_TOPICS`].
+    pub topics: Vec<Hash>,
+    /// Unindexed payload.
+    #[serde(with = "serde_hex_bytes")]
+    pub data: Vec<u8>,
+}
+
+/// 2048-bit bloom filter over log addresses and topics.
+///
+/// Each item sets three bits chosen by its blake3 hash. A filter never
+/// misses an inserted item but may report items that were not inserted.
+#[derive(Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct Bloom(pub [u8; BLOOM_BYTES]);
+
+impl Bloom {
+    /// Returns the bloom of `logs`.
+    #[must_use]
+    pub fn from_logs<'a>(logs: impl IntoIterator<Item = &'a Log>) -> Self {
+        let mut bloom = Self::default();
+        for log in logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    /// Adds the address and topics of `log`.
+    pub fn accrue_log(&mut self, log: &Log) {
+        self.accrue(&log.address.0);
+        for topic in &log.topics {
+            self.accrue(&topic.0);
+        }
+    }
+
+    /// Adds `item`.
+    pub fn accrue(&mut self, item: &[u8]) {
+        for (byte, mask) in Self::bits(item) {
+            self.0[byte] |= mask;
+        }
+    }
+
+    /// Adds every item added to `other`.
+    pub fn accrue_bloom(&mut self, other: &Self) {
+        for (byte, other) in self.0.iter_mut().zip(other.0) {
+            *byte |= other;
+        }
+    }
+
+    /// Returns `false` if `item` was certainly never added.
+    #[must_use]
+    pub fn contains(&self, item: &[u8]) -> bool {
+        Self::bits(item).all(|(byte, mask)| self.0[byte] & mask != 0)
+    }
+
+    /// Returns `true` if nothing has been added.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|byte| *byte == 0)
+    }
+
+    /// Byte offsets and masks of the bits `item` sets.
+    fn bits(item: &[u8]) -> impl Iterator<Item = (usize, u8)> {
+        let hash = blake3_hash(item);
+        (0..BLOOM_HASHES).map(move |i| {
+            let bit =
+                usize::from(u16::from_be_bytes([hash[2 * i], hash[2 * i + 1]])) % (BLOOM_BYTES * 8);
+            (bit / 8, 1 << (bit % 8))
+        })
+    }
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Self([0u8; BLOOM_BYTES])
+    }
+}
+
+impl std::fmt::Debug for Bloom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bloom(0x{})", hex::encode(self.0))
+    }
+}
+
+impl Serialize for Bloom {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.0)))
+    }
+}
+
+impl<'de> Deserialize<'de> for Bloom {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let bytes =
+            hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(serde::de::Error::custom)?;
+        let bytes: [u8; BLOOM_BYTES] = bytes
+            .try_into()
+            .map_err(|_| serde::de::Error::custom(format!("expected {BLOOM_BYTES} bytes")))?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Selects logs by block range, address and topics.
+///
+/// Unset fields match everything. `topics` is positional: entry `i` must
+/// equal the log's topic `i`, and `None` matches any topic in that position.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct LogFilter {
+    /// Lowest block height to search.
+    #[serde(default)]
+    pub from_height: Option<u64>,
+    /// Highest block height to search.
+    #[serde(default)]
+    pub to_height: Option<u64>,
+    /// Only logs concerning this address.
+    #[serde(default)]
+    pub address: Option<Address>,
+    /// Required topics by position.
+    #[serde(default)]
+    pub topics: Vec<Option<Hash>>,
+}
+
+impl LogFilter {
+    /// Returns `true` if `log` has the filter's address and topics. Block
+    /// heights are not checked.
+    #[must_use]
+    pub fn matches(&self, log: &Log) -> bool {
+        self.address.map_or(true, |address| log.address == address)
+            && self
+                .topics
+                .iter()
+                .enumerate()
+                .all(|(i, topic)| topic.map_or(true, |topic| log.topics.get(i) == Some(&topic)))
+    }
+
+    /// Returns `false` if no log added to `bloom` can match the filter.
+    #[must_use]
+    pub fn may_match(&self, bloom: &Bloom) -> bool {
+        self.address.map_or(true, |address| bloom.contains(&address.0))
+            && self.topics.iter().flatten().all(|topic| bloom.contains(&topic.0))
+    }
+}
+
+/// A log together with where it was emitted, as returned by log queries.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Height of the block that included the transaction.
+    pub block_height: u64,
+    /// Hash of the block that included the transaction.
+    pub block_hash: Hash,
+    /// Hash of the transaction that emitted the log.
+    pub tx_hash: Hash,
+    /// Position of the transaction in the block body.
+    pub tx_index: u32,
+    /// Position of the log among the transaction's logs.
+    pub log_index: u32,
+    /// The log itself.
+    #[serde(flatten)]
+    pub log: Log,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(address: u8, topics: &[u8]) -> Log {
+        Log {
+            address: Address([address; 32]),
+            topics: topics.iter().map(|t| Hash([*t; 32])).collect(),
+            data: vec![1, 2, 3],
+        }
+    }
+
+    #[test]
+    fn bloom_contains_what_was_added() {
+        let logs = [log(1, &[2, 3]), log(4, &[])];
+        let bloom = Bloom::from_logs(&logs);
+
+        assert!(!bloom.is_empty());
+        for item in [[1u8; 32], [2; 32], [3; 32], [4; 32]] {
+            assert!(bloom.contains(&item));
+        }
+        assert!(!Bloom::default().contains(&[1u8; 32]));
+
+        let mut union = Bloom::from_logs([&log(5, &[])]);
+        union.accrue_bloom(&bloom);
+        for item in [[1u8; 32], [4; 32], [5; 32]] {
+            assert!(union.contains(&item));
+        }
+    }
+
+    #[test]
+    fn filter_matches_address_and_positional_topics() {
+        let target = log(1, &[2, 3]);
+        let bloom = Bloom::from_logs([&target]);
+        let filter = |address: Option<u8>, topics: &[Option<u8>]| LogFilter {
+            address: address.map(|a| Address([a; 32])),
+            topics: topics.iter().map(|t| t.map(|t| Hash([t; 32]))).collect(),
+            ..LogFilter::default()
+        };
+
+        for matching in [
+            filter(None, &[]),
+            filter(Some(1), &[]),
+            filter(None, &[None, Some(3)]),
+            filter(Some(1), &[Some(2), Some(3)]),
+        ] {
+            assert!(matching.matches(&target), "{matching:?}");
+            assert!(matching.may_match(&bloom), "{matching:?}");
+        }
+        for other in [
+            filter(Some(9), &[]),
+            filter(None, &[Some(3)]),
+            filter(None, &[Some(2), Some(3), Some(4)]),
+        ] {
+            assert!(!other.matches(&target), "{other:?}");
+        }
+        assert!(!filter(Some(9), &[]).may_match(&bloom));
+    }
+
+    #[test]
+    fn log_entry_json_is_flat() {
+        let entry = LogEntry {
+            block_height: 3,
+            block_hash: Hash([7u8; 32]),
+            tx_hash: Hash([8u8; 32]),
+            tx_index: 1,
+            log_index: 0,
+            log: log(1, &[2]),
+        };
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["data"], "0x010203");
+        assert_eq!(json["topics"][0], Hash([2u8; 32]).to_string());
+        assert_eq!(serde_json::from_value::<LogEntry>(json).unwrap(), entry);
+
+        let bloom = Bloom::from_logs([&entry.log]);
+        let json = serde_json::to_value(bloom).unwrap();
+        assert_eq!(serde_json::from_value::<Bloom>(json).unwrap(), bloom);
+    }
+}
@@ -0,0 +1,472 @@
+//! M-of-N multisignature accounts.
+//!
+//! A multisig account is identified by an address derived from its
+//! [`Multisig`] configuration: the blake3 hash of a domain tag and the
+//! SCALE-encoded threshold and sorted member set. Nobody holds a private key
+//! for that address, so funds sent to it can only leave through a
+//! transaction carrying a [`MultisigWitness`] with `threshold` member
+//! signatures over [`Transaction::signing_payload`].
+//!
+//! Witnesses are canonical: members and signatures are sorted by address and
+//! a complete witness holds exactly `threshold` signatures, so one set of
+//! signers yields exactly one transaction encoding and hash.
+//!
+//! ```rust
+//! use ed25519_dalek::{Signer, SigningKey};
+//! use mbongo_core::multisig::{Multisig, MultisigWitness};
+//! use mbongo_core::{Address, Transaction, TransactionType};
+//!
+//! let keys: Vec<SigningKey> = (1u8..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+//! let members = keys.iter().map(|k| Address(k.verifying_key().to_bytes())).collect();
+//! let config = Multisig::new(2, members).unwrap();
+//!
+//! let mut tx = Transaction {
+//!     tx_type: TransactionType::Transfer,
+//!     sender: config.address(),
+//!     receiver: Address([9; 32]),
+//!     amount: 100,
+//!     nonce: 0,
+//!     signature: [0u8; 64],
+//!     multisig: Some(MultisigWitness::new(config)),
+//!     data: Vec::new(),
+//! };
+//! for key in &keys[..2] {
+//!     let signature = key.sign(&tx.signing_payload()).to_bytes();
+//!     let signer = Address(key.verifying_key().to_bytes());
+//!     tx.multisig.as_mut().unwrap().add_signature(signer, signature).unwrap();
+//! }
+//! assert!(tx.verify_signature());
+//! ```
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::crypto::blake3_hash_multi;
+use crate::primitives::serde_arr64;
+use crate::{Address, Transaction};
+
+/// Largest member set a multisig account may have.
+pub const MAX_MULTISIG_MEMBERS: usize = 16;
+
+/// Domain tag hashed into every multisig address, so an address can never
+/// collide with the hash of some other structure.
+const ADDRESS_DOMAIN: &[u8] = b"mbongo/multisig/v1";
+
+/// Errors from building or verifying a multisig configuration or witness.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MultisigError {
+    /// The threshold is zero or larger than the member count.
+    #[error("threshold {threshold} is invalid for {members} members")]
+    InvalidThreshold {
+        /// Requested threshold.
+        threshold: u8,
+        /// Number of members.
+        members: usize,
+    },
+    /// More than [`MAX_MULTISIG_MEMBERS`] members.
+    #[error("{0} members exceeds the maximum of {MAX_MULTISIG_MEMBERS}")]
+    TooManyMembers(usize),
+    /// A member is listed twice.
+    #[error("duplicate member {0}")]
+    DuplicateMember(Address),
+    /// Members are not in ascending order.
+    #[error("members are not sorted")]
+    UnsortedMembers,
+    /// The witness configuration does not hash to the transaction sender.
+    #[error("multisig configuration has address {derived}, but the sender is {sender}")]
+    WrongSender {
+        /// Transaction sender.
+        sender: Address,
+        /// Address derived from the witness configuration.
+        derived: Address,
+    },
+    /// A multisig transaction also carries a single-key signature.
+    #[error("multisig transaction has a non-zero single-key signature")]
+    UnexpectedSignature,
+    /// A signer is not a member of the account.
+    #[error("{0} is not a member of the multisig account")]
+    NotAMember(Address),
+    /// A member signed twice, or signatures are out of order.
+    #[error("signatures must be sorted by signer without duplicates")]
+    UnsortedSignatures,
+    /// The account already has `threshold` signatures.
+    #[error("multisig witness already has its {0} required signatures")]
+    ThresholdReached(u8),
+    /// A member's signature does not verify.
+    #[error("invalid signature from {0}")]
+    InvalidSignature(Address),
+    /// Fewer signatures than the threshold.
+    #[error("{got} of {required} required signatures")]
+    NotEnoughSignatures {
+        /// Account threshold.
+        required: u8,
+        /// Signatures present.
+        got: usize,
+    },
+}
+
+/// Configuration of an M-of-N multisig account: `threshold` of `members`
+/// must sign.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize, Encode, Decode)]
+pub struct Multisig {
+    /// Number of member signatures required.
+    pub threshold: u8,
+    /// Member public keys, sorted ascending.
+    pub members: Vec<Address>,
+}
+
+impl Multisig {
+    /// Builds a configuration, sorting `members` so the resulting address
+    /// does not depend on the order they were given in.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultisigError`] if `members` is empty, too large or has
+    /// duplicates, or `threshold` is not between 1 and the member count.
+    pub fn new(threshold: u8, mut members: Vec<Address>) -> Result<Self, MultisigError> {
+        members.sort_unstable_by_key(|m| m.0);
+        if let Some(pair) = members.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(MultisigError::DuplicateMember(pair[0]));
+        }
+        let config = Self { threshold, members };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is canonical: 1 to
+    /// [`MAX_MULTISIG_MEMBERS`] strictly ascending members and a threshold
+    /// no larger than the member count.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`MultisigError`] describing the first problem found.
+    pub fn validate(&self) -> Result<(), MultisigError> {
+        let members = self.members.len();
+        if members > MAX_MULTISIG_MEMBERS {
+            return Err(MultisigError::TooManyMembers(members));
+        }
+        if self.threshold == 0 || usize::from(self.threshold) > members {
+            return Err(MultisigError::InvalidThreshold {
+                threshold: self.threshold,
+                members,
+            });
+        }
+        if self.members.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+            return Err(MultisigError::UnsortedMembers);
+        }
+        Ok(())
+    }
+
+    /// The account address: blake3 over a domain tag and the SCALE-encoded
+    /// configuration.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        Address(blake3_hash_multi(&[ADDRESS_DOMAIN, &self.encode()]))
+    }
+
+    /// Returns `true` if `address` is one of the members.
+    #[must_use]
+    pub fn is_member(&self, address: &Address) -> bool {
+        self.members.binary_search_by_key(&address.0, |m| m.0).is_ok()
+    }
+}
+
+/// One member's signature in a [`MultisigWitness`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+pub struct MultisigSignature {
+    /// Member that signed.
+    pub signer: Address,
+    /// ed25519 signature over the transaction signing payload.
+    #[serde(with = "serde_arr64")]
+    pub signature: [u8; 64],
+}
+
+/// The multisig configuration and member signatures authorizing a
+/// transaction sent from a multisig account.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+pub struct MultisigWitness {
+    /// Configuration whose address is the transaction sender.
+    pub config: Multisig,
+    /// Member signatures, sorted by signer.
+    pub signatures: Vec<MultisigSignature>,
+}
+
+impl MultisigWitness {
+    /// Creates a witness for `config` with no signatures yet.
+    #[must_use]
+    pub fn new(config: Multisig) -> Self {
+        Self {
+            config,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Returns `true` once `threshold` signatures are present.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.signatures.len() >= usize::from(self.config.threshold)
+    }
+
+    /// Adds `signer`'s signature, keeping signatures sorted. The signature
+    /// itself is checked by [`MultisigWitness::verify`], not here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MultisigError::NotAMember`] for outsiders,
+    /// [`MultisigError::UnsortedSignatures`] if `signer` already signed, and
+    /// [`MultisigError::ThresholdReached`] if the witness is complete.
+    pub fn add_signature(
+        &mut self,
+        signer: Address,
+        signature: [u8; 64],
+    ) -> Result<(), MultisigError> {
+        if !self.config.is_member(&signer) {
+            return Err(MultisigError::NotAMember(signer));
+        }
+        if self.is_complete() {
+            return Err(MultisigError::ThresholdReached(self.config.threshold));
+        }
+        match self.signatures.binary_search_by_key(&signer.0, |s| s.signer.0) {
+            Ok(_) => Err(MultisigError::UnsortedSignatures),
+            Err(at) => {
+                self.signatures.insert(at, MultisigSignature { signer, signature });
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks the signatures collected so far for `tx`, without requiring
+    /// the threshold to be met: the configuration is canonical and hashes to
+    /// `tx.sender`, the single-key signature is zero, and every signature is
+    /// from a distinct member, in order, and valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`MultisigError`] describing the first problem found.
+    pub fn verify_partial(&self, tx: &Transaction) -> Result<(), MultisigError> {
+        use ed25519_dalek::{Signature, VerifyingKey};
+
+        self.config.validate()?;
+        let derived = self.config.address();
+        if derived != tx.sender {
+            return Err(MultisigError::WrongSender {
+                sender: tx.sender,
+                derived,
+            });
+        }
+        if tx.signature != [0u8; 64] {
+            return Err(MultisigError::UnexpectedSignature);
+        }
+        if self.signatures.len() > usize::from(self.config.threshold) {
+            return Err(MultisigError::ThresholdReached(self.config.threshold));
+        }
+        if self.signatures.windows(2).any(|pair| pair[0].signer.0 >= pair[1].signer.0) {
+            return Err(MultisigError::UnsortedSignatures);
+        }
+
+        let payload = tx.signing_payload();
+        for s in &self.signatures {
+            if !self.config.is_member(&s.signer) {
+                return Err(MultisigError::NotAMember(s.signer));
+            }
+            let valid = VerifyingKey::from_bytes(&s.signer.0).is_ok_and(|key| {
+                key.verify_strict(&payload, &Signature::from_bytes(&s.signature)).is_ok()
+            });
+            if !valid {
+                return Err(MultisigError::InvalidSignature(s.signer));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the witness fully authorizes `tx`: everything
+    /// [`MultisigWitness::verify_partial`] checks, plus exactly `threshold`
+    /// signatures.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`MultisigError`] describing the first problem found.
+    pub fn verify(&self, tx: &Transaction) -> Result<(), MultisigError> {
+        self.verify_partial(tx)?;
+        if !self.is_complete() {
+            return Err(MultisigError::NotEnoughSignatures {
+                required: self.config.threshold,
+                got: self.signatures.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TransactionType;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn keys() -> Vec<SigningKey> {
+        (1u8..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect()
+    }
+
+    fn address(key: &SigningKey) -> Address {
+        Address(key.verifying_key().to_bytes())
+    }
+
+    fn two_of_three() -> Multisig {
+        Multisig::new(2, keys().iter().map(address).collect()).unwrap()
+    }
+
+    fn unsigned_tx(config: &Multisig) -> Transaction {
+        Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: config.address(),
+            receiver: Address([9; 32]),
+            amount: 100,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: Some(MultisigWitness::new(config.clone())),
+            data: Vec::new(),
+        }
+    }
+
+    fn sign(tx: &mut Transaction, key: &SigningKey) -> Result<(), MultisigError> {
+        let signature = key.sign(&tx.signing_payload()).to_bytes();
+        tx.multisig.as_mut().unwrap().add_signature(address(key), signature)
+    }
+
+    #[test]
+    fn address_ignores_member_order() {
+        let mut reversed: Vec<Address> = keys().iter().map(address).collect();
+        reversed.reverse();
+        let config = Multisig::new(2, reversed).unwrap();
+        assert_eq!(config, two_of_three());
+        assert_eq!(config.address(), two_of_three().address());
+        assert_ne!(
+            Multisig::new(3, config.members.clone()).unwrap().address(),
+            config.address()
+        );
+    }
+
+    #[test]
+    fn invalid_configurations_are_rejected() {
+        let members: Vec<Address> = keys().iter().map(address).collect();
+        assert_eq!(
+            Multisig::new(0, members.clone()),
+            Err(MultisigError::InvalidThreshold {
+                threshold: 0,
+                members: 3
+            })
+        );
+        assert!(matches!(
+            Multisig::new(4, members.clone()),
+            Err(MultisigError::InvalidThreshold { .. })
+        ));
+        assert_eq!(
+            Multisig::new(1, vec![members[0], members[1], members[0]]),
+            Err(MultisigError::DuplicateMember(members[0]))
+        );
+        let many = (0u8..=16).map(|i| Address([i; 32])).collect();
+        assert_eq!(
+            Multisig::new(1, many),
+            Err(MultisigError::TooManyMembers(17))
+        );
+
+        let mut unsorted = two_of_three();
+        unsorted.members.swap(0, 1);
+        assert_eq!(unsorted.validate(), Err(MultisigError::UnsortedMembers));
+    }
+
+    #[test]
+    fn threshold_signatures_authorize_the_transaction() {
+        let keys = keys();
+        let config = two_of_three();
+        let mut tx = unsigned_tx(&config);
+        let witness = |tx: &Transaction| tx.multisig.clone().unwrap();
+
+        sign(&mut tx, &keys[2]).unwrap();
+        witness(&tx).verify_partial(&tx).unwrap();
+        assert_eq!(
+            witness(&tx).verify(&tx),
+            Err(MultisigError::NotEnoughSignatures {
+                required: 2,
+                got: 1
+            })
+        );
+        assert!(!tx.verify_signature());
+        assert_eq!(
+            sign(&mut tx, &keys[2]),
+            Err(MultisigError::UnsortedSignatures)
+        );
+
+        sign(&mut tx, &keys[0]).unwrap();
+        witness(&tx).verify(&tx).unwrap();
+        assert!(tx.verify_signature());
+        assert_eq!(
+            sign(&mut tx, &keys[1]),
+            Err(MultisigError::ThresholdReached(2))
+        );
+
+        let outsider = SigningKey::from_bytes(&[7; 32]);
+        let mut other = unsigned_tx(&config);
+        assert_eq!(
+            sign(&mut other, &outsider),
+            Err(MultisigError::NotAMember(address(&outsider)))
+        );
+    }
+
+    #[test]
+    fn tampered_witnesses_fail() {
+        let keys = keys();
+        let mut tx = unsigned_tx(&two_of_three());
+        sign(&mut tx, &keys[0]).unwrap();
+        sign(&mut tx, &keys[1]).unwrap();
+        let witness = tx.multisig.clone().unwrap();
+
+        let mut changed = tx.clone();
+        changed.amount += 1;
+        assert_eq!(
+            witness.verify(&changed),
+            Err(MultisigError::InvalidSignature(
+                witness.signatures[0].signer
+            ))
+        );
+
+        let mut with_single_signature = tx.clone();
+        with_single_signature.signature = [1u8; 64];
+        assert_eq!(
+            witness.verify(&with_single_signature),
+            Err(MultisigError::UnexpectedSignature)
+        );
+
+        let mut other_account = tx.clone();
+        other_account.sender = Address([5; 32]);
+        assert!(matches!(
+            witness.verify(&other_account),
+            Err(MultisigError::WrongSender { .. })
+        ));
+
+        let mut reordered = witness.clone();
+        reordered.signatures.swap(0, 1);
+        assert_eq!(
+            reordered.verify(&tx),
+            Err(MultisigError::UnsortedSignatures)
+        );
+
+        let mut lowered = witness;
+        lowered.config.threshold = 1;
+        assert!(matches!(
+            lowered.verify(&tx),
+            Err(MultisigError::WrongSender { .. })
+        ));
+    }
+
+    #[test]
+    fn single_key_transactions_cannot_spend_from_a_multisig_address() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let mut tx = unsigned_tx(&two_of_three());
+        tx.multisig = None;
+        tx.signature = key.sign(&tx.signing_payload()).to_bytes();
+        assert!(!tx.verify_signature());
+    }
+}
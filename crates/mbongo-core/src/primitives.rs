@@ -1,6 +1,8 @@
-use parity_scale_codec::{Decode, Encode};
+use parity_scale_codec::{Decode, DecodeAll, Encode, EncodeLike, Input, Output};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::multisig::MultisigWitness;
+
 /// 32-byte hash used across headers and roots.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Encode, Decode)]
 pub struct Hash(pub [u8; 32]);
@@ -100,10 +102,29 @@ pub enum TransactionType {
     ComputeTask,
     /// Stake `amount` to validator or staking contract.
     Stake,
+    /// Deploy the WASM module in `data` as a new contract at
+    /// [`Transaction::deployed_address`], endowed with `amount`.
+    Deploy,
+    /// Call the contract at `receiver` with the call payload in `data`,
+    /// sending it `amount`.
+    Call,
 }
 
+/// Leading byte of the SCALE encoding of a transaction that carries a
+/// [`MultisigWitness`]. Single-key transactions start with their
+/// [`TransactionType`] index instead, which is never this value, so their
+/// encoding (and hash) is unchanged by multisig support.
+const MULTISIG_TAG: u8 = 0xFF;
+
+/// Leading byte (after any [`MULTISIG_TAG`]) of the SCALE encoding of a
+/// transaction with a non-empty `data` payload, which is appended last.
+const DATA_TAG: u8 = 0xFE;
+
 /// Transaction structure (SCALE serializable) with ed25519 signature.
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+///
+/// Transactions from a multisig account carry a [`MultisigWitness`] and an
+/// all-zero `signature`; see [`crate::multisig`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Transaction {
     /// Transaction type.
     pub tx_type: TransactionType,
@@ -118,10 +139,99 @@ pub struct Transaction {
     /// ed25519 signature over the signing payload.
     #[serde(with = "serde_arr64")]
     pub signature: [u8; 64],
+    /// Member signatures when `sender` is a multisig account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigWitness>,
+    /// Contract code for [`TransactionType::Deploy`] or the call payload for
+    /// [`TransactionType::Call`]; empty for other types.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_hex_bytes"
+    )]
+    pub data: Vec<u8>,
+}
+
+impl Encode for Transaction {
+    fn size_hint(&self) -> usize {
+        1 + 32
+            + 32
+            + 16
+            + 8
+            + 64
+            + self.multisig.as_ref().map_or(0, |w| 1 + w.size_hint())
+            + if self.data.is_empty() {
+                0
+            } else {
+                1 + self.data.size_hint()
+            }
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        if self.multisig.is_some() {
+            dest.push_byte(MULTISIG_TAG);
+        }
+        if !self.data.is_empty() {
+            dest.push_byte(DATA_TAG);
+        }
+        self.tx_type.encode_to(dest);
+        self.sender.encode_to(dest);
+        self.receiver.encode_to(dest);
+        self.amount.encode_to(dest);
+        self.nonce.encode_to(dest);
+        self.signature.encode_to(dest);
+        if let Some(witness) = &self.multisig {
+            witness.encode_to(dest);
+        }
+        if !self.data.is_empty() {
+            self.data.encode_to(dest);
+        }
+    }
+}
+
+impl EncodeLike for Transaction {}
+
+impl Decode for Transaction {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, parity_scale_codec::Error> {
+        let mut byte = input.read_byte()?;
+        let is_multisig = byte == MULTISIG_TAG;
+        if is_multisig {
+            byte = input.read_byte()?;
+        }
+        let has_data = byte == DATA_TAG;
+        if has_data {
+            byte = input.read_byte()?;
+        }
+        let tx = Self {
+            tx_type: TransactionType::decode(&mut &[byte][..])?,
+            sender: Address::decode(input)?,
+            receiver: Address::decode(input)?,
+            amount: u128::decode(input)?,
+            nonce: u64::decode(input)?,
+            signature: <[u8; 64]>::decode(input)?,
+            multisig: if is_multisig {
+                Some(MultisigWitness::decode(input)?)
+            } else {
+                None
+            },
+            data: if has_data {
+                Vec::decode(input)?
+            } else {
+                Vec::new()
+            },
+        };
+        if has_data && tx.data.is_empty() {
+            return Err("tagged transaction data must not be empty".into());
+        }
+        Ok(tx)
+    }
 }
 
 impl Transaction {
     /// Returns SCALE-encoded signing payload (all fields except signature).
+    ///
+    /// A non-empty `data` payload is appended, so transactions without one
+    /// sign the same bytes as before contracts existed.
     #[must_use]
     pub fn signing_payload(&self) -> Vec<u8> {
         #[derive(Encode)]
@@ -132,25 +242,69 @@ impl Transaction {
             amount: u128,
             nonce: u64,
         }
-        Payload {
+        let mut payload = Payload {
             tx_type: self.tx_type,
             sender: self.sender,
             receiver: self.receiver,
             amount: self.amount,
             nonce: self.nonce,
         }
-        .encode()
+        .encode();
+        if !self.data.is_empty() {
+            self.data.encode_to(&mut payload);
+        }
+        payload
     }
 
-    /// Verifies signature using ed25519 and sender's public key.
+    /// Address of the contract a [`TransactionType::Deploy`] transaction
+    /// creates: `blake3("mbongo/contract/v1" ‖ sender ‖ nonce)`, with the
+    /// nonce little-endian. Unique because a sender never reuses a nonce.
+    #[must_use]
+    pub fn deployed_address(&self) -> Address {
+        Address(crate::crypto::blake3_hash_multi(&[
+            b"mbongo/contract/v1",
+            &self.sender.0,
+            &self.nonce.to_le_bytes(),
+        ]))
+    }
+
+    /// Verifies signature using ed25519 and sender's public key, or the
+    /// [`MultisigWitness`] of a multisig transaction.
+    ///
+    /// Uses `verify_strict`, which rejects small-order keys and nonces and
+    /// signatures that only hold up to a small-order component. This is the
+    /// only signature rule: [`verify_transaction_signatures`] applies it too.
     #[must_use]
     pub fn verify_signature(&self) -> bool {
-        use ed25519_dalek::{Signature, Verifier};
+        use ed25519_dalek::Signature;
+        if let Some(witness) = &self.multisig {
+            return witness.verify(self).is_ok();
+        }
         let Ok(pk) = ed25519_dalek::VerifyingKey::from_bytes(&self.sender.0) else {
             return false;
         };
         let sig = Signature::from_bytes(&self.signature);
-        pk.verify(&self.signing_payload(), &sig).is_ok()
+        pk.verify_strict(&self.signing_payload(), &sig).is_ok()
+    }
+
+    /// Returns the SCALE encoding of the whole transaction, signature
+    /// included, as `0x`-prefixed hex. Inverse of [`Transaction::decode_hex`].
+    #[must_use]
+    pub fn encode_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.encode()))
+    }
+
+    /// Decodes a transaction from hex-encoded SCALE bytes, with or without a
+    /// `0x` prefix.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if `s` is not valid hex, does not decode as a
+    /// transaction, or has trailing bytes.
+    pub fn decode_hex(s: &str) -> Result<Self, String> {
+        let s = s.strip_prefix("0x").unwrap_or(s);
+        let bytes = hex::decode(s).map_err(|e| e.to_string())?;
+        Self::decode_all(&mut bytes.as_slice()).map_err(|e| e.to_string())
     }
 }
 
@@ -204,8 +358,39 @@ pub fn compute_transactions_root(txs: &[Transaction]) -> Hash {
     Hash(out)
 }
 
+/// Verifies the signature of every transaction in `txs`, returning the index
+/// of the first invalid one.
+///
+/// The verdict is exactly that of [`Transaction::verify_signature`] on each
+/// transaction, so blocks and the mempool accept the same signatures.
+/// Transactions are checked in parallel over the rayon thread pool. Ed25519
+/// batch verification is not used: its equation is weaker than
+/// `verify_strict`, so a passing batch would still need every signature
+/// checked on its own.
+///
+/// # Errors
+///
+/// Returns the index of the first transaction whose signature is invalid.
+pub fn verify_transaction_signatures(txs: &[Transaction]) -> Result<(), usize> {
+    use rayon::prelude::*;
+
+    txs.par_iter().position_first(|tx| !tx.verify_signature()).map_or(Ok(()), Err)
+}
+
 // Serde helpers for fixed-size 64-byte arrays as hex strings
-mod serde_arr64 {
+pub(crate) mod serde_hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+    #[allow(clippy::ptr_arg)] // signature required by `#[serde(with)]`
+    pub fn serialize<S: Serializer>(v: &Vec<u8>, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("0x{}", hex::encode(v)))
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(d)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(serde::de::Error::custom)
+    }
+}
+
+pub(crate) mod serde_arr64 {
     use serde::{Deserialize, Deserializer, Serializer};
     pub fn serialize<S: Serializer>(v: &[u8; 64], s: S) -> Result<S::Ok, S::Error> {
         s.serialize_str(&format!("0x{}", hex::encode(v)))
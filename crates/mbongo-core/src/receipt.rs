@@ -0,0 +1,383 @@
+//! Transaction receipts recording where and how a transaction was executed.
+
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use crate::logs::{event_topic, Bloom, Log, LogEntry};
+use crate::{Address, Hash, Transaction, TransactionType};
+
+/// Outcome of executing an included transaction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptStatus {
+    /// The transaction executed and its state changes were committed.
+    Success,
+    /// The transaction was included but execution failed; only the fee was charged.
+    Failed,
+}
+
+/// A state change emitted while executing a transaction.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::cast_possible_truncation)] // variant index cast in the SCALE derive
+pub enum Event {
+    /// `amount` moved from `from` to `to`.
+    Transfer {
+        /// Debited account.
+        from: Address,
+        /// Credited account.
+        to: Address,
+        /// Amount moved.
+        amount: u128,
+    },
+    /// `staker` staked `amount` with `validator`.
+    Stake {
+        /// Account that staked.
+        staker: Address,
+        /// Validator or staking contract receiving the stake.
+        validator: Address,
+        /// Amount staked.
+        amount: u128,
+    },
+    /// `requester` paid `provider` for a compute task.
+    ComputeTask {
+        /// Account that submitted and paid for the task.
+        requester: Address,
+        /// Compute provider receiving the payment.
+        provider: Address,
+        /// Payment in compute units.
+        payment: u128,
+    },
+    /// `deployer` deployed a contract running `code_hash` at `contract`.
+    ContractDeployed {
+        /// Account that sent the deploy transaction.
+        deployer: Address,
+        /// Address of the new contract.
+        contract: Address,
+        /// Blake3 hash of the contract code.
+        code_hash: Hash,
+        /// Amount endowed to the contract.
+        value: u128,
+    },
+    /// `caller` called `contract`, sending it `value`.
+    ContractCalled {
+        /// Account that sent the call transaction.
+        caller: Address,
+        /// Contract that was called.
+        contract: Address,
+        /// Amount sent with the call.
+        value: u128,
+    },
+    /// A contract emitted `Log` while running.
+    Log(Log),
+}
+
+impl Event {
+    /// Returns the event emitted by successfully executing `tx`.
+    #[must_use]
+    pub fn from_transaction(tx: &Transaction) -> Self {
+        match tx.tx_type {
+            TransactionType::Transfer => Self::Transfer {
+                from: tx.sender,
+                to: tx.receiver,
+                amount: tx.amount,
+            },
+            TransactionType::Stake => Self::Stake {
+                staker: tx.sender,
+                validator: tx.receiver,
+                amount: tx.amount,
+            },
+            TransactionType::ComputeTask => Self::ComputeTask {
+                requester: tx.sender,
+                provider: tx.receiver,
+                payment: tx.amount,
+            },
+            TransactionType::Deploy => Self::ContractDeployed {
+                deployer: tx.sender,
+                contract: tx.deployed_address(),
+                code_hash: Hash(crate::crypto::blake3_hash(&tx.data)),
+                value: tx.amount,
+            },
+            TransactionType::Call => Self::ContractCalled {
+                caller: tx.sender,
+                contract: tx.receiver,
+                value: tx.amount,
+            },
+        }
+    }
+
+    /// Returns the event as a [`Log`].
+    ///
+    /// Native events are logged under the account that acted, or the
+    /// contract for contract events. Their first topic is [`event_topic`]
+    /// of the variant name, followed by the other addresses involved; the
+    /// amount is the SCALE-encoded data.
+    ///
+    /// | Event | Address | Topics after the name | Data |
+    /// |-------|---------|-----------------------|------|
+    /// | `Transfer` | `from` | `to` | `amount` |
+    /// | `Stake` | `staker` | `validator` | `amount` |
+    /// | `ComputeTask` | `requester` | `provider` | `payment` |
+    /// | `ContractDeployed` | `contract` | `deployer`, `code_hash` | `value` |
+    /// | `ContractCalled` | `contract` | `caller` | `value` |
+    ///
+    /// Logs emitted by contracts are returned as they were emitted.
+    #[must_use]
+    pub fn to_log(&self) -> Log {
+        let native = |name: &str, address: Address, topics: &[Hash], amount: u128| Log {
+            address,
+            topics: std::iter::once(event_topic(name)).chain(topics.iter().copied()).collect(),
+            data: amount.encode(),
+        };
+        match self {
+            Self::Transfer { from, to, amount } => {
+                native("Transfer", *from, &[Hash(to.0)], *amount)
+            }
+            Self::Stake {
+                staker,
+                validator,
+                amount,
+            } => native("Stake", *staker, &[Hash(validator.0)], *amount),
+            Self::ComputeTask {
+                requester,
+                provider,
+                payment,
+            } => native("ComputeTask", *requester, &[Hash(provider.0)], *payment),
+            Self::ContractDeployed {
+                deployer,
+                contract,
+                code_hash,
+                value,
+            } => native(
+                "ContractDeployed",
+                *contract,
+                &[Hash(deployer.0), *code_hash],
+                *value,
+            ),
+            Self::ContractCalled {
+                caller,
+                contract,
+                value,
+            } => native("ContractCalled", *contract, &[Hash(caller.0)], *value),
+            Self::Log(log) => log.clone(),
+        }
+    }
+}
+
+/// Record of a transaction's inclusion in a block and its execution result.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize, Encode, Decode)]
+pub struct Receipt {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// Hash of the block that included the transaction.
+    pub block_hash: Hash,
+    /// Height of the block that included the transaction.
+    pub block_height: u64,
+    /// Position of the transaction in the block body.
+    pub index: u32,
+    /// Execution outcome.
+    pub status: ReceiptStatus,
+    /// Fee paid by the sender for the gas used; 0 for native transactions.
+    pub fee: u128,
+    /// Gas consumed by contract execution; 0 for native transactions.
+    pub gas_used: u64,
+    /// Why execution failed, for [`ReceiptStatus::Failed`].
+    pub error: Option<String>,
+    /// Events emitted during execution, in order.
+    pub events: Vec<Event>,
+    /// Bloom of the addresses and topics of [`Receipt::logs`].
+    pub bloom: Bloom,
+}
+
+impl Receipt {
+    /// Builds the receipt for `tx` executed successfully at `index` in the
+    /// block `block_hash` at `block_height`.
+    #[must_use]
+    pub fn success(
+        tx_hash: Hash,
+        tx: &Transaction,
+        block_hash: Hash,
+        block_height: u64,
+        index: u32,
+    ) -> Self {
+        let event = Event::from_transaction(tx);
+        Self {
+            tx_hash,
+            block_hash,
+            block_height,
+            index,
+            status: ReceiptStatus::Success,
+            fee: 0,
+            gas_used: 0,
+            error: None,
+            bloom: Bloom::from_logs([&event.to_log()]),
+            events: vec![event],
+        }
+    }
+
+    /// Appends the logs a contract emitted while executing the transaction.
+    #[must_use]
+    pub fn with_logs(mut self, logs: Vec<Log>) -> Self {
+        for log in logs {
+            self.bloom.accrue_log(&log);
+            self.events.push(Event::Log(log));
+        }
+        self
+    }
+
+    /// Records the gas the transaction consumed and the fee paid for it.
+    #[must_use]
+    pub fn with_gas(mut self, gas_used: u64, fee: u128) -> Self {
+        self.gas_used = gas_used;
+        self.fee = fee;
+        self
+    }
+
+    /// Builds the receipt for a transaction that was included at `index` in
+    /// the block `block_hash` at `block_height` but failed to execute because
+    /// of `error`. Only the sender's nonce and fee were applied, so no events
+    /// are recorded.
+    #[must_use]
+    pub fn failed(
+        tx_hash: Hash,
+        block_hash: Hash,
+        block_height: u64,
+        index: u32,
+        error: String,
+    ) -> Self {
+        Self {
+            tx_hash,
+            block_hash,
+            block_height,
+            index,
+            status: ReceiptStatus::Failed,
+            fee: 0,
+            gas_used: 0,
+            error: Some(error),
+            events: Vec::new(),
+            bloom: Bloom::default(),
+        }
+    }
+
+    /// Returns the logs of the receipt's events, in order.
+    pub fn logs(&self) -> impl Iterator<Item = Log> + '_ {
+        self.events.iter().map(Event::to_log)
+    }
+
+    /// Returns the receipt's logs with their block and transaction position.
+    pub fn log_entries(&self) -> impl Iterator<Item = LogEntry> + '_ {
+        (0u32..).zip(self.logs()).map(|(log_index, log)| LogEntry {
+            block_height: self.block_height,
+            block_hash: self.block_hash,
+            tx_hash: self.tx_hash,
+            tx_index: self.index,
+            log_index,
+            log,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_follows_transaction_type() {
+        let mut tx = Transaction {
+            tx_type: TransactionType::Stake,
+            sender: Address([1u8; 32]),
+            receiver: Address([2u8; 32]),
+            amount: 7,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        assert_eq!(
+            Event::from_transaction(&tx),
+            Event::Stake {
+                staker: Address([1u8; 32]),
+                validator: Address([2u8; 32]),
+                amount: 7
+            }
+        );
+        tx.tx_type = TransactionType::ComputeTask;
+        assert!(matches!(
+            Event::from_transaction(&tx),
+            Event::ComputeTask { payment: 7, .. }
+        ));
+    }
+
+    #[test]
+    fn receipt_scale_and_json_roundtrip() {
+        let tx = Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: Address([1u8; 32]),
+            receiver: Address([2u8; 32]),
+            amount: 5,
+            nonce: 3,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        let receipt = Receipt::success(Hash([3u8; 32]), &tx, Hash([4u8; 32]), 9, 2);
+
+        let decoded = Receipt::decode(&mut &receipt.encode()[..]).unwrap();
+        assert_eq!(decoded, receipt);
+
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["events"][0]["transfer"]["amount"], 5);
+        let back: Receipt = serde_json::from_value(json).unwrap();
+        assert_eq!(back, receipt);
+
+        let failed = Receipt::failed(Hash([3u8; 32]), Hash([4u8; 32]), 9, 2, "out of gas".into())
+            .with_gas(700, 700);
+        assert_eq!(Receipt::decode(&mut &failed.encode()[..]).unwrap(), failed);
+        let json = serde_json::to_value(&failed).unwrap();
+        assert_eq!(json["error"], "out of gas");
+        assert_eq!(json["gas_used"], 700);
+    }
+
+    #[test]
+    fn receipt_logs_are_indexed_by_the_bloom() {
+        let tx = Transaction {
+            tx_type: TransactionType::Call,
+            sender: Address([1u8; 32]),
+            receiver: Address([2u8; 32]),
+            amount: 5,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
+        let emitted = Log {
+            address: Address([2u8; 32]),
+            topics: vec![Hash([9u8; 32])],
+            data: b"hi".to_vec(),
+        };
+        let receipt = Receipt::success(Hash([3u8; 32]), &tx, Hash([4u8; 32]), 9, 2)
+            .with_logs(vec![emitted.clone()]);
+
+        let logs: Vec<Log> = receipt.logs().collect();
+        assert_eq!(
+            logs[0],
+            Log {
+                address: Address([2u8; 32]),
+                topics: vec![event_topic("ContractCalled"), Hash([1u8; 32])],
+                data: 5u128.encode(),
+            }
+        );
+        assert_eq!(logs[1], emitted);
+        assert_eq!(receipt.bloom, Bloom::from_logs(&logs));
+        assert!(receipt.bloom.contains(&[9u8; 32]));
+
+        let entries: Vec<LogEntry> = receipt.log_entries().collect();
+        assert_eq!((entries[1].block_height, entries[1].tx_index), (9, 2));
+        assert_eq!(entries[1].log_index, 1);
+
+        let failed = Receipt::failed(Hash([3u8; 32]), Hash([4u8; 32]), 9, 2, "trap".into());
+        assert!(failed.bloom.is_empty());
+        assert_eq!(failed.logs().count(), 0);
+    }
+}
@@ -0,0 +1,117 @@
+//! Parallel execution must be indistinguishable from sequential execution:
+//! same touched accounts, same balances and nonces, same failing index and
+//! error, for any block.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use mbongo_core::execution::{conflict_groups, execute_parallel, execute_sequential};
+use mbongo_core::{Account, Address, Transaction, TransactionType};
+use proptest::prelude::*;
+
+/// Accounts 0..8 exist with small balances so that overdrafts, nonce gaps
+/// and unknown senders all show up in generated blocks.
+fn genesis() -> HashMap<Address, Account> {
+    (0u8..8)
+        .map(|i| {
+            let address = Address([i; 32]);
+            let account = Account {
+                address,
+                balance: u128::from(i) * 50,
+                nonce: 0,
+            };
+            (address, account)
+        })
+        .collect()
+}
+
+prop_compose! {
+    fn arb_tx()(
+        sender in 0u8..12,
+        receiver in 0u8..12,
+        amount in 0u128..120,
+        nonce in 0u64..4,
+    ) -> Transaction {
+        Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: Address([sender; 32]),
+            receiver: Address([receiver; 32]),
+            amount,
+            nonce,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        }
+    }
+}
+
+/// A block where every sender uses its next nonce and pays someone else, so
+/// every transaction succeeds.
+fn valid_block(senders: &[(u8, u8)]) -> Vec<Transaction> {
+    let mut nonces: HashMap<u8, u64> = HashMap::new();
+    senders
+        .iter()
+        .map(|&(sender, receiver)| {
+            let receiver = if receiver == sender {
+                receiver + 40
+            } else {
+                receiver
+            };
+            let nonce = nonces.entry(sender).or_default();
+            *nonce += 1;
+            Transaction {
+                tx_type: TransactionType::Transfer,
+                sender: Address([sender; 32]),
+                receiver: Address([receiver; 32]),
+                amount: 1,
+                nonce: *nonce - 1,
+                signature: [0u8; 64],
+                multisig: None,
+                data: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn parallel_matches_sequential(block in proptest::collection::vec(arb_tx(), 0..64)) {
+        let state = genesis();
+        let load = |address: &Address| Ok::<_, Infallible>(state.get(address).cloned());
+        let txs: Vec<&Transaction> = block.iter().collect();
+        prop_assert_eq!(execute_parallel(&load, &txs), execute_sequential(&load, &txs));
+    }
+
+    #[test]
+    fn parallel_matches_sequential_on_valid_blocks(
+        senders in proptest::collection::vec((1u8..8, 0u8..40), 0..200),
+    ) {
+        let state = genesis();
+        let load = |address: &Address| Ok::<_, Infallible>(state.get(address).cloned());
+        let block = valid_block(&senders);
+        let txs: Vec<&Transaction> = block.iter().collect();
+        let parallel = execute_parallel(&load, &txs);
+        prop_assert!(parallel.is_ok());
+        prop_assert_eq!(parallel, execute_sequential(&load, &txs));
+    }
+
+    #[test]
+    fn groups_partition_the_block(block in proptest::collection::vec(arb_tx(), 0..64)) {
+        let txs: Vec<&Transaction> = block.iter().collect();
+        let groups = conflict_groups(&txs);
+
+        let mut seen: Vec<usize> = groups.iter().flatten().copied().collect();
+        seen.sort_unstable();
+        prop_assert_eq!(seen, (0..block.len()).collect::<Vec<_>>());
+
+        let mut owner: HashMap<Address, usize> = HashMap::new();
+        for (g, group) in groups.iter().enumerate() {
+            prop_assert!(group.windows(2).all(|w| w[0] < w[1]));
+            for &i in group {
+                for address in [block[i].sender, block[i].receiver] {
+                    prop_assert_eq!(*owner.entry(address).or_insert(g), g);
+                }
+            }
+        }
+    }
+}
@@ -27,7 +27,7 @@ prop_compose! {
         nonce in any::<u64>(),
         signature in arb_signature(),
     ) -> Transaction {
-        Transaction { tx_type, sender, receiver, amount, nonce, signature }
+        Transaction { tx_type, sender, receiver, amount, nonce, signature, multisig: None, data: Vec::new() }
     }
 }
 
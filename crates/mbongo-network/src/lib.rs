@@ -12,10 +12,14 @@
 #![warn(clippy::all)]
 #![warn(clippy::pedantic)]
 
+/// Built-in JSON-RPC methods grouped by namespace.
+pub mod methods;
 /// Minimal libp2p networking node (ping, identify, mDNS, block-sync).
 pub mod p2p;
 /// Block sync protocol messages and SCALE codec.
 pub mod p2p_protocol;
+/// Typed JSON-RPC method registry with namespaces and param decoding.
+pub mod registry;
 /// JSON-RPC 2.0 request/response types and backend trait.
 pub mod rpc;
 /// HTTP server wiring (Axum router + serve).
@@ -28,10 +32,13 @@ pub use crate::p2p_protocol::{
     BlockNotifyAck, BlockNotifyCodec, SyncCodec, SyncNotification, SyncRequest, SyncResponse,
     BLOCK_NOTIFY_PROTOCOL, MAX_RANGE, SYNC_PROTOCOL,
 };
+pub use crate::registry::{CallError, Namespace, NoParams, RpcModule, RpcParams};
 pub use crate::rpc::{
-    BackendError, JsonRpcRequest, JsonRpcResponse, RpcBackend, RpcError, RpcErrorCode,
+    AccountDiff, AccountInfo, AccountTransaction, BackendError, EncodedTransaction, JsonRpcRequest,
+    JsonRpcResponse, MempoolContent, MempoolStatus, PendingTransaction, RpcBackend, RpcError,
+    RpcErrorCode, SimulationResult, TransactionLookup, TransactionStatus,
 };
-pub use crate::server::{router, serve_on_addr};
+pub use crate::server::{router, router_with_module, serve_on_addr};
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,273 @@
+use mbongo_core::{Address, Hash, LogFilter, Transaction};
+use serde::{Deserialize, Deserializer};
+
+use crate::registry::{Namespace, NoParams, RpcModule, RpcParams};
+use crate::rpc::{EncodedTransaction, RpcBackend};
+
+/// Parameters of `chain_get_block_by_height`.
+#[derive(Debug, Deserialize)]
+pub struct HeightParams {
+    /// Block height.
+    pub height: u64,
+}
+
+impl RpcParams for HeightParams {
+    const NAMES: &'static [&'static str] = &["height"];
+}
+
+/// Parameters of methods that look up a transaction by hash.
+#[derive(Debug, Deserialize)]
+pub struct HashParams {
+    /// Hex-encoded transaction hash.
+    pub hash: Hash,
+}
+
+impl RpcParams for HashParams {
+    const NAMES: &'static [&'static str] = &["hash"];
+}
+
+/// Parameters of the `state_` account queries.
+#[derive(Debug, Deserialize)]
+pub struct AddressParams {
+    /// Account address.
+    pub address: Address,
+}
+
+impl RpcParams for AddressParams {
+    const NAMES: &'static [&'static str] = &["address"];
+}
+
+/// Default page size of the paginated methods.
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+/// Largest page the paginated methods return.
+pub const MAX_PAGE_SIZE: usize = 1000;
+
+/// Parameters of `state_get_account_transactions`.
+#[derive(Debug, Deserialize)]
+pub struct AccountTransactionsParams {
+    /// Account address.
+    pub address: Address,
+    /// Only list transactions from blocks below this height.
+    pub before: Option<u64>,
+    /// Page size. Defaults to [`DEFAULT_PAGE_SIZE`], capped at
+    /// [`MAX_PAGE_SIZE`].
+    pub limit: Option<usize>,
+}
+
+impl RpcParams for AccountTransactionsParams {
+    const NAMES: &'static [&'static str] = &["address", "before", "limit"];
+}
+
+/// Most blocks a single `chain_get_logs` call may search.
+pub const MAX_LOG_BLOCK_RANGE: u64 = 1000;
+
+/// Most log entries a single `chain_get_logs` call may return.
+pub const MAX_LOG_ENTRIES: usize = 10_000;
+
+/// Parameters of `chain_get_logs`.
+impl RpcParams for LogFilter {
+    const NAMES: &'static [&'static str] = &["from_height", "to_height", "address", "topics"];
+}
+
+/// Parameters of `mempool_content`.
+#[derive(Debug, Deserialize)]
+pub struct MempoolContentParams {
+    /// Only list transactions sent from this address.
+    pub sender: Option<Address>,
+    /// Number of matching transactions to skip. Defaults to 0.
+    #[serde(default)]
+    pub offset: usize,
+    /// Page size. Defaults to [`DEFAULT_PAGE_SIZE`], capped at
+    /// [`MAX_PAGE_SIZE`].
+    pub limit: Option<usize>,
+}
+
+impl RpcParams for MempoolContentParams {
+    const NAMES: &'static [&'static str] = &["sender", "offset", "limit"];
+}
+
+/// Parameters of `author_submit_transaction` and
+/// `author_encode_transaction`.
+#[derive(Debug, Deserialize)]
+pub struct SubmitTransactionParams {
+    /// The transaction; must be signed unless only encoding it.
+    pub tx: Transaction,
+}
+
+impl RpcParams for SubmitTransactionParams {
+    const NAMES: &'static [&'static str] = &["tx"];
+}
+
+/// Parameters of `author_submit_raw_transaction`.
+#[derive(Debug, Deserialize)]
+pub struct RawTransactionParams {
+    /// The signed transaction, sent as hex-encoded SCALE bytes.
+    #[serde(deserialize_with = "scale_hex")]
+    pub raw: Transaction,
+}
+
+impl RpcParams for RawTransactionParams {
+    const NAMES: &'static [&'static str] = &["raw"];
+}
+
+/// Deserializes a transaction from hex-encoded SCALE bytes, so malformed
+/// input is reported as invalid params.
+fn scale_hex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Transaction, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    Transaction::decode_hex(&raw).map_err(serde::de::Error::custom)
+}
+
+/// Parameters of `author_simulate_transaction`.
+#[derive(Debug, Deserialize)]
+pub struct SimulateTransactionParams {
+    /// The transaction to dry-run; may be unsigned.
+    pub tx: Transaction,
+    /// Whether to check the signature. Defaults to `false`.
+    #[serde(default)]
+    pub verify_signature: bool,
+}
+
+impl RpcParams for SimulateTransactionParams {
+    const NAMES: &'static [&'static str] = &["tx", "verify_signature"];
+}
+
+/// `chain_` methods: blocks, transactions and receipts.
+#[must_use]
+pub fn chain_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut m = RpcModule::new();
+    m.register("get_block_height", |b: B, _: NoParams| async move {
+        b.get_block_height().await
+    });
+    m.register("get_latest_block_hash", |b: B, _: NoParams| async move {
+        b.get_latest_block_hash().await
+    });
+    m.register("get_block_by_height", |b: B, p: HeightParams| async move {
+        b.get_block_by_height(p.height).await
+    });
+    m.register("get_transaction", |b: B, p: HashParams| async move {
+        b.get_transaction(p.hash).await
+    });
+    m.register(
+        "get_transaction_receipt",
+        |b: B, p: HashParams| async move { b.get_transaction_receipt(p.hash).await },
+    );
+    m.register("get_logs", |b: B, filter: LogFilter| async move {
+        b.get_logs(filter).await
+    });
+    m
+}
+
+/// `state_` methods: account and world state queries.
+#[must_use]
+pub fn state_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut m = RpcModule::new();
+    m.register("get_account", |b: B, p: AddressParams| async move {
+        b.get_account(p.address).await
+    });
+    m.register("get_balance", |b: B, p: AddressParams| async move {
+        b.get_balance(p.address).await
+    });
+    m.register("get_next_nonce", |b: B, p: AddressParams| async move {
+        b.get_next_nonce(p.address).await
+    });
+    m.register(
+        "get_account_transactions",
+        |b: B, p: AccountTransactionsParams| async move {
+            let limit = p.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+            b.get_account_transactions(p.address, p.before, limit).await
+        },
+    );
+    m
+}
+
+/// `author_` methods: transaction submission and block authoring.
+#[must_use]
+pub fn author_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut m = RpcModule::new();
+    m.register(
+        "submit_transaction",
+        |b: B, p: SubmitTransactionParams| async move { b.submit_transaction(p.tx).await },
+    );
+    m.register(
+        "submit_raw_transaction",
+        |b: B, p: RawTransactionParams| async move { b.submit_transaction(p.raw).await },
+    );
+    m.register(
+        "encode_transaction",
+        |_: B, p: SubmitTransactionParams| async move { Ok(EncodedTransaction::new(&p.tx)) },
+    );
+    m.register(
+        "simulate_transaction",
+        |b: B, p: SimulateTransactionParams| async move {
+            b.simulate_transaction(p.tx, p.verify_signature).await
+        },
+    );
+    m.register("produce_block", |b: B, _: NoParams| async move {
+        b.produce_block().await
+    });
+    m
+}
+
+/// `system_` methods: node health.
+#[must_use]
+pub fn system_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut m = RpcModule::new();
+    m.register("ping", |b: B, _: NoParams| async move { b.ping().await });
+    m
+}
+
+/// `mempool_` methods: pending transaction inspection.
+#[must_use]
+pub fn mempool_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut m = RpcModule::new();
+    m.register("status", |b: B, _: NoParams| async move {
+        b.mempool_status().await
+    });
+    m.register("content", |b: B, p: MempoolContentParams| async move {
+        let limit = p.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+        b.mempool_content(p.sender, p.offset, limit).await
+    });
+    m.register(
+        "get_pending_transaction",
+        |b: B, p: HashParams| async move { b.get_pending_transaction(p.hash).await },
+    );
+    m
+}
+
+/// Unprefixed names kept for clients written before namespaces existed.
+const LEGACY_ALIASES: &[(&str, &str)] = &[
+    ("ping", "system_ping"),
+    ("get_block_height", "chain_get_block_height"),
+    ("get_latest_block_hash", "chain_get_latest_block_hash"),
+    ("get_block_by_height", "chain_get_block_by_height"),
+    ("get_transaction", "chain_get_transaction"),
+    ("get_transaction_receipt", "chain_get_transaction_receipt"),
+    ("get_logs", "chain_get_logs"),
+    ("get_pending_transaction", "mempool_get_pending_transaction"),
+    ("get_account", "state_get_account"),
+    ("get_balance", "state_get_balance"),
+    ("get_next_nonce", "state_get_next_nonce"),
+    ("get_account_transactions", "state_get_account_transactions"),
+    ("submit_transaction", "author_submit_transaction"),
+    ("submit_raw_transaction", "author_submit_raw_transaction"),
+    ("encode_transaction", "author_encode_transaction"),
+    ("simulate_transaction", "author_simulate_transaction"),
+    ("produce_block", "author_produce_block"),
+];
+
+/// Builds the full method set served by [`crate::server::router`]: every
+/// namespace plus the legacy unprefixed aliases.
+#[must_use]
+pub fn rpc_module<B: RpcBackend>() -> RpcModule<B> {
+    let mut module = RpcModule::new();
+    module
+        .merge(Namespace::Chain, chain_module())
+        .merge(Namespace::State, state_module())
+        .merge(Namespace::Author, author_module())
+        .merge(Namespace::System, system_module())
+        .merge(Namespace::Mempool, mempool_module());
+    for (alias, target) in LEGACY_ALIASES {
+        module.alias(alias, target);
+    }
+    module
+}
@@ -313,6 +313,8 @@ mod tests {
                     amount: 100,
                     nonce: 0,
                     signature: [0u8; 64],
+                    multisig: None,
+                    data: Vec::new(),
                 }],
             },
         };
@@ -0,0 +1,358 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::rpc::BackendError;
+
+/// Name of the built-in method that lists every registered method.
+pub const RPC_METHODS: &str = "rpc_methods";
+
+/// Parameter set of a registered RPC method.
+///
+/// Implementors are plain `Deserialize` structs whose field names are listed
+/// in positional order in [`RpcParams::NAMES`]. Callers may then pass the
+/// parameters either by name (`{"height": 5}`) or by position (`[5]`).
+/// Methods with exactly one parameter also accept the bare value
+/// (`"params": 5`).
+pub trait RpcParams: DeserializeOwned + Send + 'static {
+    /// Parameter names in positional order.
+    const NAMES: &'static [&'static str];
+}
+
+/// Parameter set for methods that take no parameters.
+#[derive(Debug, Clone, Copy, Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NoParams {}
+
+impl RpcParams for NoParams {
+    const NAMES: &'static [&'static str] = &[];
+}
+
+/// Decodes raw JSON-RPC `params` into `P`.
+///
+/// # Errors
+///
+/// Returns a human-readable message if the parameters are missing, have the
+/// wrong arity, or do not match the declared types.
+pub fn decode_params<P: RpcParams>(params: Option<Value>) -> Result<P, String> {
+    let named = match params {
+        None | Some(Value::Null) => Value::Object(Map::new()),
+        Some(Value::Array(values)) => {
+            if values.len() > P::NAMES.len() {
+                return Err(format!(
+                    "expected at most {} positional param(s), got {}",
+                    P::NAMES.len(),
+                    values.len()
+                ));
+            }
+            Value::Object(P::NAMES.iter().map(|name| (*name).to_string()).zip(values).collect())
+        }
+        Some(Value::Object(map)) if P::NAMES.len() != 1 || map.contains_key(P::NAMES[0]) => {
+            Value::Object(map)
+        }
+        // A single-parameter method called with the bare value.
+        Some(value) if P::NAMES.len() == 1 => json!({ P::NAMES[0]: value }),
+        Some(_) => return Err("params must be an array or an object".to_string()),
+    };
+    serde_json::from_value(named).map_err(|e| e.to_string())
+}
+
+/// JSON-RPC method namespaces. A method registered in a namespace is exposed
+/// as `<prefix><name>`, e.g. `chain_get_block_height`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Namespace {
+    /// Blocks, headers and receipts.
+    Chain,
+    /// Account and world state queries.
+    State,
+    /// Transaction submission and block authoring.
+    Author,
+    /// Node health and introspection.
+    System,
+    /// Pending transaction pool.
+    Mempool,
+}
+
+impl Namespace {
+    /// Returns the method-name prefix for this namespace.
+    #[must_use]
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Namespace::Chain => "chain_",
+            Namespace::State => "state_",
+            Namespace::Author => "author_",
+            Namespace::System => "system_",
+            Namespace::Mempool => "mempool_",
+        }
+    }
+}
+
+/// Failure to dispatch or execute an RPC call.
+#[derive(Debug, Error)]
+pub enum CallError {
+    /// No method with the requested name is registered.
+    #[error("Method not found: {0}")]
+    MethodNotFound(String),
+    /// The parameters could not be decoded into the method's parameter type.
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+    /// The backend returned an error.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
+/// Description of a registered method, as returned by `rpc_methods`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MethodInfo {
+    /// Fully qualified method name.
+    pub name: String,
+    /// Parameter names in positional order.
+    pub params: &'static [&'static str],
+    /// Name of the method this one forwards to, for legacy aliases.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias_of: Option<String>,
+}
+
+type CallFuture = Pin<Box<dyn Future<Output = Result<Value, CallError>> + Send>>;
+type Handler<B> = Arc<dyn Fn(B, Option<Value>) -> CallFuture + Send + Sync>;
+
+struct Method<B> {
+    params: &'static [&'static str],
+    alias_of: Option<String>,
+    handler: Handler<B>,
+}
+
+/// A set of JSON-RPC methods dispatched against a backend `B`.
+///
+/// Each method is registered with a typed parameter struct and result type;
+/// parameter decoding, result encoding and error mapping are shared.
+pub struct RpcModule<B> {
+    methods: BTreeMap<String, Method<B>>,
+}
+
+impl<B> Default for RpcModule<B> {
+    fn default() -> Self {
+        Self {
+            methods: BTreeMap::new(),
+        }
+    }
+}
+
+impl<B: Clone + Send + Sync + 'static> RpcModule<B> {
+    /// Creates an empty module.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `handler` under `name`.
+    ///
+    /// The handler receives a clone of the backend and the decoded
+    /// parameters; its result is serialised as the JSON-RPC `result`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a method with the same name is already registered.
+    pub fn register<P, R, F, Fut>(&mut self, name: &str, handler: F) -> &mut Self
+    where
+        P: RpcParams,
+        R: Serialize,
+        F: Fn(B, P) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<R, BackendError>> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let erased: Handler<B> = Arc::new(move |backend, raw| {
+            let handler = Arc::clone(&handler);
+            Box::pin(async move {
+                let params = decode_params::<P>(raw).map_err(CallError::InvalidParams)?;
+                let result = handler(backend, params).await?;
+                serde_json::to_value(result).map_err(|e| {
+                    CallError::Backend(BackendError::Internal(format!("serialization error: {e}")))
+                })
+            })
+        });
+        self.insert(
+            name.to_string(),
+            Method {
+                params: P::NAMES,
+                alias_of: None,
+                handler: erased,
+            },
+        );
+        self
+    }
+
+    /// Registers `alias` as another name for the existing method `target`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `target` is not registered or `alias` already is.
+    pub fn alias(&mut self, alias: &str, target: &str) -> &mut Self {
+        let method = self
+            .methods
+            .get(target)
+            .unwrap_or_else(|| panic!("alias target {target} is not registered"));
+        let method = Method {
+            params: method.params,
+            alias_of: Some(target.to_string()),
+            handler: Arc::clone(&method.handler),
+        };
+        self.insert(alias.to_string(), method);
+        self
+    }
+
+    /// Adds every method of `other` to this module under `namespace`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a prefixed name is already registered.
+    pub fn merge(&mut self, namespace: Namespace, other: RpcModule<B>) -> &mut Self {
+        for (name, mut method) in other.methods {
+            method.alias_of = method.alias_of.map(|t| format!("{}{t}", namespace.prefix()));
+            self.insert(format!("{}{name}", namespace.prefix()), method);
+        }
+        self
+    }
+
+    /// Returns a description of every registered method, sorted by name,
+    /// including the built-in [`RPC_METHODS`].
+    #[must_use]
+    pub fn methods(&self) -> Vec<MethodInfo> {
+        let mut list: Vec<MethodInfo> = self
+            .methods
+            .iter()
+            .map(|(name, m)| MethodInfo {
+                name: name.clone(),
+                params: m.params,
+                alias_of: m.alias_of.clone(),
+            })
+            .collect();
+        list.push(MethodInfo {
+            name: RPC_METHODS.to_string(),
+            params: NoParams::NAMES,
+            alias_of: None,
+        });
+        list.sort_by(|a, b| a.name.cmp(&b.name));
+        list
+    }
+
+    /// Dispatches a call to `method` with raw JSON `params`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CallError`] if the method is unknown, the parameters do not
+    /// decode, or the backend fails.
+    pub async fn call(
+        &self,
+        backend: &B,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<Value, CallError> {
+        if method == RPC_METHODS {
+            decode_params::<NoParams>(params).map_err(CallError::InvalidParams)?;
+            return Ok(json!({ "methods": self.methods() }));
+        }
+        let entry = self
+            .methods
+            .get(method)
+            .ok_or_else(|| CallError::MethodNotFound(method.to_string()))?;
+        (entry.handler)(backend.clone(), params).await
+    }
+
+    fn insert(&mut self, name: String, method: Method<B>) {
+        assert!(
+            name != RPC_METHODS && !self.methods.contains_key(&name),
+            "RPC method {name} is registered twice"
+        );
+        self.methods.insert(name, method);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Pair {
+        a: u64,
+        #[serde(default)]
+        b: Option<String>,
+    }
+
+    impl RpcParams for Pair {
+        const NAMES: &'static [&'static str] = &["a", "b"];
+    }
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Single {
+        value: u64,
+    }
+
+    impl RpcParams for Single {
+        const NAMES: &'static [&'static str] = &["value"];
+    }
+
+    #[test]
+    fn decodes_named_and_positional_params() {
+        let expected = Pair {
+            a: 1,
+            b: Some("x".into()),
+        };
+        assert_eq!(
+            decode_params::<Pair>(Some(json!({"a": 1, "b": "x"}))),
+            Ok(expected)
+        );
+        assert_eq!(
+            decode_params::<Pair>(Some(json!([1]))),
+            Ok(Pair { a: 1, b: None })
+        );
+        assert!(decode_params::<Pair>(Some(json!([1, "x", 3]))).is_err());
+        assert!(decode_params::<Pair>(None).is_err());
+        assert!(decode_params::<Pair>(Some(json!(1))).is_err());
+    }
+
+    #[test]
+    fn single_param_accepts_bare_value() {
+        for raw in [json!(7), json!([7]), json!({"value": 7})] {
+            assert_eq!(decode_params::<Single>(Some(raw)), Ok(Single { value: 7 }));
+        }
+        assert!(decode_params::<NoParams>(Some(json!({"extra": 1}))).is_err());
+        assert!(decode_params::<NoParams>(Some(json!([]))).is_ok());
+    }
+
+    #[tokio::test]
+    async fn namespaces_aliases_and_listing() {
+        let mut chain = RpcModule::<u64>::new();
+        chain.register("double", |b: u64, p: Single| async move { Ok(b * p.value) });
+
+        let mut module = RpcModule::new();
+        module.merge(Namespace::Chain, chain);
+        module.alias("double", "chain_double");
+
+        assert_eq!(
+            module.call(&3, "chain_double", Some(json!([2]))).await.unwrap(),
+            6
+        );
+        assert_eq!(module.call(&3, "double", Some(json!(4))).await.unwrap(), 12);
+        assert!(matches!(
+            module.call(&3, "double", Some(json!("x"))).await,
+            Err(CallError::InvalidParams(_))
+        ));
+        assert!(matches!(
+            module.call(&3, "triple", None).await,
+            Err(CallError::MethodNotFound(_))
+        ));
+
+        let names: Vec<String> = module.methods().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, ["chain_double", "double", "rpc_methods"]);
+        let listed = module.call(&3, RPC_METHODS, None).await.unwrap();
+        assert_eq!(listed["methods"][1]["alias_of"], "chain_double");
+        assert_eq!(listed["methods"][0]["params"], json!(["value"]));
+    }
+}
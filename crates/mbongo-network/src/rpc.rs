@@ -1,11 +1,18 @@
 use std::future::Future;
 
 use axum::http::StatusCode;
-use mbongo_core::Transaction;
+use mbongo_core::{Address, Hash, LogEntry, LogFilter, Transaction};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Trait for the RPC server backend. Implementors supply chain data.
+///
+/// Only block production, submission and block lookup are required. The
+/// remaining methods default to failing with
+/// [`BackendError::MethodNotFound`], so new queries can be added without
+/// breaking existing implementors. Methods that only make sense for one
+/// backend can instead be registered on an
+/// [`RpcModule`](crate::registry::RpcModule) over the concrete backend type.
 pub trait RpcBackend: Clone + Send + Sync + 'static {
     /// Returns the current block height.
     fn get_block_height(&self) -> impl Future<Output = Result<u64, BackendError>> + Send;
@@ -33,14 +40,381 @@ pub trait RpcBackend: Clone + Send + Sync + 'static {
         &self,
         height: u64,
     ) -> impl Future<Output = Result<serde_json::Value, BackendError>> + Send;
+
+    /// Returns the receipt of an included transaction as a JSON value, or
+    /// `null` if the transaction has not been included in a block.
+    /// Read-only; does not modify state.
+    fn get_transaction_receipt(
+        &self,
+        _hash: Hash,
+    ) -> impl Future<Output = Result<serde_json::Value, BackendError>> + Send {
+        unsupported("get_transaction_receipt")
+    }
+
+    /// Returns the committed state of `address`, or `None` if the account
+    /// does not exist. Read-only; does not modify state.
+    fn get_account(
+        &self,
+        _address: Address,
+    ) -> impl Future<Output = Result<Option<AccountInfo>, BackendError>> + Send {
+        unsupported("get_account")
+    }
+
+    /// Returns the committed balance of `address` as a decimal string; `"0"`
+    /// for accounts that do not exist. Read-only; does not modify state.
+    fn get_balance(
+        &self,
+        _address: Address,
+    ) -> impl Future<Output = Result<String, BackendError>> + Send {
+        unsupported("get_balance")
+    }
+
+    /// Returns the nonce the next transaction from `address` must carry,
+    /// counting transactions from `address` already pending in the mempool.
+    /// Read-only; does not modify state.
+    fn get_next_nonce(
+        &self,
+        _address: Address,
+    ) -> impl Future<Output = Result<u64, BackendError>> + Send {
+        unsupported("get_next_nonce")
+    }
+
+    /// Dry-runs `tx` as if it were included next, after the transactions
+    /// already pending in the mempool, and reports the state changes it
+    /// would make. Commits nothing. The signature is only checked when
+    /// `verify_signature` is set, so unsigned transactions can be simulated.
+    ///
+    /// Validation failures are reported in [`SimulationResult::error`]; an
+    /// `Err` means the simulation itself could not run.
+    fn simulate_transaction(
+        &self,
+        _tx: Transaction,
+        _verify_signature: bool,
+    ) -> impl Future<Output = Result<SimulationResult, BackendError>> + Send {
+        unsupported("simulate_transaction")
+    }
+
+    /// Looks up a transaction in the mempool and then in committed blocks.
+    /// Unknown hashes are reported with [`TransactionStatus::Unknown`]
+    /// rather than an error. Read-only; does not modify state.
+    fn get_transaction(
+        &self,
+        _hash: Hash,
+    ) -> impl Future<Output = Result<TransactionLookup, BackendError>> + Send {
+        unsupported("get_transaction")
+    }
+
+    /// Returns the mempool transaction with the given hash, or `None` if it
+    /// is not pending. Read-only; does not modify state.
+    fn get_pending_transaction(
+        &self,
+        _hash: Hash,
+    ) -> impl Future<Output = Result<Option<PendingTransaction>, BackendError>> + Send {
+        unsupported("get_pending_transaction")
+    }
+
+    /// Returns mempool size counters. Read-only; does not modify state.
+    fn mempool_status(&self) -> impl Future<Output = Result<MempoolStatus, BackendError>> + Send {
+        unsupported("mempool_status")
+    }
+
+    /// Returns up to `limit` pending transactions, skipping the first
+    /// `offset`, in the order they will be included. If `sender` is set,
+    /// only that address's transactions are listed and counted.
+    /// Read-only; does not modify state.
+    fn mempool_content(
+        &self,
+        _sender: Option<Address>,
+        _offset: usize,
+        _limit: usize,
+    ) -> impl Future<Output = Result<MempoolContent, BackendError>> + Send {
+        unsupported("mempool_content")
+    }
+
+    /// Returns the included transactions sent or received by `address`,
+    /// newest first, from blocks below height `before` (or from the tip if
+    /// `None`). Pages hold at least `limit` entries when that many exist and
+    /// always end on a block boundary, so the last entry's `block_height` is
+    /// the `before` cursor of the next page. Read-only; does not modify state.
+    fn get_account_transactions(
+        &self,
+        _address: Address,
+        _before: Option<u64>,
+        _limit: usize,
+    ) -> impl Future<Output = Result<Vec<AccountTransaction>, BackendError>> + Send {
+        unsupported("get_account_transactions")
+    }
+
+    /// Returns the logs matching `filter` from committed blocks, in block,
+    /// transaction and log order. The range defaults to the chain tip at
+    /// both ends; ranges spanning more than
+    /// [`MAX_LOG_BLOCK_RANGE`](crate::methods::MAX_LOG_BLOCK_RANGE) blocks,
+    /// and queries matching more than
+    /// [`MAX_LOG_ENTRIES`](crate::methods::MAX_LOG_ENTRIES) logs, are
+    /// rejected with [`BackendError::InvalidParams`].
+    /// Read-only; does not modify state.
+    fn get_logs(
+        &self,
+        _filter: LogFilter,
+    ) -> impl Future<Output = Result<Vec<LogEntry>, BackendError>> + Send {
+        unsupported("get_logs")
+    }
+}
+
+/// Default body of optional [`RpcBackend`] methods.
+fn unsupported<T>(method: &'static str) -> std::future::Ready<Result<T, BackendError>> {
+    std::future::ready(Err(BackendError::MethodNotFound(method)))
+}
+
+/// Account state returned by [`RpcBackend::get_account`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    /// Account address.
+    pub address: Address,
+    /// Balance as a decimal string, since `u128` exceeds the integer range
+    /// most JSON clients handle exactly.
+    pub balance: String,
+    /// Nonce of the last committed transaction plus one.
+    pub nonce: u64,
+}
+
+/// Where a transaction is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Waiting in the mempool.
+    Pending,
+    /// Included in a committed block.
+    Included,
+    /// Neither pending nor included.
+    Unknown,
+}
+
+/// Result of [`RpcBackend::get_transaction`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionLookup {
+    /// The hash that was looked up.
+    pub hash: Hash,
+    /// Lifecycle status of the transaction.
+    pub status: TransactionStatus,
+    /// The transaction, unless its status is unknown.
+    pub transaction: Option<Transaction>,
+    /// Hash of the including block, once included.
+    pub block_hash: Option<Hash>,
+    /// Height of the including block, once included.
+    pub block_height: Option<u64>,
+}
+
+/// A transaction waiting in the mempool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    /// Transaction hash.
+    pub hash: Hash,
+    /// The signed transaction.
+    pub transaction: Transaction,
+}
+
+/// Mempool counters returned by [`RpcBackend::mempool_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolStatus {
+    /// Number of pending transactions.
+    pub pending: u64,
+    /// Number of distinct senders with pending transactions.
+    pub senders: u64,
+}
+
+/// A page of pending transactions returned by [`RpcBackend::mempool_content`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MempoolContent {
+    /// Number of pending transactions matching the sender filter, before
+    /// pagination.
+    pub total: u64,
+    /// The requested page, in inclusion order.
+    pub transactions: Vec<PendingTransaction>,
+}
+
+/// SCALE encodings of a transaction returned by `author_encode_transaction`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodedTransaction {
+    /// `0x`-prefixed hex of [`Transaction::signing_payload`], the exact
+    /// bytes an ed25519 signer must sign.
+    pub signing_payload: String,
+    /// `0x`-prefixed hex SCALE encoding of the whole transaction, as
+    /// accepted by `author_submit_raw_transaction`.
+    pub raw: String,
+}
+
+impl EncodedTransaction {
+    /// Encodes `tx`.
+    #[must_use]
+    pub fn new(tx: &Transaction) -> Self {
+        Self {
+            signing_payload: format!("0x{}", hex::encode(tx.signing_payload())),
+            raw: tx.encode_hex(),
+        }
+    }
+}
+
+/// An included transaction returned by [`RpcBackend::get_account_transactions`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountTransaction {
+    /// Transaction hash.
+    pub hash: Hash,
+    /// Height of the including block.
+    pub block_height: u64,
+    /// Position of the transaction in the block body.
+    pub index: u32,
+    /// The signed transaction.
+    pub transaction: Transaction,
 }
 
 /// Errors returned by [`RpcBackend`] implementations.
+///
+/// Each variant maps to a distinct JSON-RPC error code (see
+/// [`BackendError::code`]); domain failures use application codes in the
+/// -32000 range so clients can branch on the code instead of the message.
 #[derive(Debug, Error)]
 pub enum BackendError {
     /// An opaque internal error with a human-readable message.
     #[error("internal backend error: {0}")]
     Internal(String),
+    /// The requested block, transaction, or account does not exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// The parameters decoded but describe a request the backend refuses.
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+    /// The transaction signature does not verify against its sender.
+    #[error("invalid signature")]
+    InvalidSignature,
+    /// The transaction nonce is not the sender's next nonce.
+    #[error("invalid nonce: expected {expected}, got {actual}")]
+    InvalidNonce {
+        /// Nonce the sender's account expects next.
+        expected: u64,
+        /// Nonce carried by the transaction.
+        actual: u64,
+    },
+    /// The sender cannot cover the transaction amount.
+    #[error("insufficient balance: have {balance}, need {required}")]
+    InsufficientBalance {
+        /// Sender's current balance.
+        balance: u128,
+        /// Amount the transaction needs.
+        required: u128,
+    },
+    /// A transaction with the same hash is already pending.
+    #[error("duplicate transaction")]
+    DuplicateTransaction,
+    /// Another pending transaction from the same sender uses this nonce.
+    #[error("duplicate sender nonce {nonce}")]
+    DuplicateSenderNonce {
+        /// The contested nonce.
+        nonce: u64,
+    },
+    /// The node is not configured as a block producer.
+    #[error("node is not configured as producer")]
+    NotProducer,
+    /// The backend does not implement this [`RpcBackend`] method.
+    #[error("method not supported by this backend: {0}")]
+    MethodNotFound(&'static str),
+    /// A storage-layer failure. `data` carries structured context (column
+    /// family, key, engine error kind) and is returned in the JSON-RPC
+    /// error `data` field.
+    #[error("storage error: {message}")]
+    Storage {
+        /// Human-readable description of the failure.
+        message: String,
+        /// Structured context for the error response.
+        data: serde_json::Value,
+    },
+}
+
+impl BackendError {
+    /// Returns structured data to attach to the JSON-RPC error object, if any.
+    #[must_use]
+    pub fn data(&self) -> Option<serde_json::Value> {
+        match self {
+            BackendError::Storage { data, .. } => Some(data.clone()),
+            BackendError::InvalidNonce { expected, actual } => {
+                Some(serde_json::json!({ "expected": expected, "actual": actual }))
+            }
+            BackendError::InsufficientBalance { balance, required } => Some(serde_json::json!({
+                "balance": balance.to_string(),
+                "required": required.to_string(),
+            })),
+            BackendError::DuplicateSenderNonce { nonce } => {
+                Some(serde_json::json!({ "nonce": nonce }))
+            }
+            BackendError::Internal(_)
+            | BackendError::NotFound(_)
+            | BackendError::InvalidParams(_)
+            | BackendError::InvalidSignature
+            | BackendError::DuplicateTransaction
+            | BackendError::NotProducer
+            | BackendError::MethodNotFound(_) => None,
+        }
+    }
+
+    /// Returns the JSON-RPC error object reported for this error.
+    #[must_use]
+    pub fn to_rpc_error(&self) -> RpcError {
+        RpcError {
+            code: self.code().code(),
+            message: self.to_string(),
+            data: self.data(),
+        }
+    }
+
+    /// Returns the JSON-RPC error code reported for this error.
+    #[must_use]
+    pub fn code(&self) -> RpcErrorCode {
+        match self {
+            BackendError::Internal(_) | BackendError::Storage { .. } => RpcErrorCode::InternalError,
+            BackendError::NotFound(_) => RpcErrorCode::NotFound,
+            BackendError::InvalidParams(_) => RpcErrorCode::InvalidParams,
+            BackendError::InvalidSignature => RpcErrorCode::InvalidSignature,
+            BackendError::InvalidNonce { .. } => RpcErrorCode::InvalidNonce,
+            BackendError::InsufficientBalance { .. } => RpcErrorCode::InsufficientBalance,
+            BackendError::DuplicateTransaction => RpcErrorCode::DuplicateTransaction,
+            BackendError::DuplicateSenderNonce { .. } => RpcErrorCode::DuplicateSenderNonce,
+            BackendError::NotProducer => RpcErrorCode::NotProducer,
+            BackendError::MethodNotFound(_) => RpcErrorCode::MethodNotFound,
+        }
+    }
+}
+
+/// Outcome of [`RpcBackend::simulate_transaction`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    /// Whether the transaction would execute successfully.
+    pub success: bool,
+    /// Why the transaction would be rejected, with the same code, message,
+    /// and data `submit_transaction` would return.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    /// Why the contract deployment or call would fail. Such a transaction
+    /// is still included: it pays `fee` and advances the sender's nonce.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<String>,
+    /// Fee the transaction would pay, as a decimal string.
+    pub fee: String,
+    /// Gas the transaction would consume; zero for native transactions.
+    #[serde(default)]
+    pub gas_used: u64,
+    /// Accounts the transaction would change; empty if it is rejected.
+    pub state_diff: Vec<AccountDiff>,
+}
+
+/// Change to one account in a [`SimulationResult`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountDiff {
+    /// Account address.
+    pub address: Address,
+    /// State before the transaction; `None` if the account would be created.
+    pub before: Option<AccountInfo>,
+    /// State after the transaction.
+    pub after: AccountInfo,
 }
 
 /// A single JSON-RPC 2.0 request object.
@@ -87,8 +461,9 @@ pub struct RpcError {
     pub data: Option<serde_json::Value>,
 }
 
-/// Standard JSON-RPC 2.0 error codes.
-#[derive(Debug, Copy, Clone)]
+/// JSON-RPC 2.0 error codes: the standard codes plus application codes in
+/// the -32000 to -32099 server-error range.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum RpcErrorCode {
     /// Invalid JSON was received (-32700).
     ParseError,
@@ -100,6 +475,20 @@ pub enum RpcErrorCode {
     InvalidParams,
     /// Internal server error (-32603).
     InternalError,
+    /// The requested resource does not exist (-32001).
+    NotFound,
+    /// The transaction signature is invalid (-32010).
+    InvalidSignature,
+    /// The transaction nonce is not the sender's next nonce (-32011).
+    InvalidNonce,
+    /// The sender cannot cover the transaction amount (-32012).
+    InsufficientBalance,
+    /// The transaction is already pending (-32013).
+    DuplicateTransaction,
+    /// Another pending transaction from the sender uses the same nonce (-32014).
+    DuplicateSenderNonce,
+    /// The node does not produce blocks (-32020).
+    NotProducer,
 }
 
 impl RpcErrorCode {
@@ -112,6 +501,13 @@ impl RpcErrorCode {
             RpcErrorCode::MethodNotFound => -32601,
             RpcErrorCode::InvalidParams => -32602,
             RpcErrorCode::InternalError => -32603,
+            RpcErrorCode::NotFound => -32001,
+            RpcErrorCode::InvalidSignature => -32010,
+            RpcErrorCode::InvalidNonce => -32011,
+            RpcErrorCode::InsufficientBalance => -32012,
+            RpcErrorCode::DuplicateTransaction => -32013,
+            RpcErrorCode::DuplicateSenderNonce => -32014,
+            RpcErrorCode::NotProducer => -32020,
         }
     }
 }
@@ -154,7 +550,10 @@ impl JsonRpcResponse {
 pub fn http_status_for_error(code: i32) -> StatusCode {
     match code {
         -32700 | -32600 | -32602 => StatusCode::BAD_REQUEST,
-        -32601 => StatusCode::NOT_FOUND,
+        -32601 | -32001 => StatusCode::NOT_FOUND,
+        -32012..=-32010 => StatusCode::UNPROCESSABLE_ENTITY,
+        -32013 | -32014 => StatusCode::CONFLICT,
+        -32020 => StatusCode::FORBIDDEN,
         _ => StatusCode::INTERNAL_SERVER_ERROR,
     }
 }
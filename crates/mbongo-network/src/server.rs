@@ -5,21 +5,41 @@ use axum::{
     routing::post,
     Json, Router,
 };
-use serde_json::{json, Value};
+use std::sync::Arc;
+
+use serde_json::Value;
 use tower_http::cors::{Any, CorsLayer};
 
+use crate::methods::rpc_module;
+use crate::registry::{CallError, RpcModule};
 use crate::rpc::{
     http_status_for_error, JsonRpcRequest, JsonRpcResponse, RpcBackend, RpcErrorCode,
 };
 
-/// Shared application state holding the backend implementation.
-#[derive(Clone)]
+/// Shared application state holding the backend implementation and the
+/// methods dispatched against it.
 pub struct AppState<B: RpcBackend> {
     backend: B,
+    module: Arc<RpcModule<B>>,
 }
 
-/// Builds an Axum [`Router`] with the JSON-RPC endpoint and CORS middleware.
+impl<B: RpcBackend> Clone for AppState<B> {
+    fn clone(&self) -> Self {
+        Self {
+            backend: self.backend.clone(),
+            module: Arc::clone(&self.module),
+        }
+    }
+}
+
+/// Builds an Axum [`Router`] with the JSON-RPC endpoint and CORS middleware,
+/// serving the default method set from [`rpc_module`].
 pub fn router<B: RpcBackend>(backend: B) -> Router {
+    router_with_module(backend, rpc_module())
+}
+
+/// Builds an Axum [`Router`] serving the methods in `module`.
+pub fn router_with_module<B: RpcBackend>(backend: B, module: RpcModule<B>) -> Router {
     let cors = CorsLayer::new()
         .allow_methods([Method::POST])
         .allow_headers(Any)
@@ -27,7 +47,10 @@ pub fn router<B: RpcBackend>(backend: B) -> Router {
 
     Router::new()
         .route("/rpc", post(handle_rpc::<B>))
-        .with_state(AppState { backend })
+        .with_state(AppState {
+            backend,
+            module: Arc::new(module),
+        })
         .layer(cors)
 }
 
@@ -53,7 +76,7 @@ async fn handle_rpc<B: RpcBackend>(
     let response = if let Some(arr) = body.as_array() {
         let mut responses = Vec::new();
         for item in arr {
-            responses.push(process_single(&state.backend, item.clone()).await);
+            responses.push(process_single(&state.module, &state.backend, item.clone()).await);
         }
         Json(Value::Array(
             responses
@@ -84,7 +107,7 @@ async fn handle_rpc<B: RpcBackend>(
         ))
         .into_response()
     } else {
-        let resp = process_single(&state.backend, body).await;
+        let resp = process_single(&state.module, &state.backend, body).await;
         let status = if let Some(err) = &resp.error {
             http_status_for_error(err.code)
         } else {
@@ -96,8 +119,11 @@ async fn handle_rpc<B: RpcBackend>(
     response
 }
 
-#[allow(clippy::too_many_lines)]
-async fn process_single<B: RpcBackend>(backend: &B, raw: Value) -> JsonRpcResponse {
+async fn process_single<B: RpcBackend>(
+    module: &RpcModule<B>,
+    backend: &B,
+    raw: Value,
+) -> JsonRpcResponse {
     let req: JsonRpcRequest = match serde_json::from_value(raw.clone()) {
         Ok(r) => r,
         Err(err) => {
@@ -120,110 +146,16 @@ async fn process_single<B: RpcBackend>(backend: &B, raw: Value) -> JsonRpcRespon
         );
     }
 
-    match req.method.as_str() {
-        "ping" => match backend.ping().await {
-            Ok(p) => JsonRpcResponse::success(req.id.clone(), json!(p)),
-            Err(e) => JsonRpcResponse::error(
-                req.id.clone(),
-                RpcErrorCode::InternalError,
-                e.to_string(),
-                None,
-            ),
-        },
-        "get_block_height" => match backend.get_block_height().await {
-            Ok(h) => JsonRpcResponse::success(req.id.clone(), json!(h)),
-            Err(e) => JsonRpcResponse::error(
-                req.id.clone(),
-                RpcErrorCode::InternalError,
-                e.to_string(),
-                None,
-            ),
-        },
-        "submit_transaction" => {
-            let Some(params) = req.params else {
-                return JsonRpcResponse::error(
-                    req.id.clone(),
-                    RpcErrorCode::InvalidParams,
-                    "missing params",
-                    None,
-                );
-            };
-            let tx: mbongo_core::Transaction = match serde_json::from_value(params) {
-                Ok(t) => t,
-                Err(e) => {
-                    return JsonRpcResponse::error(
-                        req.id.clone(),
-                        RpcErrorCode::InvalidParams,
-                        format!("invalid transaction: {e}"),
-                        None,
-                    )
-                }
-            };
-            match backend.submit_transaction(tx).await {
-                Ok(hash) => JsonRpcResponse::success(req.id.clone(), json!(hash)),
-                Err(e) => JsonRpcResponse::error(
-                    req.id.clone(),
-                    RpcErrorCode::InternalError,
-                    e.to_string(),
-                    None,
-                ),
-            }
+    match module.call(backend, &req.method, req.params).await {
+        Ok(result) => JsonRpcResponse::success(req.id, result),
+        Err(e @ CallError::MethodNotFound(_)) => {
+            JsonRpcResponse::error(req.id, RpcErrorCode::MethodNotFound, e.to_string(), None)
         }
-        "produce_block" => match backend.produce_block().await {
-            Ok(hash) => JsonRpcResponse::success(req.id.clone(), json!(hash)),
-            Err(e) => JsonRpcResponse::error(
-                req.id.clone(),
-                RpcErrorCode::InternalError,
-                e.to_string(),
-                None,
-            ),
-        },
-        "get_latest_block_hash" => match backend.get_latest_block_hash().await {
-            Ok(hash) => JsonRpcResponse::success(req.id.clone(), json!(hash)),
-            Err(e) => JsonRpcResponse::error(
-                req.id.clone(),
-                RpcErrorCode::InternalError,
-                e.to_string(),
-                None,
-            ),
-        },
-        "get_block_by_height" => {
-            let Some(params) = req.params else {
-                return JsonRpcResponse::error(
-                    req.id.clone(),
-                    RpcErrorCode::InvalidParams,
-                    "missing params",
-                    None,
-                );
-            };
-            let height: u64 = match serde_json::from_value(
-                params.get("height").cloned().unwrap_or(params.clone()),
-            ) {
-                Ok(h) => h,
-                Err(e) => {
-                    return JsonRpcResponse::error(
-                        req.id.clone(),
-                        RpcErrorCode::InvalidParams,
-                        format!("invalid height: {e}"),
-                        None,
-                    )
-                }
-            };
-            match backend.get_block_by_height(height).await {
-                Ok(block) => JsonRpcResponse::success(req.id.clone(), block),
-                Err(e) => JsonRpcResponse::error(
-                    req.id.clone(),
-                    RpcErrorCode::InternalError,
-                    e.to_string(),
-                    None,
-                ),
-            }
+        Err(e @ CallError::InvalidParams(_)) => {
+            JsonRpcResponse::error(req.id, RpcErrorCode::InvalidParams, e.to_string(), None)
+        }
+        Err(CallError::Backend(e)) => {
+            JsonRpcResponse::error(req.id, e.code(), e.to_string(), e.data())
         }
-        _ => JsonRpcResponse::error(
-            req.id.clone(),
-            RpcErrorCode::MethodNotFound,
-            format!("Method not found: {}", req.method),
-            None,
-        ),
     }
 }
@@ -1,8 +1,13 @@
 use axum::body::to_bytes;
 use axum::http::StatusCode;
-use mbongo_core::Transaction;
-use mbongo_network::rpc::{BackendError, RpcBackend};
-use mbongo_network::server::router;
+use mbongo_core::{Address, Event, Hash, LogEntry, LogFilter, Transaction, TransactionType};
+use mbongo_network::methods::{rpc_module, MAX_LOG_BLOCK_RANGE};
+use mbongo_network::rpc::{
+    AccountDiff, AccountInfo, AccountTransaction, BackendError, MempoolContent, MempoolStatus,
+    PendingTransaction, RpcBackend, SimulationResult, TransactionLookup, TransactionStatus,
+};
+use mbongo_network::server::{router, router_with_module};
+use mbongo_network::{Namespace, NoParams, RpcModule};
 use serde_json::{json, Value};
 use tower::ServiceExt; // for oneshot()
 
@@ -38,6 +43,180 @@ impl RpcBackend for MockBackend {
             "body": { "transactions": [] }
         }))
     }
+
+    async fn get_transaction_receipt(&self, hash: Hash) -> Result<Value, BackendError> {
+        if hash == Hash::zero() {
+            return Ok(Value::Null);
+        }
+        Ok(json!({ "tx_hash": hash, "block_height": 3, "index": 0, "status": "success" }))
+    }
+    async fn get_account(&self, address: Address) -> Result<Option<AccountInfo>, BackendError> {
+        if address == Address::zero() {
+            return Ok(None);
+        }
+        Ok(Some(AccountInfo {
+            address,
+            balance: "42".to_string(),
+            nonce: 3,
+        }))
+    }
+
+    async fn get_balance(&self, _address: Address) -> Result<String, BackendError> {
+        Ok("42".to_string())
+    }
+
+    async fn get_next_nonce(&self, _address: Address) -> Result<u64, BackendError> {
+        Ok(5)
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: Transaction,
+        verify_signature: bool,
+    ) -> Result<SimulationResult, BackendError> {
+        Ok(SimulationResult {
+            success: !verify_signature,
+            error: None,
+            failure: None,
+            fee: "0".to_string(),
+            gas_used: 0,
+            state_diff: vec![AccountDiff {
+                address: tx.receiver,
+                before: None,
+                after: AccountInfo {
+                    address: tx.receiver,
+                    balance: tx.amount.to_string(),
+                    nonce: 0,
+                },
+            }],
+        })
+    }
+
+    async fn get_transaction(&self, hash: Hash) -> Result<TransactionLookup, BackendError> {
+        let pending = pending_tx(hash);
+        Ok(match hash.0[0] {
+            1 => TransactionLookup {
+                hash,
+                status: TransactionStatus::Pending,
+                transaction: Some(pending.transaction),
+                block_hash: None,
+                block_height: None,
+            },
+            2 => TransactionLookup {
+                hash,
+                status: TransactionStatus::Included,
+                transaction: Some(pending.transaction),
+                block_hash: Some(Hash([9u8; 32])),
+                block_height: Some(3),
+            },
+            _ => TransactionLookup {
+                hash,
+                status: TransactionStatus::Unknown,
+                transaction: None,
+                block_hash: None,
+                block_height: None,
+            },
+        })
+    }
+
+    async fn get_pending_transaction(
+        &self,
+        hash: Hash,
+    ) -> Result<Option<PendingTransaction>, BackendError> {
+        Ok((hash.0[0] == 1).then(|| pending_tx(hash)))
+    }
+
+    async fn mempool_status(&self) -> Result<MempoolStatus, BackendError> {
+        Ok(MempoolStatus {
+            pending: 10,
+            senders: 2,
+        })
+    }
+
+    async fn mempool_content(
+        &self,
+        sender: Option<Address>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<MempoolContent, BackendError> {
+        let all: Vec<PendingTransaction> = (0..10u8)
+            .map(|i| pending_tx(Hash([i; 32])))
+            .filter(|p| sender.map_or(true, |s| p.transaction.sender == s))
+            .collect();
+        Ok(MempoolContent {
+            total: all.len() as u64,
+            transactions: all.into_iter().skip(offset).take(limit).collect(),
+        })
+    }
+
+    async fn get_account_transactions(
+        &self,
+        address: Address,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AccountTransaction>, BackendError> {
+        // One transaction per block at heights 1..=9, all sent by `address`.
+        Ok((1..before.unwrap_or(10).min(10))
+            .rev()
+            .take(limit)
+            .map(|height| {
+                let hash = Hash([height as u8; 32]);
+                AccountTransaction {
+                    hash,
+                    block_height: height,
+                    index: 0,
+                    transaction: Transaction {
+                        sender: address,
+                        ..pending_tx(hash).transaction
+                    },
+                }
+            })
+            .collect())
+    }
+
+    async fn get_logs(&self, filter: LogFilter) -> Result<Vec<LogEntry>, BackendError> {
+        // One transfer from `Address([height; 32])` per block at heights 1..=9.
+        let to = filter.to_height.unwrap_or(9);
+        let from = filter.from_height.unwrap_or(to);
+        if to.saturating_sub(from) >= MAX_LOG_BLOCK_RANGE {
+            return Err(BackendError::InvalidParams(
+                "block range too large".to_string(),
+            ));
+        }
+        Ok((from.max(1)..=to.min(9))
+            .map(|height| LogEntry {
+                block_height: height,
+                block_hash: Hash([height as u8; 32]),
+                tx_hash: Hash([height as u8; 32]),
+                tx_index: 0,
+                log_index: 0,
+                log: Event::Transfer {
+                    from: Address([height as u8; 32]),
+                    to: Address([0xAA; 32]),
+                    amount: u128::from(height),
+                }
+                .to_log(),
+            })
+            .filter(|entry| filter.matches(&entry.log))
+            .collect())
+    }
+}
+
+/// Pending transfer whose sender alternates with the first hash byte.
+fn pending_tx(hash: Hash) -> PendingTransaction {
+    PendingTransaction {
+        hash,
+        transaction: Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: Address([hash.0[0] % 2; 32]),
+            receiver: Address([2u8; 32]),
+            amount: 10,
+            nonce: u64::from(hash.0[0] / 2),
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        },
+    }
 }
 
 #[tokio::test]
@@ -207,3 +386,609 @@ async fn test_get_latest_block_hash() {
     assert_eq!(v["result"], json!("0xmocktiphash"));
     assert_eq!(v["id"], json!("tip"));
 }
+
+#[tokio::test]
+async fn test_get_transaction_receipt() {
+    let hash = format!("0x{}", "ab".repeat(32));
+    let zero = format!("0x{}", "00".repeat(32));
+    let body = json!([
+        {"jsonrpc":"2.0","method":"get_transaction_receipt","params":{"hash":hash},"id":1},
+        {"jsonrpc":"2.0","method":"get_transaction_receipt","params":[zero],"id":2},
+        {"jsonrpc":"2.0","method":"get_transaction_receipt","params":{"hash":"0x12"},"id":3}
+    ]);
+    let response = router(MockBackend)
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[0]["result"]["tx_hash"], json!(hash));
+    assert_eq!(v[0]["result"]["status"], json!("success"));
+    assert_eq!(v[1]["result"], Value::Null);
+    assert_eq!(v[2]["error"]["code"], json!(-32602));
+}
+
+/// Backend whose every method fails with a storage error.
+#[derive(Clone)]
+struct FailingBackend;
+
+fn storage_failure() -> BackendError {
+    BackendError::Storage {
+        message: "database error on height 7 in column family `height_index`".to_string(),
+        data: json!({"column_family": "height_index", "key": "height 7", "kind": "corruption"}),
+    }
+}
+
+impl RpcBackend for FailingBackend {
+    async fn get_block_height(&self) -> Result<u64, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn submit_transaction(&self, _tx: Transaction) -> Result<String, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn produce_block(&self) -> Result<String, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_latest_block_hash(&self) -> Result<String, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_block_by_height(&self, _height: u64) -> Result<Value, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_transaction_receipt(&self, _hash: Hash) -> Result<Value, BackendError> {
+        Err(storage_failure())
+    }
+    async fn get_account(&self, _address: Address) -> Result<Option<AccountInfo>, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_balance(&self, _address: Address) -> Result<String, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_next_nonce(&self, _address: Address) -> Result<u64, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn simulate_transaction(
+        &self,
+        _tx: Transaction,
+        _verify_signature: bool,
+    ) -> Result<SimulationResult, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_transaction(&self, _hash: Hash) -> Result<TransactionLookup, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_pending_transaction(
+        &self,
+        _hash: Hash,
+    ) -> Result<Option<PendingTransaction>, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn mempool_status(&self) -> Result<MempoolStatus, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn mempool_content(
+        &self,
+        _sender: Option<Address>,
+        _offset: usize,
+        _limit: usize,
+    ) -> Result<MempoolContent, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_account_transactions(
+        &self,
+        _address: Address,
+        _before: Option<u64>,
+        _limit: usize,
+    ) -> Result<Vec<AccountTransaction>, BackendError> {
+        Err(storage_failure())
+    }
+
+    async fn get_logs(&self, _filter: LogFilter) -> Result<Vec<LogEntry>, BackendError> {
+        Err(storage_failure())
+    }
+}
+
+#[tokio::test]
+async fn test_storage_error_carries_data() {
+    let app = router(FailingBackend);
+    let body = json!({"jsonrpc":"2.0","method":"get_block_by_height","params":{"height":7},"id":9});
+    let response = app
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["error"]["code"], json!(-32603));
+    assert_eq!(v["error"]["data"]["column_family"], json!("height_index"));
+    assert_eq!(v["error"]["data"]["kind"], json!("corruption"));
+}
+
+#[tokio::test]
+async fn test_namespaced_methods_and_rpc_methods() {
+    let mut module = rpc_module::<MockBackend>();
+    let mut extra = RpcModule::new();
+    extra.register("version", |_: MockBackend, _: NoParams| async move {
+        Ok("1.2.3")
+    });
+    module.merge(Namespace::System, extra);
+    let app = router_with_module(MockBackend, module);
+
+    let body = json!([
+        {"jsonrpc":"2.0","method":"chain_get_block_by_height","params":[5],"id":1},
+        {"jsonrpc":"2.0","method":"chain_get_block_by_height","params":6,"id":2},
+        {"jsonrpc":"2.0","method":"chain_get_block_by_height","params":{"height":"x"},"id":3},
+        {"jsonrpc":"2.0","method":"system_version","id":4},
+        {"jsonrpc":"2.0","method":"system_ping","params":[1],"id":5},
+        {"jsonrpc":"2.0","method":"rpc_methods","id":6}
+    ]);
+    let response = app
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[0]["result"]["header"]["height"], json!(5));
+    assert_eq!(v[1]["result"]["header"]["height"], json!(6));
+    assert_eq!(v[2]["error"]["code"], json!(-32602));
+    assert_eq!(v[3]["result"], json!("1.2.3"));
+    assert_eq!(v[4]["error"]["code"], json!(-32602));
+
+    let methods = v[5]["result"]["methods"].as_array().unwrap();
+    let find = |name: &str| methods.iter().find(|m| m["name"] == name).cloned();
+    assert_eq!(
+        find("chain_get_transaction_receipt").unwrap()["params"],
+        json!(["hash"])
+    );
+    assert_eq!(
+        find("submit_transaction").unwrap()["alias_of"],
+        json!("author_submit_transaction")
+    );
+    assert!(find("system_version").is_some());
+    assert!(find("rpc_methods").is_some());
+}
+
+/// Backend whose methods fail with domain errors.
+#[derive(Clone)]
+struct RejectingBackend;
+
+impl RpcBackend for RejectingBackend {
+    async fn get_block_height(&self) -> Result<u64, BackendError> {
+        Ok(0)
+    }
+
+    async fn submit_transaction(&self, tx: Transaction) -> Result<String, BackendError> {
+        Err(BackendError::InvalidNonce {
+            expected: 3,
+            actual: tx.nonce,
+        })
+    }
+
+    async fn produce_block(&self) -> Result<String, BackendError> {
+        Err(BackendError::NotProducer)
+    }
+
+    async fn get_latest_block_hash(&self) -> Result<String, BackendError> {
+        Err(BackendError::InsufficientBalance {
+            balance: 1,
+            required: 2,
+        })
+    }
+
+    async fn get_block_by_height(&self, height: u64) -> Result<Value, BackendError> {
+        Err(BackendError::NotFound(format!("block at height {height}")))
+    }
+
+    async fn get_transaction_receipt(&self, _hash: Hash) -> Result<Value, BackendError> {
+        Err(BackendError::DuplicateSenderNonce { nonce: 4 })
+    }
+    async fn get_account(&self, _address: Address) -> Result<Option<AccountInfo>, BackendError> {
+        Err(BackendError::NotFound("account".to_string()))
+    }
+
+    async fn get_balance(&self, _address: Address) -> Result<String, BackendError> {
+        Err(BackendError::NotFound("account".to_string()))
+    }
+
+    async fn get_next_nonce(&self, _address: Address) -> Result<u64, BackendError> {
+        Err(BackendError::NotFound("account".to_string()))
+    }
+
+    async fn simulate_transaction(
+        &self,
+        tx: Transaction,
+        _verify_signature: bool,
+    ) -> Result<SimulationResult, BackendError> {
+        let error = BackendError::InsufficientBalance {
+            balance: 1,
+            required: tx.amount,
+        };
+        Ok(SimulationResult {
+            success: false,
+            error: Some(error.to_rpc_error()),
+            failure: None,
+            fee: "0".to_string(),
+            gas_used: 0,
+            state_diff: Vec::new(),
+        })
+    }
+
+    async fn get_pending_transaction(
+        &self,
+        _hash: Hash,
+    ) -> Result<Option<PendingTransaction>, BackendError> {
+        Ok(None)
+    }
+
+    async fn mempool_status(&self) -> Result<MempoolStatus, BackendError> {
+        Ok(MempoolStatus {
+            pending: 0,
+            senders: 0,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_domain_errors_use_application_codes() {
+    let tx = Transaction {
+        tx_type: TransactionType::Transfer,
+        sender: Address([1u8; 32]),
+        receiver: Address([2u8; 32]),
+        amount: 10,
+        nonce: 7,
+        signature: [0u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    };
+    let cases = [
+        (
+            "submit_transaction",
+            json!(tx),
+            -32011,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        ("produce_block", Value::Null, -32020, StatusCode::FORBIDDEN),
+        (
+            "get_latest_block_hash",
+            Value::Null,
+            -32012,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ),
+        (
+            "get_block_by_height",
+            json!([9]),
+            -32001,
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            "get_transaction_receipt",
+            json!([Hash::zero()]),
+            -32014,
+            StatusCode::CONFLICT,
+        ),
+        // Not implemented by this backend; the trait default applies.
+        ("chain_get_logs", json!({}), -32601, StatusCode::NOT_FOUND),
+    ];
+
+    for (method, params, code, status) in cases {
+        let body = json!({"jsonrpc":"2.0","method":method,"params":params,"id":1});
+        let response = router(RejectingBackend)
+            .oneshot(
+                axum::http::Request::builder()
+                    .uri("/rpc")
+                    .method("POST")
+                    .header("content-type", "application/json")
+                    .body(axum::body::Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), status, "{method}");
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let v: Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(v["error"]["code"], json!(code), "{method}");
+        if method == "submit_transaction" {
+            assert_eq!(
+                v["error"]["message"],
+                json!("invalid nonce: expected 3, got 7")
+            );
+            assert_eq!(v["error"]["data"], json!({"expected": 3, "actual": 7}));
+        }
+        if method == "get_latest_block_hash" {
+            assert_eq!(v["error"]["data"], json!({"balance": "1", "required": "2"}));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_account_queries() {
+    let app = router(MockBackend);
+    let addr = Address([7u8; 32]);
+    let body = json!([
+        {"jsonrpc":"2.0","method":"state_get_account","params":[addr],"id":1},
+        {"jsonrpc":"2.0","method":"get_account","params":{"address":Address::zero()},"id":2},
+        {"jsonrpc":"2.0","method":"get_balance","params":[addr],"id":3},
+        {"jsonrpc":"2.0","method":"state_get_next_nonce","params":addr,"id":4},
+        {"jsonrpc":"2.0","method":"get_next_nonce","params":["0x12"],"id":5}
+    ]);
+    let response = app
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(
+        v[0]["result"],
+        json!({"address": addr, "balance": "42", "nonce": 3})
+    );
+    assert_eq!(v[1]["result"], Value::Null);
+    assert_eq!(v[2]["result"], json!("42"));
+    assert_eq!(v[3]["result"], json!(5));
+    assert_eq!(v[4]["error"]["code"], json!(-32602));
+}
+
+#[tokio::test]
+async fn test_simulate_transaction() {
+    let tx = Transaction {
+        tx_type: TransactionType::Transfer,
+        sender: Address([1u8; 32]),
+        receiver: Address([2u8; 32]),
+        amount: 10,
+        nonce: 0,
+        signature: [0u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    };
+    let body = json!([
+        {"jsonrpc":"2.0","method":"author_simulate_transaction","params":[tx],"id":1},
+        {"jsonrpc":"2.0","method":"simulate_transaction","params":{"tx":tx,"verify_signature":true},"id":2}
+    ]);
+    let request = |body: &Value| {
+        axum::http::Request::builder()
+            .uri("/rpc")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let response = router(MockBackend).oneshot(request(&body)).await.unwrap();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[0]["result"]["success"], true);
+    assert!(v[0]["result"].get("error").is_none());
+    assert_eq!(v[0]["result"]["state_diff"][0]["before"], Value::Null);
+    assert_eq!(v[0]["result"]["state_diff"][0]["after"]["balance"], "10");
+    assert_eq!(v[1]["result"]["success"], false);
+
+    // A rejected transaction is still a successful call; the reason is in the result.
+    let response = router(RejectingBackend).oneshot(request(&body)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[0]["result"]["success"], false);
+    assert_eq!(v[0]["result"]["error"]["code"], -32012);
+    assert_eq!(v[0]["result"]["error"]["data"]["required"], "10");
+    assert_eq!(v[0]["result"]["state_diff"], json!([]));
+}
+
+#[tokio::test]
+async fn test_mempool_methods_and_transaction_status() {
+    let sender = Address([1u8; 32]);
+    let body = json!([
+        {"jsonrpc":"2.0","method":"mempool_status","id":1},
+        {"jsonrpc":"2.0","method":"mempool_content","params":{"sender":sender,"offset":1,"limit":2},"id":2},
+        {"jsonrpc":"2.0","method":"mempool_content","id":3},
+        {"jsonrpc":"2.0","method":"get_pending_transaction","params":[Hash([1u8; 32])],"id":4},
+        {"jsonrpc":"2.0","method":"mempool_get_pending_transaction","params":[Hash([2u8; 32])],"id":5},
+        {"jsonrpc":"2.0","method":"get_transaction","params":[Hash([1u8; 32])],"id":6},
+        {"jsonrpc":"2.0","method":"chain_get_transaction","params":[Hash([2u8; 32])],"id":7},
+        {"jsonrpc":"2.0","method":"chain_get_transaction","params":[Hash([3u8; 32])],"id":8}
+    ]);
+    let response = router(MockBackend)
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[0]["result"], json!({"pending": 10, "senders": 2}));
+
+    assert_eq!(v[1]["result"]["total"], json!(5));
+    let page = v[1]["result"]["transactions"].as_array().unwrap();
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0]["hash"], json!(Hash([3u8; 32])));
+    assert_eq!(page[1]["transaction"]["sender"], json!(sender));
+    assert_eq!(v[2]["result"]["transactions"].as_array().unwrap().len(), 10);
+
+    assert_eq!(v[3]["result"]["hash"], json!(Hash([1u8; 32])));
+    assert_eq!(v[4]["result"], Value::Null);
+
+    assert_eq!(v[5]["result"]["status"], json!("pending"));
+    assert_eq!(v[5]["result"]["block_height"], Value::Null);
+    assert_eq!(v[6]["result"]["status"], json!("included"));
+    assert_eq!(v[6]["result"]["block_height"], json!(3));
+    assert_eq!(v[7]["result"]["status"], json!("unknown"));
+    assert_eq!(v[7]["result"]["transaction"], Value::Null);
+}
+
+#[tokio::test]
+async fn test_account_transactions_pagination() {
+    let address = Address([7u8; 32]);
+    let body = json!([
+        {"jsonrpc":"2.0","method":"state_get_account_transactions","params":{"address":address},"id":1},
+        {"jsonrpc":"2.0","method":"get_account_transactions","params":[address, 5, 2],"id":2},
+        {"jsonrpc":"2.0","method":"state_get_account_transactions","params":{"address":address,"before":1},"id":3},
+        {"jsonrpc":"2.0","method":"state_get_account_transactions","params":{"before":1},"id":4}
+    ]);
+    let response = router(MockBackend)
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+
+    let all = v[0]["result"].as_array().unwrap();
+    assert_eq!(all.len(), 9);
+    assert_eq!(all[0]["block_height"], json!(9));
+    assert_eq!(all[0]["transaction"]["sender"], json!(address));
+
+    let page = v[1]["result"].as_array().unwrap();
+    let heights: Vec<&Value> = page.iter().map(|t| &t["block_height"]).collect();
+    assert_eq!(heights, [&json!(4), &json!(3)]);
+    assert_eq!(page[0]["hash"], json!(Hash([4u8; 32])));
+    assert_eq!(page[0]["index"], json!(0));
+
+    assert_eq!(v[2]["result"], json!([]));
+    assert_eq!(v[3]["error"]["code"], -32602);
+}
+
+#[tokio::test]
+async fn test_get_logs_filters_by_range_address_and_topics() {
+    let transfer = mbongo_core::logs::event_topic("Transfer");
+    let body = json!([
+        {"jsonrpc":"2.0","method":"chain_get_logs","params":{"from_height":1,"to_height":9},"id":1},
+        {"jsonrpc":"2.0","method":"get_logs","params":[2, 6, Address([4u8; 32])],"id":2},
+        {"jsonrpc":"2.0","method":"chain_get_logs","params":{"from_height":1,"topics":[transfer, Address([0xAA; 32])]},"id":3},
+        {"jsonrpc":"2.0","method":"chain_get_logs","params":{"topics":[null, Hash([1u8; 32])]},"id":4},
+        {"jsonrpc":"2.0","method":"chain_get_logs","params":{"from_height":0,"to_height":MAX_LOG_BLOCK_RANGE},"id":5}
+    ]);
+    let response = router(MockBackend)
+        .oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+
+    let all = v[0]["result"].as_array().unwrap();
+    assert_eq!(all.len(), 9);
+    assert_eq!(all[0]["block_height"], json!(1));
+    assert_eq!(all[0]["topics"][0], json!(transfer));
+    assert_eq!(all[0]["data"], json!(format!("0x01{}", "00".repeat(15))));
+
+    let by_address = v[1]["result"].as_array().unwrap();
+    assert_eq!(by_address.len(), 1);
+    assert_eq!(by_address[0]["address"], json!(Address([4u8; 32])));
+
+    assert_eq!(v[2]["result"].as_array().unwrap().len(), 9);
+    assert_eq!(v[3]["result"], json!([]));
+    assert_eq!(v[4]["error"]["code"], json!(-32602));
+}
+
+#[tokio::test]
+async fn test_raw_submission_and_encoding() {
+    let tx = Transaction {
+        tx_type: TransactionType::Transfer,
+        sender: Address([1u8; 32]),
+        receiver: Address([2u8; 32]),
+        amount: 10,
+        nonce: 7,
+        signature: [9u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    };
+    let body = json!([
+        {"jsonrpc":"2.0","method":"author_encode_transaction","params":{"tx":tx},"id":1},
+        {"jsonrpc":"2.0","method":"submit_raw_transaction","params":[tx.encode_hex()],"id":2},
+        {"jsonrpc":"2.0","method":"author_submit_raw_transaction","params":{"raw":"0x1234"},"id":3}
+    ]);
+    let call = |app: axum::Router| {
+        app.oneshot(
+            axum::http::Request::builder()
+                .uri("/rpc")
+                .method("POST")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(body.to_string()))
+                .unwrap(),
+        )
+    };
+
+    let response = call(router(MockBackend)).await.unwrap();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    let payload = format!("0x{}", hex::encode(tx.signing_payload()));
+    assert_eq!(v[0]["result"]["signing_payload"], json!(payload));
+    assert_eq!(v[0]["result"]["raw"], json!(tx.encode_hex()));
+    assert_eq!(v[1]["result"], "0xmockhash");
+    assert_eq!(v[2]["error"]["code"], -32602);
+
+    // The raw bytes reach the backend as the same transaction.
+    let response = call(router(RejectingBackend)).await.unwrap();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let v: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v[1]["error"]["data"]["actual"], json!(7));
+}
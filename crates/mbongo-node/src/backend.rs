@@ -6,15 +6,26 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use async_trait::async_trait;
 use log::{info, warn};
 use mbongo_api::rest::{
-    Account as RestAccount, ApiBackend, ApiError, BlockDetail, BlockSummary,
-    Transaction as RestTransaction, Validator,
+    Account as RestAccount, ApiBackend, ApiError, BlockDetail, BlockSummary, MempoolPage,
+    Transaction as RestTransaction, TransactionStatus as RestTransactionStatus, Validator,
 };
+use mbongo_api::ws::{ChainEvent, EventBus};
+use mbongo_core::execution::{self, ExecutionFailure};
 use mbongo_core::{
-    compute_transactions_root, Account, Address, Block, BlockBody, BlockHeader, Hash, Transaction,
+    compute_transactions_root, verify_transaction_signatures, Account, Address, Block, BlockBody,
+    BlockHeader, Bloom, Hash, LogEntry, LogFilter, MultisigError, Receipt, Transaction,
+};
+use mbongo_network::methods::{MAX_LOG_BLOCK_RANGE, MAX_LOG_ENTRIES};
+use mbongo_network::rpc::{
+    AccountDiff, AccountInfo, AccountTransaction, BackendError, MempoolContent, MempoolStatus,
+    PendingTransaction, RpcBackend, SimulationResult, TransactionLookup, TransactionStatus,
 };
-use mbongo_network::rpc::{BackendError, RpcBackend};
 use mbongo_network::BlockBroadcaster;
-use mbongo_storage::{BatchOp, Storage};
+use mbongo_runtime::{
+    max_fee, validate_data, BlockExecution, ContractState, DataError, Outcome, Runtime,
+    StateChanges,
+};
+use mbongo_storage::{AddressTxEntry, BatchOp, Storage, StorageError};
 use parity_scale_codec::Encode;
 use tokio::sync::RwLock;
 
@@ -33,8 +44,12 @@ pub struct NodeBackend<S: Storage> {
     mempool: Arc<RwLock<Mempool>>,
     /// Optional block broadcaster for pushing blocks to peers.
     broadcaster: Option<Arc<dyn BlockBroadcaster>>,
+    /// Optional bus for WebSocket subscribers.
+    events: Option<EventBus>,
     /// Whether this node is configured as a block producer.
     is_producer: bool,
+    /// Executes block transactions, including contract deployments and calls.
+    runtime: Arc<Runtime>,
 }
 
 impl<S: Storage> Clone for NodeBackend<S> {
@@ -43,12 +58,14 @@ impl<S: Storage> Clone for NodeBackend<S> {
             storage: Arc::clone(&self.storage),
             mempool: Arc::clone(&self.mempool),
             broadcaster: self.broadcaster.clone(),
+            events: self.events.clone(),
             is_producer: self.is_producer,
+            runtime: Arc::clone(&self.runtime),
         }
     }
 }
 
-impl<S: Storage> NodeBackend<S> {
+impl<S: Storage + 'static> NodeBackend<S> {
     /// Creates a new backend wrapping the given storage.
     ///
     /// `is_producer` controls whether this node is allowed to produce blocks.
@@ -58,7 +75,9 @@ impl<S: Storage> NodeBackend<S> {
             storage: Arc::new(storage),
             mempool: Arc::new(RwLock::new(Mempool::new())),
             broadcaster: None,
+            events: None,
             is_producer,
+            runtime: Arc::new(Runtime::new()),
         }
     }
 
@@ -67,14 +86,18 @@ impl<S: Storage> NodeBackend<S> {
         self.broadcaster = Some(b);
     }
 
+    /// Sets the bus that new heads, account changes, and accepted mempool
+    /// transactions are published on.
+    pub fn set_event_bus(&mut self, bus: EventBus) {
+        self.events = Some(bus);
+    }
+
     /// Returns the current chain tip height.
     ///
     /// Convenience wrapper for use by the sync orchestrator without
     /// going through the async `RpcBackend` trait.
     pub fn latest_height(&self) -> Result<u64, BackendError> {
-        self.storage
-            .get_latest_height()
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")))
+        self.storage.get_latest_height().map_err(storage_error)
     }
 
     /// Writes the genesis block (height 0) if it does not already exist.
@@ -83,12 +106,7 @@ impl<S: Storage> NodeBackend<S> {
     /// timestamp 0. This method is idempotent.
     pub fn ensure_genesis(&self) -> Result<(), BackendError> {
         // If height 0 already exists, nothing to do.
-        if self
-            .storage
-            .get_block_by_height(0)
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?
-            .is_some()
-        {
+        if self.storage.get_block_by_height(0).map_err(storage_error)?.is_some() {
             return Ok(());
         }
 
@@ -106,29 +124,19 @@ impl<S: Storage> NodeBackend<S> {
 
         let block_hash = compute_block_hash(&block);
 
+        self.storage.put_block(&block_hash, &block).map_err(storage_error)?;
+        self.storage.put_block_height_index(0, block_hash).map_err(storage_error)?;
         self.storage
-            .put_block(&block_hash, &block)
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
-        self.storage
-            .put_block_height_index(0, block_hash)
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
+            .write_batch(vec![BatchOp::PutBlockBloom(0, Bloom::default())])
+            .map_err(storage_error)?;
 
         // DEV ONLY: Pre-funded account for testing.
-        // Deterministic dev key (must match wallet example).
-        use ed25519_dalek::SigningKey;
-        let signing_key = SigningKey::from_bytes(&[0xAAu8; 32]);
-        let verifying_key = signing_key.verifying_key();
-        let dev_addr = Address(verifying_key.to_bytes());
-        let existing = self
-            .storage
-            .get_account(&dev_addr)
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
+        let dev_addr = dev_address();
+        let existing = self.storage.get_account(&dev_addr).map_err(storage_error)?;
         if existing.is_none() {
             let mut dev_account = Account::new(dev_addr);
             dev_account.balance = 1_000_000_000;
-            self.storage
-                .put_account(&dev_addr, &dev_account)
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
+            self.storage.put_account(&dev_addr, &dev_account).map_err(storage_error)?;
         }
 
         Ok(())
@@ -140,11 +148,19 @@ impl<S: Storage> NodeBackend<S> {
     /// 1. `block.header.parent_hash` matches the current chain tip hash.
     /// 2. `block.header.height == current_height + 1`.
     /// 3. `transactions_root` matches re-computed commitment.
-    /// 4. Every transaction has a valid signature.
-    /// 5. Nonce and balance rules pass for every transaction (re-executed).
+    /// 4. Every transaction has a valid signature, or a multisig witness with
+    ///    exactly `threshold` valid member signatures. Signatures are
+    ///    verified in parallel; see [`verify_transaction_signatures`].
+    /// 5. Every transaction's `data` fits its type; see [`validate_data`].
+    /// 6. Nonce and balance rules pass for every transaction (re-executed).
+    ///    A contract deployment or call that fails after these checks is
+    ///    still included with a failed receipt; see [`mbongo_runtime`].
     ///
-    /// On success the block, its transactions, and all account updates are
-    /// committed atomically via [`Storage::write_batch`].
+    /// On success the block, its transactions, a [`Receipt`] per newly
+    /// included transaction, the union of those receipts' log blooms, and
+    /// all account and contract updates are committed
+    /// atomically via [`Storage::write_batch`]. The new head and every
+    /// changed account are then published on the event bus, if one is set.
     ///
     /// Used by both `produce_block` (after building the block locally) and
     /// the follower sync path (applying blocks received from peers).
@@ -156,9 +172,7 @@ impl<S: Storage> NodeBackend<S> {
         let storage = &self.storage;
 
         // ── Parent linkage ─────────────────────────────────────────────
-        let current_height = storage
-            .get_latest_height()
-            .map_err(|e| ApplyBlockError::Storage(e.to_string()))?;
+        let current_height = storage.get_latest_height()?;
 
         let expected_height = current_height + 1;
         if block.header.height != expected_height {
@@ -169,9 +183,8 @@ impl<S: Storage> NodeBackend<S> {
         }
 
         let parent_block = storage
-            .get_block_by_height(current_height)
-            .map_err(|e| ApplyBlockError::Storage(e.to_string()))?
-            .ok_or_else(|| ApplyBlockError::Storage("parent block not found".to_string()))?;
+            .get_block_by_height(current_height)?
+            .ok_or(ApplyBlockError::MissingParent(current_height))?;
 
         let expected_parent_hash = compute_block_hash(&parent_block);
         if block.header.parent_hash != expected_parent_hash {
@@ -187,88 +200,127 @@ impl<S: Storage> NodeBackend<S> {
             return Err(ApplyBlockError::TransactionsRootMismatch);
         }
 
-        // ── Re-execute transactions ────────────────────────────────────
-        let mut ops: Vec<BatchOp> = Vec::new();
-        let mut account_cache: std::collections::HashMap<Address, Account> =
-            std::collections::HashMap::new();
+        // ── Signatures ─────────────────────────────────────────────────
+        if let Err(i) = verify_transaction_signatures(&block.body.transactions) {
+            let tx = &block.body.transactions[i];
+            if let Some(Err(reason)) = tx.multisig.as_ref().map(|witness| witness.verify(tx)) {
+                return Err(ApplyBlockError::InvalidMultisig { index: i, reason });
+            }
+            return Err(ApplyBlockError::InvalidSignature(i));
+        }
 
-        let mut last_seq = storage
-            .get_last_included_tx_seq()
-            .map_err(|e| ApplyBlockError::Storage(e.to_string()))?;
+        // ── Transaction data ───────────────────────────────────────────
+        for (index, tx) in block.body.transactions.iter().enumerate() {
+            validate_data(tx).map_err(|reason| ApplyBlockError::InvalidData { index, reason })?;
+        }
 
-        for (i, tx) in block.body.transactions.iter().enumerate() {
-            // Signature validation.
-            if !tx.verify_signature() {
-                return Err(ApplyBlockError::InvalidSignature(i));
-            }
+        // ── Re-execute transactions ────────────────────────────────────
+        let block_hash = compute_block_hash(block);
+        let mut ops: Vec<BatchOp> = Vec::new();
 
+        // Skip transactions already persisted (idempotent re-apply guard).
+        let mut pending = Vec::with_capacity(block.body.transactions.len());
+        for ((i, tx), index) in block.body.transactions.iter().enumerate().zip(0u32..) {
             let tx_hash = compute_tx_hash(tx);
-
-            // Skip if already persisted (idempotent re-apply guard).
-            let already_stored = storage
-                .get_transaction(&tx_hash)
-                .map_err(|e| ApplyBlockError::Storage(e.to_string()))?
-                .is_some();
-            if already_stored {
-                continue;
+            if storage.get_transaction(&tx_hash)?.is_none() {
+                pending.push((i, index, tx, tx_hash));
             }
+        }
 
-            // Load sender (from cache or storage).
-            let sender_addr = tx.sender;
-            let mut sender = match account_cache.get(&sender_addr) {
-                Some(acc) => acc.clone(),
-                None => storage
-                    .get_account(&sender_addr)
-                    .map_err(|e| ApplyBlockError::Storage(e.to_string()))?
-                    .ok_or(ApplyBlockError::InsufficientBalance(i))?,
-            };
-
-            sender
-                .validate_and_increment_nonce(tx.nonce)
-                .map_err(|_| ApplyBlockError::InvalidNonce(i))?;
-
-            // Load receiver (from cache or storage).
-            let receiver_addr = tx.receiver;
-            let mut receiver = match account_cache.get(&receiver_addr) {
-                Some(acc) => acc.clone(),
-                None => storage
-                    .get_account(&receiver_addr)
-                    .map_err(|e| ApplyBlockError::Storage(e.to_string()))?
-                    .unwrap_or_else(|| Account::new(receiver_addr)),
-            };
+        // Senders and receivers that never meet execute in parallel; blocks
+        // with contract transactions run in order. Either way the result and
+        // the failing index match sequential execution.
+        let txs: Vec<&Transaction> = pending.iter().map(|&(_, _, tx, _)| tx).collect();
+        let state = Arc::new(StorageState(Arc::clone(storage)));
+        let BlockExecution { changes, outcomes } = self
+            .runtime
+            .execute_block(&state, &txs)
+            .map_err(|ExecutionFailure { index, error }| {
+                let i = pending[index].0;
+                match error {
+                    ExecutionError::InvalidNonce { .. } => ApplyBlockError::InvalidNonce(i),
+                    ExecutionError::InsufficientBalance { .. }
+                    | ExecutionError::BalanceOverflow => ApplyBlockError::InsufficientBalance(i),
+                    ExecutionError::Storage(se) => ApplyBlockError::Storage(se),
+                }
+            })?;
 
-            Account::transfer(&mut sender, &mut receiver, tx.amount)
-                .map_err(|_| ApplyBlockError::InsufficientBalance(i))?;
+        let mut last_seq = storage.get_last_included_tx_seq()?;
+        let mut logs = Vec::new();
+        let mut bloom = Bloom::default();
 
+        for ((_, index, tx, tx_hash), outcome) in pending.into_iter().zip(outcomes) {
             // Allocate sequence number (safe to leak on batch failure).
-            last_seq =
-                storage.next_tx_seq().map_err(|e| ApplyBlockError::Storage(e.to_string()))?;
+            last_seq = storage.next_tx_seq()?;
 
+            let height = block.header.height;
+            let (gas_used, fee) = (outcome.gas_used(), outcome.fee());
+            let receipt = match outcome {
+                Outcome::Success { logs: emitted, .. } => {
+                    Receipt::success(tx_hash, tx, block_hash, height, index).with_logs(emitted)
+                }
+                Outcome::Failed { reason, .. } => {
+                    Receipt::failed(tx_hash, block_hash, height, index, reason.to_string())
+                }
+            }
+            .with_gas(gas_used, fee);
+            logs.extend(receipt.log_entries());
+            bloom.accrue_bloom(&receipt.bloom);
             ops.push(BatchOp::PutTransaction(tx_hash, tx.clone()));
             ops.push(BatchOp::PutTxSeqIndex(last_seq, tx_hash));
-
-            account_cache.insert(sender_addr, sender);
-            account_cache.insert(receiver_addr, receiver);
+            ops.push(BatchOp::PutReceipt(tx_hash, receipt));
+            ops.extend(address_index_ops(
+                tx,
+                AddressTxEntry {
+                    height,
+                    index,
+                    tx_hash,
+                },
+            ));
         }
 
-        // Flush modified accounts.
-        for (addr, account) in &account_cache {
+        // Flush modified accounts and contract state.
+        for (addr, account) in &changes.accounts {
             ops.push(BatchOp::PutAccount(*addr, account.clone()));
         }
+        for (code_hash, code) in changes.code {
+            ops.push(BatchOp::PutCode(code_hash, code));
+        }
+        for (contract, code_hash) in changes.contracts {
+            ops.push(BatchOp::PutContract(contract, code_hash));
+        }
+        for ((contract, key), value) in changes.storage {
+            ops.push(match value {
+                Some(value) => BatchOp::PutContractStorage(contract, key, value),
+                None => BatchOp::DeleteContractStorage(contract, key),
+            });
+        }
 
         if !block.body.transactions.is_empty() {
             ops.push(BatchOp::SetLastIncludedTxSeq(last_seq));
         }
 
-        let block_hash = compute_block_hash(block);
         ops.push(BatchOp::PutBlock(block_hash, block.clone()));
         ops.push(BatchOp::PutBlockHeightIndex(
             block.header.height,
             block_hash,
         ));
+        ops.push(BatchOp::PutBlockBloom(block.header.height, bloom));
 
         // Atomic commit.
-        storage.write_batch(ops).map_err(|e| ApplyBlockError::Storage(e.to_string()))?;
+        storage.write_batch(ops)?;
+
+        if let Some(ref events) = self.events {
+            events.publish(ChainEvent::NewHead(rest_block_detail(
+                block_hash, block, 0, 0,
+            )));
+            for (addr, account) in &changes.accounts {
+                events.publish(ChainEvent::AccountChanged(rest_account(*addr, account)));
+            }
+            for entry in logs {
+                events.publish(ChainEvent::Log(entry));
+            }
+        }
 
         Ok(block_hash)
     }
@@ -339,15 +391,60 @@ pub enum ApplyBlockError {
     /// A transaction in the block has an invalid signature.
     #[error("invalid transaction signature at index {0}")]
     InvalidSignature(usize),
+    /// A multisig transaction's witness does not authorize it.
+    #[error("invalid multisig witness at index {index}: {reason}")]
+    InvalidMultisig {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// Why the witness was rejected.
+        reason: MultisigError,
+    },
+    /// A transaction's data does not fit its type.
+    #[error("invalid transaction data at index {index}: {reason}")]
+    InvalidData {
+        /// Index of the transaction in the block.
+        index: usize,
+        /// What is wrong with the data.
+        reason: DataError,
+    },
     /// A transaction has an invalid nonce.
     #[error("invalid nonce at index {0}")]
     InvalidNonce(usize),
     /// A transaction has insufficient balance.
     #[error("insufficient balance at index {0}")]
     InsufficientBalance(usize),
+    /// The block at the current chain tip is missing from storage.
+    #[error("parent block not found at height {0}")]
+    MissingParent(u64),
     /// Storage error.
     #[error("storage error: {0}")]
-    Storage(String),
+    Storage(#[from] StorageError),
+}
+
+/// Why a transaction could not be executed against storage.
+pub(crate) type ExecutionError = execution::ExecutionError<StorageError>;
+
+/// Committed chain state read from storage, as seen by the contract runtime.
+struct StorageState<S>(Arc<S>);
+
+impl<S: Storage> ContractState for StorageState<S> {
+    type Error = StorageError;
+
+    fn account(&self, address: &Address) -> Result<Option<Account>, StorageError> {
+        self.0.get_account(address)
+    }
+
+    fn code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, StorageError> {
+        self.0.get_code(code_hash)
+    }
+
+    fn contract(&self, contract: &Address) -> Result<Option<Hash>, StorageError> {
+        self.0.get_contract(contract)
+    }
+
+    fn storage(&self, contract: &Address, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
+        self.0.get_contract_storage(contract, key)
+    }
 }
 
 /// Computes a deterministic blake3 hash over the SCALE-encoded transaction.
@@ -368,6 +465,98 @@ pub(crate) fn compute_block_hash(block: &Block) -> Hash {
     Hash(out)
 }
 
+/// Address-index writes recording `entry` for the sender and receiver of
+/// `tx`; a self-transfer is indexed once.
+pub(crate) fn address_index_ops(tx: &Transaction, entry: AddressTxEntry) -> Vec<BatchOp> {
+    let mut ops = vec![BatchOp::PutAddressTxIndex(tx.sender, entry)];
+    if tx.receiver != tx.sender {
+        ops.push(BatchOp::PutAddressTxIndex(tx.receiver, entry));
+    }
+    ops
+}
+
+/// Address of the pre-funded dev account created by
+/// [`NodeBackend::ensure_genesis`].
+///
+/// DEV ONLY: derived from a deterministic key (must match wallet example).
+pub(crate) fn dev_address() -> Address {
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&[0xAAu8; 32]);
+    Address(signing_key.verifying_key().to_bytes())
+}
+
+/// REST view of the block `block` stored under `hash`, embedding up to
+/// `tx_limit` of its transactions starting at index `tx_offset`.
+fn rest_block_detail(hash: Hash, block: &Block, tx_offset: usize, tx_limit: usize) -> BlockDetail {
+    let height = block.header.height;
+    let transactions = block
+        .body
+        .transactions
+        .iter()
+        .skip(tx_offset)
+        .take(tx_limit)
+        .map(|tx| RestTransaction {
+            block_hash: Some(hash.to_string()),
+            block_height: Some(height),
+            ..rest_transaction(compute_tx_hash(tx), tx, None)
+        })
+        .collect();
+    BlockDetail {
+        hash: hash.to_string(),
+        height,
+        timestamp: block.header.timestamp,
+        parent_hash: block.header.parent_hash.to_string(),
+        state_root: block.header.state_root.to_string(),
+        transactions_root: block.header.transactions_root.to_string(),
+        tx_count: block.body.transactions.len() as u32,
+        transactions,
+    }
+}
+
+/// REST view of a committed `tx`, with inclusion metadata taken from its
+/// receipt.
+fn rest_transaction(hash: Hash, tx: &Transaction, receipt: Option<&Receipt>) -> RestTransaction {
+    RestTransaction {
+        hash: hash.to_string(),
+        from: tx.sender.to_string(),
+        to: Some(tx.receiver.to_string()),
+        value: tx.amount.to_string(),
+        block_hash: receipt.map(|r| r.block_hash.to_string()),
+        block_height: receipt.map(|r| r.block_height),
+        status: RestTransactionStatus::Included,
+    }
+}
+
+/// REST view of a mempool transaction.
+fn rest_pending_transaction(hash: Hash, tx: &Transaction) -> RestTransaction {
+    RestTransaction {
+        status: RestTransactionStatus::Pending,
+        ..rest_transaction(hash, tx, None)
+    }
+}
+
+/// REST view of the account stored under `address`.
+fn rest_account(address: Address, account: &Account) -> RestAccount {
+    RestAccount {
+        address: address.to_string(),
+        balance: account.balance.to_string(),
+        nonce: account.nonce,
+    }
+}
+
+/// Converts a storage failure into a [`BackendError`] whose JSON-RPC `data`
+/// field carries the column family, key, and engine error kind.
+pub(crate) fn storage_error(e: StorageError) -> BackendError {
+    let data = serde_json::json!({
+        "column_family": e.column_family(),
+        "key": e.key().map(|k| k.to_string()),
+        "kind": e.db_kind().map(|k| k.to_string()),
+    });
+    BackendError::Storage {
+        message: e.to_string(),
+        data,
+    }
+}
+
 /// Returns the current Unix timestamp in seconds.
 fn now_secs() -> u64 {
     SystemTime::now()
@@ -382,10 +571,7 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
     fn get_block_height(
         &self,
     ) -> impl std::future::Future<Output = Result<u64, BackendError>> + Send {
-        let result = self
-            .storage
-            .get_latest_height()
-            .map_err(|e| BackendError::Internal(format!("storage error: {e}")));
+        let result = self.storage.get_latest_height().map_err(storage_error);
         std::future::ready(result)
     }
 
@@ -397,53 +583,74 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
     ) -> impl std::future::Future<Output = Result<String, BackendError>> + Send {
         let storage = Arc::clone(&self.storage);
         let mempool = Arc::clone(&self.mempool);
+        let events = self.events.clone();
         async move {
             let tx_hash = compute_tx_hash(&tx);
 
             // Idempotence: if already in storage (included in a block), return the hash.
-            if storage
-                .get_transaction(&tx_hash)
-                .map_err(|_| BackendError::Internal("storage error".to_string()))?
-                .is_some()
-            {
+            if storage.get_transaction(&tx_hash).map_err(storage_error)?.is_some() {
                 return Ok(tx_hash.to_string());
             }
 
             // Verify signature.
             if !tx.verify_signature() {
-                return Err(BackendError::Internal("invalid signature".to_string()));
+                return Err(BackendError::InvalidSignature);
             }
 
+            // Native transactions carry no data; code and calls are capped.
+            validate_data(&tx).map_err(|e| BackendError::InvalidParams(e.to_string()))?;
+
             // Load sender account for validation (nonce, balance).
             let sender_addr = tx.sender;
-            let sender = storage
-                .get_account(&sender_addr)
-                .map_err(|_| BackendError::Internal("storage error".to_string()))?
-                .ok_or_else(|| BackendError::Internal("insufficient balance".to_string()))?;
-
-            // Validate nonce (do not mutate; we only check).
-            if sender.nonce != tx.nonce {
-                return Err(BackendError::Internal("invalid nonce".to_string()));
-            }
-
-            // Validate balance.
-            if sender.balance < tx.amount {
-                return Err(BackendError::Internal("insufficient balance".to_string()));
-            }
+            let sender = storage.get_account(&sender_addr).map_err(storage_error)?.ok_or(
+                BackendError::InsufficientBalance {
+                    balance: 0,
+                    required: tx.amount.saturating_add(max_fee(&tx)),
+                },
+            )?;
 
-            // Insert into mempool. Idempotent: if already in mempool, return hash.
+            // Hold the pool lock while validating against pending transactions.
+            // Idempotent: if already in mempool, return hash.
             let mut pool = mempool.write().await;
             if pool.contains_hash(&tx_hash) {
                 return Ok(tx_hash.to_string());
             }
+
+            // Validate nonce: the account nonce, or the next one after the
+            // sender's pending transactions (do not mutate; we only check).
+            if pool.contains_sender_nonce(&sender_addr, tx.nonce) {
+                return Err(BackendError::DuplicateSenderNonce { nonce: tx.nonce });
+            }
+            let expected = pool.next_nonce(&sender_addr, sender.nonce);
+            if tx.nonce != expected {
+                return Err(BackendError::InvalidNonce {
+                    expected,
+                    actual: tx.nonce,
+                });
+            }
+
+            // Validate balance net of pending spends, reserving the most a
+            // contract call can be charged for gas.
+            let available = sender.balance.saturating_sub(pool.pending_spend(&sender_addr));
+            let required = tx.amount.saturating_add(max_fee(&tx));
+            if available < required {
+                return Err(BackendError::InsufficientBalance {
+                    balance: available,
+                    required,
+                });
+            }
+
+            let pending = rest_pending_transaction(tx_hash, &tx);
+            let nonce = tx.nonce;
             pool.insert(tx_hash, tx).map_err(|e| match e {
-                MempoolError::DuplicateHash => {
-                    BackendError::Internal("duplicate transaction".to_string())
-                }
-                MempoolError::DuplicateSenderNonce => {
-                    BackendError::Internal("duplicate sender nonce".to_string())
-                }
+                MempoolError::DuplicateHash => BackendError::DuplicateTransaction,
+                MempoolError::DuplicateSenderNonce => BackendError::DuplicateSenderNonce { nonce },
             })?;
+            drop(pool);
+
+            if let Some(events) = events {
+                events.publish(ChainEvent::PendingTransaction(pending));
+            }
 
             Ok(tx_hash.to_string())
         }
@@ -457,27 +664,19 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
         let backend = self.clone();
         async move {
             if !backend.is_producer {
-                return Err(BackendError::Internal(
-                    "node is not configured as producer".to_string(),
-                ));
+                return Err(BackendError::NotProducer);
             }
 
             // Ensure genesis exists.
-            if storage
-                .get_block_by_height(0)
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?
-                .is_none()
-            {
+            if storage.get_block_by_height(0).map_err(storage_error)?.is_none() {
                 return Err(BackendError::Internal("genesis block required".to_string()));
             }
 
-            let current_height = storage
-                .get_latest_height()
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
+            let current_height = storage.get_latest_height().map_err(storage_error)?;
 
             let parent_block = storage
                 .get_block_by_height(current_height)
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?
+                .map_err(storage_error)?
                 .ok_or_else(|| BackendError::Internal("parent block not found".to_string()))?;
 
             let parent_hash = compute_block_hash(&parent_block);
@@ -501,8 +700,10 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
             };
 
             // Delegate to apply_block (shared validation + atomic commit).
-            let block_hash =
-                backend.apply_block(&block).map_err(|e| BackendError::Internal(e.to_string()))?;
+            let block_hash = backend.apply_block(&block).map_err(|e| match e {
+                ApplyBlockError::Storage(se) => storage_error(se),
+                other => BackendError::Internal(other.to_string()),
+            })?;
 
             // Broadcast the newly produced block to connected peers.
             if let Some(ref broadcaster) = backend.broadcaster {
@@ -518,16 +719,12 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
     ) -> impl std::future::Future<Output = Result<String, BackendError>> + Send {
         let storage = Arc::clone(&self.storage);
         async move {
-            let height = storage
-                .get_latest_height()
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?;
+            let height = storage.get_latest_height().map_err(storage_error)?;
 
             let block = storage
                 .get_block_by_height(height)
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?
-                .ok_or_else(|| {
-                    BackendError::Internal(format!("block not found at height {height}"))
-                })?;
+                .map_err(storage_error)?
+                .ok_or_else(|| BackendError::NotFound(format!("block at height {height}")))?;
 
             Ok(compute_block_hash(&block).to_string())
         }
@@ -541,31 +738,438 @@ impl<S: Storage + Send + Sync + 'static> RpcBackend for NodeBackend<S> {
         async move {
             let block = storage
                 .get_block_by_height(height)
-                .map_err(|e| BackendError::Internal(format!("storage error: {e}")))?
-                .ok_or_else(|| {
-                    BackendError::Internal(format!("block not found at height {height}"))
-                })?;
+                .map_err(storage_error)?
+                .ok_or_else(|| BackendError::NotFound(format!("block at height {height}")))?;
 
             serde_json::to_value(&block)
                 .map_err(|e| BackendError::Internal(format!("serialization error: {e}")))
         }
     }
+
+    fn get_transaction_receipt(
+        &self,
+        hash: Hash,
+    ) -> impl std::future::Future<Output = Result<serde_json::Value, BackendError>> + Send {
+        let result = self.storage.get_receipt(&hash).map_err(storage_error).and_then(|receipt| {
+            serde_json::to_value(receipt)
+                .map_err(|e| BackendError::Internal(format!("serialization error: {e}")))
+        });
+        std::future::ready(result)
+    }
+
+    fn get_account(
+        &self,
+        address: Address,
+    ) -> impl std::future::Future<Output = Result<Option<AccountInfo>, BackendError>> + Send {
+        let result = self.storage.get_account(&address).map_err(storage_error).map(|account| {
+            account.map(|a| AccountInfo {
+                address,
+                balance: a.balance.to_string(),
+                nonce: a.nonce,
+            })
+        });
+        std::future::ready(result)
+    }
+
+    fn get_balance(
+        &self,
+        address: Address,
+    ) -> impl std::future::Future<Output = Result<String, BackendError>> + Send {
+        let result = self
+            .storage
+            .get_account(&address)
+            .map_err(storage_error)
+            .map(|account| account.map_or(0, |a| a.balance).to_string());
+        std::future::ready(result)
+    }
+
+    fn get_next_nonce(
+        &self,
+        address: Address,
+    ) -> impl std::future::Future<Output = Result<u64, BackendError>> + Send {
+        let storage = Arc::clone(&self.storage);
+        let mempool = Arc::clone(&self.mempool);
+        async move {
+            let account_nonce =
+                storage.get_account(&address).map_err(storage_error)?.map_or(0, |a| a.nonce);
+            Ok(mempool.read().await.next_nonce(&address, account_nonce))
+        }
+    }
+
+    fn simulate_transaction(
+        &self,
+        tx: Transaction,
+        verify_signature: bool,
+    ) -> impl std::future::Future<Output = Result<SimulationResult, BackendError>> + Send {
+        let storage = Arc::clone(&self.storage);
+        let mempool = Arc::clone(&self.mempool);
+        let runtime = Arc::clone(&self.runtime);
+        async move {
+            let failed = |e: BackendError| SimulationResult {
+                success: false,
+                error: Some(e.to_rpc_error()),
+                failure: None,
+                fee: "0".to_string(),
+                gas_used: 0,
+                state_diff: Vec::new(),
+            };
+
+            if verify_signature && !tx.verify_signature() {
+                return Ok(failed(BackendError::InvalidSignature));
+            }
+            if let Err(e) = validate_data(&tx) {
+                return Ok(failed(BackendError::InvalidParams(e.to_string())));
+            }
+            let tx_hash = compute_tx_hash(&tx);
+            if storage.get_transaction(&tx_hash).map_err(storage_error)?.is_some() {
+                return Ok(failed(BackendError::DuplicateTransaction));
+            }
+
+            // Replay pending transactions into a throwaway overlay so the
+            // simulation sees the state `tx` would actually run against.
+            // Pending transactions that no longer apply are skipped, as they
+            // would fail block production too; a rejected transaction leaves
+            // the overlay unchanged.
+            let state = Arc::new(StorageState(Arc::clone(&storage)));
+            let mut changes = StateChanges::default();
+            {
+                let pool = mempool.read().await;
+                if pool.contains_hash(&tx_hash) {
+                    return Ok(failed(BackendError::DuplicateTransaction));
+                }
+                for pending in pool.iter() {
+                    match runtime.execute_transaction(&state, &mut changes, pending) {
+                        Ok(_)
+                        | Err(
+                            ExecutionError::InvalidNonce { .. }
+                            | ExecutionError::InsufficientBalance { .. }
+                            | ExecutionError::BalanceOverflow,
+                        ) => {}
+                        Err(ExecutionError::Storage(e)) => return Err(storage_error(e)),
+                    }
+                }
+            }
+
+            let before = changes.accounts.clone();
+            let outcome = match runtime.execute_transaction(&state, &mut changes, &tx) {
+                Ok(outcome) => outcome,
+                Err(ExecutionError::InvalidNonce { expected, actual }) => {
+                    return Ok(failed(BackendError::InvalidNonce { expected, actual }));
+                }
+                Err(ExecutionError::InsufficientBalance { balance, required }) => {
+                    return Ok(failed(BackendError::InsufficientBalance {
+                        balance,
+                        required,
+                    }));
+                }
+                Err(ExecutionError::BalanceOverflow) => {
+                    return Ok(failed(BackendError::Internal(
+                        "receiver balance overflow".to_string(),
+                    )));
+                }
+                Err(ExecutionError::Storage(e)) => return Err(storage_error(e)),
+            };
+
+            // Sender and receiver first, then any other account a contract
+            // changed, in address order.
+            let mut touched = vec![tx.sender];
+            if tx.receiver != tx.sender {
+                touched.push(tx.receiver);
+            }
+            let mut others: Vec<Address> = changes
+                .accounts
+                .iter()
+                .filter(|(address, account)| {
+                    !touched.contains(address) && before.get(address) != Some(account)
+                })
+                .map(|(address, _)| *address)
+                .collect();
+            others.sort_unstable_by_key(|address| address.0);
+            touched.extend(others);
+
+            let mut state_diff = Vec::with_capacity(touched.len());
+            for address in touched {
+                let Some(after) = changes.accounts.get(&address) else {
+                    continue;
+                };
+                let before = match before.get(&address) {
+                    Some(acc) => Some(acc.clone()),
+                    None => storage.get_account(&address).map_err(storage_error)?,
+                };
+                state_diff.push(AccountDiff {
+                    address,
+                    before: before.map(|a| account_info(&a)),
+                    after: account_info(after),
+                });
+            }
+
+            let failure = match &outcome {
+                Outcome::Success { .. } => None,
+                Outcome::Failed { reason, .. } => Some(reason.to_string()),
+            };
+            Ok(SimulationResult {
+                success: failure.is_none(),
+                error: None,
+                failure,
+                fee: outcome.fee().to_string(),
+                gas_used: outcome.gas_used(),
+                state_diff,
+            })
+        }
+    }
+
+    fn get_transaction(
+        &self,
+        hash: Hash,
+    ) -> impl std::future::Future<Output = Result<TransactionLookup, BackendError>> + Send {
+        let storage = Arc::clone(&self.storage);
+        let mempool = Arc::clone(&self.mempool);
+        async move {
+            if let Some(tx) = mempool.read().await.get(&hash) {
+                return Ok(TransactionLookup {
+                    hash,
+                    status: TransactionStatus::Pending,
+                    transaction: Some(tx.clone()),
+                    block_hash: None,
+                    block_height: None,
+                });
+            }
+            let Some(tx) = storage.get_transaction(&hash).map_err(storage_error)? else {
+                return Ok(TransactionLookup {
+                    hash,
+                    status: TransactionStatus::Unknown,
+                    transaction: None,
+                    block_hash: None,
+                    block_height: None,
+                });
+            };
+            let receipt = storage.get_receipt(&hash).map_err(storage_error)?;
+            Ok(TransactionLookup {
+                hash,
+                status: TransactionStatus::Included,
+                transaction: Some(tx),
+                block_hash: receipt.as_ref().map(|r| r.block_hash),
+                block_height: receipt.as_ref().map(|r| r.block_height),
+            })
+        }
+    }
+
+    fn get_pending_transaction(
+        &self,
+        hash: Hash,
+    ) -> impl std::future::Future<Output = Result<Option<PendingTransaction>, BackendError>> + Send
+    {
+        let mempool = Arc::clone(&self.mempool);
+        async move {
+            let pool = mempool.read().await;
+            Ok(pool.get(&hash).map(|tx| PendingTransaction {
+                hash,
+                transaction: tx.clone(),
+            }))
+        }
+    }
+
+    fn mempool_status(
+        &self,
+    ) -> impl std::future::Future<Output = Result<MempoolStatus, BackendError>> + Send {
+        let mempool = Arc::clone(&self.mempool);
+        async move {
+            let pool = mempool.read().await;
+            Ok(MempoolStatus {
+                pending: pool.len() as u64,
+                senders: pool.sender_count() as u64,
+            })
+        }
+    }
+
+    fn mempool_content(
+        &self,
+        sender: Option<Address>,
+        offset: usize,
+        limit: usize,
+    ) -> impl std::future::Future<Output = Result<MempoolContent, BackendError>> + Send {
+        let mempool = Arc::clone(&self.mempool);
+        async move {
+            let pool = mempool.read().await;
+            let (total, page) = mempool_page(&pool, sender.as_ref(), offset, limit);
+            Ok(MempoolContent {
+                total,
+                transactions: page
+                    .into_iter()
+                    .map(|(hash, tx)| PendingTransaction {
+                        hash,
+                        transaction: tx.clone(),
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    fn get_account_transactions(
+        &self,
+        address: Address,
+        before: Option<u64>,
+        limit: usize,
+    ) -> impl std::future::Future<Output = Result<Vec<AccountTransaction>, BackendError>> + Send
+    {
+        let result = account_history(self.storage.as_ref(), &address, before, limit)
+            .map_err(storage_error)
+            .map(|history| {
+                history
+                    .into_iter()
+                    .map(|(entry, transaction)| AccountTransaction {
+                        hash: entry.tx_hash,
+                        block_height: entry.height,
+                        index: entry.index,
+                        transaction,
+                    })
+                    .collect()
+            });
+        std::future::ready(result)
+    }
+
+    fn get_logs(
+        &self,
+        filter: LogFilter,
+    ) -> impl std::future::Future<Output = Result<Vec<LogEntry>, BackendError>> + Send {
+        std::future::ready(find_logs(self.storage.as_ref(), &filter))
+    }
+}
+
+/// Collects the logs matching `filter` from the committed blocks in its
+/// height range. Blocks whose stored bloom rules out a match are skipped
+/// without reading their body or receipts; within a block, receipts whose
+/// bloom rules out a match are skipped before their logs are compared.
+/// Only a transaction's first inclusion is searched.
+///
+/// Fails with [`BackendError::InvalidParams`] once more than
+/// [`MAX_LOG_ENTRIES`] logs match, rather than returning a truncated list.
+fn find_logs<S: Storage>(storage: &S, filter: &LogFilter) -> Result<Vec<LogEntry>, BackendError> {
+    let tip = storage.get_latest_height().map_err(storage_error)?;
+    let to = filter.to_height.unwrap_or(tip);
+    let from = filter.from_height.unwrap_or(to.min(tip));
+    if filter.to_height.is_some() && from > to {
+        return Err(BackendError::InvalidParams(format!(
+            "from_height {from} is above to_height {to}"
+        )));
+    }
+    if to.saturating_sub(from) >= MAX_LOG_BLOCK_RANGE {
+        return Err(BackendError::InvalidParams(format!(
+            "block range {from}..={to} spans more than {MAX_LOG_BLOCK_RANGE} blocks"
+        )));
+    }
+
+    let mut entries = Vec::new();
+    for height in from..=to.min(tip) {
+        let bloom = storage.get_block_bloom(height).map_err(storage_error)?;
+        if bloom.is_some_and(|bloom| !filter.may_match(&bloom)) {
+            continue;
+        }
+        let Some(block) = storage.get_block_by_height(height).map_err(storage_error)? else {
+            continue;
+        };
+        for (index, tx) in (0u32..).zip(&block.body.transactions) {
+            let Some(receipt) = storage.get_receipt(&compute_tx_hash(tx)).map_err(storage_error)?
+            else {
+                continue;
+            };
+            if receipt.block_height != height
+                || receipt.index != index
+                || !filter.may_match(&receipt.bloom)
+            {
+                continue;
+            }
+            entries.extend(receipt.log_entries().filter(|entry| filter.matches(&entry.log)));
+            if entries.len() > MAX_LOG_ENTRIES {
+                return Err(BackendError::InvalidParams(format!(
+                    "more than {MAX_LOG_ENTRIES} logs match; narrow the block range or filter"
+                )));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Looks up one page of the address index for `address` together with the
+/// indexed transactions. Entries whose transaction is not stored are
+/// skipped; `db check` reports those as missing transactions.
+fn account_history<S: Storage>(
+    storage: &S,
+    address: &Address,
+    before: Option<u64>,
+    limit: usize,
+) -> Result<Vec<(AddressTxEntry, Transaction)>, StorageError> {
+    let mut history = Vec::new();
+    for entry in storage.get_address_transactions(address, before, limit)? {
+        if let Some(tx) = storage.get_transaction(&entry.tx_hash)? {
+            history.push((entry, tx));
+        }
+    }
+    Ok(history)
+}
+
+/// Returns the number of pending transactions from `sender` (or from anyone,
+/// if `None`) together with the `offset..offset + limit` slice of them.
+fn mempool_page<'a>(
+    pool: &'a Mempool,
+    sender: Option<&Address>,
+    offset: usize,
+    limit: usize,
+) -> (u64, Vec<(Hash, &'a Transaction)>) {
+    let mut total = 0usize;
+    let mut page = Vec::new();
+    for (hash, tx) in pool.entries() {
+        if sender.is_some_and(|s| tx.sender != *s) {
+            continue;
+        }
+        if total >= offset && page.len() < limit {
+            page.push((*hash, tx));
+        }
+        total += 1;
+    }
+    (total as u64, page)
+}
+
+fn account_info(account: &Account) -> AccountInfo {
+    AccountInfo {
+        address: account.address,
+        balance: account.balance.to_string(),
+        nonce: account.nonce,
+    }
 }
 
 // ── ApiBackend ──────────────────────────────────────────────────────────
 
 #[async_trait]
 impl<S: Storage + Send + Sync + 'static> ApiBackend for NodeBackend<S> {
-    async fn list_blocks(&self, limit: u32) -> Result<Vec<BlockSummary>, ApiError> {
+    async fn list_blocks(
+        &self,
+        before: Option<u64>,
+        after: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<BlockSummary>, ApiError> {
         let latest = self
             .storage
             .get_latest_height()
             .map_err(|e| ApiError::Internal(e.to_string()))?;
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Inclusive height range to list, newest first.
+        let (low, high) = match (before, after) {
+            (Some(0), _) => return Ok(Vec::new()),
+            (Some(before), _) => {
+                let high = latest.min(before - 1);
+                (high.saturating_sub(u64::from(limit) - 1), high)
+            }
+            (None, Some(after)) if after >= latest => return Ok(Vec::new()),
+            (None, Some(after)) => (after + 1, latest.min(after + u64::from(limit))),
+            (None, None) => (latest.saturating_sub(u64::from(limit) - 1), latest),
+        };
 
         let mut blocks = Vec::new();
-        let count = std::cmp::min(limit as u64, latest + 1);
-        for i in 0..count {
-            let height = latest - i;
+        for height in (low..=high).rev() {
             if let Some(block) = self
                 .storage
                 .get_block_by_height(height)
@@ -582,7 +1186,12 @@ impl<S: Storage + Send + Sync + 'static> ApiBackend for NodeBackend<S> {
         Ok(blocks)
     }
 
-    async fn get_block(&self, hash: String) -> Result<BlockDetail, ApiError> {
+    async fn get_block(
+        &self,
+        hash: String,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError> {
         let parsed: Hash = hash.parse().map_err(|e: String| ApiError::Invalid(e))?;
 
         let block = self
@@ -591,32 +1200,51 @@ impl<S: Storage + Send + Sync + 'static> ApiBackend for NodeBackend<S> {
             .map_err(|e| ApiError::Internal(e.to_string()))?
             .ok_or(ApiError::NotFound)?;
 
-        Ok(BlockDetail {
-            hash: parsed.to_string(),
-            height: block.header.height,
-            timestamp: block.header.timestamp,
-            parent_hash: block.header.parent_hash.to_string(),
-            tx_count: block.body.transactions.len() as u32,
-        })
+        Ok(rest_block_detail(
+            parsed,
+            &block,
+            tx_offset as usize,
+            tx_limit as usize,
+        ))
+    }
+
+    async fn get_block_by_height(
+        &self,
+        height: u64,
+        tx_offset: u32,
+        tx_limit: u32,
+    ) -> Result<BlockDetail, ApiError> {
+        let block = self
+            .storage
+            .get_block_by_height(height)
+            .map_err(|e| ApiError::Internal(e.to_string()))?
+            .ok_or(ApiError::NotFound)?;
+
+        Ok(rest_block_detail(
+            compute_block_hash(&block),
+            &block,
+            tx_offset as usize,
+            tx_limit as usize,
+        ))
     }
 
     async fn get_transaction(&self, hash: String) -> Result<RestTransaction, ApiError> {
         let parsed: Hash = hash.parse().map_err(|e: String| ApiError::Invalid(e))?;
 
+        if let Some(tx) = self.mempool.read().await.get(&parsed) {
+            return Ok(rest_pending_transaction(parsed, tx));
+        }
         let tx = self
             .storage
             .get_transaction(&parsed)
             .map_err(|e| ApiError::Internal(e.to_string()))?
             .ok_or(ApiError::NotFound)?;
+        let receipt = self
+            .storage
+            .get_receipt(&parsed)
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
 
-        Ok(RestTransaction {
-            hash: parsed.to_string(),
-            from: tx.sender.to_string(),
-            to: Some(tx.receiver.to_string()),
-            value: tx.amount.to_string(),
-            block_hash: None,
-            block_height: None,
-        })
+        Ok(rest_transaction(parsed, &tx, receipt.as_ref()))
     }
 
     async fn get_account(&self, address: String) -> Result<RestAccount, ApiError> {
@@ -628,10 +1256,44 @@ impl<S: Storage + Send + Sync + 'static> ApiBackend for NodeBackend<S> {
             .map_err(|e| ApiError::Internal(e.to_string()))?
             .ok_or(ApiError::NotFound)?;
 
-        Ok(RestAccount {
-            address: parsed.to_string(),
-            balance: account.balance.to_string(),
-            nonce: account.nonce,
+        Ok(rest_account(parsed, &account))
+    }
+
+    async fn list_account_transactions(
+        &self,
+        address: String,
+        before: Option<u64>,
+        limit: u32,
+    ) -> Result<Vec<RestTransaction>, ApiError> {
+        let parsed: Address = address.parse().map_err(|e: String| ApiError::Invalid(e))?;
+
+        let history = account_history(self.storage.as_ref(), &parsed, before, limit as usize)
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        history
+            .into_iter()
+            .map(|(entry, tx)| {
+                let receipt = self
+                    .storage
+                    .get_receipt(&entry.tx_hash)
+                    .map_err(|e| ApiError::Internal(e.to_string()))?;
+                Ok(rest_transaction(entry.tx_hash, &tx, receipt.as_ref()))
+            })
+            .collect()
+    }
+
+    async fn post_transaction(&self, tx: Transaction) -> Result<String, ApiError> {
+        RpcBackend::submit_transaction(self, tx).await.map_err(|e| match e {
+            BackendError::Internal(_)
+            | BackendError::Storage { .. }
+            | BackendError::MethodNotFound(_) => ApiError::Internal(e.to_string()),
+            BackendError::NotFound(_) => ApiError::NotFound,
+            BackendError::InvalidParams(_)
+            | BackendError::InvalidSignature
+            | BackendError::InvalidNonce { .. }
+            | BackendError::InsufficientBalance { .. }
+            | BackendError::DuplicateTransaction
+            | BackendError::DuplicateSenderNonce { .. }
+            | BackendError::NotProducer => ApiError::Invalid(e.to_string()),
         })
     }
 
@@ -639,14 +1301,38 @@ impl<S: Storage + Send + Sync + 'static> ApiBackend for NodeBackend<S> {
         // Phase 1 minimal: no validator tracking yet.
         Ok(Vec::new())
     }
+
+    async fn list_mempool(
+        &self,
+        sender: Option<String>,
+        offset: u32,
+        limit: u32,
+    ) -> Result<MempoolPage, ApiError> {
+        let sender: Option<Address> = sender
+            .map(|s| s.parse().map_err(|e: String| ApiError::Invalid(e)))
+            .transpose()?;
+
+        let pool = self.mempool.read().await;
+        let (total, page) = mempool_page(&pool, sender.as_ref(), offset as usize, limit as usize);
+        Ok(MempoolPage {
+            pending: pool.len() as u64,
+            total,
+            transactions: page
+                .into_iter()
+                .map(|(hash, tx)| rest_pending_transaction(hash, tx))
+                .collect(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+    use mbongo_api::ws::EventKind;
     use mbongo_core::{
-        Account, Address, Block, BlockBody, BlockHeader, Hash, Transaction, TransactionType,
+        Account, Address, Block, BlockBody, BlockHeader, Hash, Multisig, MultisigWitness,
+        ReceiptStatus, Transaction, TransactionType,
     };
     use mbongo_storage::InMemoryStorage;
 
@@ -673,6 +1359,8 @@ mod tests {
                     amount: 50,
                     nonce: 0,
                     signature: [0u8; 64],
+                    multisig: None,
+                    data: Vec::new(),
                 }],
             },
         };
@@ -688,6 +1376,8 @@ mod tests {
             amount: 100,
             nonce: 0,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         (hash, tx)
     }
@@ -716,6 +1406,8 @@ mod tests {
             amount,
             nonce,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         let sig = sender_sk.sign(&tx.signing_payload());
         tx.signature = sig.to_bytes();
@@ -785,10 +1477,21 @@ mod tests {
 
         // nonce=5 but account nonce is 0.
         let tx = signed_transfer(&sender_sk, receiver_addr, 100, 5);
-        let result = backend.submit_transaction(tx).await;
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("invalid nonce"), "got: {err}");
+        let err = backend.submit_transaction(tx).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BackendError::InvalidNonce {
+                    expected: 0,
+                    actual: 5
+                }
+            ),
+            "got: {err}"
+        );
+        assert_eq!(
+            err.data(),
+            Some(serde_json::json!({ "expected": 0, "actual": 5 }))
+        );
     }
 
     #[tokio::test]
@@ -803,10 +1506,80 @@ mod tests {
         backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
 
         let tx = signed_transfer(&sender_sk, receiver_addr, 100, 0);
-        let result = backend.submit_transaction(tx).await;
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("insufficient balance"), "got: {err}");
+        let err = backend.submit_transaction(tx).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BackendError::InsufficientBalance {
+                    balance: 50,
+                    required: 100
+                }
+            ),
+            "got: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn submit_call_must_cover_its_gas_limit() {
+        let backend = make_backend();
+        let sender_sk = SigningKey::from_bytes(&[3u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 50_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        let payload = mbongo_runtime::ContractCall {
+            function: "run".to_string(),
+            input: Vec::new(),
+            gas_limit: 100_000,
+        };
+        let mut tx = signed_transfer(&sender_sk, Address([11u8; 32]), 10, 0);
+        tx.tx_type = TransactionType::Call;
+        tx.data = payload.encode();
+        tx.signature = sender_sk.sign(&tx.signing_payload()).to_bytes();
+
+        let err = backend.submit_transaction(tx).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                BackendError::InsufficientBalance {
+                    balance: 50_000,
+                    required: 100_010
+                }
+            ),
+            "got: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn submit_rejects_data_that_does_not_fit_the_type() {
+        let backend = make_backend();
+        let sender_sk = SigningKey::from_bytes(&[5u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 1_000_000_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        let with_data = |tx_type, data| {
+            let mut tx = signed_transfer(&sender_sk, Address([13u8; 32]), 10, 0);
+            tx.tx_type = tx_type;
+            tx.data = data;
+            tx.signature = sender_sk.sign(&tx.signing_payload()).to_bytes();
+            tx
+        };
+        for tx in [
+            with_data(TransactionType::Transfer, vec![1]),
+            with_data(
+                TransactionType::Call,
+                vec![0u8; mbongo_runtime::wasm::MAX_CALL_SIZE + 1],
+            ),
+        ] {
+            let err = backend.submit_transaction(tx).await.unwrap_err();
+            assert!(matches!(err, BackendError::InvalidParams(_)), "got: {err}");
+        }
+        assert_eq!(backend.mempool.read().await.len(), 0);
     }
 
     #[tokio::test]
@@ -863,7 +1636,7 @@ mod tests {
     #[tokio::test]
     async fn api_list_blocks_returns_empty() {
         let backend = make_backend();
-        let blocks = backend.list_blocks(10).await.unwrap();
+        let blocks = backend.list_blocks(None, None, 10).await.unwrap();
         assert!(blocks.is_empty());
     }
 
@@ -877,7 +1650,7 @@ mod tests {
     #[tokio::test]
     async fn api_get_block_not_found() {
         let backend = make_backend();
-        let result = backend.get_block(Hash::zero().to_string()).await;
+        let result = backend.get_block(Hash::zero().to_string(), 0, 100).await;
         assert!(matches!(result, Err(ApiError::NotFound)));
     }
 
@@ -887,18 +1660,29 @@ mod tests {
         let (hash, block) = sample_block();
         backend.storage.put_block(&hash, &block).unwrap();
 
-        let detail = backend.get_block(hash.to_string()).await.unwrap();
+        let detail = backend.get_block(hash.to_string(), 0, 100).await.unwrap();
         assert_eq!(detail.height, 1);
         assert_eq!(detail.timestamp, 1_700_000_000);
         assert_eq!(detail.tx_count, 1);
         assert_eq!(detail.hash, hash.to_string());
         assert_eq!(detail.parent_hash, Hash::zero().to_string());
+        assert_eq!(detail.state_root, Hash::zero().to_string());
+        assert_eq!(detail.transactions.len(), 1);
+        assert_eq!(
+            detail.transactions[0].hash,
+            compute_tx_hash(&block.body.transactions[0]).to_string()
+        );
+        assert_eq!(detail.transactions[0].block_height, Some(1));
+
+        let detail = backend.get_block(hash.to_string(), 1, 100).await.unwrap();
+        assert_eq!(detail.tx_count, 1);
+        assert!(detail.transactions.is_empty());
     }
 
     #[tokio::test]
     async fn api_get_transaction_not_found() {
         let backend = make_backend();
-        let result = backend.get_transaction(Hash::zero().to_string()).await;
+        let result = ApiBackend::get_transaction(&backend, Hash::zero().to_string()).await;
         assert!(matches!(result, Err(ApiError::NotFound)));
     }
 
@@ -908,7 +1692,7 @@ mod tests {
         let (hash, tx) = sample_tx();
         backend.storage.put_transaction(&hash, &tx).unwrap();
 
-        let rest_tx = backend.get_transaction(hash.to_string()).await.unwrap();
+        let rest_tx = ApiBackend::get_transaction(&backend, hash.to_string()).await.unwrap();
         assert_eq!(rest_tx.hash, hash.to_string());
         assert_eq!(rest_tx.from, Address([3u8; 32]).to_string());
         assert_eq!(rest_tx.to, Some(Address([4u8; 32]).to_string()));
@@ -918,7 +1702,7 @@ mod tests {
     #[tokio::test]
     async fn api_get_account_not_found() {
         let backend = make_backend();
-        let result = backend.get_account(Address::zero().to_string()).await;
+        let result = ApiBackend::get_account(&backend, Address::zero().to_string()).await;
         assert!(matches!(result, Err(ApiError::NotFound)));
     }
 
@@ -928,7 +1712,7 @@ mod tests {
         let (addr, account) = sample_account();
         backend.storage.put_account(&addr, &account).unwrap();
 
-        let rest_acc = backend.get_account(addr.to_string()).await.unwrap();
+        let rest_acc = ApiBackend::get_account(&backend, addr.to_string()).await.unwrap();
         assert_eq!(rest_acc.address, addr.to_string());
         assert_eq!(rest_acc.balance, "42000");
         assert_eq!(rest_acc.nonce, 3);
@@ -937,14 +1721,14 @@ mod tests {
     #[tokio::test]
     async fn api_get_block_invalid_hash() {
         let backend = make_backend();
-        let result = backend.get_block("not-a-hash".to_string()).await;
+        let result = backend.get_block("not-a-hash".to_string(), 0, 100).await;
         assert!(matches!(result, Err(ApiError::Invalid(_))));
     }
 
     #[tokio::test]
     async fn api_get_account_invalid_address() {
         let backend = make_backend();
-        let result = backend.get_account("bad".to_string()).await;
+        let result = ApiBackend::get_account(&backend, "bad".to_string()).await;
         assert!(matches!(result, Err(ApiError::Invalid(_))));
     }
 
@@ -1050,64 +1834,533 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn api_list_blocks_after_genesis_and_produce() {
+    async fn api_list_blocks_after_genesis_and_produce() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        backend.produce_block().await.unwrap();
+
+        let blocks = backend.list_blocks(None, None, 10).await.unwrap();
+        assert_eq!(blocks.len(), 2);
+        // Most recent first.
+        assert_eq!(blocks[0].height, 1);
+        assert_eq!(blocks[1].height, 0);
+    }
+
+    #[tokio::test]
+    async fn api_list_blocks_cursors_and_height_lookup() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        for _ in 0..6 {
+            backend.produce_block().await.unwrap();
+        }
+        let heights =
+            |blocks: Vec<BlockSummary>| -> Vec<u64> { blocks.iter().map(|b| b.height).collect() };
+
+        let blocks = backend.list_blocks(None, None, 3).await.unwrap();
+        assert_eq!(heights(blocks), vec![6, 5, 4]);
+        let blocks = backend.list_blocks(Some(4), None, 3).await.unwrap();
+        assert_eq!(heights(blocks), vec![3, 2, 1]);
+        let blocks = backend.list_blocks(Some(2), None, 10).await.unwrap();
+        assert_eq!(heights(blocks), vec![1, 0]);
+        let blocks = backend.list_blocks(Some(0), None, 10).await.unwrap();
+        assert!(blocks.is_empty());
+        let blocks = backend.list_blocks(None, Some(1), 2).await.unwrap();
+        assert_eq!(heights(blocks), vec![3, 2]);
+        let blocks = backend.list_blocks(None, Some(4), 10).await.unwrap();
+        assert_eq!(heights(blocks), vec![6, 5]);
+        let blocks = backend.list_blocks(None, Some(6), 10).await.unwrap();
+        assert!(blocks.is_empty());
+
+        let detail = ApiBackend::get_block_by_height(&backend, 3, 0, 10).await.unwrap();
+        assert_eq!(detail.height, 3);
+        let by_hash = backend.get_block(detail.hash.clone(), 0, 10).await.unwrap();
+        assert_eq!(by_hash.parent_hash, detail.parent_hash);
+        assert!(matches!(
+            ApiBackend::get_block_by_height(&backend, 7, 0, 10).await,
+            Err(ApiError::NotFound)
+        ));
+    }
+
+    // ── Transaction inclusion tests ─────────────────────────────────
+
+    #[tokio::test]
+    async fn api_post_transaction_validates_like_rpc() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[10u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let mut account = Account::new(sender_addr);
+        account.balance = 1_000;
+        backend.storage.put_account(&sender_addr, &account).unwrap();
+
+        let tx = signed_transfer(&sender_sk, Address([20u8; 32]), 100, 0);
+        let hash = backend.post_transaction(tx.clone()).await.unwrap();
+        assert_eq!(hash, compute_tx_hash(&tx).to_string());
+        assert_eq!(backend.mempool.read().await.len(), 1);
+
+        let err = backend
+            .post_transaction(signed_transfer(&sender_sk, Address([20u8; 32]), 100, 5))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ApiError::Invalid(ref m) if m.contains("nonce")),
+            "got: {err}"
+        );
+        let mut forged = signed_transfer(&sender_sk, Address([20u8; 32]), 100, 1);
+        forged.amount = 999;
+        assert!(matches!(
+            backend.post_transaction(forged).await,
+            Err(ApiError::Invalid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn account_transactions_cover_sender_and_receiver() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let alice_sk = SigningKey::from_bytes(&[10u8; 32]);
+        let alice = Address(alice_sk.verifying_key().to_bytes());
+        let bob_sk = SigningKey::from_bytes(&[11u8; 32]);
+        let bob = Address(bob_sk.verifying_key().to_bytes());
+        for addr in [alice, bob] {
+            let mut account = Account::new(addr);
+            account.balance = 10_000;
+            backend.storage.put_account(&addr, &account).unwrap();
+        }
+
+        let mut hashes = Vec::new();
+        for (sk, receiver, nonce) in [
+            (&alice_sk, bob, 0),
+            (&bob_sk, alice, 0),
+            (&alice_sk, Address([20u8; 32]), 1),
+        ] {
+            let tx = signed_transfer(sk, receiver, 100, nonce);
+            hashes.push(compute_tx_hash(&tx));
+            backend.submit_transaction(tx).await.unwrap();
+            backend.produce_block().await.unwrap();
+        }
+
+        let history = backend.get_account_transactions(alice, None, 10).await.unwrap();
+        let found: Vec<(u64, Hash)> = history.iter().map(|t| (t.block_height, t.hash)).collect();
+        assert_eq!(found, vec![(3, hashes[2]), (2, hashes[1]), (1, hashes[0])]);
+        assert_eq!(history[1].transaction.sender, bob);
+
+        let page = backend.get_account_transactions(alice, Some(3), 1).await.unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].hash, hashes[1]);
+        let bob_history = backend.get_account_transactions(bob, None, 10).await.unwrap();
+        assert_eq!(bob_history.len(), 2);
+
+        let rest = backend.list_account_transactions(alice.to_string(), Some(2), 10).await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].hash, hashes[0].to_string());
+        assert_eq!(rest[0].block_height, Some(1));
+        assert!(rest[0].block_hash.is_some());
+        assert!(matches!(
+            backend.list_account_transactions("nope".into(), None, 10).await,
+            Err(ApiError::Invalid(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn produce_block_includes_submitted_transactions() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[10u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let receiver_addr = Address([20u8; 32]);
+
+        // Fund sender with enough for 3 transfers.
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 10_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        // With mempool: only one tx per sender at a time (nonce must match account).
+        // Submit, produce, submit, produce, submit, produce.
+        backend
+            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 100, 0))
+            .await
+            .unwrap();
+        backend.produce_block().await.unwrap();
+
+        backend
+            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 200, 1))
+            .await
+            .unwrap();
+        backend.produce_block().await.unwrap();
+
+        backend
+            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 300, 2))
+            .await
+            .unwrap();
+        backend.produce_block().await.unwrap();
+
+        // Verify all 3 transactions in blocks 1, 2, 3.
+        let block1 = backend.storage.get_block_by_height(1).unwrap().expect("block 1");
+        let block2 = backend.storage.get_block_by_height(2).unwrap().expect("block 2");
+        let block3 = backend.storage.get_block_by_height(3).unwrap().expect("block 3");
+        assert_eq!(block1.body.transactions.len(), 1);
+        assert_eq!(block1.body.transactions[0].amount, 100);
+        assert_eq!(block2.body.transactions.len(), 1);
+        assert_eq!(block2.body.transactions[0].amount, 200);
+        assert_eq!(block3.body.transactions.len(), 1);
+        assert_eq!(block3.body.transactions[0].amount, 300);
+    }
+
+    #[tokio::test]
+    async fn produce_block_writes_receipts() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[12u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let receiver_addr = Address([22u8; 32]);
+
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 10_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        let tx = signed_transfer(&sender_sk, receiver_addr, 150, 0);
+        let tx_hash = compute_tx_hash(&tx);
+        backend.submit_transaction(tx).await.unwrap();
+        backend.produce_block().await.unwrap();
+
+        let block = backend.storage.get_block_by_height(1).unwrap().unwrap();
+        let block_hash = compute_block_hash(&block);
+        let receipt = backend.storage.get_receipt(&tx_hash).unwrap().expect("receipt");
+        assert_eq!(receipt.block_hash, block_hash);
+        assert_eq!(receipt.block_height, 1);
+        assert_eq!(receipt.index, 0);
+        assert_eq!(receipt.status, ReceiptStatus::Success);
+        assert_eq!(receipt.events.len(), 1);
+
+        let json = backend.get_transaction_receipt(tx_hash).await.unwrap();
+        assert_eq!(json["block_height"], 1);
+        assert!(backend.get_transaction_receipt(Hash::zero()).await.unwrap().is_null());
+
+        let rest_tx = ApiBackend::get_transaction(&backend, tx_hash.to_string()).await.unwrap();
+        assert_eq!(rest_tx.block_hash, Some(block_hash.to_string()));
+        assert_eq!(rest_tx.block_height, Some(1));
+    }
+
+    #[tokio::test]
+    async fn events_published_for_pending_tx_and_new_head() {
+        let mut backend = make_backend();
+        let bus = EventBus::new(16);
+        backend.set_event_bus(bus.clone());
+        let mut pending_txs = bus.subscribe(EventKind::PendingTransaction);
+        let mut heads = bus.subscribe(EventKind::NewHead);
+        let mut accounts = bus.subscribe(EventKind::AccountChanged);
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[13u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let receiver_addr = Address([23u8; 32]);
+
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 10_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        let tx = signed_transfer(&sender_sk, receiver_addr, 250, 0);
+        let tx_hash = backend.submit_transaction(tx.clone()).await.unwrap();
+        match pending_txs.try_recv().unwrap() {
+            ChainEvent::PendingTransaction(pending) => {
+                assert_eq!(pending.hash, tx_hash);
+                assert_eq!(pending.block_height, None);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        // Resubmitting a pooled transaction publishes nothing.
+        backend.submit_transaction(tx).await.unwrap();
+        assert!(pending_txs.try_recv().is_err());
+
+        let block_hash = backend.produce_block().await.unwrap();
+        match heads.try_recv().unwrap() {
+            ChainEvent::NewHead(head) => {
+                assert_eq!(head.hash, block_hash);
+                assert_eq!(head.height, 1);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+        let mut changed = Vec::new();
+        while let Ok(ChainEvent::AccountChanged(account)) = accounts.try_recv() {
+            changed.push((account.address, account.balance));
+        }
+        changed.sort();
+        let mut expected = vec![
+            (sender_addr.to_string(), "9750".to_string()),
+            (receiver_addr.to_string(), "250".to_string()),
+        ];
+        expected.sort();
+        assert_eq!(changed, expected);
+    }
+
+    #[tokio::test]
+    async fn next_nonce_counts_pending_transactions() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[14u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let receiver_addr = Address([24u8; 32]);
+
+        assert_eq!(
+            RpcBackend::get_account(&backend, sender_addr).await.unwrap(),
+            None
+        );
+        assert_eq!(backend.get_balance(sender_addr).await.unwrap(), "0");
+        assert_eq!(backend.get_next_nonce(sender_addr).await.unwrap(), 0);
+
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 1_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        for nonce in 0..2 {
+            let tx = signed_transfer(&sender_sk, receiver_addr, 400, nonce);
+            backend.submit_transaction(tx).await.unwrap();
+            assert_eq!(
+                backend.get_next_nonce(sender_addr).await.unwrap(),
+                nonce + 1
+            );
+        }
+
+        // Gaps are rejected with the nonce the sender should use instead.
+        let gap = signed_transfer(&sender_sk, receiver_addr, 1, 5);
+        assert!(matches!(
+            backend.submit_transaction(gap).await,
+            Err(BackendError::InvalidNonce {
+                expected: 2,
+                actual: 5
+            })
+        ));
+        // Pending spends count against the balance.
+        let overspend = signed_transfer(&sender_sk, receiver_addr, 300, 2);
+        assert!(matches!(
+            backend.submit_transaction(overspend).await,
+            Err(BackendError::InsufficientBalance {
+                balance: 200,
+                required: 300
+            })
+        ));
+        // Committed balance is unchanged until the block is produced.
+        assert_eq!(backend.get_balance(sender_addr).await.unwrap(), "1000");
+
+        backend.produce_block().await.unwrap();
+        let block = backend.storage.get_block_by_height(1).unwrap().unwrap();
+        assert_eq!(block.body.transactions.len(), 2);
+
+        assert_eq!(backend.get_next_nonce(sender_addr).await.unwrap(), 2);
+        assert_eq!(backend.get_balance(sender_addr).await.unwrap(), "200");
+        assert_eq!(
+            RpcBackend::get_account(&backend, receiver_addr).await.unwrap(),
+            Some(AccountInfo {
+                address: receiver_addr,
+                balance: "800".to_string(),
+                nonce: 0,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn simulate_transaction_reports_diff_without_committing() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sender_sk = SigningKey::from_bytes(&[15u8; 32]);
+        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
+        let receiver_addr = Address([25u8; 32]);
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 1_000;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+
+        // A pending transaction is applied to the overlay first.
+        let pending = signed_transfer(&sender_sk, receiver_addr, 100, 0);
+        backend.submit_transaction(pending.clone()).await.unwrap();
+
+        let tx = signed_transfer(&sender_sk, receiver_addr, 250, 1);
+        let result = backend.simulate_transaction(tx.clone(), true).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.state_diff,
+            vec![
+                AccountDiff {
+                    address: sender_addr,
+                    before: Some(AccountInfo {
+                        address: sender_addr,
+                        balance: "900".to_string(),
+                        nonce: 1,
+                    }),
+                    after: AccountInfo {
+                        address: sender_addr,
+                        balance: "650".to_string(),
+                        nonce: 2,
+                    },
+                },
+                AccountDiff {
+                    address: receiver_addr,
+                    before: Some(AccountInfo {
+                        address: receiver_addr,
+                        balance: "100".to_string(),
+                        nonce: 0,
+                    }),
+                    after: AccountInfo {
+                        address: receiver_addr,
+                        balance: "350".to_string(),
+                        nonce: 0,
+                    },
+                },
+            ]
+        );
+
+        // Nothing was committed or queued.
+        assert_eq!(
+            backend.storage.get_account(&sender_addr).unwrap(),
+            Some(sender_acc)
+        );
+        assert_eq!(backend.storage.get_account(&receiver_addr).unwrap(), None);
+        assert_eq!(backend.mempool.read().await.len(), 1);
+
+        // Failures carry the same error submit_transaction would return.
+        let stale = signed_transfer(&sender_sk, receiver_addr, 1, 0);
+        let result = backend.simulate_transaction(stale, true).await.unwrap();
+        assert!(!result.success);
+        assert!(result.state_diff.is_empty());
+        let error = result.error.unwrap();
+        assert_eq!(error.code, -32011);
+        assert_eq!(
+            error.data,
+            Some(serde_json::json!({"expected": 1, "actual": 0}))
+        );
+
+        let result = backend.simulate_transaction(pending, true).await.unwrap();
+        assert_eq!(result.error.unwrap().code, -32013);
+    }
+
+    #[tokio::test]
+    async fn simulate_transaction_signature_check_is_optional() {
         let backend = make_backend();
         backend.ensure_genesis().unwrap();
-        backend.produce_block().await.unwrap();
 
-        let blocks = backend.list_blocks(10).await.unwrap();
-        assert_eq!(blocks.len(), 2);
-        // Most recent first.
-        assert_eq!(blocks[0].height, 1);
-        assert_eq!(blocks[1].height, 0);
-    }
+        let sender_addr = Address([16u8; 32]);
+        let mut sender_acc = Account::new(sender_addr);
+        sender_acc.balance = 50;
+        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+        let unsigned = Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: sender_addr,
+            receiver: Address([26u8; 32]),
+            amount: 50,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
+        };
 
-    // ── Transaction inclusion tests ─────────────────────────────────
+        let result = backend.simulate_transaction(unsigned.clone(), true).await.unwrap();
+        assert_eq!(result.error.unwrap().code, -32010);
+
+        let result = backend.simulate_transaction(unsigned.clone(), false).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.state_diff[1].before, None);
+        assert_eq!(result.state_diff[1].after.balance, "50");
+
+        let overspend = Transaction {
+            amount: 51,
+            ..unsigned
+        };
+        let result = backend.simulate_transaction(overspend, false).await.unwrap();
+        assert_eq!(result.error.unwrap().code, -32012);
+    }
 
     #[tokio::test]
-    async fn produce_block_includes_submitted_transactions() {
+    async fn simulate_transaction_runs_contracts_and_charges_gas() {
         let backend = make_backend();
         backend.ensure_genesis().unwrap();
 
-        let sender_sk = SigningKey::from_bytes(&[10u8; 32]);
-        let sender_addr = Address(sender_sk.verifying_key().to_bytes());
-        let receiver_addr = Address([20u8; 32]);
-
-        // Fund sender with enough for 3 transfers.
-        let mut sender_acc = Account::new(sender_addr);
-        sender_acc.balance = 10_000;
-        backend.storage.put_account(&sender_addr, &sender_acc).unwrap();
+        let sk = SigningKey::from_bytes(&[17u8; 32]);
+        let sender = Address(sk.verifying_key().to_bytes());
+        let mut acc = Account::new(sender);
+        acc.balance = 1_000_000;
+        backend.storage.put_account(&sender, &acc).unwrap();
+
+        let signed = |tx_type, receiver, nonce, data| {
+            let mut tx = Transaction {
+                tx_type,
+                sender,
+                receiver,
+                amount: 0,
+                nonce,
+                signature: [0u8; 64],
+                multisig: None,
+                data,
+            };
+            tx.signature = sk.sign(&tx.signing_payload()).to_bytes();
+            tx
+        };
+        let code = wat::parse_str(
+            r#"(module
+                 (import "env" "storage_set" (func $set (param i32 i32 i32 i32)))
+                 (memory (export "memory") 1)
+                 (data (i32.const 0) "k")
+                 (func (export "set")
+                   (call $set (i32.const 0) (i32.const 1) (i32.const 0) (i32.const 1)))
+                 (func (export "trap") unreachable))"#,
+        )
+        .unwrap();
+        let deploy = signed(TransactionType::Deploy, Address::zero(), 0, code);
+        let contract = deploy.deployed_address();
+        let call = |function: &str, nonce| {
+            let payload = mbongo_runtime::ContractCall {
+                function: function.to_string(),
+                input: Vec::new(),
+                gas_limit: 100_000,
+            };
+            signed(TransactionType::Call, contract, nonce, payload.encode())
+        };
 
-        // With mempool: only one tx per sender at a time (nonce must match account).
-        // Submit, produce, submit, produce, submit, produce.
-        backend
-            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 100, 0))
-            .await
-            .unwrap();
-        backend.produce_block().await.unwrap();
+        // The pending deployment is replayed before the call runs.
+        backend.submit_transaction(deploy.clone()).await.unwrap();
+        let deploy_fee = max_fee(&deploy);
 
-        backend
-            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 200, 1))
-            .await
-            .unwrap();
-        backend.produce_block().await.unwrap();
+        let result = backend.simulate_transaction(call("set", 1), true).await.unwrap();
+        assert!(result.success, "{result:?}");
+        assert!(result.gas_used > 0);
+        let fee = mbongo_runtime::gas::fee(result.gas_used);
+        assert_eq!(result.fee, fee.to_string());
+        assert_eq!(result.state_diff[0].address, sender);
+        assert_eq!(
+            result.state_diff[0].after.balance,
+            (1_000_000 - deploy_fee - fee).to_string()
+        );
 
-        backend
-            .submit_transaction(signed_transfer(&sender_sk, receiver_addr, 300, 2))
-            .await
-            .unwrap();
-        backend.produce_block().await.unwrap();
+        // A failing call is still included: it reports why and pays for gas.
+        let result = backend.simulate_transaction(call("trap", 1), true).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.is_none());
+        assert!(
+            result.failure.as_deref().is_some_and(|e| e.contains("trap")),
+            "{result:?}"
+        );
+        assert!(result.gas_used > 0);
+        let fee = mbongo_runtime::gas::fee(result.gas_used);
+        assert_eq!(result.fee, fee.to_string());
+        let sender_diff = &result.state_diff[0];
+        assert_eq!(sender_diff.after.nonce, 2);
+        assert_eq!(
+            sender_diff.after.balance,
+            (1_000_000 - deploy_fee - fee).to_string()
+        );
 
-        // Verify all 3 transactions in blocks 1, 2, 3.
-        let block1 = backend.storage.get_block_by_height(1).unwrap().expect("block 1");
-        let block2 = backend.storage.get_block_by_height(2).unwrap().expect("block 2");
-        let block3 = backend.storage.get_block_by_height(3).unwrap().expect("block 3");
-        assert_eq!(block1.body.transactions.len(), 1);
-        assert_eq!(block1.body.transactions[0].amount, 100);
-        assert_eq!(block2.body.transactions.len(), 1);
-        assert_eq!(block2.body.transactions[0].amount, 200);
-        assert_eq!(block3.body.transactions.len(), 1);
-        assert_eq!(block3.body.transactions[0].amount, 300);
+        // The gas limit must be covered, as on submission.
+        let mut poor = Account::new(sender);
+        poor.balance = 100;
+        backend.storage.put_account(&sender, &poor).unwrap();
+        backend.mempool.write().await.remove(&compute_tx_hash(&deploy));
+        let result = backend.simulate_transaction(call("set", 0), true).await.unwrap();
+        assert_eq!(result.error.unwrap().code, -32012);
     }
 
     #[tokio::test]
@@ -1290,6 +2543,81 @@ mod tests {
         assert_eq!(block2.body.transactions.len(), 0);
     }
 
+    #[tokio::test]
+    async fn mempool_queries_and_transaction_status() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let receiver_addr = Address([40u8; 32]);
+        let mut senders = Vec::new();
+        for seed in [41u8, 42u8] {
+            let sk = SigningKey::from_bytes(&[seed; 32]);
+            let addr = Address(sk.verifying_key().to_bytes());
+            let mut acc = Account::new(addr);
+            acc.balance = 1_000;
+            backend.storage.put_account(&addr, &acc).unwrap();
+            senders.push((sk, addr));
+        }
+
+        let mut hashes = Vec::new();
+        for nonce in 0..3 {
+            for (sk, _) in &senders {
+                let tx = signed_transfer(sk, receiver_addr, 10, nonce);
+                let hash: Hash = backend.submit_transaction(tx).await.unwrap().parse().unwrap();
+                hashes.push(hash);
+            }
+        }
+
+        assert_eq!(
+            backend.mempool_status().await.unwrap(),
+            MempoolStatus {
+                pending: 6,
+                senders: 2
+            }
+        );
+
+        let (_, first_sender) = senders[0];
+        let content = backend.mempool_content(Some(first_sender), 1, 5).await.unwrap();
+        assert_eq!(content.total, 3);
+        let page: Vec<Hash> = content.transactions.iter().map(|p| p.hash).collect();
+        assert_eq!(page, vec![hashes[2], hashes[4]]);
+
+        let rest_page = backend.list_mempool(None, 4, 10).await.unwrap();
+        assert_eq!((rest_page.pending, rest_page.total), (6, 6));
+        assert_eq!(rest_page.transactions.len(), 2);
+        assert_eq!(
+            rest_page.transactions[0].status,
+            RestTransactionStatus::Pending
+        );
+        assert!(matches!(
+            backend.list_mempool(Some("nope".into()), 0, 10).await,
+            Err(ApiError::Invalid(_))
+        ));
+
+        let pending = backend.get_pending_transaction(hashes[0]).await.unwrap().unwrap();
+        assert_eq!(pending.transaction.sender, first_sender);
+        let lookup = RpcBackend::get_transaction(&backend, hashes[0]).await.unwrap();
+        assert_eq!(lookup.status, TransactionStatus::Pending);
+        let rest_tx = ApiBackend::get_transaction(&backend, hashes[0].to_string()).await.unwrap();
+        assert_eq!(rest_tx.status, RestTransactionStatus::Pending);
+
+        let block_hash: Hash = backend.produce_block().await.unwrap().parse().unwrap();
+        assert_eq!(
+            backend.get_pending_transaction(hashes[0]).await.unwrap(),
+            None
+        );
+        let lookup = RpcBackend::get_transaction(&backend, hashes[0]).await.unwrap();
+        assert_eq!(lookup.status, TransactionStatus::Included);
+        assert_eq!(lookup.block_hash, Some(block_hash));
+        assert_eq!(lookup.block_height, Some(1));
+        let rest_tx = ApiBackend::get_transaction(&backend, hashes[0].to_string()).await.unwrap();
+        assert_eq!(rest_tx.status, RestTransactionStatus::Included);
+
+        let lookup = RpcBackend::get_transaction(&backend, Hash([0xEEu8; 32])).await.unwrap();
+        assert_eq!(lookup.status, TransactionStatus::Unknown);
+        assert_eq!(lookup.transaction, None);
+    }
+
     // ── Atomic write_batch tests ────────────────────────────────────────
 
     #[tokio::test]
@@ -1491,6 +2819,167 @@ mod tests {
         );
     }
 
+    #[test]
+    fn apply_block_rejects_data_on_native_transactions() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sk = SigningKey::from_bytes(&[54u8; 32]);
+        let mut acc = Account::new(Address(sk.verifying_key().to_bytes()));
+        acc.balance = 5000;
+        backend.storage.put_account(&acc.address, &acc).unwrap();
+
+        let mut tx = signed_transfer(&sk, Address([55u8; 32]), 100, 0);
+        tx.data = b"payload".to_vec();
+        tx.signature = sk.sign(&tx.signing_payload()).to_bytes();
+
+        let block = build_valid_block(&backend, vec![tx]);
+        let err = backend.apply_block(&block).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ApplyBlockError::InvalidData {
+                    index: 0,
+                    reason: DataError::Unexpected(TransactionType::Transfer),
+                }
+            ),
+            "expected InvalidData, got: {err}"
+        );
+        assert_eq!(backend.storage.get_latest_height().unwrap(), 0);
+    }
+
+    #[test]
+    fn apply_block_reports_index_of_invalid_signature_in_batch() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let receiver_addr = Address([65u8; 32]);
+        let mut txs = Vec::new();
+        for seed in 66u8..71 {
+            let sk = SigningKey::from_bytes(&[seed; 32]);
+            let mut acc = Account::new(Address(sk.verifying_key().to_bytes()));
+            acc.balance = 1000;
+            backend.storage.put_account(&acc.address, &acc).unwrap();
+            txs.push(signed_transfer(&sk, receiver_addr, 10, 0));
+        }
+        txs[2].signature[10] ^= 0x01;
+        txs[4].amount = 11;
+
+        let block = build_valid_block(&backend, txs);
+        let err = backend.apply_block(&block).unwrap_err();
+        assert!(
+            matches!(err, ApplyBlockError::InvalidSignature(2)),
+            "expected InvalidSignature(2), got: {err}"
+        );
+        assert_eq!(backend.storage.get_latest_height().unwrap(), 0);
+        assert!(backend.storage.get_account(&receiver_addr).unwrap().is_none());
+    }
+
+    /// A 2-of-3 multisig account funded with 5000, and its member keys.
+    fn funded_multisig<S: Storage>(backend: &NodeBackend<S>) -> (Vec<SigningKey>, Multisig) {
+        let keys: Vec<SigningKey> = (60u8..63).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let members = keys.iter().map(|k| Address(k.verifying_key().to_bytes())).collect();
+        let config = Multisig::new(2, members).unwrap();
+        let mut acc = Account::new(config.address());
+        acc.balance = 5000;
+        backend.storage.put_account(&config.address(), &acc).unwrap();
+        (keys, config)
+    }
+
+    /// A transfer from the multisig account `config`, signed by `signers`.
+    fn multisig_transfer(
+        config: &Multisig,
+        signers: &[SigningKey],
+        receiver: Address,
+        amount: u128,
+    ) -> Transaction {
+        let mut tx = Transaction {
+            tx_type: TransactionType::Transfer,
+            sender: config.address(),
+            receiver,
+            amount,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: Some(MultisigWitness::new(config.clone())),
+            data: Vec::new(),
+        };
+        for key in signers {
+            let signature = key.sign(&tx.signing_payload()).to_bytes();
+            let signer = Address(key.verifying_key().to_bytes());
+            tx.multisig.as_mut().unwrap().add_signature(signer, signature).unwrap();
+        }
+        tx
+    }
+
+    #[test]
+    fn apply_block_accepts_threshold_multisig_transfer() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        let (keys, config) = funded_multisig(&backend);
+        let receiver_addr = Address([63u8; 32]);
+
+        let tx = multisig_transfer(&config, &keys[1..], receiver_addr, 300);
+        let block = build_valid_block(&backend, vec![tx.clone()]);
+        backend.apply_block(&block).unwrap();
+
+        let account = backend.storage.get_account(&config.address()).unwrap().unwrap();
+        assert_eq!((account.balance, account.nonce), (4700, 1));
+        let receiver = backend.storage.get_account(&receiver_addr).unwrap().unwrap();
+        assert_eq!(receiver.balance, 300);
+        assert_eq!(
+            backend.storage.get_transaction(&compute_tx_hash(&tx)).unwrap(),
+            Some(tx)
+        );
+    }
+
+    #[test]
+    fn apply_block_rejects_multisig_below_threshold() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        let (keys, config) = funded_multisig(&backend);
+
+        let tx = multisig_transfer(&config, &keys[..1], Address([63u8; 32]), 300);
+        let block = build_valid_block(&backend, vec![tx]);
+        let err = backend.apply_block(&block).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ApplyBlockError::InvalidMultisig {
+                    index: 0,
+                    reason: MultisigError::NotEnoughSignatures {
+                        required: 2,
+                        got: 1
+                    },
+                }
+            ),
+            "expected InvalidMultisig, got: {err}"
+        );
+        let account = backend.storage.get_account(&config.address()).unwrap().unwrap();
+        assert_eq!(account.balance, 5000);
+    }
+
+    #[test]
+    fn apply_block_rejects_tampered_multisig_transfer() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        let (keys, config) = funded_multisig(&backend);
+
+        let mut tx = multisig_transfer(&config, &keys[..2], Address([63u8; 32]), 300);
+        tx.amount = 4000;
+        let block = build_valid_block(&backend, vec![tx]);
+        let err = backend.apply_block(&block).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                ApplyBlockError::InvalidMultisig {
+                    index: 0,
+                    reason: MultisigError::InvalidSignature(_),
+                }
+            ),
+            "expected InvalidMultisig, got: {err}"
+        );
+    }
+
     #[test]
     fn apply_block_rejects_invalid_nonce() {
         let backend = make_backend();
@@ -1536,6 +3025,300 @@ mod tests {
         );
     }
 
+    #[test]
+    fn apply_block_executes_independent_senders_like_sequential() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let keys: Vec<SigningKey> = (72u8..74).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        for key in &keys {
+            let mut acc = Account::new(Address(key.verifying_key().to_bytes()));
+            acc.balance = 1000;
+            backend.storage.put_account(&acc.address, &acc).unwrap();
+        }
+        let (a, b) = (&keys[0], &keys[1]);
+        let (to_a, to_b) = (Address([74u8; 32]), Address([75u8; 32]));
+
+        // Both groups fail; the error names the earlier transaction and
+        // nothing from the block is committed.
+        let block = build_valid_block(
+            &backend,
+            vec![
+                signed_transfer(a, to_a, 100, 0),
+                signed_transfer(b, to_b, 100, 0),
+                signed_transfer(b, to_b, 5000, 1),
+                signed_transfer(a, to_a, 100, 0),
+            ],
+        );
+        let err = backend.apply_block(&block).unwrap_err();
+        assert!(
+            matches!(err, ApplyBlockError::InsufficientBalance(2)),
+            "expected InsufficientBalance(2), got: {err}"
+        );
+        assert!(backend.storage.get_account(&to_a).unwrap().is_none());
+
+        let block = build_valid_block(
+            &backend,
+            vec![
+                signed_transfer(a, to_a, 100, 0),
+                signed_transfer(b, to_b, 200, 0),
+                signed_transfer(a, to_a, 300, 1),
+            ],
+        );
+        backend.apply_block(&block).unwrap();
+        let balance =
+            |address: Address| backend.storage.get_account(&address).unwrap().unwrap().balance;
+        assert_eq!(balance(to_a), 400);
+        assert_eq!(balance(to_b), 200);
+        assert_eq!(balance(Address(a.verifying_key().to_bytes())), 600);
+        assert_eq!(balance(Address(b.verifying_key().to_bytes())), 800);
+    }
+
+    #[test]
+    fn apply_block_deploys_and_calls_contracts() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+
+        let sk = SigningKey::from_bytes(&[76u8; 32]);
+        let sender = Address(sk.verifying_key().to_bytes());
+        let mut acc = Account::new(sender);
+        acc.balance = 1_000_000;
+        backend.storage.put_account(&sender, &acc).unwrap();
+
+        let signed = |tx_type, receiver, nonce, data| {
+            let mut tx = Transaction {
+                tx_type,
+                sender,
+                receiver,
+                amount: 0,
+                nonce,
+                signature: [0u8; 64],
+                multisig: None,
+                data,
+            };
+            tx.signature = sk.sign(&tx.signing_payload()).to_bytes();
+            tx
+        };
+        let code = wat::parse_str(
+            r#"(module
+                 (import "env" "storage_set" (func $set (param i32 i32 i32 i32)))
+                 (memory (export "memory") 1)
+                 (data (i32.const 0) "k")
+                 (func (export "set")
+                   (call $set (i32.const 0) (i32.const 1) (i32.const 0) (i32.const 1)))
+                 (func (export "trap") unreachable))"#,
+        )
+        .unwrap();
+        let code_hash = Hash(mbongo_core::crypto::blake3_hash(&code));
+        let deploy = signed(TransactionType::Deploy, Address::zero(), 0, code);
+        let contract = deploy.deployed_address();
+        let call = |function: &str, nonce| {
+            let payload = mbongo_runtime::ContractCall {
+                function: function.to_string(),
+                input: Vec::new(),
+                gas_limit: 100_000,
+            };
+            signed(TransactionType::Call, contract, nonce, payload.encode())
+        };
+        let (set, trap) = (call("set", 1), call("trap", 2));
+
+        let block = build_valid_block(&backend, vec![deploy.clone(), set.clone(), trap.clone()]);
+        backend.apply_block(&block).unwrap();
+
+        assert_eq!(
+            backend.storage.get_contract(&contract).unwrap(),
+            Some(code_hash)
+        );
+        assert!(backend.storage.get_code(&code_hash).unwrap().is_some());
+        assert_eq!(
+            backend.storage.get_contract_storage(&contract, b"k").unwrap(),
+            Some(b"k".to_vec())
+        );
+        let receipt =
+            |tx: &Transaction| backend.storage.get_receipt(&compute_tx_hash(tx)).unwrap().unwrap();
+        let (deploy, set, trap) = (receipt(&deploy), receipt(&set), receipt(&trap));
+        assert_eq!(deploy.status, ReceiptStatus::Success);
+        assert_eq!(set.status, ReceiptStatus::Success);
+        assert_eq!(trap.status, ReceiptStatus::Failed);
+        assert!(
+            trap.error.as_deref().is_some_and(|e| e.contains("trap")),
+            "{trap:?}"
+        );
+        for receipt in [&deploy, &set, &trap] {
+            assert!(receipt.gas_used > 0);
+            assert_eq!(receipt.fee, mbongo_runtime::gas::fee(receipt.gas_used));
+        }
+        // The failed call is still included, consumes its nonce and pays
+        // for its gas like the successful one.
+        let account = backend.storage.get_account(&sender).unwrap().unwrap();
+        assert_eq!(account.nonce, 3);
+        assert_eq!(account.balance, 1_000_000 - deploy.fee - set.fee - trap.fee);
+    }
+
+    #[tokio::test]
+    async fn get_logs_filters_committed_logs_and_publishes_them() {
+        let mut backend = make_backend();
+        let bus = EventBus::new(64);
+        backend.set_event_bus(bus.clone());
+        let mut events = bus.subscribe(EventKind::Log);
+        backend.ensure_genesis().unwrap();
+
+        let sk = SigningKey::from_bytes(&[77u8; 32]);
+        let sender = Address(sk.verifying_key().to_bytes());
+        let mut acc = Account::new(sender);
+        acc.balance = 1_000_000;
+        backend.storage.put_account(&sender, &acc).unwrap();
+
+        let code = wat::parse_str(
+            r#"(module
+                 (import "env" "log" (func $log (param i32 i32 i32 i32)))
+                 (memory (export "memory") 1)
+                 (data (i32.const 0) "\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09")
+                 (data (i32.const 16) "\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09")
+                 (func (export "emit")
+                   (call $log (i32.const 0) (i32.const 1) (i32.const 0) (i32.const 2))))"#,
+        )
+        .unwrap();
+        let mut deploy = Transaction {
+            tx_type: TransactionType::Deploy,
+            sender,
+            receiver: Address::zero(),
+            amount: 0,
+            nonce: 0,
+            signature: [0u8; 64],
+            multisig: None,
+            data: code,
+        };
+        deploy.signature = sk.sign(&deploy.signing_payload()).to_bytes();
+        let contract = deploy.deployed_address();
+        let payload = mbongo_runtime::ContractCall {
+            function: "emit".to_string(),
+            input: Vec::new(),
+            gas_limit: 100_000,
+        };
+        let mut call = Transaction {
+            tx_type: TransactionType::Call,
+            receiver: contract,
+            nonce: 1,
+            data: payload.encode(),
+            ..deploy.clone()
+        };
+        call.signature = sk.sign(&call.signing_payload()).to_bytes();
+        let transfer = signed_transfer(&sk, Address([78u8; 32]), 5, 2);
+
+        let block = build_valid_block(&backend, vec![deploy]);
+        backend.apply_block(&block).unwrap();
+        let block = build_valid_block(&backend, vec![call, transfer]);
+        backend.apply_block(&block).unwrap();
+
+        let all = |filter: LogFilter| LogFilter {
+            from_height: Some(1),
+            ..filter
+        };
+        let logs = backend.get_logs(all(LogFilter::default())).await.unwrap();
+        let heights: Vec<u64> = logs.iter().map(|entry| entry.block_height).collect();
+        assert_eq!(heights, [1, 2, 2, 2]);
+
+        let emitted = backend
+            .get_logs(all(LogFilter {
+                topics: vec![Some(Hash([9u8; 32]))],
+                ..LogFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(emitted[0].log.address, contract);
+        assert_eq!(emitted[0].log.data, [9u8, 9]);
+        assert_eq!((emitted[0].tx_index, emitted[0].log_index), (0, 1));
+
+        let transfers = backend
+            .get_logs(all(LogFilter {
+                address: Some(sender),
+                topics: vec![Some(mbongo_core::logs::event_topic("Transfer"))],
+                ..LogFilter::default()
+            }))
+            .await
+            .unwrap();
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].tx_index, 1);
+
+        // Without a range only the tip is searched.
+        assert_eq!(
+            backend.get_logs(LogFilter::default()).await.unwrap().len(),
+            3
+        );
+        assert!(matches!(
+            backend
+                .get_logs(LogFilter {
+                    from_height: Some(0),
+                    to_height: Some(MAX_LOG_BLOCK_RANGE),
+                    ..LogFilter::default()
+                })
+                .await,
+            Err(BackendError::InvalidParams(_))
+        ));
+
+        let mut published = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ChainEvent::Log(entry) = event {
+                published.push(entry);
+            }
+        }
+        assert_eq!(published, logs);
+    }
+
+    #[tokio::test]
+    async fn get_logs_skips_blocks_by_bloom_and_caps_results() {
+        let backend = make_backend();
+        backend.ensure_genesis().unwrap();
+        let sk = SigningKey::from_bytes(&[79u8; 32]);
+        let sender = Address(sk.verifying_key().to_bytes());
+        let mut acc = Account::new(sender);
+        acc.balance = 1_000_000;
+        backend.storage.put_account(&sender, &acc).unwrap();
+
+        let transfer = signed_transfer(&sk, Address([80u8; 32]), 5, 0);
+        let tx_hash = compute_tx_hash(&transfer);
+        let block = build_valid_block(&backend, vec![transfer]);
+        backend.apply_block(&block).unwrap();
+        let receipt = backend.storage.get_receipt(&tx_hash).unwrap().unwrap();
+        assert_eq!(
+            backend.storage.get_block_bloom(1).unwrap(),
+            Some(receipt.bloom)
+        );
+        assert_eq!(
+            backend.get_logs(LogFilter::default()).await.unwrap().len(),
+            1
+        );
+
+        // A block whose bloom rules out the filter is not searched.
+        backend
+            .storage
+            .write_batch(vec![BatchOp::PutBlockBloom(1, Bloom::default())])
+            .unwrap();
+        assert_eq!(backend.get_logs(LogFilter::default()).await.unwrap(), []);
+
+        // Matches beyond the cap are an error, not a truncated list.
+        let log = mbongo_core::Log {
+            address: sender,
+            topics: Vec::new(),
+            data: Vec::new(),
+        };
+        let receipt = receipt.with_logs(vec![log; MAX_LOG_ENTRIES]);
+        backend
+            .storage
+            .write_batch(vec![
+                BatchOp::PutBlockBloom(1, receipt.bloom),
+                BatchOp::PutReceipt(tx_hash, receipt),
+            ])
+            .unwrap();
+        let err = backend.get_logs(LogFilter::default()).await.unwrap_err();
+        assert!(
+            matches!(&err, BackendError::InvalidParams(msg) if msg.contains("more than")),
+            "{err:?}"
+        );
+    }
+
     #[test]
     fn apply_block_rejects_transactions_root_mismatch() {
         let backend = make_backend();
@@ -1889,7 +3672,7 @@ mod tests {
         let backend = make_backend();
         backend.ensure_genesis().unwrap();
 
-        let result = backend.get_block_by_height(0).await.unwrap();
+        let result = RpcBackend::get_block_by_height(&backend, 0).await.unwrap();
         // Result is a serde_json::Value representing the genesis block.
         assert_eq!(result["header"]["height"], serde_json::json!(0));
         assert!(result["body"]["transactions"].as_array().unwrap().is_empty());
@@ -1900,10 +3683,11 @@ mod tests {
         let backend = make_backend();
         backend.ensure_genesis().unwrap();
 
-        let result = backend.get_block_by_height(999).await;
-        assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
-        assert!(err.contains("block not found"), "got: {err}");
+        let result = RpcBackend::get_block_by_height(&backend, 999).await;
+        assert!(
+            matches!(result, Err(BackendError::NotFound(_))),
+            "got: {result:?}"
+        );
     }
 
     // ── Auto-sync simulation tests ───────────────────────────────────
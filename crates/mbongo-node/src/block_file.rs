@@ -0,0 +1,512 @@
+//! Portable block archive format and the `export-blocks` / `import-blocks`
+//! subcommands.
+//!
+//! # File layout
+//!
+//! ```text
+//! BlockFileHeader (SCALE)        magic, format version, genesis hash, height range
+//! repeated:
+//!   u32 little-endian length     byte length of the following record
+//!   Block (SCALE)                one block, in ascending height order
+//! ```
+//!
+//! The genesis hash ties an archive to a chain: importing into a data
+//! directory with a different genesis block is refused. Imported blocks go
+//! through [`NodeBackend::apply_block`], so they are validated exactly like
+//! blocks received from peers.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use mbongo_core::{Block, Hash};
+use mbongo_network::rpc::BackendError;
+use mbongo_storage::{Storage, StorageError};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::backend::{compute_block_hash, ApplyBlockError, NodeBackend};
+
+/// Magic bytes at the start of every block archive.
+pub const MAGIC: [u8; 4] = *b"MBBK";
+
+/// Current block archive format version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Upper bound on a single encoded block, to reject corrupt length prefixes
+/// before allocating.
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// Header written once at the start of a block archive.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BlockFileHeader {
+    /// Always [`MAGIC`].
+    pub magic: [u8; 4],
+    /// Archive format version; see [`FORMAT_VERSION`].
+    pub version: u32,
+    /// Hash of the genesis block of the exporting chain.
+    pub genesis_hash: Hash,
+    /// Height of the first block in the archive.
+    pub from: u64,
+    /// Height of the last block in the archive.
+    pub to: u64,
+}
+
+/// Errors from reading, writing, exporting, or importing block archives.
+#[derive(Debug, thiserror::Error)]
+pub enum BlockFileError {
+    /// Reading or writing the archive file failed.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file does not start with [`MAGIC`].
+    #[error("not a block archive (bad magic)")]
+    BadMagic,
+    /// The archive was written with an unknown format version.
+    #[error("unsupported block archive version {0} (supported: {FORMAT_VERSION})")]
+    UnsupportedVersion(u32),
+    /// The header or a block record could not be decoded.
+    #[error("corrupt block archive: {0}")]
+    Decode(#[from] parity_scale_codec::Error),
+    /// A length prefix exceeds [`MAX_RECORD_LEN`].
+    #[error("corrupt block archive: record of {0} bytes exceeds limit")]
+    RecordTooLarge(u32),
+    /// The archive belongs to a different chain.
+    #[error("genesis mismatch: archive has {archive}, local chain has {local}")]
+    GenesisMismatch {
+        /// Genesis hash recorded in the archive header.
+        archive: Hash,
+        /// Genesis hash of the local chain.
+        local: Hash,
+    },
+    /// A block in the archive differs from the local block at its height.
+    #[error("fork at height {height}: archive has {archive}, local chain has {local}")]
+    Fork {
+        /// Height of the conflicting block.
+        height: u64,
+        /// Hash of the archived block.
+        archive: Hash,
+        /// Hash of the local block.
+        local: Hash,
+    },
+    /// The requested export range is empty or not fully present locally.
+    #[error("invalid range {from}..={to} (local tip is {tip})")]
+    InvalidRange {
+        /// First requested height.
+        from: u64,
+        /// Last requested height.
+        to: u64,
+        /// Local chain tip.
+        tip: u64,
+    },
+    /// A block in the requested range is missing locally.
+    #[error("block at height {0} not found")]
+    MissingBlock(u64),
+    /// An imported block failed validation.
+    #[error("block at height {height} rejected: {source}")]
+    Apply {
+        /// Height of the rejected block.
+        height: u64,
+        /// Validation or storage failure.
+        #[source]
+        source: ApplyBlockError,
+    },
+    /// Reading local chain state failed.
+    #[error("storage error: {0}")]
+    Storage(#[from] StorageError),
+    /// Creating the local genesis block failed.
+    #[error("backend error: {0}")]
+    Backend(#[from] BackendError),
+}
+
+/// Writes length-prefixed SCALE block records after a [`BlockFileHeader`].
+pub struct BlockWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> BlockWriter<W> {
+    /// Writes `header` and returns a writer positioned at the first record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlockFileError::Io`] if writing fails.
+    pub fn new(mut inner: W, header: &BlockFileHeader) -> Result<Self, BlockFileError> {
+        inner.write_all(&header.encode())?;
+        Ok(Self { inner })
+    }
+
+    /// Appends one block record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlockFileError::RecordTooLarge`] if the encoded block
+    /// exceeds the record limit, or [`BlockFileError::Io`] if writing fails.
+    pub fn write_block(&mut self, block: &Block) -> Result<(), BlockFileError> {
+        let encoded = block.encode();
+        let len = u32::try_from(encoded.len()).unwrap_or(u32::MAX);
+        if len > MAX_RECORD_LEN {
+            return Err(BlockFileError::RecordTooLarge(len));
+        }
+        self.inner.write_all(&len.to_le_bytes())?;
+        self.inner.write_all(&encoded)?;
+        Ok(())
+    }
+
+    /// Flushes and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlockFileError::Io`] if flushing fails.
+    pub fn finish(mut self) -> Result<W, BlockFileError> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads a [`BlockFileHeader`] and then yields block records in order.
+pub struct BlockReader<R: Read> {
+    inner: R,
+    header: BlockFileHeader,
+}
+
+impl<R: Read> BlockReader<R> {
+    /// Reads and validates the archive header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlockFileError::BadMagic`] or
+    /// [`BlockFileError::UnsupportedVersion`] for foreign or newer files.
+    pub fn new(mut inner: R) -> Result<Self, BlockFileError> {
+        let mut magic = [0u8; 4];
+        inner.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(BlockFileError::BadMagic);
+        }
+        let mut input = parity_scale_codec::IoReader(&mut inner);
+        let version = u32::decode(&mut input)?;
+        if version != FORMAT_VERSION {
+            return Err(BlockFileError::UnsupportedVersion(version));
+        }
+        let header = BlockFileHeader {
+            magic,
+            version,
+            genesis_hash: Hash::decode(&mut input)?,
+            from: u64::decode(&mut input)?,
+            to: u64::decode(&mut input)?,
+        };
+        Ok(Self { inner, header })
+    }
+
+    /// Returns the archive header.
+    pub fn header(&self) -> &BlockFileHeader {
+        &self.header
+    }
+
+    /// Reads the next block, or `None` at a clean end of file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlockFileError`] on a truncated or corrupt record.
+    pub fn next_block(&mut self) -> Result<Option<Block>, BlockFileError> {
+        let mut len = [0u8; 4];
+        let mut filled = 0;
+        while filled < len.len() {
+            match self.inner.read(&mut len[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        match filled {
+            0 => return Ok(None),
+            4 => {}
+            _ => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+        }
+        let len = u32::from_le_bytes(len);
+        if len > MAX_RECORD_LEN {
+            return Err(BlockFileError::RecordTooLarge(len));
+        }
+        let mut buf = vec![0u8; len as usize];
+        self.inner.read_exact(&mut buf)?;
+        Ok(Some(Block::decode(&mut &buf[..])?))
+    }
+}
+
+/// Summary of an [`export_blocks`] or [`import_blocks`] run.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TransferReport {
+    /// Blocks written or applied.
+    pub blocks: u64,
+    /// Blocks skipped on import because the local chain already has them.
+    pub skipped: u64,
+}
+
+/// Writes blocks `from..=to` from `storage` to `out`.
+///
+/// `to` defaults to the local chain tip.
+///
+/// # Errors
+///
+/// Returns [`BlockFileError`] if the range is invalid, a block is missing,
+/// or writing fails.
+pub fn export_blocks<S: Storage, W: Write>(
+    storage: &S,
+    from: u64,
+    to: Option<u64>,
+    out: W,
+) -> Result<TransferReport, BlockFileError> {
+    let tip = storage.get_latest_height()?;
+    let to = to.unwrap_or(tip);
+    if from > to || to > tip {
+        return Err(BlockFileError::InvalidRange { from, to, tip });
+    }
+    let genesis = storage.get_block_by_height(0)?.ok_or(BlockFileError::MissingBlock(0))?;
+
+    let header = BlockFileHeader {
+        magic: MAGIC,
+        version: FORMAT_VERSION,
+        genesis_hash: compute_block_hash(&genesis),
+        from,
+        to,
+    };
+    let mut writer = BlockWriter::new(out, &header)?;
+    let mut report = TransferReport::default();
+    for height in from..=to {
+        let block = storage
+            .get_block_by_height(height)?
+            .ok_or(BlockFileError::MissingBlock(height))?;
+        writer.write_block(&block)?;
+        report.blocks += 1;
+    }
+    writer.finish()?;
+    Ok(report)
+}
+
+/// Applies every block in the archive read from `input` on top of `backend`.
+///
+/// The local genesis block is created if missing and must match the
+/// archive's. Blocks at or below the local tip are skipped if they match the
+/// local block at their height; the rest go through
+/// [`NodeBackend::apply_block`].
+///
+/// # Errors
+///
+/// Returns [`BlockFileError`] on a foreign or corrupt archive, a genesis
+/// mismatch, an archived block that conflicts with a local one, or the
+/// first block that fails validation. Blocks applied
+/// before the failure stay committed.
+pub fn import_blocks<S: Storage + 'static, R: Read>(
+    backend: &NodeBackend<S>,
+    input: R,
+) -> Result<TransferReport, BlockFileError> {
+    let mut reader = BlockReader::new(input)?;
+
+    backend.ensure_genesis()?;
+    let genesis = backend.storage.get_block_by_height(0)?.ok_or(BlockFileError::MissingBlock(0))?;
+    let local = compute_block_hash(&genesis);
+    let archive = reader.header().genesis_hash;
+    if archive != local {
+        return Err(BlockFileError::GenesisMismatch { archive, local });
+    }
+
+    let mut report = TransferReport::default();
+    while let Some(block) = reader.next_block()? {
+        let height = block.header.height;
+        if height <= backend.storage.get_latest_height()? {
+            let local = backend
+                .storage
+                .get_block_by_height(height)?
+                .ok_or(BlockFileError::MissingBlock(height))?;
+            let (archive, local) = (compute_block_hash(&block), compute_block_hash(&local));
+            if archive != local {
+                return Err(BlockFileError::Fork {
+                    height,
+                    archive,
+                    local,
+                });
+            }
+            report.skipped += 1;
+            continue;
+        }
+        backend
+            .apply_block(&block)
+            .map_err(|source| BlockFileError::Apply { height, source })?;
+        report.blocks += 1;
+    }
+    Ok(report)
+}
+
+/// Runs `export-blocks`: opens `data_dir` and writes an archive to `out`.
+pub fn export_cmd(
+    data_dir: &str,
+    from: u64,
+    to: Option<u64>,
+    out: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = mbongo_storage::RocksDbStorage::open(data_dir)
+        .map_err(|e| format!("failed to open storage: {e}"))?;
+    let file = BufWriter::new(File::create(out)?);
+    let report =
+        export_blocks(&storage, from, to, file).map_err(|e| format!("export failed: {e}"))?;
+    println!("Exported {} block(s) to {}", report.blocks, out.display());
+    Ok(())
+}
+
+/// Runs `import-blocks`: opens `data_dir` and applies the archive at `input`.
+pub fn import_cmd(data_dir: &str, input: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = mbongo_storage::RocksDbStorage::open(data_dir)
+        .map_err(|e| format!("failed to open storage: {e}"))?;
+    let backend = NodeBackend::new(storage, false);
+    let file = BufReader::new(File::open(input)?);
+    let report = import_blocks(&backend, file).map_err(|e| format!("import failed: {e}"))?;
+    println!(
+        "Imported {} block(s) from {} ({} already present)",
+        report.blocks,
+        input.display(),
+        report.skipped
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use mbongo_core::{Address, Transaction, TransactionType};
+    use mbongo_network::RpcBackend;
+    use mbongo_storage::InMemoryStorage;
+
+    use crate::backend::dev_address;
+
+    /// Produces `blocks` blocks, each holding one dev-account transfer.
+    async fn producer_chain(blocks: u64) -> NodeBackend<InMemoryStorage> {
+        producer_chain_to(blocks, Address([7u8; 32])).await
+    }
+
+    /// Like [`producer_chain`], with transfers going to `receiver`.
+    async fn producer_chain_to(blocks: u64, receiver: Address) -> NodeBackend<InMemoryStorage> {
+        let backend = NodeBackend::new(InMemoryStorage::new(), true);
+        backend.ensure_genesis().unwrap();
+        let sk = SigningKey::from_bytes(&[0xAAu8; 32]);
+        for nonce in 0..blocks {
+            let mut tx = Transaction {
+                tx_type: TransactionType::Transfer,
+                sender: dev_address(),
+                receiver,
+                amount: 5,
+                nonce,
+                signature: [0u8; 64],
+                multisig: None,
+                data: Vec::new(),
+            };
+            tx.signature = sk.sign(&tx.signing_payload()).to_bytes();
+            backend.submit_transaction(tx).await.unwrap();
+            backend.produce_block().await.unwrap();
+        }
+        backend
+    }
+
+    fn export_all(backend: &NodeBackend<InMemoryStorage>) -> Vec<u8> {
+        let mut out = Vec::new();
+        export_blocks(backend.storage.as_ref(), 0, None, &mut out).unwrap();
+        out
+    }
+
+    #[tokio::test]
+    async fn export_import_roundtrip() {
+        let source = producer_chain(3).await;
+        let archive = export_all(&source);
+
+        let target = NodeBackend::new(InMemoryStorage::new(), false);
+        let report = import_blocks(&target, archive.as_slice()).unwrap();
+        assert_eq!(
+            report,
+            TransferReport {
+                blocks: 3,
+                skipped: 1
+            }
+        );
+
+        let tip = |b: &NodeBackend<InMemoryStorage>| {
+            compute_block_hash(&b.storage.get_block_by_height(3).unwrap().unwrap())
+        };
+        assert_eq!(tip(&source), tip(&target));
+        let receiver = Address([7u8; 32]);
+        assert_eq!(
+            target.storage.get_account(&receiver).unwrap(),
+            source.storage.get_account(&receiver).unwrap()
+        );
+
+        // Re-importing is a no-op.
+        let report = import_blocks(&target, archive.as_slice()).unwrap();
+        assert_eq!(
+            report,
+            TransferReport {
+                blocks: 0,
+                skipped: 4
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn export_range_is_validated() {
+        let source = producer_chain(2).await;
+        let mut out = Vec::new();
+        let report = export_blocks(source.storage.as_ref(), 1, Some(2), &mut out).unwrap();
+        assert_eq!(report.blocks, 2);
+
+        let reader = BlockReader::new(out.as_slice()).unwrap();
+        assert_eq!((reader.header().from, reader.header().to), (1, 2));
+
+        let err = export_blocks(source.storage.as_ref(), 1, Some(5), Vec::new()).unwrap_err();
+        assert!(
+            matches!(err, BlockFileError::InvalidRange { tip: 2, .. }),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn import_rejects_foreign_or_corrupt_archives() {
+        let source = producer_chain(1).await;
+        let archive = export_all(&source);
+        let target = NodeBackend::new(InMemoryStorage::new(), false);
+
+        let mut bad_magic = archive.clone();
+        bad_magic[0] ^= 0xFF;
+        let err = import_blocks(&target, bad_magic.as_slice()).unwrap_err();
+        assert!(matches!(err, BlockFileError::BadMagic), "{err}");
+
+        // The genesis hash follows the 4-byte magic and 4-byte version.
+        let mut foreign = archive.clone();
+        foreign[8] ^= 0xFF;
+        let err = import_blocks(&target, foreign.as_slice()).unwrap_err();
+        assert!(
+            matches!(err, BlockFileError::GenesisMismatch { .. }),
+            "{err}"
+        );
+
+        // Flip a byte in the last record's signature.
+        let mut tampered = archive;
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xFF;
+        let err = import_blocks(&target, tampered.as_slice()).unwrap_err();
+        assert!(
+            matches!(err, BlockFileError::Apply { height: 1, .. }),
+            "{err}"
+        );
+        assert_eq!(target.storage.get_latest_height().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn import_rejects_blocks_conflicting_with_local_chain() {
+        let source = producer_chain(2).await;
+        let archive = export_all(&source);
+        let target = producer_chain_to(1, Address([8u8; 32])).await;
+
+        let err = import_blocks(&target, archive.as_slice()).unwrap_err();
+        assert!(
+            matches!(err, BlockFileError::Fork { height: 1, .. }),
+            "{err}"
+        );
+        assert_eq!(target.storage.get_latest_height().unwrap(), 1);
+    }
+}
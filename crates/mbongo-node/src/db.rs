@@ -0,0 +1,897 @@
+//! `mbongo-node db ...` maintenance subcommands.
+//!
+//! These run against a data directory without starting networking, RPC,
+//! or block production.
+
+use std::collections::HashSet;
+
+use clap::Subcommand;
+use mbongo_core::{compute_transactions_root, Account, Address, Bloom, Hash, TransactionType};
+use mbongo_storage::{
+    AddressTxEntry, BatchOp, InMemoryStorage, MigrationReport, RocksDbStorage, Storage,
+    StorageError,
+};
+
+use crate::backend::{
+    address_index_ops, compute_block_hash, compute_tx_hash, dev_address, NodeBackend,
+};
+
+/// Database maintenance subcommands.
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+    /// Upgrade the data directory to the current storage schema version
+    Migrate,
+    /// Verify that the data directory is internally consistent
+    Check {
+        /// Rebuild the transaction, tx-sequence, receipt, address and block-bloom indexes from block bodies before checking
+        #[arg(long)]
+        rebuild_indexes: bool,
+    },
+}
+
+/// Runs a `db` subcommand against the database in `data_dir`.
+pub fn run(cmd: DbCommand, data_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    match cmd {
+        DbCommand::Migrate => migrate(data_dir),
+        DbCommand::Check { rebuild_indexes } => check_cmd(data_dir, rebuild_indexes),
+    }
+}
+
+/// Opens the existing database in `data_dir`, applies pending schema
+/// migrations, and prints a summary. A missing database is an error rather
+/// than being created.
+fn migrate(data_dir: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let (_, report) = RocksDbStorage::open_existing_and_migrate(data_dir)
+        .map_err(|e| format!("failed to open storage: {e}"))?;
+    println!("{}", describe(&report, data_dir));
+    for step in &report.applied {
+        println!("  applied: {step}");
+    }
+    Ok(())
+}
+
+/// One-line human-readable summary of a [`MigrationReport`].
+pub fn describe(report: &MigrationReport, data_dir: &str) -> String {
+    if report.created {
+        format!("Created new database at {data_dir} (schema v{})", report.to)
+    } else if report.migrated() {
+        format!(
+            "Migrated {data_dir} from schema v{} to v{} ({} step(s))",
+            report.from,
+            report.to,
+            report.applied.len()
+        )
+    } else {
+        format!("{data_dir} is already at schema v{}", report.to)
+    }
+}
+
+/// Opens `data_dir`, optionally rebuilds derived indexes, and prints every
+/// inconsistency found. Fails if any inconsistency remains.
+fn check_cmd(data_dir: &str, rebuild_indexes: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let storage =
+        RocksDbStorage::open(data_dir).map_err(|e| format!("failed to open storage: {e}"))?;
+
+    if rebuild_indexes {
+        let rebuilt = rebuild_indexes_from_blocks(&storage)?;
+        println!(
+            "Rebuilt indexes from {} block(s), {} transaction(s)",
+            rebuilt.blocks, rebuilt.transactions
+        );
+        if let Some(height) = rebuilt.reexecution_failed {
+            println!("  block {height} failed to re-execute; receipts from there on were kept");
+        }
+    }
+
+    let report = check(&storage)?;
+    println!(
+        "Checked {} block(s) and {} transaction(s) up to height {}",
+        report.blocks, report.transactions, report.latest_height
+    );
+    if report.issues.is_empty() {
+        println!("No inconsistencies found");
+        return Ok(());
+    }
+    for issue in &report.issues {
+        println!("  {issue}");
+    }
+    Err(format!("{} inconsistencies found", report.issues.len()).into())
+}
+
+/// An inconsistency found by [`check`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Issue {
+    /// No block is reachable through the height index at this height.
+    #[error("height {0}: no block in height index")]
+    MissingBlock(u64),
+    /// The block indexed at `height` records a different height in its header.
+    #[error("height {height}: indexed block has header height {got}")]
+    WrongHeight {
+        /// Height the block is indexed under.
+        height: u64,
+        /// Height in the block header.
+        got: u64,
+    },
+    /// The block's parent hash does not match the hash of the block below it.
+    #[error("height {height}: parent hash {got} does not match block below ({expected})")]
+    BrokenParentLink {
+        /// Height of the child block.
+        height: u64,
+        /// Hash of the block at `height - 1`.
+        expected: Hash,
+        /// Parent hash recorded in the header.
+        got: Hash,
+    },
+    /// The header's `transactions_root` does not match the body.
+    #[error("height {0}: transactions_root does not match body")]
+    TransactionsRootMismatch(u64),
+    /// A transaction in a block body is missing from the transactions column family.
+    #[error("height {height}: transaction {index} ({hash}) not stored")]
+    MissingTransaction {
+        /// Height of the containing block.
+        height: u64,
+        /// Index within the block body.
+        index: usize,
+        /// Transaction hash.
+        hash: Hash,
+    },
+    /// A transaction's receipt is missing or does not match its position in the chain.
+    #[error("height {height}: receipt for transaction {index} ({hash}) missing or stale")]
+    ReceiptMismatch {
+        /// Height of the containing block.
+        height: u64,
+        /// Index within the block body.
+        index: usize,
+        /// Transaction hash.
+        hash: Hash,
+    },
+    /// An address involved in an included transaction has no index entry for it.
+    #[error(
+        "height {height}: transaction {index} ({hash}) missing from address index of {address}"
+    )]
+    AddressIndexMissing {
+        /// Height of the containing block.
+        height: u64,
+        /// Index within the block body.
+        index: usize,
+        /// Transaction hash.
+        hash: Hash,
+        /// Sender or receiver lacking the entry.
+        address: Address,
+    },
+    /// The block's stored log bloom is missing or differs from the union of
+    /// its receipts' blooms.
+    #[error("height {0}: block bloom missing or does not match its receipts")]
+    BlockBloomMismatch(u64),
+    /// The tx sequence index diverges from the order of transactions in blocks.
+    #[error(
+        "tx_seq_index position {position} holds {}, but blocks include {} there",
+        or_none(*indexed),
+        or_none(*in_blocks)
+    )]
+    TxSeqIndexMismatch {
+        /// Zero-based position among included transactions.
+        position: usize,
+        /// Hash at this position in the tx sequence index.
+        indexed: Option<Hash>,
+        /// Hash at this position when walking block bodies.
+        in_blocks: Option<Hash>,
+    },
+    /// `last_included_tx_seq` does not match the tx sequence index.
+    #[error("last_included_tx_seq is {recorded}, but tx_seq_index ends at {indexed}")]
+    LastIncludedTxSeq {
+        /// Value stored in metadata.
+        recorded: u64,
+        /// Highest sequence number found in the index.
+        indexed: u64,
+    },
+    /// The stored genesis block differs from the one this build creates.
+    #[error("genesis block {stored} differs from expected {expected}")]
+    GenesisMismatch {
+        /// Hash of the stored genesis block.
+        stored: Hash,
+        /// Hash of the genesis block this build creates.
+        expected: Hash,
+    },
+    /// A stored block failed to re-execute on a scratch state.
+    #[error("height {height}: re-execution failed: {error}")]
+    ReexecutionFailed {
+        /// Height of the failing block.
+        height: u64,
+        /// Rendered [`crate::backend::ApplyBlockError`].
+        error: String,
+    },
+    /// An account differs from the state obtained by re-executing the chain.
+    #[error(
+        "account {address}: stored {}, re-executed {}",
+        describe_account(stored.as_ref()),
+        describe_account(expected.as_ref())
+    )]
+    AccountMismatch {
+        /// Account address.
+        address: Address,
+        /// Account in the checked database.
+        stored: Option<Account>,
+        /// Account after re-execution.
+        expected: Option<Account>,
+    },
+    /// A contract registration differs from the state obtained by
+    /// re-executing the chain.
+    #[error(
+        "contract {address}: stored code {}, re-executed {}",
+        or_none(*stored),
+        or_none(*expected)
+    )]
+    ContractMismatch {
+        /// Contract address.
+        address: Address,
+        /// Code hash registered in the checked database.
+        stored: Option<Hash>,
+        /// Code hash registered after re-execution.
+        expected: Option<Hash>,
+    },
+    /// Contract code is missing or differs from the deployed module.
+    #[error("code {0}: missing or differs from the deployed module")]
+    CodeMismatch(Hash),
+}
+
+/// Result of [`check`].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Latest height recorded in metadata.
+    pub latest_height: u64,
+    /// Number of blocks walked.
+    pub blocks: u64,
+    /// Number of distinct transactions found in block bodies.
+    pub transactions: u64,
+    /// Inconsistencies found, in discovery order.
+    pub issues: Vec<Issue>,
+}
+
+/// Result of [`rebuild_indexes_from_blocks`].
+#[derive(Debug, Default)]
+pub struct RebuildReport {
+    /// Number of blocks walked.
+    pub blocks: u64,
+    /// Number of transactions re-indexed.
+    pub transactions: u64,
+    /// Height of the first block that failed to re-execute. Receipts of
+    /// transactions from that height on were left as stored.
+    pub reexecution_failed: Option<u64>,
+}
+
+/// Walks the chain through the [`Storage`] trait and reports inconsistencies.
+///
+/// Checks, for every height from 0 to the latest height:
+/// - the height index resolves to a block with a matching header height;
+/// - parent linkage and the `transactions_root` commitment;
+/// - every transaction in a block body is stored, with address-index
+///   entries for its sender and receiver and the receipt re-execution
+///   produces (status, logs and bloom included);
+/// - the block bloom re-execution produces, or while re-execution is not
+///   possible, the union of the blooms of the block's stored receipts.
+///
+/// Blocks are read one height at a time and re-executed on a scratch
+/// [`InMemoryStorage`] as they are walked. The scratch store keeps what
+/// [`NodeBackend::apply_block`] writes, including every block body,
+/// transaction and receipt, so memory use grows with the size of the whole
+/// chain, about as large as the database being checked. Afterwards it
+/// checks that the tx sequence index lists included transactions in block
+/// order and ends at `last_included_tx_seq`, and compares the accounts,
+/// contract registrations and contract code touched by the chain with the
+/// re-executed state. Contract storage slots are not checked: [`Storage`]
+/// cannot enumerate the slots a contract wrote.
+///
+/// # Errors
+///
+/// Returns [`StorageError`] if the database cannot be read. Inconsistent
+/// data is reported in [`CheckReport::issues`], not as an error.
+pub fn check<S: Storage>(storage: &S) -> Result<CheckReport, StorageError> {
+    let latest_height = storage.get_latest_height()?;
+    let mut report = CheckReport {
+        latest_height,
+        ..CheckReport::default()
+    };
+
+    let scratch = NodeBackend::new(InMemoryStorage::new(), false);
+    // Cleared once re-execution cannot continue; the state comparison at
+    // the end is skipped, since partial state would only repeat the failure.
+    let mut replaying = true;
+    if let Err(e) = scratch.ensure_genesis() {
+        report.issues.push(Issue::ReexecutionFailed {
+            height: 0,
+            error: e.to_string(),
+        });
+        replaying = false;
+    }
+
+    // ── Chain walk ─────────────────────────────────────────────────────
+    let mut included: Vec<Hash> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut addresses = HashSet::new();
+    let mut contracts = HashSet::new();
+    let mut parent_hash: Option<Hash> = None;
+
+    for height in 0..=latest_height {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            report.issues.push(Issue::MissingBlock(height));
+            parent_hash = None;
+            // Without a stored genesis there is no chain to replay; a gap
+            // higher up makes the next block fail to re-execute.
+            if height == 0 {
+                replaying = false;
+            }
+            continue;
+        };
+        report.blocks += 1;
+
+        if block.header.height != height {
+            report.issues.push(Issue::WrongHeight {
+                height,
+                got: block.header.height,
+            });
+        }
+        if let Some(expected) = parent_hash {
+            if block.header.parent_hash != expected {
+                report.issues.push(Issue::BrokenParentLink {
+                    height,
+                    expected,
+                    got: block.header.parent_hash,
+                });
+            }
+        }
+        if block.header.transactions_root != compute_transactions_root(&block.body.transactions) {
+            report.issues.push(Issue::TransactionsRootMismatch(height));
+        }
+
+        let block_hash = compute_block_hash(&block);
+        if replaying {
+            if height == 0 {
+                let expected =
+                    scratch.storage.get_block_by_height(0)?.map(|b| compute_block_hash(&b));
+                if let Some(expected) = expected.filter(|expected| *expected != block_hash) {
+                    report.issues.push(Issue::GenesisMismatch {
+                        stored: block_hash,
+                        expected,
+                    });
+                    replaying = false;
+                }
+            } else if let Err(e) = scratch.apply_block(&block) {
+                report.issues.push(Issue::ReexecutionFailed {
+                    height,
+                    error: e.to_string(),
+                });
+                replaying = false;
+            }
+        }
+
+        let mut bloom = Bloom::default();
+        for ((index, tx), position) in block.body.transactions.iter().enumerate().zip(0u32..) {
+            let hash = compute_tx_hash(tx);
+            if storage.get_transaction(&hash)?.is_none() {
+                report.issues.push(Issue::MissingTransaction {
+                    height,
+                    index,
+                    hash,
+                });
+            }
+            // `apply_block` skips transactions that are already stored, so
+            // only the first occurrence receives a sequence number and receipt.
+            if seen.insert(hash) {
+                included.push(hash);
+                let stored = storage.get_receipt(&hash)?;
+                if let Some(receipt) = &stored {
+                    bloom.accrue_bloom(&receipt.bloom);
+                }
+                let matches = if replaying {
+                    stored.is_some() && stored == scratch.storage.get_receipt(&hash)?
+                } else {
+                    // Without a re-executed receipt only its position can be checked.
+                    stored.is_some_and(|r| {
+                        (r.tx_hash, r.block_hash, r.block_height, r.index)
+                            == (hash, block_hash, height, position)
+                    })
+                };
+                if !matches {
+                    report.issues.push(Issue::ReceiptMismatch {
+                        height,
+                        index,
+                        hash,
+                    });
+                }
+                let entry = AddressTxEntry {
+                    height,
+                    index: position,
+                    tx_hash: hash,
+                };
+                for address in [tx.sender, tx.receiver] {
+                    let page = storage.get_address_transactions(&address, Some(height + 1), 1)?;
+                    if !page.contains(&entry) {
+                        report.issues.push(Issue::AddressIndexMissing {
+                            height,
+                            index,
+                            hash,
+                            address,
+                        });
+                    }
+                }
+            }
+            addresses.insert(tx.sender);
+            addresses.insert(tx.receiver);
+            match tx.tx_type {
+                TransactionType::Deploy => {
+                    contracts.insert(tx.deployed_address());
+                }
+                TransactionType::Call => {
+                    contracts.insert(tx.receiver);
+                }
+                _ => {}
+            }
+        }
+        let expected = if replaying {
+            scratch.storage.get_block_bloom(height)?
+        } else {
+            Some(bloom)
+        };
+        if storage.get_block_bloom(height)? != expected {
+            report.issues.push(Issue::BlockBloomMismatch(height));
+        }
+
+        parent_hash = Some(block_hash);
+    }
+    report.transactions = included.len() as u64;
+
+    // ── Tx sequence index ──────────────────────────────────────────────
+    // Sequence numbers leak when a block fails after allocating them, so
+    // gaps are expected; only the order of present entries matters.
+    let last_included = storage.get_last_included_tx_seq()?;
+    let mut indexed = Vec::new();
+    let mut last_indexed = 0;
+    for seq in 1..=last_included {
+        if let Some(hash) = storage.get_tx_hash_by_seq(seq)? {
+            indexed.push(hash);
+            last_indexed = seq;
+        }
+    }
+    while let Some(hash) = storage.get_tx_hash_by_seq(last_indexed + 1)? {
+        indexed.push(hash);
+        last_indexed += 1;
+    }
+    if last_indexed != last_included {
+        report.issues.push(Issue::LastIncludedTxSeq {
+            recorded: last_included,
+            indexed: last_indexed,
+        });
+    }
+    let len = indexed.len().max(included.len());
+    if let Some(position) = (0..len).find(|&i| indexed.get(i) != included.get(i)) {
+        report.issues.push(Issue::TxSeqIndexMismatch {
+            position,
+            indexed: indexed.get(position).copied(),
+            in_blocks: included.get(position).copied(),
+        });
+    }
+
+    // ── Re-executed state ──────────────────────────────────────────────
+    if !replaying {
+        return Ok(report);
+    }
+
+    addresses.insert(dev_address());
+    let mut addresses: Vec<Address> = addresses.into_iter().collect();
+    addresses.sort_by_key(|a| a.0);
+    for address in addresses {
+        let stored = storage.get_account(&address)?;
+        let expected = scratch.storage.get_account(&address)?;
+        if stored != expected {
+            report.issues.push(Issue::AccountMismatch {
+                address,
+                stored,
+                expected,
+            });
+        }
+    }
+
+    let mut contracts: Vec<Address> = contracts.into_iter().collect();
+    contracts.sort_by_key(|a| a.0);
+    let mut code_hashes = HashSet::new();
+    for address in contracts {
+        let stored = storage.get_contract(&address)?;
+        let expected = scratch.storage.get_contract(&address)?;
+        if stored != expected {
+            report.issues.push(Issue::ContractMismatch {
+                address,
+                stored,
+                expected,
+            });
+        }
+        code_hashes.extend(expected);
+    }
+    let mut code_hashes: Vec<Hash> = code_hashes.into_iter().collect();
+    code_hashes.sort_by_key(|h| h.0);
+    for code_hash in code_hashes {
+        if storage.get_code(&code_hash)? != scratch.storage.get_code(&code_hash)? {
+            report.issues.push(Issue::CodeMismatch(code_hash));
+        }
+    }
+
+    Ok(report)
+}
+
+/// Renders an optional account for [`Issue`] messages.
+fn describe_account(account: Option<&Account>) -> String {
+    account.map_or_else(
+        || "nothing".to_string(),
+        |a| format!("balance {} nonce {}", a.balance, a.nonce),
+    )
+}
+
+/// Renders an optional hash for [`Issue`] messages.
+fn or_none(hash: Option<Hash>) -> String {
+    hash.map_or_else(|| "nothing".to_string(), |h| h.to_string())
+}
+
+/// Rewrites the indexes derived from block bodies: the transactions column
+/// family, receipts, the tx sequence index and counters, the address index
+/// and block blooms.
+///
+/// Walks blocks through the height index, so blocks that are missing from
+/// it are not re-indexed. Receipts are taken from re-executing each block on
+/// a scratch [`InMemoryStorage`], so failed contract transactions and
+/// emitted logs keep their status, logs and bloom; once a block fails to
+/// re-execute, the remaining receipts are left untouched and the height is
+/// reported in [`RebuildReport::reexecution_failed`]. Block blooms are the
+/// union of the receipts written or kept. Transactions are
+/// renumbered contiguously from 1;
+/// stale sequence entries past the new end are overwritten as new
+/// transactions are included. Stale address-index entries are not removed.
+///
+/// # Errors
+///
+/// Returns [`StorageError`] if the database cannot be read or written.
+pub fn rebuild_indexes_from_blocks<S: Storage>(storage: &S) -> Result<RebuildReport, StorageError> {
+    let latest_height = storage.get_latest_height()?;
+    let mut report = RebuildReport::default();
+    let mut seen = HashSet::new();
+    let mut seq = 0;
+
+    let scratch = NodeBackend::new(InMemoryStorage::new(), false);
+    if scratch.ensure_genesis().is_err() {
+        report.reexecution_failed = Some(0);
+    }
+
+    for height in 0..=latest_height {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            continue;
+        };
+        report.blocks += 1;
+
+        if height > 0 && report.reexecution_failed.is_none() && scratch.apply_block(&block).is_err()
+        {
+            report.reexecution_failed = Some(height);
+        }
+        let replayed = report.reexecution_failed.is_none();
+
+        let mut ops = Vec::new();
+        let mut bloom = Bloom::default();
+        for (tx, index) in block.body.transactions.iter().zip(0u32..) {
+            let hash = compute_tx_hash(tx);
+            if !seen.insert(hash) {
+                continue;
+            }
+            seq += 1;
+            ops.push(BatchOp::PutTransaction(hash, tx.clone()));
+            ops.push(BatchOp::PutTxSeqIndex(seq, hash));
+            if replayed {
+                if let Some(receipt) = scratch.storage.get_receipt(&hash)? {
+                    bloom.accrue_bloom(&receipt.bloom);
+                    ops.push(BatchOp::PutReceipt(hash, receipt));
+                }
+            } else if let Some(receipt) = storage.get_receipt(&hash)? {
+                bloom.accrue_bloom(&receipt.bloom);
+            }
+            ops.extend(address_index_ops(
+                tx,
+                AddressTxEntry {
+                    height,
+                    index,
+                    tx_hash: hash,
+                },
+            ));
+        }
+        ops.push(BatchOp::PutBlockBloom(height, bloom));
+        storage.write_batch(ops)?;
+    }
+
+    storage.write_batch(vec![
+        BatchOp::SetTxSeq(seq),
+        BatchOp::SetLastIncludedTxSeq(seq),
+    ])?;
+    report.transactions = seq;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use mbongo_core::{Block, BlockBody, BlockHeader, Bloom, ReceiptStatus, Transaction};
+    use parity_scale_codec::Encode;
+
+    /// Dev-account transaction signed with the dev key.
+    fn dev_tx(
+        tx_type: TransactionType,
+        receiver: Address,
+        amount: u128,
+        nonce: u64,
+        data: Vec<u8>,
+    ) -> Transaction {
+        let sk = SigningKey::from_bytes(&[0xAAu8; 32]);
+        let mut tx = Transaction {
+            tx_type,
+            sender: dev_address(),
+            receiver,
+            amount,
+            nonce,
+            signature: [0u8; 64],
+            multisig: None,
+            data,
+        };
+        tx.signature = sk.sign(&tx.signing_payload()).to_bytes();
+        tx
+    }
+
+    /// Dev-account transfer of `amount` to `receiver` at `nonce`.
+    fn dev_transfer(receiver: Address, amount: u128, nonce: u64) -> Transaction {
+        dev_tx(
+            TransactionType::Transfer,
+            receiver,
+            amount,
+            nonce,
+            Vec::new(),
+        )
+    }
+
+    /// Dev-account call of `function` on `contract` at `nonce`.
+    fn dev_call(contract: Address, function: &str, nonce: u64) -> Transaction {
+        let call = mbongo_runtime::ContractCall {
+            function: function.to_string(),
+            input: Vec::new(),
+            gas_limit: 100_000,
+        };
+        dev_tx(TransactionType::Call, contract, 0, nonce, call.encode())
+    }
+
+    /// Contract whose `set` export writes a storage slot, whose `trap`
+    /// export fails and whose `emit` export logs one topic.
+    const CONTRACT: &str = r#"(module
+        (import "env" "storage_set" (func $set (param i32 i32 i32 i32)))
+        (import "env" "log" (func $log (param i32 i32 i32 i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "k")
+        (data (i32.const 32) "\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09\09")
+        (func (export "set")
+          (call $set (i32.const 0) (i32.const 1) (i32.const 0) (i32.const 1)))
+        (func (export "trap") unreachable)
+        (func (export "emit")
+          (call $log (i32.const 32) (i32.const 1) (i32.const 0) (i32.const 1))))"#;
+
+    /// A genesis block plus one block deploying [`CONTRACT`] and calling
+    /// `set`, `trap` and `emit`. Returns the contract address.
+    fn contract_chain() -> (NodeBackend<InMemoryStorage>, Address) {
+        let backend = NodeBackend::new(InMemoryStorage::new(), false);
+        backend.ensure_genesis().unwrap();
+        let code = wat::parse_str(CONTRACT).unwrap();
+        let deploy = dev_tx(TransactionType::Deploy, Address::zero(), 0, 0, code);
+        let contract = deploy.deployed_address();
+        extend(
+            &backend,
+            vec![
+                deploy,
+                dev_call(contract, "set", 1),
+                dev_call(contract, "trap", 2),
+                dev_call(contract, "emit", 3),
+            ],
+        );
+        (backend, contract)
+    }
+
+    /// Applies a block with `txs` on top of the current tip.
+    fn extend(backend: &NodeBackend<InMemoryStorage>, txs: Vec<Transaction>) {
+        let height = backend.storage.get_latest_height().unwrap();
+        let parent = backend.storage.get_block_by_height(height).unwrap().unwrap();
+        let block = Block {
+            header: BlockHeader {
+                parent_hash: compute_block_hash(&parent),
+                state_root: Hash::zero(),
+                transactions_root: compute_transactions_root(&txs),
+                timestamp: height + 1,
+                height: height + 1,
+            },
+            body: BlockBody { transactions: txs },
+        };
+        backend.apply_block(&block).unwrap();
+    }
+
+    /// A genesis block plus two blocks holding three transfers.
+    fn sample_chain() -> NodeBackend<InMemoryStorage> {
+        let backend = NodeBackend::new(InMemoryStorage::new(), false);
+        backend.ensure_genesis().unwrap();
+        extend(
+            &backend,
+            vec![
+                dev_transfer(Address([1u8; 32]), 10, 0),
+                dev_transfer(Address([2u8; 32]), 20, 1),
+            ],
+        );
+        extend(&backend, vec![dev_transfer(Address([1u8; 32]), 30, 2)]);
+        backend
+    }
+
+    #[test]
+    fn consistent_chain_has_no_issues() {
+        let backend = sample_chain();
+        let report = check(backend.storage.as_ref()).unwrap();
+        assert_eq!(report.issues, Vec::new());
+        assert_eq!(report.latest_height, 2);
+        assert_eq!(report.blocks, 3);
+        assert_eq!(report.transactions, 3);
+    }
+
+    #[test]
+    fn detects_tampered_account_and_tx_index() {
+        let backend = sample_chain();
+        let storage = backend.storage.as_ref();
+
+        let victim = Address([2u8; 32]);
+        let mut account = storage.get_account(&victim).unwrap().unwrap();
+        account.balance += 1;
+        storage.put_account(&victim, &account).unwrap();
+        storage.put_tx_seq_index(2, &Hash([9u8; 32])).unwrap();
+        storage.set_last_included_tx_seq(2).unwrap();
+        let first = compute_tx_hash(&dev_transfer(Address([1u8; 32]), 10, 0));
+        let mut receipt = storage.get_receipt(&first).unwrap().unwrap();
+        receipt.index += 1;
+        storage.write_batch(vec![BatchOp::PutReceipt(first, receipt)]).unwrap();
+
+        let issues = check(storage).unwrap().issues;
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::ReceiptMismatch { hash, .. } if *hash == first)));
+        assert!(issues.contains(&Issue::TxSeqIndexMismatch {
+            position: 1,
+            indexed: Some(Hash([9u8; 32])),
+            in_blocks: Some(compute_tx_hash(&dev_transfer(victim, 20, 1))),
+        }));
+        assert!(issues.contains(&Issue::LastIncludedTxSeq {
+            recorded: 2,
+            indexed: 3
+        }));
+        assert!(issues
+            .iter()
+            .any(|i| matches!(i, Issue::AccountMismatch { address, .. } if *address == victim)));
+
+        // Rebuilding fixes the derived indexes but not account state.
+        let rebuilt = rebuild_indexes_from_blocks(storage).unwrap();
+        assert_eq!(rebuilt.transactions, 3);
+        let issues = check(storage).unwrap().issues;
+        assert_eq!(issues.len(), 1, "{issues:?}");
+        assert!(matches!(issues[0], Issue::AccountMismatch { .. }));
+    }
+
+    #[test]
+    fn detects_and_rebuilds_block_blooms() {
+        let backend = sample_chain();
+        let storage = backend.storage.as_ref();
+        let bloom = storage.get_block_bloom(1).unwrap().expect("block bloom");
+        assert!(!bloom.is_empty());
+
+        storage.write_batch(vec![BatchOp::PutBlockBloom(1, Bloom::default())]).unwrap();
+        assert_eq!(
+            check(storage).unwrap().issues,
+            vec![Issue::BlockBloomMismatch(1)]
+        );
+
+        rebuild_indexes_from_blocks(storage).unwrap();
+        assert_eq!(storage.get_block_bloom(1).unwrap(), Some(bloom));
+        assert_eq!(check(storage).unwrap().issues, Vec::new());
+    }
+
+    #[test]
+    fn detects_broken_parent_link() {
+        let backend = sample_chain();
+        let storage = backend.storage.as_ref();
+
+        let mut forged = storage.get_block_by_height(2).unwrap().unwrap();
+        forged.header.parent_hash = Hash([7u8; 32]);
+        let forged_hash = compute_block_hash(&forged);
+        storage.put_block(&forged_hash, &forged).unwrap();
+        storage.put_block_height_index(2, forged_hash).unwrap();
+
+        let issues = check(storage).unwrap().issues;
+        assert!(
+            matches!(issues[0], Issue::BrokenParentLink { height: 2, .. }),
+            "{issues:?}"
+        );
+        assert!(issues.iter().any(|i| matches!(i, Issue::ReexecutionFailed { height: 2, .. })));
+    }
+
+    #[test]
+    fn compares_contracts_with_re_executed_state() {
+        let (backend, contract) = contract_chain();
+        let storage = backend.storage.as_ref();
+        assert_eq!(check(storage).unwrap().issues, Vec::new());
+
+        let code_hash = storage.get_contract(&contract).unwrap().unwrap();
+        storage
+            .write_batch(vec![
+                BatchOp::PutContract(contract, Hash([3u8; 32])),
+                BatchOp::PutCode(code_hash, vec![0]),
+            ])
+            .unwrap();
+
+        let issues = check(storage).unwrap().issues;
+        assert_eq!(
+            issues,
+            vec![
+                Issue::ContractMismatch {
+                    address: contract,
+                    stored: Some(Hash([3u8; 32])),
+                    expected: Some(code_hash),
+                },
+                Issue::CodeMismatch(code_hash),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_contract_calls_keep_failed_receipts() {
+        let (backend, contract) = contract_chain();
+        let storage = backend.storage.as_ref();
+        let trap = compute_tx_hash(&dev_call(contract, "trap", 2));
+        let status = || storage.get_receipt(&trap).unwrap().unwrap().status;
+        assert_eq!(status(), ReceiptStatus::Failed);
+        assert_eq!(check(storage).unwrap().issues, Vec::new());
+
+        let rebuilt = rebuild_indexes_from_blocks(storage).unwrap();
+        assert_eq!(rebuilt.reexecution_failed, None);
+        assert_eq!(status(), ReceiptStatus::Failed);
+        assert_eq!(check(storage).unwrap().issues, Vec::new());
+
+        // A receipt flipped to success is reported.
+        let mut receipt = storage.get_receipt(&trap).unwrap().unwrap();
+        receipt.status = ReceiptStatus::Success;
+        storage.write_batch(vec![BatchOp::PutReceipt(trap, receipt)]).unwrap();
+        let issues = check(storage).unwrap().issues;
+        assert!(
+            matches!(issues[..], [Issue::ReceiptMismatch { hash, index: 2, .. }] if hash == trap),
+            "{issues:?}"
+        );
+    }
+
+    #[test]
+    fn contract_logs_survive_rebuild_and_are_checked() {
+        let (backend, contract) = contract_chain();
+        let storage = backend.storage.as_ref();
+        let emit = compute_tx_hash(&dev_call(contract, "emit", 3));
+        let receipt = storage.get_receipt(&emit).unwrap().unwrap();
+        let topic = Hash([9u8; 32]);
+        assert!(receipt.logs().any(|log| log.topics == [topic]));
+        assert!(receipt.bloom.contains(&topic.0));
+
+        rebuild_indexes_from_blocks(storage).unwrap();
+        assert_eq!(storage.get_receipt(&emit).unwrap().as_ref(), Some(&receipt));
+        assert_eq!(check(storage).unwrap().issues, Vec::new());
+
+        // A receipt that lost its contract log and bloom is reported.
+        let mut stripped = receipt;
+        stripped.events.truncate(1);
+        stripped.bloom = Bloom::from_logs(&stripped.logs().collect::<Vec<_>>());
+        storage.write_batch(vec![BatchOp::PutReceipt(emit, stripped)]).unwrap();
+        let issues = check(storage).unwrap().issues;
+        assert!(
+            matches!(issues[..], [Issue::ReceiptMismatch { hash, index: 3, .. }] if hash == emit),
+            "{issues:?}"
+        );
+    }
+}
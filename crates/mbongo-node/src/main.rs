@@ -20,18 +20,30 @@
 //!
 //! # Run compute provider
 //! mbongo-node --chain mainnet --provider --gpu nvidia-rtx-4090
+//!
+//! # Upgrade a data directory to the current storage schema
+//! mbongo-node --data-dir data db migrate
+//!
+//! # Archive blocks 0..=100 and seed another data directory from the file
+//! mbongo-node --data-dir data export-blocks --from 0 --to 100 --out chain.blocks
+//! mbongo-node --data-dir fresh import-blocks --in chain.blocks
 //! ```
 
 mod backend;
+mod block_file;
+mod db;
 mod mempool;
 mod sync_service;
 
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 use backend::NodeBackend;
+use mbongo_api::rest;
+use mbongo_api::ws::{self, EventBus, WsConfig};
 use mbongo_network::{P2PNode, RpcBackend, SyncCommand, SyncEvent, SyncResponse, MAX_RANGE};
 use mbongo_storage::RocksDbStorage;
 
@@ -86,8 +98,40 @@ struct Args {
     block_time: u64,
 
     /// Data directory for storage (default: "data")
-    #[arg(long, default_value = "data")]
+    #[arg(long, default_value = "data", global = true)]
     data_dir: String,
+
+    /// Maintenance subcommand; runs instead of the node when given
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Maintenance subcommands that operate on the data directory offline.
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Database maintenance
+    Db {
+        #[command(subcommand)]
+        command: db::DbCommand,
+    },
+    /// Write a range of blocks to a portable archive file
+    ExportBlocks {
+        /// First height to export
+        #[arg(long, default_value = "0")]
+        from: u64,
+        /// Last height to export (default: chain tip)
+        #[arg(long)]
+        to: Option<u64>,
+        /// Output file
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Validate and apply blocks from an archive written by `export-blocks`
+    ImportBlocks {
+        /// Input file
+        #[arg(long = "in")]
+        input: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -96,10 +140,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let args = Args::parse();
 
+    if let Some(command) = args.command {
+        return match command {
+            Command::Db { command } => db::run(command, &args.data_dir),
+            Command::ExportBlocks { from, to, out } => {
+                block_file::export_cmd(&args.data_dir, from, to, &out)
+            }
+            Command::ImportBlocks { input } => block_file::import_cmd(&args.data_dir, &input),
+        };
+    }
+
     println!("Starting Mbongo Chain node...");
     println!("  Chain:    {}", args.chain);
     println!("  RPC:      http://127.0.0.1:{}", args.rpc_port);
     println!("  REST:     http://127.0.0.1:{}", args.rest_port);
+    println!("  WS:       ws://127.0.0.1:{}/ws", args.rest_port);
     println!("  P2P:      0.0.0.0:{}", args.p2p_port);
 
     if let Some(name) = &args.name {
@@ -107,11 +162,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // Initialize storage
-    let storage =
-        RocksDbStorage::open(&args.data_dir).map_err(|e| format!("failed to open storage: {e}"))?;
+    let (storage, report) = RocksDbStorage::open_and_migrate(&args.data_dir)
+        .map_err(|e| format!("failed to open storage: {e}"))?;
+    if report.migrated() {
+        println!("  Storage:  {}", db::describe(&report, &args.data_dir));
+    }
 
     let mut backend = NodeBackend::new(storage, args.producer);
 
+    // Feed WebSocket subscribers from block commits and mempool admissions.
+    let ws_config = WsConfig::default();
+    let event_bus = EventBus::new(ws_config.event_buffer);
+    backend.set_event_bus(event_bus.clone());
+
     // Ensure genesis block exists (idempotent).
     backend
         .ensure_genesis()
@@ -172,11 +235,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    // ── REST ───────────────────────────────────────────────────────────
+    // ── REST + WebSocket ───────────────────────────────────────────────
     let rest_addr: SocketAddr = ([127, 0, 0, 1], args.rest_port).into();
-    let rest_backend = backend.clone();
+    let rest_app = rest::router(backend.clone()).merge(ws::router(event_bus, ws_config));
     let rest_handle = tokio::spawn(async move {
-        if let Err(e) = mbongo_api::rest::serve_on_addr(rest_addr, rest_backend).await {
+        if let Err(e) = rest::serve_router_on_addr(rest_addr, rest_app).await {
             eprintln!("REST server error: {e}");
         }
     });
@@ -1,8 +1,9 @@
 //! Minimal deterministic mempool for Phase 2.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use mbongo_core::{Address, Hash, Transaction};
+use mbongo_runtime::max_fee;
 
 /// Errors returned by mempool operations.
 #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
@@ -85,7 +86,6 @@ impl Mempool {
 
     /// Returns the number of transactions in the mempool.
     #[must_use]
-    #[allow(dead_code)] // Part of public API; used in tests and future metrics.
     pub fn len(&self) -> usize {
         self.order.len()
     }
@@ -95,6 +95,59 @@ impl Mempool {
     pub fn contains_hash(&self, hash: &Hash) -> bool {
         self.by_hash.contains_key(hash)
     }
+
+    /// Returns the pending transaction with the given hash, if any.
+    #[must_use]
+    pub fn get(&self, hash: &Hash) -> Option<&Transaction> {
+        self.by_hash.get(hash)
+    }
+
+    /// Returns the number of distinct senders with pending transactions.
+    #[must_use]
+    pub fn sender_count(&self) -> usize {
+        self.by_sender_nonce
+            .keys()
+            .map(|(sender, _)| sender)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Returns true if a transaction from `sender` with `nonce` is pending.
+    #[must_use]
+    pub fn contains_sender_nonce(&self, sender: &Address, nonce: u64) -> bool {
+        self.by_sender_nonce.contains_key(&(*sender, nonce))
+    }
+
+    /// Returns the nonce `sender` should use next, given its on-chain
+    /// `account_nonce`: the first nonce not already taken by a contiguous run
+    /// of pending transactions starting at `account_nonce`.
+    #[must_use]
+    pub fn next_nonce(&self, sender: &Address, account_nonce: u64) -> u64 {
+        let mut nonce = account_nonce;
+        while self.contains_sender_nonce(sender, nonce) {
+            nonce += 1;
+        }
+        nonce
+    }
+
+    /// Returns pending transactions in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &Transaction> {
+        self.order.iter().filter_map(|h| self.by_hash.get(h))
+    }
+
+    /// Returns pending transactions with their hashes in insertion order.
+    pub fn entries(&self) -> impl Iterator<Item = (&Hash, &Transaction)> {
+        self.order.iter().filter_map(|h| self.by_hash.get_key_value(h))
+    }
+
+    /// Returns the total `sender` may spend across its pending transactions:
+    /// their amounts plus the [`max_fee`] of each.
+    #[must_use]
+    pub fn pending_spend(&self, sender: &Address) -> u128 {
+        self.by_hash.values().filter(|tx| tx.sender == *sender).fold(0u128, |acc, tx| {
+            acc.saturating_add(tx.amount).saturating_add(max_fee(tx))
+        })
+    }
 }
 
 impl Default for Mempool {
@@ -123,6 +176,8 @@ mod tests {
             amount: 100,
             nonce,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         (hash, tx)
     }
@@ -174,6 +229,42 @@ mod tests {
         assert!(pool.contains_hash(&h3));
     }
 
+    #[test]
+    fn mempool_next_nonce_skips_contiguous_pending() {
+        let mut pool = Mempool::new();
+        let sender = Address([1u8; 32]);
+        assert_eq!(pool.next_nonce(&sender, 4), 4);
+
+        let (h1, tx1) = make_tx_with_hash(1, 4, 10);
+        let (h2, tx2) = make_tx_with_hash(1, 5, 11);
+        let (h3, tx3) = make_tx_with_hash(1, 7, 12);
+        pool.insert(h1, tx1).unwrap();
+        pool.insert(h2, tx2).unwrap();
+        pool.insert(h3, tx3).unwrap();
+
+        // Nonce 6 is missing, so 7 does not extend the run.
+        assert_eq!(pool.next_nonce(&sender, 4), 6);
+        assert_eq!(pool.next_nonce(&Address([2u8; 32]), 0), 0);
+        assert_eq!(pool.pending_spend(&sender), 300);
+    }
+
+    #[test]
+    fn mempool_lookup_and_entries() {
+        let mut pool = Mempool::new();
+        let (h1, tx1) = make_tx_with_hash(1, 0, 10);
+        let (h2, tx2) = make_tx_with_hash(1, 1, 11);
+        let (h3, tx3) = make_tx_with_hash(2, 0, 12);
+        pool.insert(h1, tx1.clone()).unwrap();
+        pool.insert(h2, tx2).unwrap();
+        pool.insert(h3, tx3).unwrap();
+
+        assert_eq!(pool.get(&h1), Some(&tx1));
+        assert_eq!(pool.get(&Hash([0u8; 32])), None);
+        assert_eq!(pool.sender_count(), 2);
+        let hashes: Vec<Hash> = pool.entries().map(|(h, _)| *h).collect();
+        assert_eq!(hashes, vec![h1, h2, h3]);
+    }
+
     #[test]
     fn mempool_remove() {
         let mut pool = Mempool::new();
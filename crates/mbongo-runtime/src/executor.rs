@@ -0,0 +1,1058 @@
+//! Block execution with contract support.
+//!
+//! [`Runtime::execute_block`] applies a block's transactions to a
+//! [`StateChanges`] overlay over committed [`ContractState`]. Native
+//! transactions behave exactly as in [`mbongo_core::execution`]; a block
+//! without contract transactions is handed to
+//! [`execute_parallel`](mbongo_core::execution::execute_parallel) unchanged.
+//!
+//! A contract transaction first goes through the same checks as a native
+//! one: a wrong nonce or a sender who cannot cover `amount` plus
+//! [`max_fee`] makes the block invalid. Past that point the transaction is
+//! always included, the sender's nonce advances and the sender pays the
+//! [fee](crate::gas::fee) of the gas used. Its other effects are collected
+//! in a separate layer that is merged only if the deployment or call
+//! succeeds; otherwise it is discarded and the outcome records why.
+//!
+//! - [`TransactionType::Deploy`] stores the WebAssembly module in `data`
+//!   under its blake3 hash, registers a contract at
+//!   [`Transaction::deployed_address`] and moves `amount` to it. No code
+//!   runs at deployment; it costs [`deploy_gas`](gas::deploy_gas) of the
+//!   code size, and only a successful deployment's module is cached.
+//! - [`TransactionType::Call`] moves `amount` to the contract at `receiver`
+//!   and runs the [`ContractCall`] SCALE-encoded in `data`. If `receiver` is
+//!   a [precompile](crate::precompiles), the call's `input` goes to it
+//!   instead, its `function` is ignored and `amount` must be zero.
+
+use std::sync::Arc;
+
+use mbongo_core::crypto::blake3_hash;
+use mbongo_core::execution::{self, ExecutionError, ExecutionFailure};
+use mbongo_core::{Hash, Log, Transaction, TransactionType};
+use parity_scale_codec::DecodeAll;
+use thiserror::Error;
+use wasmtime::Module;
+
+use crate::gas::{self, MAX_GAS_LIMIT};
+use crate::precompiles::{self, Precompile, PrecompileError};
+use crate::state::{ContractState, Layered, StateChanges};
+use crate::wasm::{
+    move_balance, CallContext, CallError, ContractCall, ContractError, Vm, MAX_CALL_SIZE,
+    MAX_CODE_SIZE,
+};
+
+/// Result of a transaction that was included in a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The transaction applied all its effects.
+    Success {
+        /// Gas consumed; zero for native transactions.
+        gas_used: u64,
+        /// Logs emitted by the contract, in order; empty for transactions
+        /// that run no contract code.
+        logs: Vec<Log>,
+    },
+    /// Only the sender's nonce advanced.
+    Failed {
+        /// Why the deployment or call failed.
+        reason: ContractError,
+        /// Gas consumed before the failure.
+        gas_used: u64,
+    },
+}
+
+impl Outcome {
+    /// Returns `true` for [`Outcome::Success`].
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Success { .. })
+    }
+
+    /// Returns the gas consumed, successful or not.
+    #[must_use]
+    pub fn gas_used(&self) -> u64 {
+        match self {
+            Self::Success { gas_used, .. } | Self::Failed { gas_used, .. } => *gas_used,
+        }
+    }
+
+    /// Returns the fee the sender paid for the gas consumed.
+    #[must_use]
+    pub fn fee(&self) -> u128 {
+        gas::fee(self.gas_used())
+    }
+}
+
+/// Returns the most `tx` can be charged in fees: the [fee](gas::fee) of
+/// the [deployment gas](gas::deploy_gas) of a deployment, of the gas limit
+/// of a well-formed call within [`MAX_GAS_LIMIT`], and zero for anything
+/// else, since those never consume gas.
+#[must_use]
+pub fn max_fee(tx: &Transaction) -> u128 {
+    match tx.tx_type {
+        TransactionType::Deploy => gas::fee(gas::deploy_gas(tx.data.len())),
+        TransactionType::Call => ContractCall::decode_all(&mut tx.data.as_slice())
+            .ok()
+            .filter(|call| call.gas_limit <= MAX_GAS_LIMIT)
+            .map_or(0, |call| gas::fee(call.gas_limit)),
+        _ => 0,
+    }
+}
+
+/// Why a transaction's `data` makes it invalid.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DataError {
+    /// A native transaction carries data.
+    #[error("{0:?} transactions carry no data")]
+    Unexpected(TransactionType),
+    /// The code or call payload is larger than allowed.
+    #[error("data is {size} bytes, more than the maximum of {max}")]
+    TooLarge {
+        /// Size of `data`.
+        size: usize,
+        /// Largest allowed size for the transaction type.
+        max: usize,
+    },
+}
+
+/// Checks the size of `tx.data`: empty for native transactions, at most
+/// [`MAX_CODE_SIZE`] for deployments and [`MAX_CALL_SIZE`] for calls.
+/// A transaction that fails this check is invalid, unlike a contract
+/// failure, so mempools and blocks must reject it.
+///
+/// # Errors
+///
+/// Returns the [`DataError`] describing the violation.
+pub fn validate_data(tx: &Transaction) -> Result<(), DataError> {
+    let max = match tx.tx_type {
+        TransactionType::Deploy => MAX_CODE_SIZE,
+        TransactionType::Call => MAX_CALL_SIZE,
+        _ if tx.data.is_empty() => return Ok(()),
+        other => return Err(DataError::Unexpected(other)),
+    };
+    if tx.data.len() > max {
+        return Err(DataError::TooLarge {
+            size: tx.data.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Everything a block did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockExecution {
+    /// State to commit.
+    pub changes: StateChanges,
+    /// Outcome of each transaction, in block order.
+    pub outcomes: Vec<Outcome>,
+}
+
+/// Executes native and contract transactions.
+#[derive(Debug, Default)]
+pub struct Runtime {
+    vm: Vm,
+}
+
+impl Runtime {
+    /// Creates a runtime with an empty module cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Executes `txs` in order against `state`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the index and error of the first transaction that makes the
+    /// block invalid; later transactions are not executed.
+    pub fn execute_block<S>(
+        &self,
+        state: &Arc<S>,
+        txs: &[&Transaction],
+    ) -> Result<BlockExecution, ExecutionFailure<S::Error>>
+    where
+        S: ContractState + Send + Sync + 'static,
+        S::Error: Send,
+    {
+        if !txs.iter().any(|tx| is_contract(tx)) {
+            let load = |address: &_| state.account(address);
+            let accounts = execution::execute_parallel(&load, txs)?;
+            return Ok(BlockExecution {
+                changes: StateChanges {
+                    accounts,
+                    ..StateChanges::default()
+                },
+                outcomes: vec![
+                    Outcome::Success {
+                        gas_used: 0,
+                        logs: Vec::new(),
+                    };
+                    txs.len()
+                ],
+            });
+        }
+
+        let mut changes = StateChanges::default();
+        let mut outcomes = Vec::with_capacity(txs.len());
+        for (index, tx) in txs.iter().enumerate() {
+            let outcome = self
+                .execute_transaction(state, &mut changes, tx)
+                .map_err(|error| ExecutionFailure { index, error })?;
+            outcomes.push(outcome);
+        }
+        Ok(BlockExecution { changes, outcomes })
+    }
+
+    /// Executes `tx` on top of `changes`. On error the block is invalid. A
+    /// wrong nonce or an insufficient balance leaves `changes` unchanged;
+    /// after a storage error `changes` must be discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExecutionError`] if the transaction makes the block invalid.
+    pub fn execute_transaction<S>(
+        &self,
+        state: &Arc<S>,
+        changes: &mut StateChanges,
+        tx: &Transaction,
+    ) -> Result<Outcome, ExecutionError<S::Error>>
+    where
+        S: ContractState + 'static,
+    {
+        if !is_contract(tx) {
+            let load = |address: &_| state.account(address);
+            execution::execute_transaction(&load, &mut changes.accounts, tx)?;
+            return Ok(Outcome::Success {
+                gas_used: 0,
+                logs: Vec::new(),
+            });
+        }
+
+        let empty = StateChanges::default();
+        let view = Layered {
+            base: state.as_ref(),
+            layers: [changes, &empty],
+        };
+        let required = tx.amount.saturating_add(max_fee(tx));
+        let mut sender = view.account(&tx.sender).map_err(ExecutionError::Storage)?.ok_or(
+            ExecutionError::InsufficientBalance {
+                balance: 0,
+                required,
+            },
+        )?;
+        let expected = sender.nonce;
+        sender.validate_and_increment_nonce(tx.nonce).map_err(|_| {
+            ExecutionError::InvalidNonce {
+                expected,
+                actual: tx.nonce,
+            }
+        })?;
+        if sender.balance < required {
+            return Err(ExecutionError::InsufficientBalance {
+                balance: sender.balance,
+                required,
+            });
+        }
+        changes.accounts.insert(tx.sender, sender);
+
+        let mut pending = StateChanges::default();
+        let mut deployed = None;
+        let result = if tx.tx_type == TransactionType::Deploy {
+            self.deploy(state, changes, &mut pending, tx).map(|module| {
+                deployed = Some(module);
+                (gas::deploy_gas(tx.data.len()), Vec::new())
+            })
+        } else {
+            self.call(state, changes, &mut pending, tx)
+        };
+        let outcome = match result {
+            Ok((gas_used, logs)) => {
+                changes.merge(pending);
+                if let Some((code_hash, module)) = deployed {
+                    self.vm.cache(code_hash, module);
+                }
+                Outcome::Success { gas_used, logs }
+            }
+            Err(CallError::Contract { reason, gas_used }) => Outcome::Failed { reason, gas_used },
+            Err(CallError::Storage(e)) => return Err(ExecutionError::Storage(e)),
+        };
+        charge(changes, tx.sender, outcome.fee())?;
+        Ok(outcome)
+    }
+
+    /// Registers the contract deployed by `tx` in `pending`. Returns the
+    /// code hash and compiled module, to be cached once the deployment is
+    /// merged. Fails having used the whole deployment gas.
+    fn deploy<S: ContractState>(
+        &self,
+        state: &Arc<S>,
+        changes: &StateChanges,
+        pending: &mut StateChanges,
+        tx: &Transaction,
+    ) -> Result<(Hash, Module), CallError<S::Error>> {
+        let gas_used = gas::deploy_gas(tx.data.len());
+        let failed = |reason| CallError::Contract { reason, gas_used };
+        let code_hash = Hash(blake3_hash(&tx.data));
+        let module = match self.vm.cached(&code_hash) {
+            Some(module) => module,
+            None => self.vm.compile(&tx.data).map_err(failed)?,
+        };
+
+        let contract = tx.deployed_address();
+        let view = Layered {
+            base: state.as_ref(),
+            layers: [pending, changes],
+        };
+        if view.code(&code_hash).map_err(CallError::Storage)?.is_none() {
+            pending.code.insert(code_hash, tx.data.clone());
+        }
+        pending.contracts.insert(contract, code_hash);
+        endow(state, changes, pending, tx, contract).map_err(|e| match e {
+            CallError::Contract { reason, .. } => failed(reason),
+            CallError::Storage(e) => CallError::Storage(e),
+        })?;
+        Ok((code_hash, module))
+    }
+
+    fn call<S: ContractState + 'static>(
+        &self,
+        state: &Arc<S>,
+        changes: &mut StateChanges,
+        pending: &mut StateChanges,
+        tx: &Transaction,
+    ) -> Result<(u64, Vec<Log>), CallError<S::Error>> {
+        let call = ContractCall::decode_all(&mut tx.data.as_slice())
+            .map_err(|e| ContractError::InvalidPayload(e.to_string()))?;
+        if call.gas_limit > MAX_GAS_LIMIT {
+            return Err(ContractError::GasLimitTooHigh {
+                limit: call.gas_limit,
+                max: MAX_GAS_LIMIT,
+            }
+            .into());
+        }
+        if let Some(precompile) = precompiles::get(&tx.receiver) {
+            return call_precompile(precompile, tx.amount, &call);
+        }
+
+        let view = Layered {
+            base: state.as_ref(),
+            layers: [pending, changes],
+        };
+        let code_hash = view
+            .contract(&tx.receiver)
+            .map_err(CallError::Storage)?
+            .ok_or(ContractError::NoContract(tx.receiver))?;
+        let module = if let Some(module) = self.vm.cached(&code_hash) {
+            module
+        } else {
+            let code = view
+                .code(&code_hash)
+                .map_err(CallError::Storage)?
+                .ok_or(ContractError::NoContract(tx.receiver))?;
+            let module = self.vm.compile(&code)?;
+            self.vm.cache(code_hash, module.clone());
+            module
+        };
+
+        endow(state, changes, pending, tx, tx.receiver)?;
+        let context = CallContext {
+            contract: tx.receiver,
+            caller: tx.sender,
+            value: tx.amount,
+        };
+        self.vm.call(&module, Arc::clone(state), changes, pending, context, call)
+    }
+}
+
+/// Runs a call to a native precompile. Returns the gas used and no logs.
+fn call_precompile<E>(
+    precompile: &Precompile,
+    value: u128,
+    call: &ContractCall,
+) -> Result<(u64, Vec<Log>), CallError<E>> {
+    if value != 0 {
+        return Err(ContractError::Precompile(PrecompileError::ValueNotAccepted).into());
+    }
+    let gas_used = precompile.gas(call.input.len());
+    if call.gas_limit < gas_used {
+        return Err(CallError::Contract {
+            reason: ContractError::OutOfGas,
+            gas_used: call.gas_limit,
+        });
+    }
+    precompile.call(&call.input).map_err(|e| CallError::Contract {
+        reason: ContractError::Precompile(e),
+        gas_used,
+    })?;
+    Ok((gas_used, Vec::new()))
+}
+
+/// Moves the transaction amount from the sender to `contract`.
+fn endow<S: ContractState>(
+    state: &Arc<S>,
+    changes: &StateChanges,
+    pending: &mut StateChanges,
+    tx: &Transaction,
+    contract: mbongo_core::Address,
+) -> Result<(), CallError<S::Error>> {
+    let view = Layered {
+        base: state.as_ref(),
+        layers: [pending, changes],
+    };
+    let accounts =
+        move_balance(&view, tx.sender, contract, tx.amount).map_err(CallError::Storage)??;
+    pending.accounts.extend(accounts);
+    Ok(())
+}
+
+/// Takes `fee` from the sender, whose account is already in `changes`.
+/// Contracts cannot debit the sender, so the balance checked against
+/// [`max_fee`] before execution still covers it.
+fn charge<E>(
+    changes: &mut StateChanges,
+    sender: mbongo_core::Address,
+    fee: u128,
+) -> Result<(), ExecutionError<E>> {
+    let account = changes.accounts.get_mut(&sender).ok_or(ExecutionError::InsufficientBalance {
+        balance: 0,
+        required: fee,
+    })?;
+    account.balance =
+        account.balance.checked_sub(fee).ok_or(ExecutionError::InsufficientBalance {
+            balance: account.balance,
+            required: fee,
+        })?;
+    Ok(())
+}
+
+fn is_contract(tx: &Transaction) -> bool {
+    matches!(tx.tx_type, TransactionType::Deploy | TransactionType::Call)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::{self, STORAGE_WRITE_GAS};
+    use crate::state::tests::MemoryState;
+    use crate::wasm::MAX_CACHED_MODULES;
+    use mbongo_core::{Account, Address};
+    use parity_scale_codec::Encode;
+
+    const COUNTER: &str = r#"
+        (module
+          (import "env" "caller" (func $caller (param i32)))
+          (import "env" "value" (func $value (param i32)))
+          (import "env" "storage_get" (func $get (param i32 i32 i32 i32) (result i32)))
+          (import "env" "storage_set" (func $set (param i32 i32 i32 i32)))
+          (import "env" "transfer" (func $transfer (param i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (data (i32.const 0) "count")
+          (func $bump
+            (drop (call $get (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8)))
+            (i64.store (i32.const 16) (i64.add (i64.load (i32.const 16)) (i64.const 1)))
+            (call $set (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 8)))
+          (func (export "increment") (call $bump))
+          (func (export "increment_then_trap") (call $bump) unreachable)
+          (func (export "spin") (loop $l (br $l)))
+          (func (export "refund")
+            (call $caller (i32.const 32))
+            (call $value (i32.const 64))
+            (drop (call $transfer (i32.const 32) (i32.const 64)))))
+    "#;
+
+    /// Starting balance of [`user`], enough for the gas limits used below.
+    const FUNDS: u128 = 1_000_000;
+
+    fn user() -> Address {
+        Address([1u8; 32])
+    }
+
+    /// Total fees paid in `result`.
+    fn fees(result: &BlockExecution) -> u128 {
+        result.outcomes.iter().map(Outcome::fee).sum()
+    }
+
+    fn funded() -> Arc<MemoryState> {
+        let mut state = MemoryState::default();
+        state.0.accounts.insert(
+            user(),
+            Account {
+                address: user(),
+                balance: FUNDS,
+                nonce: 0,
+            },
+        );
+        Arc::new(state)
+    }
+
+    fn tx(
+        tx_type: TransactionType,
+        receiver: Address,
+        amount: u128,
+        nonce: u64,
+        data: Vec<u8>,
+    ) -> Transaction {
+        Transaction {
+            tx_type,
+            sender: user(),
+            receiver,
+            amount,
+            nonce,
+            signature: [0u8; 64],
+            multisig: None,
+            data,
+        }
+    }
+
+    fn deploy(nonce: u64) -> Transaction {
+        let code = wat::parse_str(COUNTER).unwrap();
+        tx(TransactionType::Deploy, Address([0u8; 32]), 0, nonce, code)
+    }
+
+    fn call(
+        contract: Address,
+        function: &str,
+        amount: u128,
+        nonce: u64,
+        gas_limit: u64,
+    ) -> Transaction {
+        let payload = ContractCall {
+            function: function.to_string(),
+            input: Vec::new(),
+            gas_limit,
+        };
+        tx(
+            TransactionType::Call,
+            contract,
+            amount,
+            nonce,
+            payload.encode(),
+        )
+    }
+
+    fn counter(changes: &StateChanges, contract: Address) -> Option<u64> {
+        changes
+            .storage
+            .get(&(contract, b"count".to_vec()))
+            .cloned()
+            .flatten()
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    #[test]
+    fn deployed_contract_keeps_storage_across_calls() {
+        let deploy = deploy(0);
+        let contract = deploy.deployed_address();
+        let block = [
+            deploy,
+            call(contract, "increment", 0, 1, 100_000),
+            call(contract, "increment", 0, 2, 100_000),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert_eq!(
+            result.outcomes[0],
+            Outcome::Success {
+                gas_used: gas::deploy_gas(block[0].data.len()),
+                logs: Vec::new()
+            }
+        );
+        for outcome in &result.outcomes[1..] {
+            let Outcome::Success { gas_used, .. } = outcome else {
+                panic!("call failed: {outcome:?}");
+            };
+            assert!(*gas_used > STORAGE_WRITE_GAS);
+        }
+        assert_eq!(counter(&result.changes, contract), Some(2));
+        let code_hash = result.changes.contracts[&contract];
+        assert_eq!(result.changes.code[&code_hash], block[0].data);
+        assert_eq!(result.changes.accounts[&user()].nonce, 3);
+    }
+
+    #[test]
+    fn running_out_of_gas_fails_the_call_but_advances_the_nonce() {
+        let deploy = deploy(0);
+        let contract = deploy.deployed_address();
+        let block = [deploy, call(contract, "spin", 0, 1, 10_000)];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert_eq!(
+            result.outcomes[1],
+            Outcome::Failed {
+                reason: ContractError::OutOfGas,
+                gas_used: 10_000,
+            }
+        );
+        assert_eq!(result.changes.accounts[&user()].nonce, 2);
+    }
+
+    #[test]
+    fn sender_pays_for_gas_used_and_must_cover_the_gas_limit() {
+        let deploy = deploy(0);
+        let contract = deploy.deployed_address();
+        let block = [
+            deploy.clone(),
+            call(contract, "increment", 0, 1, 100_000),
+            call(contract, "spin", 0, 2, 10_000),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        let gas_used = result.outcomes[1].gas_used();
+        assert!(gas_used > 0);
+        assert_eq!(result.outcomes[0].fee(), max_fee(&deploy));
+        assert_eq!(result.outcomes[1].fee(), gas::fee(gas_used));
+        assert_eq!(result.outcomes[2].fee(), gas::fee(10_000));
+        assert_eq!(
+            result.changes.accounts[&user()].balance,
+            FUNDS - max_fee(&deploy) - gas::fee(gas_used) - gas::fee(10_000)
+        );
+
+        // A call whose gas limit the sender cannot pay for invalidates the block.
+        let costly = call(contract, "increment", 1, 1, MAX_GAS_LIMIT);
+        let failure = Runtime::new().execute_block(&funded(), &[&deploy, &costly]).unwrap_err();
+        assert_eq!(failure.index, 1);
+        assert_eq!(
+            failure.error,
+            ExecutionError::InsufficientBalance {
+                balance: FUNDS - max_fee(&deploy),
+                required: 1 + gas::fee(MAX_GAS_LIMIT),
+            }
+        );
+    }
+
+    #[test]
+    fn deployments_pay_for_their_code_whether_or_not_it_compiles() {
+        let deploy = deploy(0);
+        let mut bad_code = tx(
+            TransactionType::Deploy,
+            Address([0u8; 32]),
+            0,
+            1,
+            vec![0u8; 1000],
+        );
+        bad_code.data[..4].copy_from_slice(b"\0asm");
+        assert_eq!(
+            max_fee(&bad_code),
+            gas::fee(gas::DEPLOY_GAS + 1000 * gas::CODE_BYTE_GAS)
+        );
+        let runtime = Runtime::new();
+
+        let result = runtime.execute_block(&funded(), &[&deploy, &bad_code]).unwrap();
+
+        assert!(result.outcomes[0].is_success());
+        assert!(matches!(
+            result.outcomes[1],
+            Outcome::Failed {
+                reason: ContractError::InvalidCode(_),
+                gas_used,
+            } if gas_used == gas::deploy_gas(1000)
+        ));
+        assert_eq!(
+            result.changes.accounts[&user()].balance,
+            FUNDS - max_fee(&deploy) - max_fee(&bad_code)
+        );
+        let cached = |tx: &Transaction| runtime.vm.cached(&Hash(blake3_hash(&tx.data)));
+        assert!(cached(&deploy).is_some());
+        assert!(cached(&bad_code).is_none());
+
+        // A sender who cannot pay for the code cannot deploy it.
+        let mut poor = funded();
+        Arc::get_mut(&mut poor).unwrap().0.accounts.get_mut(&user()).unwrap().balance =
+            max_fee(&deploy) - 1;
+        let failure = runtime.execute_block(&poor, &[&deploy]).unwrap_err();
+        assert_eq!(
+            failure.error,
+            ExecutionError::InsufficientBalance {
+                balance: max_fee(&deploy) - 1,
+                required: max_fee(&deploy),
+            }
+        );
+    }
+
+    #[test]
+    fn data_is_limited_by_transaction_type() {
+        let receiver = Address([2u8; 32]);
+        assert_eq!(validate_data(&deploy(0)), Ok(()));
+        assert_eq!(
+            validate_data(&call(receiver, "increment", 0, 0, 100_000)),
+            Ok(())
+        );
+        assert_eq!(
+            validate_data(&tx(TransactionType::Transfer, receiver, 1, 0, Vec::new())),
+            Ok(())
+        );
+        assert_eq!(
+            validate_data(&tx(TransactionType::Stake, receiver, 1, 0, vec![1])),
+            Err(DataError::Unexpected(TransactionType::Stake))
+        );
+        let oversized = |tx_type, max| {
+            validate_data(&tx(tx_type, receiver, 0, 0, vec![0u8; max + 1]))
+                == Err(DataError::TooLarge { size: max + 1, max })
+        };
+        assert!(oversized(TransactionType::Deploy, MAX_CODE_SIZE));
+        assert!(oversized(TransactionType::Call, MAX_CALL_SIZE));
+    }
+
+    #[test]
+    fn module_cache_evicts_the_least_recently_used() {
+        let vm = Vm::new();
+        let module = vm.compile(&wat::parse_str(COUNTER).unwrap()).unwrap();
+        let hash = |i: usize| Hash([u8::try_from(i).unwrap(); 32]);
+        for i in 0..MAX_CACHED_MODULES {
+            vm.cache(hash(i), module.clone());
+        }
+        assert!(vm.cached(&hash(0)).is_some());
+
+        vm.cache(hash(MAX_CACHED_MODULES), module);
+
+        assert!(vm.cached(&hash(0)).is_some());
+        assert!(vm.cached(&hash(1)).is_none());
+        assert!(vm.cached(&hash(MAX_CACHED_MODULES)).is_some());
+    }
+
+    #[test]
+    fn trapping_call_reverts_storage_writes_and_value() {
+        let deploy = deploy(0);
+        let contract = deploy.deployed_address();
+        let block = [
+            deploy,
+            call(contract, "increment", 0, 1, 100_000),
+            call(contract, "increment_then_trap", 50, 2, 100_000),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert!(matches!(
+            result.outcomes[2],
+            Outcome::Failed {
+                reason: ContractError::Trap(_),
+                ..
+            }
+        ));
+        assert_eq!(counter(&result.changes, contract), Some(1));
+        let sender = &result.changes.accounts[&user()];
+        assert_eq!((sender.balance, sender.nonce), (FUNDS - fees(&result), 3));
+        assert_eq!(result.changes.accounts[&contract].balance, 0);
+    }
+
+    #[test]
+    fn contract_can_transfer_its_balance() {
+        let deploy = deploy(0);
+        let contract = deploy.deployed_address();
+        let block = [deploy, call(contract, "refund", 30, 1, 100_000)];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert!(result.outcomes[1].is_success());
+        assert_eq!(
+            result.changes.accounts[&user()].balance,
+            FUNDS - fees(&result)
+        );
+        assert_eq!(result.changes.accounts[&contract].balance, 0);
+    }
+
+    #[test]
+    fn invalid_calls_fail_without_invalidating_the_block() {
+        let mut bad_code = deploy(0);
+        bad_code.data = b"not wasm".to_vec();
+        let nowhere = Address([9u8; 32]);
+        let deploy = deploy(1);
+        let contract = deploy.deployed_address();
+        let mut garbage = call(contract, "increment", 0, 4, 100_000);
+        garbage.data = vec![0xFF];
+        let block = [
+            bad_code,
+            deploy,
+            call(nowhere, "increment", 0, 2, 100_000),
+            call(contract, "missing", 0, 3, 100_000),
+            garbage,
+            call(contract, "increment", 0, 5, MAX_GAS_LIMIT + 1),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        let reasons: Vec<_> = result
+            .outcomes
+            .iter()
+            .map(|outcome| match outcome {
+                Outcome::Failed { reason, .. } => Some(reason.clone()),
+                Outcome::Success { .. } => None,
+            })
+            .collect();
+        assert!(matches!(reasons[0], Some(ContractError::InvalidCode(_))));
+        assert_eq!(reasons[1], None);
+        assert_eq!(reasons[2], Some(ContractError::NoContract(nowhere)));
+        assert_eq!(
+            reasons[3],
+            Some(ContractError::MissingExport("missing".into()))
+        );
+        assert!(matches!(reasons[4], Some(ContractError::InvalidPayload(_))));
+        assert_eq!(
+            reasons[5],
+            Some(ContractError::GasLimitTooHigh {
+                limit: MAX_GAS_LIMIT + 1,
+                max: MAX_GAS_LIMIT,
+            })
+        );
+        assert_eq!(result.changes.contracts.len(), 1);
+        assert_eq!(result.changes.accounts[&user()].nonce, 6);
+    }
+
+    fn call_with_input(
+        contract: Address,
+        function: &str,
+        input: Vec<u8>,
+        nonce: u64,
+    ) -> Transaction {
+        let payload = ContractCall {
+            function: function.to_string(),
+            input,
+            gas_limit: 100_000,
+        };
+        tx(TransactionType::Call, contract, 0, nonce, payload.encode())
+    }
+
+    #[test]
+    fn calls_to_precompiles_run_natively() {
+        let mut with_value = call_with_input(precompiles::SHA256, "", b"abc".to_vec(), 2);
+        with_value.amount = 1;
+        let block = [
+            call_with_input(precompiles::SHA256, "", b"abc".to_vec(), 0),
+            call_with_input(precompiles::ED25519_VERIFY, "", vec![0u8; 100], 1),
+            with_value,
+            call(
+                precompiles::ED25519_VERIFY,
+                "",
+                0,
+                3,
+                gas::ED25519_VERIFY_GAS - 1,
+            ),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert_eq!(
+            result.outcomes[0],
+            Outcome::Success {
+                gas_used: gas::SHA256_GAS + gas::SHA256_WORD_GAS,
+                logs: Vec::new(),
+            }
+        );
+        assert!(matches!(
+            result.outcomes[1],
+            Outcome::Failed {
+                reason: ContractError::Precompile(
+                    PrecompileError::InvalidInput(_) | PrecompileError::VerificationFailed
+                ),
+                gas_used,
+            } if gas_used == gas::ED25519_VERIFY_GAS + 4 * gas::ED25519_VERIFY_WORD_GAS
+        ));
+        assert!(matches!(
+            result.outcomes[2],
+            Outcome::Failed {
+                reason: ContractError::Precompile(PrecompileError::ValueNotAccepted),
+                ..
+            }
+        ));
+        assert!(matches!(
+            result.outcomes[3],
+            Outcome::Failed {
+                reason: ContractError::OutOfGas,
+                ..
+            }
+        ));
+        let sender = &result.changes.accounts[&user()];
+        assert_eq!((sender.balance, sender.nonce), (FUNDS - fees(&result), 4));
+    }
+
+    #[test]
+    fn precompile_gas_grows_with_the_input() {
+        let code = wat::parse_str(
+            r#"
+            (module
+              (import "env" "precompile"
+                (func $precompile (param i32 i32 i32 i32 i32) (result i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 31) "\02")
+              (func (export "huge")
+                (drop (call $precompile
+                  (i32.const 0) (i32.const 64) (i32.const 0x7fffffff) (i32.const 0) (i32.const 0)))))
+            "#,
+        )
+        .unwrap();
+        let deploy = tx(TransactionType::Deploy, Address([0u8; 32]), 0, 0, code);
+        let contract = deploy.deployed_address();
+        let input = vec![0u8; precompiles::MAX_INPUT_SIZE];
+        let block = [
+            deploy,
+            call(contract, "huge", 0, 1, 100_000),
+            call_with_input(precompiles::SHA256, "", input, 2),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        // The input length is charged before the input is read, so an
+        // oversized length runs out of gas rather than reading memory.
+        assert!(matches!(
+            result.outcomes[1],
+            Outcome::Failed {
+                reason: ContractError::OutOfGas,
+                ..
+            }
+        ));
+        let words = u64::try_from(precompiles::MAX_INPUT_SIZE / 32).unwrap();
+        assert_eq!(
+            result.outcomes[2].gas_used(),
+            gas::SHA256_GAS + words * gas::SHA256_WORD_GAS
+        );
+    }
+
+    #[test]
+    fn contracts_call_precompiles_through_the_host() {
+        let code = wat::parse_str(
+            r#"
+            (module
+              (import "env" "input_len" (func $input_len (result i32)))
+              (import "env" "input" (func $input (param i32)))
+              (import "env" "precompile"
+                (func $precompile (param i32 i32 i32 i32 i32) (result i32)))
+              (import "env" "storage_set" (func $set (param i32 i32 i32 i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 31) "\01")
+              (func (export "hash")
+                (call $input (i32.const 128))
+                (drop (call $precompile
+                  (i32.const 0) (i32.const 128) (call $input_len) (i32.const 64) (i32.const 32)))
+                (call $set (i32.const 32) (i32.const 1) (i32.const 64) (i32.const 32))))
+            "#,
+        )
+        .unwrap();
+        let deploy = tx(TransactionType::Deploy, Address([0u8; 32]), 0, 0, code);
+        let contract = deploy.deployed_address();
+        let block = [
+            deploy,
+            call_with_input(contract, "hash", b"abc".to_vec(), 1),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        let Outcome::Success { gas_used, .. } = result.outcomes[1] else {
+            panic!("call failed: {:?}", result.outcomes[1]);
+        };
+        assert!(gas_used > gas::BLAKE3_GAS);
+        assert_eq!(
+            result.changes.storage[&(contract, vec![0u8])],
+            Some(mbongo_core::crypto::blake3_hash(b"abc").to_vec())
+        );
+    }
+
+    #[test]
+    fn contract_logs_are_returned_only_on_success() {
+        let code = wat::parse_str(
+            r#"
+            (module
+              (import "env" "log" (func $log (param i32 i32 i32 i32)))
+              (memory (export "memory") 1)
+              (data (i32.const 0) "\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07")
+              (data (i32.const 16) "\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07\07")
+              (data (i32.const 32) "ping")
+              (func (export "emit")
+                (call $log (i32.const 0) (i32.const 1) (i32.const 32) (i32.const 4)))
+              (func (export "emit_then_trap")
+                (call $log (i32.const 0) (i32.const 1) (i32.const 32) (i32.const 4))
+                unreachable)
+              (func (export "too_many_topics")
+                (call $log (i32.const 0) (i32.const 5) (i32.const 32) (i32.const 0))))
+            "#,
+        )
+        .unwrap();
+        let deploy = tx(TransactionType::Deploy, Address([0u8; 32]), 0, 0, code);
+        let contract = deploy.deployed_address();
+        let block = [
+            deploy,
+            call(contract, "emit", 0, 1, 100_000),
+            call(contract, "emit_then_trap", 0, 2, 100_000),
+            call(contract, "too_many_topics", 0, 3, 100_000),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        let Outcome::Success { gas_used, logs } = &result.outcomes[1] else {
+            panic!("call failed: {:?}", result.outcomes[1]);
+        };
+        assert!(*gas_used > gas::LOG_GAS + gas::LOG_TOPIC_GAS + 4 * gas::LOG_BYTE_GAS);
+        assert_eq!(
+            logs,
+            &[Log {
+                address: contract,
+                topics: vec![Hash([7u8; 32])],
+                data: b"ping".to_vec(),
+            }]
+        );
+        for outcome in &result.outcomes[2..] {
+            assert!(matches!(
+                outcome,
+                Outcome::Failed {
+                    reason: ContractError::Trap(_),
+                    ..
+                }
+            ));
+        }
+    }
+
+    #[test]
+    fn wrong_nonce_on_a_contract_transaction_invalidates_the_block() {
+        let block = [deploy(0), deploy(0)];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let failure = Runtime::new().execute_block(&funded(), &txs).unwrap_err();
+
+        assert_eq!(failure.index, 1);
+        assert_eq!(
+            failure.error,
+            ExecutionError::InvalidNonce {
+                expected: 1,
+                actual: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn native_transfers_mix_with_contract_transactions() {
+        let receiver = Address([2u8; 32]);
+        let deploy = deploy(1);
+        let contract = deploy.deployed_address();
+        let block = [
+            tx(TransactionType::Transfer, receiver, 100, 0, Vec::new()),
+            deploy,
+            call(contract, "increment", 0, 2, 100_000),
+            tx(TransactionType::Transfer, receiver, 100, 3, Vec::new()),
+        ];
+        let txs: Vec<&Transaction> = block.iter().collect();
+
+        let result = Runtime::new().execute_block(&funded(), &txs).unwrap();
+
+        assert!(result.outcomes.iter().all(Outcome::is_success));
+        assert_eq!(result.changes.accounts[&receiver].balance, 200);
+        assert_eq!(
+            result.changes.accounts[&user()].balance,
+            FUNDS - 200 - fees(&result)
+        );
+        assert_eq!(counter(&result.changes, contract), Some(1));
+    }
+}
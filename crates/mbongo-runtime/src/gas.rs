@@ -0,0 +1,140 @@
+//! Gas schedule for contract execution.
+//!
+//! One unit of gas is one unit of wasmtime fuel, which the VM consumes at
+//! roughly one unit per WebAssembly instruction. Host functions charge the
+//! fixed costs below on top, so storage and transfers are priced by what
+//! they cost the node rather than by the few instructions that call them.
+//!
+//! A call transaction names its own gas limit, at most [`MAX_GAS_LIMIT`].
+//! Running out of gas fails the call and reverts everything it did.
+//!
+//! A deployment runs no code but costs [`deploy_gas`] of its code size,
+//! which pays for validating and compiling the module.
+//!
+//! The sender pays [`fee`] of the gas used, whether the call succeeds or
+//! fails, and must hold [`fee`] of the gas limit, or of the deployment
+//! gas, on top of the value sent for the transaction to be valid. Fees are
+//! burned.
+
+/// Largest gas limit a single call may request.
+pub const MAX_GAS_LIMIT: u64 = 50_000_000;
+
+/// Balance charged per unit of gas.
+pub const GAS_PRICE: u128 = 1;
+
+/// Deploying a contract, on top of the cost of its code.
+pub const DEPLOY_GAS: u64 = 20_000;
+
+/// Each byte of deployed contract code.
+pub const CODE_BYTE_GAS: u64 = 50;
+
+/// Reading the caller, the call value or the call input length.
+pub const CONTEXT_GAS: u64 = 10;
+
+/// Copying one byte of call input into contract memory.
+pub const INPUT_BYTE_GAS: u64 = 1;
+
+/// Looking up one contract storage slot.
+pub const STORAGE_READ_GAS: u64 = 200;
+
+/// Writing or deleting one contract storage slot.
+pub const STORAGE_WRITE_GAS: u64 = 5_000;
+
+/// Each byte of key and value read from or written to contract storage.
+pub const STORAGE_BYTE_GAS: u64 = 10;
+
+/// Moving balance out of the contract with the `transfer` host function.
+pub const TRANSFER_GAS: u64 = 2_000;
+
+/// Emitting a log with the `log` host function.
+pub const LOG_GAS: u64 = 400;
+
+/// Each topic of an emitted log.
+pub const LOG_TOPIC_GAS: u64 = 200;
+
+/// Each byte of data of an emitted log.
+pub const LOG_BYTE_GAS: u64 = 8;
+
+/// Calling the blake3 precompile, on top of its per-word cost.
+pub const BLAKE3_GAS: u64 = 200;
+
+/// Each 32-byte word of input to the blake3 precompile.
+pub const BLAKE3_WORD_GAS: u64 = 6;
+
+/// Calling the SHA-256 precompile, on top of its per-word cost.
+pub const SHA256_GAS: u64 = 300;
+
+/// Each 32-byte word of input to the SHA-256 precompile.
+pub const SHA256_WORD_GAS: u64 = 12;
+
+/// Calling the ed25519 signature verification precompile, on top of its
+/// per-word cost.
+pub const ED25519_VERIFY_GAS: u64 = 3_000;
+
+/// Each 32-byte word of input to the ed25519 verification precompile,
+/// which hashes the message.
+pub const ED25519_VERIFY_WORD_GAS: u64 = 12;
+
+/// Calling the Merkle proof verification precompile, on top of its
+/// per-word cost.
+pub const MERKLE_VERIFY_GAS: u64 = 2_000;
+
+/// Each 32-byte word of input to the Merkle proof verification precompile,
+/// roughly one hash per proof level.
+pub const MERKLE_VERIFY_WORD_GAS: u64 = 60;
+
+/// Fee charged for `gas` units of gas.
+#[must_use]
+pub fn fee(gas: u64) -> u128 {
+    u128::from(gas) * GAS_PRICE
+}
+
+/// Gas charged for a precompile call costing `base` plus `per_word` for each
+/// started 32-byte word of its `bytes`-byte input.
+#[must_use]
+pub fn precompile_gas(base: u64, per_word: u64, bytes: usize) -> u64 {
+    let words = u64::try_from(bytes.div_ceil(32)).unwrap_or(u64::MAX);
+    base.saturating_add(words.saturating_mul(per_word))
+}
+
+/// Gas charged for a storage access touching `bytes` bytes of key and value.
+#[must_use]
+pub fn storage_gas(base: u64, bytes: usize) -> u64 {
+    let bytes = u64::try_from(bytes).unwrap_or(u64::MAX);
+    base.saturating_add(bytes.saturating_mul(STORAGE_BYTE_GAS))
+}
+
+/// Gas charged for deploying `code_len` bytes of contract code.
+#[must_use]
+pub fn deploy_gas(code_len: usize) -> u64 {
+    let bytes = u64::try_from(code_len).unwrap_or(u64::MAX);
+    DEPLOY_GAS.saturating_add(bytes.saturating_mul(CODE_BYTE_GAS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn storage_gas_scales_with_size() {
+        assert_eq!(storage_gas(STORAGE_READ_GAS, 0), STORAGE_READ_GAS);
+        assert_eq!(
+            storage_gas(STORAGE_WRITE_GAS, 40),
+            STORAGE_WRITE_GAS + 40 * STORAGE_BYTE_GAS
+        );
+        assert_eq!(storage_gas(STORAGE_WRITE_GAS, usize::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn precompile_gas_counts_started_words() {
+        assert_eq!(precompile_gas(SHA256_GAS, SHA256_WORD_GAS, 0), SHA256_GAS);
+        assert_eq!(
+            precompile_gas(SHA256_GAS, SHA256_WORD_GAS, 1),
+            SHA256_GAS + SHA256_WORD_GAS
+        );
+        assert_eq!(
+            precompile_gas(SHA256_GAS, SHA256_WORD_GAS, 65),
+            SHA256_GAS + 3 * SHA256_WORD_GAS
+        );
+    }
+}
@@ -9,17 +9,33 @@
 //! # Examples
 //!
 //! ```
-//! // TODO: Add examples once runtime is implemented
+//! use mbongo_runtime::{ContractCall, Outcome, Runtime};
+//! use parity_scale_codec::Encode;
+//!
+//! let runtime = Runtime::new();
+//! let payload = ContractCall {
+//!     function: "increment".to_string(),
+//!     input: Vec::new(),
+//!     gas_limit: 100_000,
+//! }
+//! .encode();
+//! # let _ = (runtime, payload, Outcome::Success { gas_used: 0, logs: Vec::new() });
 //! ```
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 #![warn(clippy::pedantic)]
 
-// Module structure (to be implemented)
-// pub mod wasm;
-// pub mod precompiles;
-// pub mod gas;
+pub mod executor;
+pub mod gas;
+pub mod precompiles;
+pub mod state;
+pub mod wasm;
+
+pub use executor::{max_fee, validate_data, BlockExecution, DataError, Outcome, Runtime};
+pub use precompiles::{Precompile, PrecompileError};
+pub use state::{ContractState, StateChanges};
+pub use wasm::{ContractCall, ContractError};
 
 #[cfg(test)]
 mod tests {
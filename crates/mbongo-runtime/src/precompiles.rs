@@ -0,0 +1,309 @@
+//! Native precompiled contracts.
+//!
+//! Precompiles live at reserved addresses, `0x00..00` followed by a
+//! one-byte id, and run Rust code instead of WebAssembly. Each charges a
+//! base cost plus a cost per 32-byte word of input, listed in
+//! [`crate::gas`]; see [`Precompile::gas`].
+//!
+//! | Address | Name | Input | Output |
+//! |---------|------|-------|--------|
+//! | `0x…01` | `blake3` | Bytes to hash | 32-byte blake3 hash |
+//! | `0x…02` | `sha256` | Bytes to hash | 32-byte SHA-256 hash |
+//! | `0x…03` | `ed25519_verify` | 32-byte public key ‖ 64-byte signature ‖ message | Empty |
+//! | `0x…04` | `merkle_verify` | SCALE [`MerkleInput`] | Empty |
+//!
+//! The verifiers fail with [`PrecompileError::VerificationFailed`] when the
+//! signature or proof does not check out, `ed25519_verify` by the same strict
+//! rule as transaction signatures, so a call transaction to them
+//! succeeds only if verification does. Contracts reach precompiles through
+//! the `precompile` host function; see [`crate::wasm`].
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use mbongo_core::crypto::{blake3_hash, sha256_hash, verify_proof, HashOutput, MerkleProof};
+use mbongo_core::Address;
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use thiserror::Error;
+
+use crate::gas;
+
+/// Largest input, in bytes, a precompile accepts.
+pub const MAX_INPUT_SIZE: usize = 64 * 1024;
+
+/// Deepest Merkle proof [`MERKLE_VERIFY`] accepts.
+pub const MAX_PROOF_DEPTH: usize = 64;
+
+/// blake3 hashing.
+pub const BLAKE3: Address = reserved(1);
+
+/// SHA-256 hashing.
+pub const SHA256: Address = reserved(2);
+
+/// ed25519 signature verification.
+pub const ED25519_VERIFY: Address = reserved(3);
+
+/// Merkle proof verification against a root, as built by
+/// [`MerkleTree`](mbongo_core::crypto::MerkleTree).
+pub const MERKLE_VERIFY: Address = reserved(4);
+
+/// Returns the reserved address with the given precompile id.
+const fn reserved(id: u8) -> Address {
+    let mut bytes = [0u8; 32];
+    bytes[31] = id;
+    Address(bytes)
+}
+
+/// Why a precompile rejected its input.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PrecompileError {
+    /// The input is larger than [`MAX_INPUT_SIZE`].
+    #[error("precompile input is {size} bytes, more than the maximum of {max}")]
+    InputTooLarge {
+        /// Size of the input.
+        size: usize,
+        /// Largest allowed size.
+        max: usize,
+    },
+    /// The input does not have the shape the precompile expects.
+    #[error("invalid precompile input: {0}")]
+    InvalidInput(&'static str),
+    /// The signature or proof does not verify.
+    #[error("verification failed")]
+    VerificationFailed,
+    /// Precompiles do not hold balances; calls must not send value.
+    #[error("precompiles do not accept value")]
+    ValueNotAccepted,
+}
+
+/// Input of [`MERKLE_VERIFY`].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct MerkleInput {
+    /// Leaf hash being proven.
+    pub leaf: HashOutput,
+    /// Root the proof must lead to.
+    pub root: HashOutput,
+    /// Sibling hashes from the leaf up to the root.
+    pub siblings: Vec<HashOutput>,
+    /// For each level, whether the running hash is the left child.
+    pub indices: Vec<bool>,
+}
+
+/// A native contract at a reserved address.
+pub struct Precompile {
+    /// Reserved address the precompile is called at.
+    pub address: Address,
+    /// Short name, for logs and errors.
+    pub name: &'static str,
+    /// Gas charged per call.
+    pub base_gas: u64,
+    /// Gas charged per started 32-byte word of input.
+    pub word_gas: u64,
+    /// Computes the output for an input of at most [`MAX_INPUT_SIZE`] bytes.
+    pub run: fn(&[u8]) -> Result<Vec<u8>, PrecompileError>,
+}
+
+impl Precompile {
+    /// Returns the gas charged for a call with `input_len` bytes of input.
+    #[must_use]
+    pub fn gas(&self, input_len: usize) -> u64 {
+        gas::precompile_gas(self.base_gas, self.word_gas, input_len)
+    }
+
+    /// Runs the precompile on `input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PrecompileError`] if the input is too large or malformed,
+    /// or does not verify.
+    pub fn call(&self, input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+        if input.len() > MAX_INPUT_SIZE {
+            return Err(PrecompileError::InputTooLarge {
+                size: input.len(),
+                max: MAX_INPUT_SIZE,
+            });
+        }
+        (self.run)(input)
+    }
+}
+
+impl std::fmt::Debug for Precompile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Precompile")
+            .field("address", &self.address)
+            .field("name", &self.name)
+            .field("base_gas", &self.base_gas)
+            .field("word_gas", &self.word_gas)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Registry of precompiles, ordered by address.
+pub const PRECOMPILES: &[Precompile] = &[
+    Precompile {
+        address: BLAKE3,
+        name: "blake3",
+        base_gas: gas::BLAKE3_GAS,
+        word_gas: gas::BLAKE3_WORD_GAS,
+        run: |input| Ok(blake3_hash(input).to_vec()),
+    },
+    Precompile {
+        address: SHA256,
+        name: "sha256",
+        base_gas: gas::SHA256_GAS,
+        word_gas: gas::SHA256_WORD_GAS,
+        run: |input| Ok(sha256_hash(input).to_vec()),
+    },
+    Precompile {
+        address: ED25519_VERIFY,
+        name: "ed25519_verify",
+        base_gas: gas::ED25519_VERIFY_GAS,
+        word_gas: gas::ED25519_VERIFY_WORD_GAS,
+        run: ed25519_verify,
+    },
+    Precompile {
+        address: MERKLE_VERIFY,
+        name: "merkle_verify",
+        base_gas: gas::MERKLE_VERIFY_GAS,
+        word_gas: gas::MERKLE_VERIFY_WORD_GAS,
+        run: merkle_verify,
+    },
+];
+
+/// Returns the precompile at `address`, if there is one.
+#[must_use]
+pub fn get(address: &Address) -> Option<&'static Precompile> {
+    PRECOMPILES.iter().find(|p| p.address == *address)
+}
+
+fn ed25519_verify(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    if input.len() < 96 {
+        return Err(PrecompileError::InvalidInput(
+            "expected a 32-byte key and a 64-byte signature",
+        ));
+    }
+    let (key, rest) = input.split_at(32);
+    let (signature, message) = rest.split_at(64);
+    let key = VerifyingKey::from_bytes(key.try_into().expect("32-byte slice"))
+        .map_err(|_| PrecompileError::InvalidInput("public key is not a curve point"))?;
+    let signature = Signature::from_bytes(signature.try_into().expect("64-byte slice"));
+    key.verify_strict(message, &signature)
+        .map(|()| Vec::new())
+        .map_err(|_| PrecompileError::VerificationFailed)
+}
+
+fn merkle_verify(input: &[u8]) -> Result<Vec<u8>, PrecompileError> {
+    let input = MerkleInput::decode_all(&mut &input[..])
+        .map_err(|_| PrecompileError::InvalidInput("expected a SCALE MerkleInput"))?;
+    if input.siblings.len() != input.indices.len() {
+        return Err(PrecompileError::InvalidInput(
+            "siblings and indices differ in length",
+        ));
+    }
+    if input.siblings.len() > MAX_PROOF_DEPTH {
+        return Err(PrecompileError::InvalidInput("proof is too deep"));
+    }
+    let proof = MerkleProof {
+        leaf_index: 0,
+        siblings: input.siblings,
+        indices: input.indices,
+    };
+    if verify_proof(&input.leaf, &proof, &input.root) {
+        Ok(Vec::new())
+    } else {
+        Err(PrecompileError::VerificationFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use mbongo_core::crypto::MerkleTree;
+
+    #[test]
+    fn registry_addresses_are_reserved_and_ordered() {
+        for (i, precompile) in PRECOMPILES.iter().enumerate() {
+            assert_eq!(precompile.address, reserved(u8::try_from(i + 1).unwrap()));
+            assert_eq!(get(&precompile.address).unwrap().name, precompile.name);
+        }
+        assert!(get(&Address::zero()).is_none());
+    }
+
+    #[test]
+    fn hashes_match_core_crypto() {
+        assert_eq!(
+            get(&BLAKE3).unwrap().call(b"abc").unwrap(),
+            blake3_hash(b"abc")
+        );
+        assert_eq!(
+            get(&SHA256).unwrap().call(b"abc").unwrap(),
+            sha256_hash(b"abc")
+        );
+        let too_large = vec![0u8; MAX_INPUT_SIZE + 1];
+        assert!(matches!(
+            get(&BLAKE3).unwrap().call(&too_large),
+            Err(PrecompileError::InputTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn ed25519_verify_checks_the_signature() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let input = |message: &[u8]| {
+            let signature = key.sign(b"hello");
+            [
+                &key.verifying_key().to_bytes()[..],
+                &signature.to_bytes(),
+                message,
+            ]
+            .concat()
+        };
+        let verify = get(&ED25519_VERIFY).unwrap();
+
+        assert_eq!(verify.call(&input(b"hello")), Ok(Vec::new()));
+        assert_eq!(
+            verify.call(&input(b"other")),
+            Err(PrecompileError::VerificationFailed)
+        );
+        assert!(matches!(
+            verify.call(&[0u8; 95]),
+            Err(PrecompileError::InvalidInput(_))
+        ));
+
+        // The identity is a small-order key: R = identity and s = 0 satisfy
+        // the plain verification equation for every message, but the chain
+        // rejects such signatures and so does the precompile.
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let forged = [&identity[..], &identity, &[0u8; 32], b"anything"].concat();
+        assert_eq!(
+            verify.call(&forged),
+            Err(PrecompileError::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn merkle_verify_checks_the_proof() {
+        let leaves: Vec<HashOutput> = (0u8..5).map(|i| blake3_hash(&[i])).collect();
+        let tree = MerkleTree::new(&leaves);
+        let proof = tree.proof(3).unwrap();
+        let input = |leaf: HashOutput| MerkleInput {
+            leaf,
+            root: tree.root(),
+            siblings: proof.siblings.clone(),
+            indices: proof.indices.clone(),
+        };
+        let verify = get(&MERKLE_VERIFY).unwrap();
+
+        assert_eq!(verify.call(&input(leaves[3]).encode()), Ok(Vec::new()));
+        assert_eq!(
+            verify.call(&input(leaves[2]).encode()),
+            Err(PrecompileError::VerificationFailed)
+        );
+        let mut mismatched = input(leaves[3]);
+        mismatched.indices.pop();
+        assert!(matches!(
+            verify.call(&mismatched.encode()),
+            Err(PrecompileError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,180 @@
+//! Chain state as seen by contracts.
+//!
+//! [`ContractState`] is the committed state a block executes against; the
+//! node implements it over its storage backend. Everything a block changes
+//! is collected in a [`StateChanges`] overlay instead of being written
+//! through, so a failed block leaves storage untouched and a successful one
+//! is persisted in a single atomic batch.
+
+use std::collections::HashMap;
+
+use mbongo_core::{Account, Address, Hash};
+
+/// Read access to committed chain state.
+pub trait ContractState {
+    /// Error returned when the backing store fails.
+    type Error;
+
+    /// Returns the account at `address`, if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the backing store fails.
+    fn account(&self, address: &Address) -> Result<Option<Account>, Self::Error>;
+
+    /// Returns the WASM code stored under `code_hash`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the backing store fails.
+    fn code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Returns the code hash of the contract deployed at `contract`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the backing store fails.
+    fn contract(&self, contract: &Address) -> Result<Option<Hash>, Self::Error>;
+
+    /// Returns the value stored under `key` by `contract`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Self::Error`] if the backing store fails.
+    fn storage(&self, contract: &Address, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error>;
+}
+
+/// State written by executing transactions, not yet committed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateChanges {
+    /// Accounts created or modified.
+    pub accounts: HashMap<Address, Account>,
+    /// Newly stored contract code, keyed by its blake3 hash.
+    pub code: HashMap<Hash, Vec<u8>>,
+    /// Newly deployed contracts and their code hashes.
+    pub contracts: HashMap<Address, Hash>,
+    /// Contract storage writes; `None` deletes the slot.
+    pub storage: HashMap<(Address, Vec<u8>), Option<Vec<u8>>>,
+}
+
+impl StateChanges {
+    /// Applies `other` on top of these changes; its writes win.
+    pub fn merge(&mut self, other: StateChanges) {
+        self.accounts.extend(other.accounts);
+        self.code.extend(other.code);
+        self.contracts.extend(other.contracts);
+        self.storage.extend(other.storage);
+    }
+
+    /// Returns `true` if nothing has been written.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+            && self.code.is_empty()
+            && self.contracts.is_empty()
+            && self.storage.is_empty()
+    }
+}
+
+/// Committed state seen through overlays of uncommitted changes, newest
+/// first.
+pub(crate) struct Layered<'a, S> {
+    pub(crate) base: &'a S,
+    pub(crate) layers: [&'a StateChanges; 2],
+}
+
+impl<S: ContractState> Layered<'_, S> {
+    pub(crate) fn account(&self, address: &Address) -> Result<Option<Account>, S::Error> {
+        match self.layers.iter().find_map(|l| l.accounts.get(address)) {
+            Some(account) => Ok(Some(account.clone())),
+            None => self.base.account(address),
+        }
+    }
+
+    pub(crate) fn code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, S::Error> {
+        match self.layers.iter().find_map(|l| l.code.get(code_hash)) {
+            Some(code) => Ok(Some(code.clone())),
+            None => self.base.code(code_hash),
+        }
+    }
+
+    pub(crate) fn contract(&self, contract: &Address) -> Result<Option<Hash>, S::Error> {
+        match self.layers.iter().find_map(|l| l.contracts.get(contract)) {
+            Some(code_hash) => Ok(Some(*code_hash)),
+            None => self.base.contract(contract),
+        }
+    }
+
+    pub(crate) fn storage(
+        &self,
+        contract: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, S::Error> {
+        let slot = (*contract, key.to_vec());
+        match self.layers.iter().find_map(|l| l.storage.get(&slot)) {
+            Some(value) => Ok(value.clone()),
+            None => self.base.storage(contract, key),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::convert::Infallible;
+
+    /// Committed state held in memory.
+    #[derive(Debug, Default)]
+    pub(crate) struct MemoryState(pub(crate) StateChanges);
+
+    impl ContractState for MemoryState {
+        type Error = Infallible;
+
+        fn account(&self, address: &Address) -> Result<Option<Account>, Infallible> {
+            Ok(self.0.accounts.get(address).cloned())
+        }
+
+        fn code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, Infallible> {
+            Ok(self.0.code.get(code_hash).cloned())
+        }
+
+        fn contract(&self, contract: &Address) -> Result<Option<Hash>, Infallible> {
+            Ok(self.0.contracts.get(contract).copied())
+        }
+
+        fn storage(&self, contract: &Address, key: &[u8]) -> Result<Option<Vec<u8>>, Infallible> {
+            Ok(self.0.storage.get(&(*contract, key.to_vec())).cloned().flatten())
+        }
+    }
+
+    #[test]
+    fn newer_layers_shadow_older_ones_and_deletes_hide_base_values() {
+        let contract = Address([1u8; 32]);
+        let mut base = MemoryState::default();
+        base.0.storage.insert((contract, b"a".to_vec()), Some(b"base".to_vec()));
+        base.0.storage.insert((contract, b"b".to_vec()), Some(b"base".to_vec()));
+
+        let mut older = StateChanges::default();
+        older.storage.insert((contract, b"a".to_vec()), Some(b"older".to_vec()));
+        let mut newer = StateChanges::default();
+        newer.storage.insert((contract, b"a".to_vec()), Some(b"newer".to_vec()));
+        newer.storage.insert((contract, b"b".to_vec()), None);
+
+        let view = Layered {
+            base: &base,
+            layers: [&newer, &older],
+        };
+        assert_eq!(
+            view.storage(&contract, b"a").unwrap(),
+            Some(b"newer".to_vec())
+        );
+        assert_eq!(view.storage(&contract, b"b").unwrap(), None);
+
+        older.merge(newer);
+        assert_eq!(
+            older.storage[&(contract, b"a".to_vec())],
+            Some(b"newer".to_vec())
+        );
+        assert!(!older.is_empty());
+    }
+}
@@ -0,0 +1,635 @@
+//! WebAssembly contract VM built on wasmtime.
+//!
+//! # Contract ABI
+//!
+//! A contract is a binary WebAssembly module that exports its linear memory
+//! as `memory`. A [`ContractCall`] names an exported function taking no
+//! parameters and returning nothing; arguments are read with the `input`
+//! host function and results are left in contract storage.
+//!
+//! Host functions are imported from the `env` module. Pointers and lengths
+//! are `i32` offsets into contract memory; out-of-bounds accesses trap.
+//!
+//! | Import | Signature | Effect |
+//! |--------|-----------|--------|
+//! | `input_len` | `() -> i32` | Length of the call input |
+//! | `input` | `(ptr)` | Copies the call input to `ptr` |
+//! | `caller` | `(ptr)` | Writes the 32-byte calling address to `ptr` |
+//! | `value` | `(ptr)` | Writes the amount sent with the call, a little-endian `u128`, to `ptr` |
+//! | `storage_get` | `(key_ptr, key_len, out_ptr, out_len) -> i32` | Copies up to `out_len` bytes of the value under the key to `out_ptr` and returns its full length, or `-1` if the slot is empty |
+//! | `storage_set` | `(key_ptr, key_len, value_ptr, value_len)` | Stores the value under the key; an empty value clears the slot |
+//! | `precompile` | `(address_ptr, input_ptr, input_len, out_ptr, out_len) -> i32` | Runs the [precompile](crate::precompiles) at the 32-byte address on the input, copies up to `out_len` bytes of its output to `out_ptr` and returns the output's full length, or `-1` if the precompile rejected the input; traps if no precompile lives at the address |
+//! | `log` | `(topics_ptr, topic_count, data_ptr, data_len)` | Emits a [`Log`] from the contract with `topic_count` consecutive 32-byte topics, at most [`MAX_TOPICS`], and at most [`MAX_LOG_DATA`] bytes of data; logs of a failed call are discarded |
+//! | `transfer` | `(to_ptr, amount_ptr) -> i32` | Sends a little-endian `u128` amount from the contract to a 32-byte address; returns `0` on success, `1` if the contract's balance is too low, `2` if the receiver would overflow |
+//!
+//! Execution is metered with wasmtime fuel; see [`crate::gas`] for the
+//! host function costs. NaN canonicalization is enabled and threads and
+//! relaxed SIMD are disabled so that every node computes the same result
+//! and consumes the same gas.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use mbongo_core::logs::MAX_TOPICS;
+use mbongo_core::{Account, AccountError, Address, Hash, Log};
+use parity_scale_codec::{Decode, Encode};
+use thiserror::Error;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    Trap,
+};
+
+use crate::gas;
+use crate::precompiles::{self, PrecompileError};
+use crate::state::{ContractState, Layered, StateChanges};
+
+/// Largest contract module, in bytes, that can be deployed.
+pub const MAX_CODE_SIZE: usize = 256 * 1024;
+
+/// Largest SCALE-encoded [`ContractCall`], in bytes, a call may carry.
+pub const MAX_CALL_SIZE: usize = 128 * 1024;
+
+/// Largest linear memory, in bytes, a contract may grow to.
+pub const MAX_MEMORY_SIZE: usize = 16 * 64 * 1024;
+
+/// Largest data, in bytes, of a log emitted by a contract.
+pub const MAX_LOG_DATA: usize = 1024;
+
+/// Most compiled modules the VM keeps cached.
+pub const MAX_CACHED_MODULES: usize = 64;
+
+/// Name of the memory export every contract must provide.
+const MEMORY_EXPORT: &str = "memory";
+
+/// Module name host functions are imported from.
+const HOST_MODULE: &str = "env";
+
+/// Payload of a [`TransactionType::Call`](mbongo_core::TransactionType::Call)
+/// transaction, SCALE-encoded into its `data`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ContractCall {
+    /// Exported function to run.
+    pub function: String,
+    /// Bytes returned by the `input` host function.
+    pub input: Vec<u8>,
+    /// Most gas the call may consume, at most [`gas::MAX_GAS_LIMIT`].
+    pub gas_limit: u64,
+}
+
+/// Why a contract transaction failed. The transaction is still included:
+/// the sender's nonce advances, the fee for the gas used is charged, and
+/// everything else it did is reverted.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContractError {
+    /// The call payload does not decode as a [`ContractCall`].
+    #[error("malformed call payload: {0}")]
+    InvalidPayload(String),
+    /// The call asked for more than [`gas::MAX_GAS_LIMIT`].
+    #[error("gas limit {limit} exceeds the maximum of {max}")]
+    GasLimitTooHigh {
+        /// Requested gas limit.
+        limit: u64,
+        /// Largest allowed gas limit.
+        max: u64,
+    },
+    /// The deployed module is larger than [`MAX_CODE_SIZE`].
+    #[error("contract code is {size} bytes, more than the maximum of {max}")]
+    CodeTooLarge {
+        /// Size of the module.
+        size: usize,
+        /// Largest allowed size.
+        max: usize,
+    },
+    /// The deployed module is not valid WebAssembly for this VM.
+    #[error("invalid contract code: {0}")]
+    InvalidCode(String),
+    /// The call's receiver is not a contract.
+    #[error("no contract deployed at {0}")]
+    NoContract(Address),
+    /// The contract has no exported `() -> ()` function of that name.
+    #[error("contract does not export function `{0}`")]
+    MissingExport(String),
+    /// The call ran out of gas.
+    #[error("out of gas")]
+    OutOfGas,
+    /// The contract trapped.
+    #[error("contract trapped: {0}")]
+    Trap(String),
+    /// A precompile rejected the call.
+    #[error("precompile failed: {0}")]
+    Precompile(PrecompileError),
+    /// The sender cannot cover the amount sent with the transaction.
+    #[error("insufficient balance")]
+    InsufficientBalance,
+    /// Crediting the amount sent with the transaction would overflow.
+    #[error("balance overflow")]
+    BalanceOverflow,
+}
+
+/// Why a contract call stopped.
+pub(crate) enum CallError<E> {
+    /// The contract failed; the call's changes are discarded.
+    Contract {
+        /// What went wrong.
+        reason: ContractError,
+        /// Gas consumed before the failure.
+        gas_used: u64,
+    },
+    /// Committed state could not be read; the block cannot be executed.
+    Storage(E),
+}
+
+impl<E> From<ContractError> for CallError<E> {
+    fn from(reason: ContractError) -> Self {
+        Self::Contract {
+            reason,
+            gas_used: 0,
+        }
+    }
+}
+
+/// Who is calling which contract with how much.
+pub(crate) struct CallContext {
+    pub(crate) contract: Address,
+    pub(crate) caller: Address,
+    pub(crate) value: u128,
+}
+
+/// Data owned by the wasmtime store for the duration of one call.
+struct HostState<S: ContractState> {
+    base: Arc<S>,
+    /// Changes of earlier transactions in the block.
+    below: StateChanges,
+    /// Changes of this call.
+    pending: StateChanges,
+    context: CallContext,
+    input: Vec<u8>,
+    /// Logs emitted so far, in order.
+    logs: Vec<Log>,
+    limits: StoreLimits,
+    /// Storage failure that aborted the call, reported instead of a trap.
+    fault: Option<S::Error>,
+}
+
+impl<S: ContractState> HostState<S> {
+    fn view(&self) -> Layered<'_, S> {
+        Layered {
+            base: &self.base,
+            layers: [&self.pending, &self.below],
+        }
+    }
+
+    /// Records a storage failure and returns the trap that unwinds the call.
+    fn fail(&mut self, error: S::Error) -> anyhow::Error {
+        self.fault = Some(error);
+        anyhow::anyhow!("storage failure")
+    }
+}
+
+/// Compiles and runs contracts. Compiled modules are cached by code hash.
+pub struct Vm {
+    engine: Engine,
+    modules: Mutex<ModuleCache>,
+}
+
+/// Compiled modules of deployed contracts, at most [`MAX_CACHED_MODULES`],
+/// evicting the least recently used.
+#[derive(Default)]
+struct ModuleCache {
+    modules: HashMap<Hash, (Module, u64)>,
+    /// Incremented on every access; orders entries by last use.
+    clock: u64,
+}
+
+impl ModuleCache {
+    fn get(&mut self, code_hash: &Hash) -> Option<Module> {
+        self.clock += 1;
+        let (module, used) = self.modules.get_mut(code_hash)?;
+        *used = self.clock;
+        Some(module.clone())
+    }
+
+    fn insert(&mut self, code_hash: Hash, module: Module) {
+        self.clock += 1;
+        if self.modules.len() >= MAX_CACHED_MODULES && !self.modules.contains_key(&code_hash) {
+            let oldest =
+                self.modules.iter().min_by_key(|(_, (_, used))| *used).map(|(hash, _)| *hash);
+            if let Some(oldest) = oldest {
+                self.modules.remove(&oldest);
+            }
+        }
+        self.modules.insert(code_hash, (module, self.clock));
+    }
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Vm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vm").finish_non_exhaustive()
+    }
+}
+
+impl Vm {
+    /// Creates a VM with deterministic, fuel-metered execution.
+    ///
+    /// # Panics
+    ///
+    /// Panics if wasmtime rejects the fixed engine configuration, which
+    /// would be a bug.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut config = Config::new();
+        config
+            .consume_fuel(true)
+            .cranelift_nan_canonicalization(true)
+            .wasm_threads(false)
+            .wasm_relaxed_simd(false);
+        Self {
+            engine: Engine::new(&config).expect("valid wasmtime configuration"),
+            modules: Mutex::default(),
+        }
+    }
+
+    /// Compiles `code`, checking it is a deployable contract. The module
+    /// is not cached; see [`Vm::cache`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ContractError::CodeTooLarge`] or
+    /// [`ContractError::InvalidCode`].
+    pub fn compile(&self, code: &[u8]) -> Result<Module, ContractError> {
+        if code.len() > MAX_CODE_SIZE {
+            return Err(ContractError::CodeTooLarge {
+                size: code.len(),
+                max: MAX_CODE_SIZE,
+            });
+        }
+        Module::from_binary(&self.engine, code)
+            .map_err(|e| ContractError::InvalidCode(e.to_string()))
+    }
+
+    /// Caches the compiled module of the contract code `code_hash`,
+    /// evicting the least recently used module when the cache is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the module cache lock is poisoned.
+    pub fn cache(&self, code_hash: Hash, module: Module) {
+        self.modules.lock().expect("module cache poisoned").insert(code_hash, module);
+    }
+
+    /// Returns the compiled module for `code_hash`, if it is cached.
+    pub(crate) fn cached(&self, code_hash: &Hash) -> Option<Module> {
+        self.modules.lock().expect("module cache poisoned").get(code_hash)
+    }
+
+    /// Runs `call` on `module` with at most `call.gas_limit` gas.
+    ///
+    /// Reads see `pending`, then `below`, then `base`; writes go to
+    /// `pending`. Returns the gas used and the logs emitted.
+    pub(crate) fn call<S>(
+        &self,
+        module: &Module,
+        base: Arc<S>,
+        below: &mut StateChanges,
+        pending: &mut StateChanges,
+        context: CallContext,
+        call: ContractCall,
+    ) -> Result<(u64, Vec<Log>), CallError<S::Error>>
+    where
+        S: ContractState + 'static,
+    {
+        let host = HostState {
+            base,
+            below: std::mem::take(below),
+            pending: std::mem::take(pending),
+            context,
+            input: call.input,
+            logs: Vec::new(),
+            limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_SIZE).instances(1).build(),
+            fault: None,
+        };
+        let mut store = Store::new(&self.engine, host);
+        store.limiter(|host| &mut host.limits);
+        let result = store
+            .set_fuel(call.gas_limit)
+            .and_then(|()| run(&self.engine, &mut store, module, &call.function));
+        let gas_used = call.gas_limit - store.get_fuel().unwrap_or(0);
+
+        let host = store.into_data();
+        *below = host.below;
+        *pending = host.pending;
+        if let Some(error) = host.fault {
+            return Err(CallError::Storage(error));
+        }
+        result.map(|()| (gas_used, host.logs)).map_err(|e| CallError::Contract {
+            reason: match e.downcast::<ContractError>() {
+                Ok(reason) => reason,
+                Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+                    ContractError::OutOfGas
+                }
+                Err(e) => ContractError::Trap(e.root_cause().to_string()),
+            },
+            gas_used,
+        })
+    }
+}
+
+/// Instantiates `module` and runs its `function` export.
+fn run<S>(
+    engine: &Engine,
+    store: &mut Store<HostState<S>>,
+    module: &Module,
+    function: &str,
+) -> anyhow::Result<()>
+where
+    S: ContractState + 'static,
+{
+    let instance = linker(engine)?.instantiate(&mut *store, module)?;
+    let func = instance
+        .get_typed_func::<(), ()>(&mut *store, function)
+        .map_err(|_| ContractError::MissingExport(function.to_string()))?;
+    func.call(&mut *store, ())
+}
+
+/// Defines the host functions of the contract ABI.
+fn linker<S>(engine: &Engine) -> anyhow::Result<Linker<HostState<S>>>
+where
+    S: ContractState + 'static,
+{
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(
+        HOST_MODULE,
+        "input_len",
+        |mut caller: Caller<'_, HostState<S>>| -> anyhow::Result<u32> {
+            charge(&mut caller, gas::CONTEXT_GAS)?;
+            Ok(u32::try_from(caller.data().input.len())?)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "input",
+        |mut caller: Caller<'_, HostState<S>>, ptr: u32| -> anyhow::Result<()> {
+            let input = caller.data().input.clone();
+            let len = u64::try_from(input.len())?;
+            charge(&mut caller, len.saturating_mul(gas::INPUT_BYTE_GAS))?;
+            write(&mut caller, ptr, &input)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "caller",
+        |mut caller: Caller<'_, HostState<S>>, ptr: u32| -> anyhow::Result<()> {
+            charge(&mut caller, gas::CONTEXT_GAS)?;
+            let address = caller.data().context.caller;
+            write(&mut caller, ptr, &address.0)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "value",
+        |mut caller: Caller<'_, HostState<S>>, ptr: u32| -> anyhow::Result<()> {
+            charge(&mut caller, gas::CONTEXT_GAS)?;
+            let value = caller.data().context.value;
+            write(&mut caller, ptr, &value.to_le_bytes())
+        },
+    )?;
+    define_storage(&mut linker)?;
+    define_precompile(&mut linker)?;
+    define_log(&mut linker)?;
+    Ok(linker)
+}
+
+/// Defines the host function that emits logs.
+fn define_log<S>(linker: &mut Linker<HostState<S>>) -> anyhow::Result<()>
+where
+    S: ContractState + 'static,
+{
+    linker.func_wrap(
+        HOST_MODULE,
+        "log",
+        |mut caller: Caller<'_, HostState<S>>,
+         topics_ptr: u32,
+         topic_count: u32,
+         data_ptr: u32,
+         data_len: u32|
+         -> anyhow::Result<()> {
+            let topic_count = usize::try_from(topic_count)?;
+            if topic_count > MAX_TOPICS {
+                anyhow::bail!(
+                    "log has {topic_count} topics, more than the maximum of {MAX_TOPICS}"
+                );
+            }
+            if usize::try_from(data_len)? > MAX_LOG_DATA {
+                anyhow::bail!(
+                    "log data is {data_len} bytes, more than the maximum of {MAX_LOG_DATA}"
+                );
+            }
+            charge(
+                &mut caller,
+                gas::LOG_GAS
+                    + gas::LOG_TOPIC_GAS * u64::try_from(topic_count)?
+                    + gas::LOG_BYTE_GAS * u64::from(data_len),
+            )?;
+            let topics = read(&mut caller, topics_ptr, u32::try_from(topic_count * 32)?)?
+                .chunks_exact(32)
+                .map(|topic| Hash(topic.try_into().expect("32 bytes")))
+                .collect();
+            let data = read(&mut caller, data_ptr, data_len)?;
+            let host = caller.data_mut();
+            host.logs.push(Log {
+                address: host.context.contract,
+                topics,
+                data,
+            });
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+/// Defines the host function that dispatches to native precompiles.
+fn define_precompile<S>(linker: &mut Linker<HostState<S>>) -> anyhow::Result<()>
+where
+    S: ContractState + 'static,
+{
+    linker.func_wrap(
+        HOST_MODULE,
+        "precompile",
+        |mut caller: Caller<'_, HostState<S>>,
+         address_ptr: u32,
+         input_ptr: u32,
+         input_len: u32,
+         out_ptr: u32,
+         out_len: u32|
+         -> anyhow::Result<i32> {
+            let address =
+                Address(read(&mut caller, address_ptr, 32)?.try_into().expect("32 bytes"));
+            let Some(precompile) = precompiles::get(&address) else {
+                anyhow::bail!("no precompile at {address}");
+            };
+            charge(&mut caller, precompile.gas(usize::try_from(input_len)?))?;
+            let input = read(&mut caller, input_ptr, input_len)?;
+            let Ok(output) = precompile.call(&input) else {
+                return Ok(-1);
+            };
+            let copied = output.len().min(usize::try_from(out_len)?);
+            write(&mut caller, out_ptr, &output[..copied])?;
+            Ok(i32::try_from(output.len())?)
+        },
+    )?;
+    Ok(())
+}
+
+/// Defines the storage and transfer host functions.
+fn define_storage<S>(linker: &mut Linker<HostState<S>>) -> anyhow::Result<()>
+where
+    S: ContractState + 'static,
+{
+    linker.func_wrap(
+        HOST_MODULE,
+        "storage_get",
+        |mut caller: Caller<'_, HostState<S>>,
+         key_ptr: u32,
+         key_len: u32,
+         out_ptr: u32,
+         out_len: u32|
+         -> anyhow::Result<i32> {
+            let key = read(&mut caller, key_ptr, key_len)?;
+            charge(
+                &mut caller,
+                gas::storage_gas(gas::STORAGE_READ_GAS, key.len()),
+            )?;
+            let host = caller.data_mut();
+            let value = match host.view().storage(&host.context.contract, &key) {
+                Ok(value) => value,
+                Err(e) => return Err(host.fail(e)),
+            };
+            let Some(value) = value else {
+                return Ok(-1);
+            };
+            charge(&mut caller, gas::storage_gas(0, value.len()))?;
+            let copied = value.len().min(usize::try_from(out_len)?);
+            write(&mut caller, out_ptr, &value[..copied])?;
+            Ok(i32::try_from(value.len())?)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "storage_set",
+        |mut caller: Caller<'_, HostState<S>>,
+         key_ptr: u32,
+         key_len: u32,
+         value_ptr: u32,
+         value_len: u32|
+         -> anyhow::Result<()> {
+            let key = read(&mut caller, key_ptr, key_len)?;
+            let value = read(&mut caller, value_ptr, value_len)?;
+            charge(
+                &mut caller,
+                gas::storage_gas(gas::STORAGE_WRITE_GAS, key.len() + value.len()),
+            )?;
+            let host = caller.data_mut();
+            let slot = (host.context.contract, key);
+            host.pending.storage.insert(slot, (!value.is_empty()).then_some(value));
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "transfer",
+        |mut caller: Caller<'_, HostState<S>>,
+         to_ptr: u32,
+         amount_ptr: u32|
+         -> anyhow::Result<i32> {
+            charge(&mut caller, gas::TRANSFER_GAS)?;
+            let to = Address(read(&mut caller, to_ptr, 32)?.try_into().expect("32 bytes"));
+            let amount = u128::from_le_bytes(
+                read(&mut caller, amount_ptr, 16)?.try_into().expect("16 bytes"),
+            );
+            let host = caller.data_mut();
+            let from = host.context.contract;
+            match move_balance(&host.view(), from, to, amount) {
+                Ok(Ok(accounts)) => {
+                    host.pending.accounts.extend(accounts);
+                    Ok(0)
+                }
+                Ok(Err(ContractError::BalanceOverflow)) => Ok(2),
+                Ok(Err(_)) => Ok(1),
+                Err(e) => Err(host.fail(e)),
+            }
+        },
+    )?;
+    Ok(())
+}
+
+/// Accounts updated by a balance move, or why it is not possible.
+pub(crate) type BalanceMove = Result<Vec<(Address, Account)>, ContractError>;
+
+/// Moves `amount` from `from` to `to` as seen through `view` and returns the
+/// updated accounts, or why the move is not possible.
+pub(crate) fn move_balance<S: ContractState>(
+    view: &Layered<'_, S>,
+    from: Address,
+    to: Address,
+    amount: u128,
+) -> Result<BalanceMove, S::Error> {
+    let mut sender = view.account(&from)?.unwrap_or_else(|| Account::new(from));
+    if from == to {
+        if sender.balance < amount {
+            return Ok(Err(ContractError::InsufficientBalance));
+        }
+        return Ok(Ok(Vec::new()));
+    }
+    let mut receiver = view.account(&to)?.unwrap_or_else(|| Account::new(to));
+    Ok(
+        match Account::transfer(&mut sender, &mut receiver, amount) {
+            Ok(()) => Ok(vec![(from, sender), (to, receiver)]),
+            Err(AccountError::BalanceOverflow) => Err(ContractError::BalanceOverflow),
+            Err(AccountError::InsufficientBalance | AccountError::InvalidNonce) => {
+                Err(ContractError::InsufficientBalance)
+            }
+        },
+    )
+}
+
+/// Deducts `gas` from the call's remaining fuel, trapping when it runs out.
+fn charge<T>(caller: &mut Caller<'_, T>, gas: u64) -> anyhow::Result<()> {
+    let fuel = caller.get_fuel()?;
+    if fuel < gas {
+        caller.set_fuel(0)?;
+        return Err(Trap::OutOfFuel.into());
+    }
+    caller.set_fuel(fuel - gas)
+}
+
+fn memory<T>(caller: &mut Caller<'_, T>) -> anyhow::Result<Memory> {
+    match caller.get_export(MEMORY_EXPORT) {
+        Some(Extern::Memory(memory)) => Ok(memory),
+        _ => anyhow::bail!("contract does not export `{MEMORY_EXPORT}`"),
+    }
+}
+
+/// Copies `len` bytes at `ptr` out of contract memory.
+fn read<T>(caller: &mut Caller<'_, T>, ptr: u32, len: u32) -> anyhow::Result<Vec<u8>> {
+    let memory = memory(caller)?;
+    let (ptr, len) = (usize::try_from(ptr)?, usize::try_from(len)?);
+    if ptr.saturating_add(len) > memory.data_size(&*caller) {
+        anyhow::bail!("out of bounds memory access");
+    }
+    let mut buf = vec![0u8; len];
+    memory.read(&*caller, ptr, &mut buf)?;
+    Ok(buf)
+}
+
+/// Copies `bytes` into contract memory at `ptr`.
+fn write<T>(caller: &mut Caller<'_, T>, ptr: u32, bytes: &[u8]) -> anyhow::Result<()> {
+    let memory = memory(caller)?;
+    memory.write(caller, usize::try_from(ptr)?, bytes)?;
+    Ok(())
+}
@@ -8,17 +8,22 @@
 
 pub mod memory;
 pub mod rocksdb;
+pub mod schema;
 pub mod storage;
 
 pub use memory::InMemoryStorage;
 pub use rocksdb::RocksDbStorage;
-pub use storage::{BatchOp, Storage, StorageError};
+pub use schema::{MigrationReport, SCHEMA_VERSION};
+pub use storage::{
+    AddressTxEntry, BatchOp, BoxedSource, DbErrorKind, KeyKind, Storage, StorageError,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use mbongo_core::{
-        Account, Address, Block, BlockBody, BlockHeader, Hash, Transaction, TransactionType,
+        Account, Address, Block, BlockBody, BlockHeader, Bloom, Hash, Receipt, Transaction,
+        TransactionType,
     };
 
     fn sample_account() -> (Address, Account) {
@@ -38,6 +43,8 @@ mod tests {
             amount: 100,
             nonce: 0,
             signature: [0u8; 64],
+            multisig: None,
+            data: Vec::new(),
         };
         (hash, tx)
     }
@@ -60,6 +67,8 @@ mod tests {
                     amount: 50,
                     nonce: 0,
                     signature: [0u8; 64],
+                    multisig: None,
+                    data: Vec::new(),
                 }],
             },
         };
@@ -162,11 +171,17 @@ mod tests {
         let (tx_hash, tx) = sample_transaction();
         let (block_hash, block) = sample_block();
 
+        let receipt = Receipt::success(tx_hash, &tx, block_hash, 1, 0);
+        let mut bloom = Bloom::default();
+        bloom.accrue(&[7u8; 32]);
+
         // All state should be empty before batch.
         assert!(store.get_account(&addr1).unwrap().is_none());
         assert!(store.get_transaction(&tx_hash).unwrap().is_none());
+        assert!(store.get_receipt(&tx_hash).unwrap().is_none());
         assert!(store.get_block(&block_hash).unwrap().is_none());
         assert!(store.get_block_by_height(1).unwrap().is_none());
+        assert!(store.get_block_bloom(1).unwrap().is_none());
         assert!(store.get_tx_hash_by_seq(1).unwrap().is_none());
         assert_eq!(store.get_last_included_tx_seq().unwrap(), 0);
 
@@ -175,8 +190,10 @@ mod tests {
             .write_batch(vec![
                 BatchOp::PutAccount(addr1, account1.clone()),
                 BatchOp::PutTransaction(tx_hash, tx.clone()),
+                BatchOp::PutReceipt(tx_hash, receipt.clone()),
                 BatchOp::PutBlock(block_hash, block.clone()),
                 BatchOp::PutBlockHeightIndex(1, block_hash),
+                BatchOp::PutBlockBloom(1, bloom),
                 BatchOp::PutTxSeqIndex(1, tx_hash),
                 BatchOp::SetTxSeq(1),
                 BatchOp::SetLastIncludedTxSeq(1),
@@ -190,6 +207,8 @@ mod tests {
         let loaded_tx = store.get_transaction(&tx_hash).unwrap().expect("tx");
         assert_eq!(loaded_tx, tx);
 
+        assert_eq!(store.get_receipt(&tx_hash).unwrap(), Some(receipt));
+
         let loaded_block = store.get_block(&block_hash).unwrap().expect("block");
         assert_eq!(loaded_block, block);
 
@@ -197,6 +216,7 @@ mod tests {
         assert_eq!(loaded_by_height, block);
 
         assert_eq!(store.get_latest_height().unwrap(), 1);
+        assert_eq!(store.get_block_bloom(1).unwrap(), Some(bloom));
 
         let loaded_seq = store.get_tx_hash_by_seq(1).unwrap().expect("tx seq");
         assert_eq!(loaded_seq, tx_hash);
@@ -204,6 +224,88 @@ mod tests {
         assert_eq!(store.get_last_included_tx_seq().unwrap(), 1);
     }
 
+    /// Run the address-index suite against any [`Storage`] implementation.
+    fn address_index_suite(store: &dyn Storage) {
+        let addr = Address([1u8; 32]);
+        let other = Address([2u8; 32]);
+        let entry = |height: u64, index: u32| AddressTxEntry {
+            height,
+            index,
+            tx_hash: Hash([(height * 10) as u8 + index as u8; 32]),
+        };
+        assert!(store.get_address_transactions(&addr, None, 10).unwrap().is_empty());
+
+        let mut ops = Vec::new();
+        for (height, index) in [(1, 0), (3, 0), (3, 2), (4, 1), (7, 0)] {
+            ops.push(BatchOp::PutAddressTxIndex(addr, entry(height, index)));
+        }
+        ops.push(BatchOp::PutAddressTxIndex(other, entry(5, 0)));
+        // Neighbouring addresses must not leak into each other's pages.
+        ops.push(BatchOp::PutAddressTxIndex(Address([0u8; 32]), entry(9, 0)));
+        store.write_batch(ops).unwrap();
+
+        let all = store.get_address_transactions(&addr, None, 10).unwrap();
+        assert_eq!(
+            all,
+            vec![
+                entry(7, 0),
+                entry(4, 1),
+                entry(3, 2),
+                entry(3, 0),
+                entry(1, 0)
+            ]
+        );
+
+        // Pages end on a block boundary, so the second page takes all of block 3.
+        let page = store.get_address_transactions(&addr, None, 2).unwrap();
+        assert_eq!(page, vec![entry(7, 0), entry(4, 1)]);
+        let page = store.get_address_transactions(&addr, Some(4), 1).unwrap();
+        assert_eq!(page, vec![entry(3, 2), entry(3, 0)]);
+        let page = store.get_address_transactions(&addr, Some(3), 5).unwrap();
+        assert_eq!(page, vec![entry(1, 0)]);
+        assert!(store.get_address_transactions(&addr, Some(1), 5).unwrap().is_empty());
+        assert!(store.get_address_transactions(&addr, Some(0), 5).unwrap().is_empty());
+
+        assert_eq!(
+            store.get_address_transactions(&other, None, 10).unwrap(),
+            vec![entry(5, 0)]
+        );
+    }
+
+    /// Run the contract state suite against any [`Storage`] implementation.
+    fn contract_suite(store: &dyn Storage) {
+        let contract = Address([7u8; 32]);
+        let code_hash = Hash([8u8; 32]);
+        assert!(store.get_code(&code_hash).unwrap().is_none());
+        assert!(store.get_contract(&contract).unwrap().is_none());
+        assert!(store.get_contract_storage(&contract, b"k").unwrap().is_none());
+
+        store
+            .write_batch(vec![
+                BatchOp::PutCode(code_hash, b"\0asm".to_vec()),
+                BatchOp::PutContract(contract, code_hash),
+                BatchOp::PutContractStorage(contract, b"k".to_vec(), b"v".to_vec()),
+                BatchOp::PutContractStorage(contract, b"gone".to_vec(), b"v".to_vec()),
+            ])
+            .unwrap();
+        store
+            .write_batch(vec![BatchOp::DeleteContractStorage(
+                contract,
+                b"gone".to_vec(),
+            )])
+            .unwrap();
+
+        assert_eq!(store.get_code(&code_hash).unwrap(), Some(b"\0asm".to_vec()));
+        assert_eq!(store.get_contract(&contract).unwrap(), Some(code_hash));
+        assert_eq!(
+            store.get_contract_storage(&contract, b"k").unwrap(),
+            Some(b"v".to_vec())
+        );
+        assert!(store.get_contract_storage(&contract, b"gone").unwrap().is_none());
+        // Slots are scoped to their contract.
+        assert!(store.get_contract_storage(&Address([6u8; 32]), b"k").unwrap().is_none());
+    }
+
     // ── InMemoryStorage tests ────────────────────────────────────────
 
     #[test]
@@ -257,6 +359,18 @@ mod tests {
         write_batch_suite(&store);
     }
 
+    #[test]
+    fn memory_address_index() {
+        let store = InMemoryStorage::new();
+        address_index_suite(&store);
+    }
+
+    #[test]
+    fn memory_contract_state() {
+        let store = InMemoryStorage::new();
+        contract_suite(&store);
+    }
+
     // ── RocksDbStorage tests ─────────────────────────────────────────
 
     #[test]
@@ -316,4 +430,377 @@ mod tests {
         let store = RocksDbStorage::open(dir.path()).unwrap();
         write_batch_suite(&store);
     }
+
+    #[test]
+    fn rocksdb_address_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RocksDbStorage::open(dir.path()).unwrap();
+        address_index_suite(&store);
+    }
+
+    #[test]
+    fn rocksdb_contract_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RocksDbStorage::open(dir.path()).unwrap();
+        contract_suite(&store);
+    }
+
+    #[test]
+    fn rocksdb_corrupt_account_reports_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let (addr, account) = sample_account();
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store.put_account(&addr, &account).unwrap();
+        }
+        // Overwrite the account with bytes that are not a valid SCALE `Account`.
+        {
+            let db = open_raw(dir.path());
+            let cf = db.cf_handle("accounts").unwrap();
+            db.put_cf(cf, addr.0, [0xFFu8; 3]).unwrap();
+        }
+
+        let store = RocksDbStorage::open(dir.path()).unwrap();
+        let err = store.get_account(&addr).unwrap_err();
+        assert!(matches!(err, StorageError::Decode { .. }), "got: {err}");
+        assert_eq!(err.column_family(), Some("accounts"));
+        assert_eq!(err.key(), Some(KeyKind::Account(addr)));
+        assert!(err.to_string().contains(&addr.to_string()), "got: {err}");
+    }
+
+    #[test]
+    fn rocksdb_open_failure_reports_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("not-a-dir");
+        std::fs::write(&file, b"x").unwrap();
+
+        let Err(err) = RocksDbStorage::open(&file) else {
+            panic!("opening a plain file as a database must fail");
+        };
+        assert!(matches!(err, StorageError::Open { .. }), "got: {err}");
+        assert!(err.db_kind().is_some());
+        assert!(err.to_string().contains("not-a-dir"), "got: {err}");
+    }
+
+    #[test]
+    fn rocksdb_open_existing_refuses_missing_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("absent");
+
+        let Err(err) = RocksDbStorage::open_existing_and_migrate(&path) else {
+            panic!("migrating a missing database must fail");
+        };
+        assert!(matches!(err, StorageError::Open { .. }), "got: {err}");
+        assert!(!path.join("CURRENT").exists());
+    }
+
+    /// Opens the raw RocksDB database at `path` with all existing column families.
+    fn open_raw(path: &std::path::Path) -> ::rocksdb::DB {
+        let cfs = ::rocksdb::DB::list_cf(&::rocksdb::Options::default(), path).unwrap();
+        ::rocksdb::DB::open_cf(&::rocksdb::Options::default(), path, cfs).unwrap()
+    }
+
+    #[test]
+    fn rocksdb_fresh_database_is_stamped_with_current_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert!(report.created);
+        assert!(!report.migrated());
+        assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+        drop(store);
+
+        let (_, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert!(!report.created);
+        assert_eq!(report.from, SCHEMA_VERSION);
+        assert!(!report.migrated());
+    }
+
+    #[test]
+    fn rocksdb_unversioned_database_is_migrated() {
+        let dir = tempfile::tempdir().unwrap();
+        let (addr, account) = sample_account();
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store.put_account(&addr, &account).unwrap();
+        }
+        // Simulate a data directory written before schema versioning.
+        {
+            let db = open_raw(dir.path());
+            db.delete_cf(db.cf_handle("meta").unwrap(), schema::META_SCHEMA_VERSION)
+                .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 0);
+        assert_eq!(report.to, SCHEMA_VERSION);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len());
+        assert_eq!(store.schema_version().unwrap(), SCHEMA_VERSION);
+        assert_eq!(store.get_account(&addr).unwrap(), Some(account));
+    }
+
+    #[test]
+    fn rocksdb_v1_database_gets_receipts_backfilled() {
+        let dir = tempfile::tempdir().unwrap();
+        let (block_hash, block) = sample_block();
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store.put_block(&block_hash, &block).unwrap();
+            store.put_block_height_index(1, block_hash).unwrap();
+        }
+        // Roll the directory back to schema v1, which had no receipts.
+        {
+            let mut db = open_raw(dir.path());
+            db.drop_cf("receipts").unwrap();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                1u32.to_be_bytes(),
+            )
+            .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 1);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len() - 1);
+
+        let tx = &block.body.transactions[0];
+        let tx_hash = Hash(mbongo_core::crypto::blake3_hash(
+            &parity_scale_codec::Encode::encode(tx),
+        ));
+        let receipt = store.get_receipt(&tx_hash).unwrap().expect("backfilled receipt");
+        assert_eq!(receipt, Receipt::success(tx_hash, tx, block_hash, 1, 0));
+    }
+
+    #[test]
+    fn rocksdb_v2_database_gets_address_index_backfilled() {
+        let dir = tempfile::tempdir().unwrap();
+        let (block_hash, block) = sample_block();
+        let tx = &block.body.transactions[0];
+        let tx_hash = Hash(mbongo_core::crypto::blake3_hash(
+            &parity_scale_codec::Encode::encode(tx),
+        ));
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store
+                .write_batch(vec![
+                    BatchOp::PutBlock(block_hash, block.clone()),
+                    BatchOp::PutBlockHeightIndex(1, block_hash),
+                    BatchOp::PutReceipt(tx_hash, Receipt::success(tx_hash, tx, block_hash, 1, 0)),
+                ])
+                .unwrap();
+        }
+        // Roll the directory back to schema v2, which had no address index.
+        {
+            let mut db = open_raw(dir.path());
+            db.drop_cf("address_tx_index").unwrap();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                2u32.to_be_bytes(),
+            )
+            .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 2);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len() - 2);
+
+        let expected = vec![AddressTxEntry {
+            height: 1,
+            index: 0,
+            tx_hash,
+        }];
+        for address in [tx.sender, tx.receiver] {
+            assert_eq!(
+                store.get_address_transactions(&address, None, 10).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rocksdb_v4_receipts_get_log_blooms() {
+        let dir = tempfile::tempdir().unwrap();
+        let (block_hash, block) = sample_block();
+        let tx = &block.body.transactions[0];
+        let tx_hash = Hash(mbongo_core::crypto::blake3_hash(
+            &parity_scale_codec::Encode::encode(tx),
+        ));
+        let receipt = Receipt::success(tx_hash, tx, block_hash, 1, 0);
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store
+                .write_batch(vec![
+                    BatchOp::PutBlock(block_hash, block.clone()),
+                    BatchOp::PutBlockHeightIndex(1, block_hash),
+                ])
+                .unwrap();
+        }
+        // Roll the directory back to schema v4, whose receipts had no bloom.
+        {
+            let db = open_raw(dir.path());
+            let old = (
+                receipt.tx_hash,
+                receipt.block_hash,
+                receipt.block_height,
+                receipt.index,
+                receipt.status,
+                receipt.fee,
+                receipt.events.clone(),
+            );
+            db.put_cf(
+                db.cf_handle("receipts").unwrap(),
+                tx_hash.0,
+                parity_scale_codec::Encode::encode(&old),
+            )
+            .unwrap();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                4u32.to_be_bytes(),
+            )
+            .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 4);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len() - 4);
+
+        let migrated = store.get_receipt(&tx_hash).unwrap().expect("migrated receipt");
+        assert!(!migrated.bloom.is_empty());
+        assert_eq!(migrated, receipt);
+    }
+
+    #[test]
+    fn rocksdb_v5_receipts_get_gas_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let (block_hash, block) = sample_block();
+        let tx = &block.body.transactions[0];
+        let tx_hash = Hash(mbongo_core::crypto::blake3_hash(
+            &parity_scale_codec::Encode::encode(tx),
+        ));
+        let receipt = Receipt::success(tx_hash, tx, block_hash, 1, 0);
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store
+                .write_batch(vec![
+                    BatchOp::PutBlock(block_hash, block.clone()),
+                    BatchOp::PutBlockHeightIndex(1, block_hash),
+                ])
+                .unwrap();
+        }
+        // Roll the directory back to schema v5, whose receipts had no gas
+        // used or failure reason.
+        {
+            let db = open_raw(dir.path());
+            let old = (
+                receipt.tx_hash,
+                receipt.block_hash,
+                receipt.block_height,
+                receipt.index,
+                receipt.status,
+                receipt.fee,
+                receipt.events.clone(),
+                receipt.bloom,
+            );
+            db.put_cf(
+                db.cf_handle("receipts").unwrap(),
+                tx_hash.0,
+                parity_scale_codec::Encode::encode(&old),
+            )
+            .unwrap();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                5u32.to_be_bytes(),
+            )
+            .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 5);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len() - 5);
+        assert_eq!(store.get_receipt(&tx_hash).unwrap(), Some(receipt));
+    }
+
+    #[test]
+    fn rocksdb_v6_database_gets_block_blooms_backfilled() {
+        let dir = tempfile::tempdir().unwrap();
+        let (block_hash, block) = sample_block();
+        let tx = &block.body.transactions[0];
+        let tx_hash = Hash(mbongo_core::crypto::blake3_hash(
+            &parity_scale_codec::Encode::encode(tx),
+        ));
+        let receipt = Receipt::success(tx_hash, tx, block_hash, 1, 0);
+        {
+            let store = RocksDbStorage::open(dir.path()).unwrap();
+            store
+                .write_batch(vec![
+                    BatchOp::PutBlock(block_hash, block.clone()),
+                    BatchOp::PutBlockHeightIndex(1, block_hash),
+                    BatchOp::PutReceipt(tx_hash, receipt.clone()),
+                ])
+                .unwrap();
+        }
+        // Roll the directory back to schema v6, which had no block blooms.
+        {
+            let mut db = open_raw(dir.path());
+            db.drop_cf("block_blooms").unwrap();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                6u32.to_be_bytes(),
+            )
+            .unwrap();
+        }
+
+        let (store, report) = RocksDbStorage::open_and_migrate(dir.path()).unwrap();
+        assert_eq!(report.from, 6);
+        assert_eq!(report.applied.len(), schema::MIGRATIONS.len() - 6);
+        assert_eq!(store.get_block_bloom(1).unwrap(), Some(receipt.bloom));
+        assert_eq!(store.get_block_bloom(0).unwrap(), None);
+    }
+
+    #[test]
+    fn rocksdb_refuses_newer_schema_version() {
+        let dir = tempfile::tempdir().unwrap();
+        drop(RocksDbStorage::open(dir.path()).unwrap());
+        {
+            let db = open_raw(dir.path());
+            let newer = (SCHEMA_VERSION + 1).to_be_bytes();
+            db.put_cf(
+                db.cf_handle("meta").unwrap(),
+                schema::META_SCHEMA_VERSION,
+                newer,
+            )
+            .unwrap();
+        }
+
+        let Err(err) = RocksDbStorage::open(dir.path()) else {
+            panic!("a database from a newer build must be refused");
+        };
+        assert!(
+            matches!(err, StorageError::UnsupportedSchemaVersion { found, supported }
+                if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION),
+            "got: {err}"
+        );
+    }
+
+    #[test]
+    fn rocksdb_current_database_missing_column_family_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        drop(RocksDbStorage::open(dir.path()).unwrap());
+        {
+            let mut db = open_raw(dir.path());
+            db.drop_cf("tx_seq_index").unwrap();
+        }
+
+        let Err(err) = RocksDbStorage::open(dir.path()) else {
+            panic!("a missing column family must not be recreated silently");
+        };
+        assert!(
+            matches!(err, StorageError::MissingColumnFamily("tx_seq_index")),
+            "got: {err}"
+        );
+    }
 }
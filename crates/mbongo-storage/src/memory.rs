@@ -2,14 +2,17 @@
 //!
 //! Suitable for testing and short-lived node instances.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::RwLock;
 
-use parity_scale_codec::{Decode, Encode};
+use parity_scale_codec::Encode;
 
-use mbongo_core::{Account, Address, Block, Hash, Transaction};
+use mbongo_core::{Account, Address, Block, Bloom, Hash, Receipt, Transaction};
 
-use crate::storage::{BatchOp, Storage, StorageError};
+use crate::storage::{
+    address_tx_key, address_tx_page, address_tx_seek_key, contract_storage_key, decode, fixed,
+    AddressTxEntry, BatchOp, KeyKind, Storage, StorageError,
+};
 
 /// In-memory storage that keeps all data in a `HashMap<Vec<u8>, Vec<u8>>`.
 ///
@@ -18,10 +21,22 @@ pub struct InMemoryStorage {
     accounts: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     blocks: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     transactions: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// Maps tx hash → SCALE-encoded receipt.
+    receipts: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     /// Maps height (big-endian u64 bytes) → block hash (32 bytes).
     height_index: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// Maps height (big-endian u64 bytes) → SCALE-encoded block bloom.
+    block_blooms: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     /// Maps tx sequence number (big-endian u64 bytes) → tx hash (32 bytes).
     tx_seq_index: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// Maps (address, height, index) → tx hash; ordered for range scans.
+    address_tx_index: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+    /// Maps code hash → contract code.
+    code: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// Maps contract address → code hash (32 bytes).
+    contracts: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    /// Maps (contract address, slot key) → slot value.
+    contract_storage: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
     /// Stores metadata values under fixed keys.
     meta: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
 }
@@ -34,8 +49,14 @@ impl InMemoryStorage {
             accounts: RwLock::new(HashMap::new()),
             blocks: RwLock::new(HashMap::new()),
             transactions: RwLock::new(HashMap::new()),
+            receipts: RwLock::new(HashMap::new()),
             height_index: RwLock::new(HashMap::new()),
+            block_blooms: RwLock::new(HashMap::new()),
             tx_seq_index: RwLock::new(HashMap::new()),
+            address_tx_index: RwLock::new(BTreeMap::new()),
+            code: RwLock::new(HashMap::new()),
+            contracts: RwLock::new(HashMap::new()),
+            contract_storage: RwLock::new(HashMap::new()),
             meta: RwLock::new(HashMap::new()),
         }
     }
@@ -47,167 +68,246 @@ impl Default for InMemoryStorage {
     }
 }
 
+/// Reads a big-endian `u64` stored under a metadata key, defaulting to 0.
+fn meta_u64(meta: &HashMap<Vec<u8>, Vec<u8>>, key: &'static str) -> Result<u64, StorageError> {
+    match meta.get(key.as_bytes()) {
+        Some(b) => Ok(u64::from_be_bytes(fixed("meta", KeyKind::Meta(key), b)?)),
+        None => Ok(0),
+    }
+}
+
 impl Storage for InMemoryStorage {
     fn get_account(&self, address: &Address) -> Result<Option<Account>, StorageError> {
-        let map = self.accounts.read().map_err(|_| StorageError::Database)?;
+        let map = self.accounts.read().map_err(|_| StorageError::LockPoisoned("accounts"))?;
         match map.get(&address.0.to_vec()) {
-            Some(bytes) => {
-                let account =
-                    Account::decode(&mut &bytes[..]).map_err(|_| StorageError::Serialization)?;
-                Ok(Some(account))
-            }
+            Some(bytes) => Ok(Some(decode("accounts", KeyKind::Account(*address), bytes)?)),
             None => Ok(None),
         }
     }
 
     fn put_account(&self, address: &Address, account: &Account) -> Result<(), StorageError> {
-        let mut map = self.accounts.write().map_err(|_| StorageError::Database)?;
+        let mut map = self.accounts.write().map_err(|_| StorageError::LockPoisoned("accounts"))?;
         map.insert(address.0.to_vec(), account.encode());
         Ok(())
     }
 
     fn get_block(&self, hash: &Hash) -> Result<Option<Block>, StorageError> {
-        let map = self.blocks.read().map_err(|_| StorageError::Database)?;
+        let map = self.blocks.read().map_err(|_| StorageError::LockPoisoned("blocks"))?;
         match map.get(&hash.0.to_vec()) {
-            Some(bytes) => {
-                let block =
-                    Block::decode(&mut &bytes[..]).map_err(|_| StorageError::Serialization)?;
-                Ok(Some(block))
-            }
+            Some(bytes) => Ok(Some(decode("blocks", KeyKind::Block(*hash), bytes)?)),
             None => Ok(None),
         }
     }
 
     fn put_block(&self, hash: &Hash, block: &Block) -> Result<(), StorageError> {
-        let mut map = self.blocks.write().map_err(|_| StorageError::Database)?;
+        let mut map = self.blocks.write().map_err(|_| StorageError::LockPoisoned("blocks"))?;
         map.insert(hash.0.to_vec(), block.encode());
         Ok(())
     }
 
     fn get_transaction(&self, hash: &Hash) -> Result<Option<Transaction>, StorageError> {
-        let map = self.transactions.read().map_err(|_| StorageError::Database)?;
+        let map = self
+            .transactions
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("transactions"))?;
         match map.get(&hash.0.to_vec()) {
-            Some(bytes) => {
-                let tx = Transaction::decode(&mut &bytes[..])
-                    .map_err(|_| StorageError::Serialization)?;
-                Ok(Some(tx))
-            }
+            Some(bytes) => Ok(Some(decode(
+                "transactions",
+                KeyKind::Transaction(*hash),
+                bytes,
+            )?)),
             None => Ok(None),
         }
     }
 
     fn put_transaction(&self, hash: &Hash, tx: &Transaction) -> Result<(), StorageError> {
-        let mut map = self.transactions.write().map_err(|_| StorageError::Database)?;
+        let mut map = self
+            .transactions
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("transactions"))?;
         map.insert(hash.0.to_vec(), tx.encode());
         Ok(())
     }
 
+    fn get_receipt(&self, tx_hash: &Hash) -> Result<Option<Receipt>, StorageError> {
+        let map = self.receipts.read().map_err(|_| StorageError::LockPoisoned("receipts"))?;
+        match map.get(&tx_hash.0.to_vec()) {
+            Some(bytes) => Ok(Some(decode("receipts", KeyKind::Receipt(*tx_hash), bytes)?)),
+            None => Ok(None),
+        }
+    }
+
     fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, StorageError> {
-        let idx = self.height_index.read().map_err(|_| StorageError::Database)?;
+        let idx = self
+            .height_index
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("height_index"))?;
         let key = height.to_be_bytes().to_vec();
         let hash_bytes = match idx.get(&key) {
             Some(b) => b.clone(),
             None => return Ok(None),
         };
         drop(idx);
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&hash_bytes);
-        let hash = Hash(arr);
+        let hash = Hash(fixed("height_index", KeyKind::Height(height), &hash_bytes)?);
         self.get_block(&hash)
     }
 
     fn put_block_height_index(&self, height: u64, hash: Hash) -> Result<(), StorageError> {
-        let mut idx = self.height_index.write().map_err(|_| StorageError::Database)?;
+        let mut idx = self
+            .height_index
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("height_index"))?;
         idx.insert(height.to_be_bytes().to_vec(), hash.0.to_vec());
         drop(idx);
 
         // Update latest height if this height is greater.
-        let mut meta = self.meta.write().map_err(|_| StorageError::Database)?;
-        let current = meta
-            .get(b"latest_height".as_ref())
-            .map(|b| {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(b);
-                u64::from_be_bytes(arr)
-            })
-            .unwrap_or(0);
+        let mut meta = self.meta.write().map_err(|_| StorageError::LockPoisoned("meta"))?;
+        let current = meta_u64(&meta, "latest_height")?;
         if height > current {
             meta.insert(b"latest_height".to_vec(), height.to_be_bytes().to_vec());
         }
         Ok(())
     }
 
-    fn get_latest_height(&self) -> Result<u64, StorageError> {
-        let meta = self.meta.read().map_err(|_| StorageError::Database)?;
-        match meta.get(b"latest_height".as_ref()) {
-            Some(b) => {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(b);
-                Ok(u64::from_be_bytes(arr))
-            }
-            None => Ok(0),
+    fn get_block_bloom(&self, height: u64) -> Result<Option<Bloom>, StorageError> {
+        let map = self
+            .block_blooms
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("block_blooms"))?;
+        match map.get(&height.to_be_bytes().to_vec()) {
+            Some(bytes) => Ok(Some(decode(
+                "block_blooms",
+                KeyKind::BlockBloom(height),
+                bytes,
+            )?)),
+            None => Ok(None),
         }
     }
 
+    fn get_latest_height(&self) -> Result<u64, StorageError> {
+        let meta = self.meta.read().map_err(|_| StorageError::LockPoisoned("meta"))?;
+        meta_u64(&meta, "latest_height")
+    }
+
     fn next_tx_seq(&self) -> Result<u64, StorageError> {
-        let mut meta = self.meta.write().map_err(|_| StorageError::Database)?;
-        let current = meta
-            .get(b"tx_seq".as_ref())
-            .map(|b| {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(b);
-                u64::from_be_bytes(arr)
-            })
-            .unwrap_or(0);
-        let next = current + 1;
+        let mut meta = self.meta.write().map_err(|_| StorageError::LockPoisoned("meta"))?;
+        let next = meta_u64(&meta, "tx_seq")? + 1;
         meta.insert(b"tx_seq".to_vec(), next.to_be_bytes().to_vec());
         Ok(next)
     }
 
     fn put_tx_seq_index(&self, seq: u64, hash: &Hash) -> Result<(), StorageError> {
-        let mut idx = self.tx_seq_index.write().map_err(|_| StorageError::Database)?;
+        let mut idx = self
+            .tx_seq_index
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("tx_seq_index"))?;
         idx.insert(seq.to_be_bytes().to_vec(), hash.0.to_vec());
         Ok(())
     }
 
     fn get_tx_hash_by_seq(&self, seq: u64) -> Result<Option<Hash>, StorageError> {
-        let idx = self.tx_seq_index.read().map_err(|_| StorageError::Database)?;
+        let idx = self
+            .tx_seq_index
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("tx_seq_index"))?;
         match idx.get(&seq.to_be_bytes().to_vec()) {
-            Some(b) => {
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(b);
-                Ok(Some(Hash(arr)))
-            }
+            Some(b) => Ok(Some(Hash(fixed("tx_seq_index", KeyKind::TxSeq(seq), b)?))),
             None => Ok(None),
         }
     }
 
-    fn get_last_included_tx_seq(&self) -> Result<u64, StorageError> {
-        let meta = self.meta.read().map_err(|_| StorageError::Database)?;
-        match meta.get(b"last_included_tx_seq".as_ref()) {
-            Some(b) => {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(b);
-                Ok(u64::from_be_bytes(arr))
-            }
-            None => Ok(0),
+    fn get_address_transactions(
+        &self,
+        address: &Address,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AddressTxEntry>, StorageError> {
+        let Some(seek) = address_tx_seek_key(address, before) else {
+            return Ok(Vec::new());
+        };
+        let idx = self
+            .address_tx_index
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("address_tx_index"))?;
+        let entries = idx.range(..=seek).rev().map(Ok);
+        address_tx_page("address_tx_index", address, limit, entries)
+    }
+
+    fn get_code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, StorageError> {
+        let map = self.code.read().map_err(|_| StorageError::LockPoisoned("code"))?;
+        Ok(map.get(&code_hash.0.to_vec()).cloned())
+    }
+
+    fn get_contract(&self, address: &Address) -> Result<Option<Hash>, StorageError> {
+        let map = self.contracts.read().map_err(|_| StorageError::LockPoisoned("contracts"))?;
+        match map.get(&address.0.to_vec()) {
+            Some(b) => Ok(Some(Hash(fixed(
+                "contracts",
+                KeyKind::Contract(*address),
+                b,
+            )?))),
+            None => Ok(None),
         }
     }
 
+    fn get_contract_storage(
+        &self,
+        contract: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let map = self
+            .contract_storage
+            .read()
+            .map_err(|_| StorageError::LockPoisoned("contract_storage"))?;
+        Ok(map.get(&contract_storage_key(contract, key)).cloned())
+    }
+
+    fn get_last_included_tx_seq(&self) -> Result<u64, StorageError> {
+        let meta = self.meta.read().map_err(|_| StorageError::LockPoisoned("meta"))?;
+        meta_u64(&meta, "last_included_tx_seq")
+    }
+
     fn set_last_included_tx_seq(&self, seq: u64) -> Result<(), StorageError> {
-        let mut meta = self.meta.write().map_err(|_| StorageError::Database)?;
+        let mut meta = self.meta.write().map_err(|_| StorageError::LockPoisoned("meta"))?;
         meta.insert(b"last_included_tx_seq".to_vec(), seq.to_be_bytes().to_vec());
         Ok(())
     }
 
     fn write_batch(&self, ops: Vec<BatchOp>) -> Result<(), StorageError> {
         // Acquire all locks up front to guarantee atomicity.
-        let mut accounts = self.accounts.write().map_err(|_| StorageError::Database)?;
-        let mut blocks = self.blocks.write().map_err(|_| StorageError::Database)?;
-        let mut transactions = self.transactions.write().map_err(|_| StorageError::Database)?;
-        let mut height_index = self.height_index.write().map_err(|_| StorageError::Database)?;
-        let mut tx_seq_index = self.tx_seq_index.write().map_err(|_| StorageError::Database)?;
-        let mut meta = self.meta.write().map_err(|_| StorageError::Database)?;
+        let mut accounts =
+            self.accounts.write().map_err(|_| StorageError::LockPoisoned("accounts"))?;
+        let mut blocks = self.blocks.write().map_err(|_| StorageError::LockPoisoned("blocks"))?;
+        let mut transactions = self
+            .transactions
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("transactions"))?;
+        let mut receipts =
+            self.receipts.write().map_err(|_| StorageError::LockPoisoned("receipts"))?;
+        let mut height_index = self
+            .height_index
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("height_index"))?;
+        let mut block_blooms = self
+            .block_blooms
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("block_blooms"))?;
+        let mut tx_seq_index = self
+            .tx_seq_index
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("tx_seq_index"))?;
+        let mut address_tx_index = self
+            .address_tx_index
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("address_tx_index"))?;
+        let mut code = self.code.write().map_err(|_| StorageError::LockPoisoned("code"))?;
+        let mut contracts =
+            self.contracts.write().map_err(|_| StorageError::LockPoisoned("contracts"))?;
+        let mut contract_storage = self
+            .contract_storage
+            .write()
+            .map_err(|_| StorageError::LockPoisoned("contract_storage"))?;
+        let mut meta = self.meta.write().map_err(|_| StorageError::LockPoisoned("meta"))?;
 
         let mut max_height: Option<u64> = None;
 
@@ -222,6 +322,9 @@ impl Storage for InMemoryStorage {
                 BatchOp::PutTransaction(hash, tx) => {
                     transactions.insert(hash.0.to_vec(), tx.encode());
                 }
+                BatchOp::PutReceipt(hash, receipt) => {
+                    receipts.insert(hash.0.to_vec(), receipt.encode());
+                }
                 BatchOp::PutBlockHeightIndex(height, hash) => {
                     height_index.insert(height.to_be_bytes().to_vec(), hash.0.to_vec());
                     max_height = Some(match max_height {
@@ -229,9 +332,30 @@ impl Storage for InMemoryStorage {
                         _ => height,
                     });
                 }
+                BatchOp::PutBlockBloom(height, bloom) => {
+                    block_blooms.insert(height.to_be_bytes().to_vec(), bloom.encode());
+                }
                 BatchOp::PutTxSeqIndex(seq, hash) => {
                     tx_seq_index.insert(seq.to_be_bytes().to_vec(), hash.0.to_vec());
                 }
+                BatchOp::PutAddressTxIndex(address, entry) => {
+                    address_tx_index.insert(
+                        address_tx_key(&address, entry.height, entry.index),
+                        entry.tx_hash.0.to_vec(),
+                    );
+                }
+                BatchOp::PutCode(hash, bytes) => {
+                    code.insert(hash.0.to_vec(), bytes);
+                }
+                BatchOp::PutContract(address, hash) => {
+                    contracts.insert(address.0.to_vec(), hash.0.to_vec());
+                }
+                BatchOp::PutContractStorage(contract, key, value) => {
+                    contract_storage.insert(contract_storage_key(&contract, &key), value);
+                }
+                BatchOp::DeleteContractStorage(contract, key) => {
+                    contract_storage.remove(&contract_storage_key(&contract, &key));
+                }
                 BatchOp::SetTxSeq(seq) => {
                     meta.insert(b"tx_seq".to_vec(), seq.to_be_bytes().to_vec());
                 }
@@ -243,14 +367,7 @@ impl Storage for InMemoryStorage {
 
         // Update latest_height if any height index entries were written.
         if let Some(height) = max_height {
-            let current = meta
-                .get(b"latest_height".as_ref())
-                .map(|b| {
-                    let mut arr = [0u8; 8];
-                    arr.copy_from_slice(b);
-                    u64::from_be_bytes(arr)
-                })
-                .unwrap_or(0);
+            let current = meta_u64(&meta, "latest_height")?;
             if height > current {
                 meta.insert(b"latest_height".to_vec(), height.to_be_bytes().to_vec());
             }
@@ -2,12 +2,22 @@
 
 use std::path::Path;
 
-use parity_scale_codec::{Decode, Encode};
-use rocksdb::{ColumnFamilyDescriptor, Options, WriteBatchWithTransaction, DB};
-
-use mbongo_core::{Account, Address, Block, Hash, Transaction};
-
-use crate::storage::{BatchOp, Storage, StorageError};
+use parity_scale_codec::{Decode, DecodeAll, Encode};
+use rocksdb::{
+    ColumnFamily, ColumnFamilyDescriptor, Direction, ErrorKind, IteratorMode, Options,
+    WriteBatchWithTransaction, DB,
+};
+
+use mbongo_core::crypto::blake3_hash;
+use mbongo_core::{
+    Account, Address, Block, Bloom, Event, Hash, Log, Receipt, ReceiptStatus, Transaction,
+};
+
+use crate::schema::{self, MigrationReport, META_SCHEMA_VERSION, SCHEMA_VERSION};
+use crate::storage::{
+    address_tx_key, address_tx_page, address_tx_seek_key, contract_storage_key, decode, fixed,
+    AddressTxEntry, BatchOp, DbErrorKind, KeyKind, Storage, StorageError,
+};
 
 /// Column family name for account state.
 const CF_ACCOUNTS: &str = "accounts";
@@ -15,15 +25,99 @@ const CF_ACCOUNTS: &str = "accounts";
 const CF_BLOCKS: &str = "blocks";
 /// Column family name for transactions.
 const CF_TRANSACTIONS: &str = "transactions";
+/// Column family name for transaction receipts keyed by tx hash.
+const CF_RECEIPTS: &str = "receipts";
 /// Column family name for metadata (latest height, etc.).
 const CF_META: &str = "meta";
 /// Column family name for height → block-hash index.
 const CF_HEIGHT_INDEX: &str = "height_index";
+/// Column family name for height → aggregate block log bloom.
+const CF_BLOCK_BLOOMS: &str = "block_blooms";
 /// Column family name for tx sequence → tx hash index.
 const CF_TX_SEQ_INDEX: &str = "tx_seq_index";
+/// Column family name for (address, height, index) → tx hash index.
+const CF_ADDRESS_TX_INDEX: &str = "address_tx_index";
+/// Column family name for contract code keyed by code hash.
+const CF_CODE: &str = "code";
+/// Column family name for contract address → code hash.
+const CF_CONTRACTS: &str = "contracts";
+/// Column family name for (contract address, slot key) → slot value.
+const CF_CONTRACT_STORAGE: &str = "contract_storage";
+
+/// Every column family required by the current schema.
+const COLUMN_FAMILIES: &[&str] = &[
+    CF_ACCOUNTS,
+    CF_BLOCKS,
+    CF_TRANSACTIONS,
+    CF_META,
+    CF_HEIGHT_INDEX,
+    CF_TX_SEQ_INDEX,
+    CF_RECEIPTS,
+    CF_ADDRESS_TX_INDEX,
+    CF_CODE,
+    CF_CONTRACTS,
+    CF_CONTRACT_STORAGE,
+    CF_BLOCK_BLOOMS,
+];
+
+/// Metadata key holding the latest indexed block height.
+const META_LATEST_HEIGHT: &str = "latest_height";
+/// Metadata key holding the transaction sequence counter.
+const META_TX_SEQ: &str = "tx_seq";
+/// Metadata key holding the last tx sequence number included in a block.
+const META_LAST_INCLUDED_TX_SEQ: &str = "last_included_tx_seq";
+
+/// Maps a RocksDB error kind onto the backend-agnostic [`DbErrorKind`].
+fn classify(err: &rocksdb::Error) -> DbErrorKind {
+    match err.kind() {
+        ErrorKind::Corruption => DbErrorKind::Corruption,
+        ErrorKind::Busy | ErrorKind::TryAgain | ErrorKind::TimedOut => DbErrorKind::Busy,
+        ErrorKind::IOError => DbErrorKind::Io,
+        ErrorKind::InvalidArgument | ErrorKind::NotSupported => DbErrorKind::InvalidArgument,
+        _ => DbErrorKind::Other,
+    }
+}
+
+/// Returns a closure wrapping a RocksDB error with the column family and key it concerned.
+fn db_err(cf: &'static str, key: KeyKind) -> impl FnOnce(rocksdb::Error) -> StorageError {
+    move |e| StorageError::Database {
+        cf,
+        key,
+        kind: classify(&e),
+        source: Box::new(e),
+    }
+}
+
+/// Maps a failure to open the database at `path` onto [`StorageError::Open`].
+fn open_err(path: &Path, e: rocksdb::Error) -> StorageError {
+    StorageError::Open {
+        path: path.display().to_string(),
+        kind: classify(&e),
+        source: Box::new(e),
+    }
+}
 
-/// Persistent storage backed by RocksDB with three column families:
-/// `accounts`, `blocks`, and `transactions`.
+/// Reads the big-endian `u32` schema version from the `meta` column family.
+fn read_schema_version(db: &DB, cf: &ColumnFamily) -> Result<Option<u32>, StorageError> {
+    let key = KeyKind::Meta(META_SCHEMA_VERSION);
+    match db.get_cf(cf, META_SCHEMA_VERSION).map_err(db_err(CF_META, key))? {
+        Some(b) => Ok(Some(u32::from_be_bytes(fixed(CF_META, key, &b)?))),
+        None => Ok(None),
+    }
+}
+
+/// What [`RocksDbStorage::peek`] found in an existing database.
+struct ExistingDb {
+    /// Recorded schema version, if any.
+    version: Option<u32>,
+    /// Names of the column families present on disk.
+    column_families: Vec<String>,
+}
+
+/// Persistent storage backed by RocksDB with one column family per record
+/// kind: `accounts`, `blocks`, `transactions`, `receipts`, `meta`,
+/// `height_index`, `tx_seq_index`, `address_tx_index`, `code`, `contracts`,
+/// and `contract_storage`.
 pub struct RocksDbStorage {
     db: DB,
 }
@@ -31,208 +125,579 @@ pub struct RocksDbStorage {
 impl RocksDbStorage {
     /// Opens (or creates) a RocksDB database at the given path.
     ///
-    /// Column families `accounts`, `blocks`, and `transactions` are created
-    /// automatically if they do not already exist.
+    /// Equivalent to [`RocksDbStorage::open_and_migrate`] with the
+    /// migration report discarded.
     ///
     /// # Errors
     ///
-    /// Returns [`StorageError::Database`] if the database cannot be opened.
+    /// See [`RocksDbStorage::open_and_migrate`].
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
+        Self::open_and_migrate(path).map(|(storage, _)| storage)
+    }
+
+    /// Opens (or creates) a RocksDB database at the given path and brings
+    /// its schema up to [`SCHEMA_VERSION`].
+    ///
+    /// A new database is stamped with the current schema version. An
+    /// existing database without a recorded version is treated as version 0.
+    /// Pending steps from [`schema::MIGRATIONS`] run in order, and the
+    /// recorded version is advanced after each one so an interrupted
+    /// upgrade resumes where it stopped.
+    ///
+    /// # Errors
+    ///
+    /// - [`StorageError::Open`] if the database cannot be opened.
+    /// - [`StorageError::UnsupportedSchemaVersion`] if the database was
+    ///   written by a newer build. The database is not modified.
+    /// - [`StorageError::MissingColumnFamily`] if an up-to-date database is
+    ///   missing one of its column families.
+    /// - [`StorageError::Migration`] if a migration step fails.
+    pub fn open_and_migrate<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, MigrationReport), StorageError> {
+        Self::open_with(path.as_ref(), true)
+    }
+
+    /// Like [`RocksDbStorage::open_and_migrate`], but refuses to create a
+    /// database that does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// As [`RocksDbStorage::open_and_migrate`], and [`StorageError::Open`]
+    /// if there is no database at `path`.
+    pub fn open_existing_and_migrate<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, MigrationReport), StorageError> {
+        Self::open_with(path.as_ref(), false)
+    }
+
+    /// Shared body of the `open_*` constructors; `create` allows creating a
+    /// missing database.
+    fn open_with(path: &Path, create: bool) -> Result<(Self, MigrationReport), StorageError> {
+        // Inspect an existing database read-only first, so that one written
+        // by a newer build is refused before we create anything in it.
+        let existing = Self::peek(path)?;
+        if let Some(existing) = &existing {
+            let version = existing.version.unwrap_or(0);
+            if schema::pending(version)?.is_empty() {
+                let cfs = &existing.column_families;
+                if let Some(missing) =
+                    COLUMN_FAMILIES.iter().find(|cf| !cfs.iter().any(|c| c == *cf))
+                {
+                    return Err(StorageError::MissingColumnFamily(missing));
+                }
+            }
+        }
+
         let mut db_opts = Options::default();
-        db_opts.create_if_missing(true);
+        db_opts.create_if_missing(create);
         db_opts.create_missing_column_families(true);
 
         let cf_opts = Options::default();
+        let cfs = COLUMN_FAMILIES
+            .iter()
+            .map(|name| ColumnFamilyDescriptor::new(*name, cf_opts.clone()));
+
+        let db = DB::open_cf_descriptors(&db_opts, path, cfs).map_err(|e| open_err(path, e))?;
+        let storage = Self { db };
+
+        let Some(existing) = existing else {
+            storage.put_schema_version(SCHEMA_VERSION)?;
+            let report = MigrationReport {
+                from: SCHEMA_VERSION,
+                to: SCHEMA_VERSION,
+                applied: Vec::new(),
+                created: true,
+            };
+            return Ok((storage, report));
+        };
+
+        let from = existing.version.unwrap_or(0);
+        let mut applied = Vec::new();
+        for step in schema::pending(from)? {
+            (step.run)(&storage).map_err(|e| StorageError::Migration {
+                from: step.from,
+                description: step.description,
+                source: Box::new(e),
+            })?;
+            storage.put_schema_version(step.from + 1)?;
+            applied.push(step.description);
+        }
+
+        let report = MigrationReport {
+            from,
+            to: SCHEMA_VERSION,
+            applied,
+            created: false,
+        };
+        Ok((storage, report))
+    }
 
-        let cfs = vec![
-            ColumnFamilyDescriptor::new(CF_ACCOUNTS, cf_opts.clone()),
-            ColumnFamilyDescriptor::new(CF_BLOCKS, cf_opts.clone()),
-            ColumnFamilyDescriptor::new(CF_TRANSACTIONS, cf_opts.clone()),
-            ColumnFamilyDescriptor::new(CF_META, cf_opts.clone()),
-            ColumnFamilyDescriptor::new(CF_HEIGHT_INDEX, cf_opts.clone()),
-            ColumnFamilyDescriptor::new(CF_TX_SEQ_INDEX, cf_opts),
-        ];
+    /// Returns the schema version recorded on disk (0 if none is recorded).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database failure or a malformed value.
+    pub fn schema_version(&self) -> Result<u32, StorageError> {
+        let cf = self.cf(CF_META)?;
+        read_schema_version(&self.db, cf).map(|v| v.unwrap_or(0))
+    }
 
+    /// Opens an existing database read-only and returns its recorded schema
+    /// version and column family names, or `None` if no database exists.
+    fn peek(path: &Path) -> Result<Option<ExistingDb>, StorageError> {
+        let opts = Options::default();
+        let Ok(cfs) = DB::list_cf(&opts, path) else {
+            return Ok(None);
+        };
+        if !cfs.iter().any(|c| c == CF_META) {
+            return Ok(Some(ExistingDb {
+                version: None,
+                column_families: cfs,
+            }));
+        }
         let db =
-            DB::open_cf_descriptors(&db_opts, path, cfs).map_err(|_| StorageError::Database)?;
+            DB::open_cf_for_read_only(&opts, path, &cfs, false).map_err(|e| open_err(path, e))?;
+        let cf = db.cf_handle(CF_META).ok_or(StorageError::MissingColumnFamily(CF_META))?;
+        let version = read_schema_version(&db, cf)?;
+        Ok(Some(ExistingDb {
+            version,
+            column_families: cfs,
+        }))
+    }
+
+    /// Records the on-disk schema version.
+    fn put_schema_version(&self, version: u32) -> Result<(), StorageError> {
+        let cf = self.cf(CF_META)?;
+        self.db
+            .put_cf(cf, META_SCHEMA_VERSION, version.to_be_bytes())
+            .map_err(db_err(CF_META, KeyKind::Meta(META_SCHEMA_VERSION)))
+    }
+
+    /// Looks up a column family handle by name.
+    fn cf(&self, name: &'static str) -> Result<&ColumnFamily, StorageError> {
+        self.db.cf_handle(name).ok_or(StorageError::MissingColumnFamily(name))
+    }
+
+    /// Reads a big-endian `u64` stored under a metadata key, defaulting to 0.
+    fn get_meta_u64(&self, key: &'static str) -> Result<u64, StorageError> {
+        let cf = self.cf(CF_META)?;
+        let kind = KeyKind::Meta(key);
+        match self.db.get_cf(cf, key).map_err(db_err(CF_META, kind))? {
+            Some(b) => Ok(u64::from_be_bytes(fixed(CF_META, kind, &b)?)),
+            None => Ok(0),
+        }
+    }
+
+    /// Writes a big-endian `u64` under a metadata key.
+    fn put_meta_u64(&self, key: &'static str, value: u64) -> Result<(), StorageError> {
+        let cf = self.cf(CF_META)?;
+        self.db
+            .put_cf(cf, key, value.to_be_bytes())
+            .map_err(db_err(CF_META, KeyKind::Meta(key)))
+    }
+}
+
+/// Schema migration 1 → 2: writes a receipt for every transaction already
+/// included in a block.
+///
+/// Blocks are walked in height order and, as in block execution, only the
+/// first inclusion of a transaction hash receives a receipt. Blocks applied
+/// before fees and failure statuses existed all succeeded with no fee.
+pub(crate) fn backfill_receipts(storage: &RocksDbStorage) -> Result<(), StorageError> {
+    let cf = storage.cf(CF_HEIGHT_INDEX)?;
+    let latest = storage.get_latest_height()?;
+    for height in 0..=latest {
+        let key = KeyKind::Height(height);
+        let Some(bytes) = storage
+            .db
+            .get_cf(cf, height.to_be_bytes())
+            .map_err(db_err(CF_HEIGHT_INDEX, key))?
+        else {
+            continue;
+        };
+        let block_hash = Hash(fixed(CF_HEIGHT_INDEX, key, &bytes)?);
+        let Some(block) = storage.get_block(&block_hash)? else {
+            continue;
+        };
 
-        Ok(Self { db })
+        let mut ops = Vec::new();
+        for (index, tx) in (0u32..).zip(&block.body.transactions) {
+            let tx_hash = Hash(blake3_hash(&tx.encode()));
+            if storage.get_receipt(&tx_hash)?.is_some()
+                || ops.iter().any(|op| matches!(op, BatchOp::PutReceipt(h, _) if *h == tx_hash))
+            {
+                continue;
+            }
+            let receipt = Receipt::success(tx_hash, tx, block_hash, height, index);
+            ops.push(BatchOp::PutReceipt(tx_hash, receipt));
+        }
+        if !ops.is_empty() {
+            storage.write_batch(ops)?;
+        }
     }
+    Ok(())
+}
+
+/// Schema migration 2 → 3: indexes the sender and receiver of every
+/// transaction already included in a block.
+///
+/// Relies on the receipts written since schema 2 to find the first
+/// inclusion of each transaction hash, which is the only one indexed.
+pub(crate) fn backfill_address_index(storage: &RocksDbStorage) -> Result<(), StorageError> {
+    let latest = storage.get_latest_height()?;
+    for height in 0..=latest {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            continue;
+        };
+
+        let mut ops = Vec::new();
+        for (index, tx) in (0u32..).zip(&block.body.transactions) {
+            let tx_hash = Hash(blake3_hash(&tx.encode()));
+            let first_inclusion = storage
+                .get_receipt(&tx_hash)?
+                .is_some_and(|r| r.block_height == height && r.index == index);
+            if !first_inclusion {
+                continue;
+            }
+            let entry = AddressTxEntry {
+                height,
+                index,
+                tx_hash,
+            };
+            ops.push(BatchOp::PutAddressTxIndex(tx.sender, entry));
+            if tx.receiver != tx.sender {
+                ops.push(BatchOp::PutAddressTxIndex(tx.receiver, entry));
+            }
+        }
+        if !ops.is_empty() {
+            storage.write_batch(ops)?;
+        }
+    }
+    Ok(())
+}
+
+/// Schema migration 6 → 7: records the aggregate log bloom of every block
+/// already applied.
+///
+/// As in block execution, only the receipt of a transaction's first
+/// inclusion contributes to a block's bloom.
+pub(crate) fn backfill_block_blooms(storage: &RocksDbStorage) -> Result<(), StorageError> {
+    let latest = storage.get_latest_height()?;
+    for height in 0..=latest {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            continue;
+        };
+
+        let mut bloom = Bloom::default();
+        for (index, tx) in (0u32..).zip(&block.body.transactions) {
+            let tx_hash = Hash(blake3_hash(&tx.encode()));
+            if let Some(receipt) = storage.get_receipt(&tx_hash)? {
+                if receipt.block_height == height && receipt.index == index {
+                    bloom.accrue_bloom(&receipt.bloom);
+                }
+            }
+        }
+        storage.write_batch(vec![BatchOp::PutBlockBloom(height, bloom)])?;
+    }
+    Ok(())
+}
+
+/// Receipt layout of schema versions 2 to 4, before receipts carried a bloom.
+#[derive(Decode)]
+struct ReceiptV4 {
+    tx_hash: Hash,
+    block_hash: Hash,
+    block_height: u64,
+    index: u32,
+    status: ReceiptStatus,
+    fee: u128,
+    events: Vec<Event>,
+}
+
+impl From<ReceiptV4> for Receipt {
+    fn from(old: ReceiptV4) -> Self {
+        let logs: Vec<Log> = old.events.iter().map(Event::to_log).collect();
+        Self {
+            tx_hash: old.tx_hash,
+            block_hash: old.block_hash,
+            block_height: old.block_height,
+            index: old.index,
+            status: old.status,
+            fee: old.fee,
+            gas_used: 0,
+            error: None,
+            events: old.events,
+            bloom: Bloom::from_logs(&logs),
+        }
+    }
+}
+
+/// Receipt layout of schema version 5, before receipts recorded gas used
+/// and the failure reason.
+#[derive(Decode)]
+struct ReceiptV5 {
+    tx_hash: Hash,
+    block_hash: Hash,
+    block_height: u64,
+    index: u32,
+    status: ReceiptStatus,
+    fee: u128,
+    events: Vec<Event>,
+    bloom: Bloom,
+}
+
+impl From<ReceiptV5> for Receipt {
+    fn from(old: ReceiptV5) -> Self {
+        Self {
+            tx_hash: old.tx_hash,
+            block_hash: old.block_hash,
+            block_height: old.block_height,
+            index: old.index,
+            status: old.status,
+            fee: old.fee,
+            gas_used: 0,
+            error: None,
+            events: old.events,
+            bloom: old.bloom,
+        }
+    }
+}
+
+/// Schema migration 4 → 5: adds the log bloom to the receipt of every
+/// transaction already included in a block.
+pub(crate) fn add_receipt_blooms(storage: &RocksDbStorage) -> Result<(), StorageError> {
+    upgrade_receipts::<ReceiptV4>(storage)
+}
+
+/// Schema migration 5 → 6: adds the gas used and failure reason to the
+/// receipt of every transaction already included in a block. Neither was
+/// recorded before, so migrated receipts carry 0 and no reason.
+pub(crate) fn add_receipt_gas(storage: &RocksDbStorage) -> Result<(), StorageError> {
+    upgrade_receipts::<ReceiptV5>(storage)
+}
+
+/// Rewrites every receipt stored in the `Old` layout in the current one.
+///
+/// Receipts that already decode in the current layout, such as those an
+/// earlier step wrote in the same upgrade, are left as they are.
+fn upgrade_receipts<Old: Decode + Into<Receipt>>(
+    storage: &RocksDbStorage,
+) -> Result<(), StorageError> {
+    let cf = storage.cf(CF_RECEIPTS)?;
+    let latest = storage.get_latest_height()?;
+    for height in 0..=latest {
+        let Some(block) = storage.get_block_by_height(height)? else {
+            continue;
+        };
+
+        let mut ops = Vec::new();
+        for tx in &block.body.transactions {
+            let tx_hash = Hash(blake3_hash(&tx.encode()));
+            let key = KeyKind::Receipt(tx_hash);
+            let Some(bytes) = storage.db.get_cf(cf, tx_hash.0).map_err(db_err(CF_RECEIPTS, key))?
+            else {
+                continue;
+            };
+            if Receipt::decode_all(&mut &bytes[..]).is_ok() {
+                continue;
+            }
+            let old: Old = decode(CF_RECEIPTS, key, &bytes)?;
+            ops.push(BatchOp::PutReceipt(tx_hash, old.into()));
+        }
+        if !ops.is_empty() {
+            storage.write_batch(ops)?;
+        }
+    }
+    Ok(())
 }
 
 impl Storage for RocksDbStorage {
     fn get_account(&self, address: &Address) -> Result<Option<Account>, StorageError> {
-        let cf = self.db.cf_handle(CF_ACCOUNTS).ok_or(StorageError::Database)?;
-        match self.db.get_cf(&cf, address.0).map_err(|_| StorageError::Database)? {
-            Some(bytes) => {
-                let account =
-                    Account::decode(&mut &bytes[..]).map_err(|_| StorageError::Serialization)?;
-                Ok(Some(account))
-            }
+        let cf = self.cf(CF_ACCOUNTS)?;
+        let key = KeyKind::Account(*address);
+        match self.db.get_cf(cf, address.0).map_err(db_err(CF_ACCOUNTS, key))? {
+            Some(bytes) => Ok(Some(decode(CF_ACCOUNTS, key, &bytes)?)),
             None => Ok(None),
         }
     }
 
     fn put_account(&self, address: &Address, account: &Account) -> Result<(), StorageError> {
-        let cf = self.db.cf_handle(CF_ACCOUNTS).ok_or(StorageError::Database)?;
+        let cf = self.cf(CF_ACCOUNTS)?;
         self.db
-            .put_cf(&cf, address.0, account.encode())
-            .map_err(|_| StorageError::Database)
+            .put_cf(cf, address.0, account.encode())
+            .map_err(db_err(CF_ACCOUNTS, KeyKind::Account(*address)))
     }
 
     fn get_block(&self, hash: &Hash) -> Result<Option<Block>, StorageError> {
-        let cf = self.db.cf_handle(CF_BLOCKS).ok_or(StorageError::Database)?;
-        match self.db.get_cf(&cf, hash.0).map_err(|_| StorageError::Database)? {
-            Some(bytes) => {
-                let block =
-                    Block::decode(&mut &bytes[..]).map_err(|_| StorageError::Serialization)?;
-                Ok(Some(block))
-            }
+        let cf = self.cf(CF_BLOCKS)?;
+        let key = KeyKind::Block(*hash);
+        match self.db.get_cf(cf, hash.0).map_err(db_err(CF_BLOCKS, key))? {
+            Some(bytes) => Ok(Some(decode(CF_BLOCKS, key, &bytes)?)),
             None => Ok(None),
         }
     }
 
     fn put_block(&self, hash: &Hash, block: &Block) -> Result<(), StorageError> {
-        let cf = self.db.cf_handle(CF_BLOCKS).ok_or(StorageError::Database)?;
-        self.db.put_cf(&cf, hash.0, block.encode()).map_err(|_| StorageError::Database)
+        let cf = self.cf(CF_BLOCKS)?;
+        self.db
+            .put_cf(cf, hash.0, block.encode())
+            .map_err(db_err(CF_BLOCKS, KeyKind::Block(*hash)))
     }
 
     fn get_transaction(&self, hash: &Hash) -> Result<Option<Transaction>, StorageError> {
-        let cf = self.db.cf_handle(CF_TRANSACTIONS).ok_or(StorageError::Database)?;
-        match self.db.get_cf(&cf, hash.0).map_err(|_| StorageError::Database)? {
-            Some(bytes) => {
-                let tx = Transaction::decode(&mut &bytes[..])
-                    .map_err(|_| StorageError::Serialization)?;
-                Ok(Some(tx))
-            }
+        let cf = self.cf(CF_TRANSACTIONS)?;
+        let key = KeyKind::Transaction(*hash);
+        match self.db.get_cf(cf, hash.0).map_err(db_err(CF_TRANSACTIONS, key))? {
+            Some(bytes) => Ok(Some(decode(CF_TRANSACTIONS, key, &bytes)?)),
             None => Ok(None),
         }
     }
 
     fn put_transaction(&self, hash: &Hash, tx: &Transaction) -> Result<(), StorageError> {
-        let cf = self.db.cf_handle(CF_TRANSACTIONS).ok_or(StorageError::Database)?;
-        self.db.put_cf(&cf, hash.0, tx.encode()).map_err(|_| StorageError::Database)
+        let cf = self.cf(CF_TRANSACTIONS)?;
+        self.db
+            .put_cf(cf, hash.0, tx.encode())
+            .map_err(db_err(CF_TRANSACTIONS, KeyKind::Transaction(*hash)))
+    }
+
+    fn get_receipt(&self, tx_hash: &Hash) -> Result<Option<Receipt>, StorageError> {
+        let cf = self.cf(CF_RECEIPTS)?;
+        let key = KeyKind::Receipt(*tx_hash);
+        match self.db.get_cf(cf, tx_hash.0).map_err(db_err(CF_RECEIPTS, key))? {
+            Some(bytes) => Ok(Some(decode(CF_RECEIPTS, key, &bytes)?)),
+            None => Ok(None),
+        }
     }
 
     fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, StorageError> {
-        let cf = self.db.cf_handle(CF_HEIGHT_INDEX).ok_or(StorageError::Database)?;
-        let key = height.to_be_bytes();
-        let hash_bytes = match self.db.get_cf(&cf, key).map_err(|_| StorageError::Database)? {
-            Some(b) => b,
-            None => return Ok(None),
-        };
-        let mut arr = [0u8; 32];
-        arr.copy_from_slice(&hash_bytes);
-        let hash = Hash(arr);
+        let cf = self.cf(CF_HEIGHT_INDEX)?;
+        let key = KeyKind::Height(height);
+        let hash_bytes =
+            match self.db.get_cf(cf, height.to_be_bytes()).map_err(db_err(CF_HEIGHT_INDEX, key))? {
+                Some(b) => b,
+                None => return Ok(None),
+            };
+        let hash = Hash(fixed(CF_HEIGHT_INDEX, key, &hash_bytes)?);
         self.get_block(&hash)
     }
 
     fn put_block_height_index(&self, height: u64, hash: Hash) -> Result<(), StorageError> {
-        let cf_idx = self.db.cf_handle(CF_HEIGHT_INDEX).ok_or(StorageError::Database)?;
+        let cf_idx = self.cf(CF_HEIGHT_INDEX)?;
         self.db
-            .put_cf(&cf_idx, height.to_be_bytes(), hash.0)
-            .map_err(|_| StorageError::Database)?;
+            .put_cf(cf_idx, height.to_be_bytes(), hash.0)
+            .map_err(db_err(CF_HEIGHT_INDEX, KeyKind::Height(height)))?;
 
         // Update latest height if this height is greater.
-        let cf_meta = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
-        let current = self
-            .db
-            .get_cf(&cf_meta, b"latest_height")
-            .map_err(|_| StorageError::Database)?
-            .map(|b| {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(&b);
-                u64::from_be_bytes(arr)
-            })
-            .unwrap_or(0);
+        let current = self.get_meta_u64(META_LATEST_HEIGHT)?;
         if height > current {
-            self.db
-                .put_cf(&cf_meta, b"latest_height", height.to_be_bytes())
-                .map_err(|_| StorageError::Database)?;
+            self.put_meta_u64(META_LATEST_HEIGHT, height)?;
         }
         Ok(())
     }
 
-    fn get_latest_height(&self) -> Result<u64, StorageError> {
-        let cf = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
-        match self.db.get_cf(&cf, b"latest_height").map_err(|_| StorageError::Database)? {
-            Some(b) => {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(&b);
-                Ok(u64::from_be_bytes(arr))
-            }
-            None => Ok(0),
+    fn get_block_bloom(&self, height: u64) -> Result<Option<Bloom>, StorageError> {
+        let cf = self.cf(CF_BLOCK_BLOOMS)?;
+        let key = KeyKind::BlockBloom(height);
+        match self.db.get_cf(cf, height.to_be_bytes()).map_err(db_err(CF_BLOCK_BLOOMS, key))? {
+            Some(bytes) => Ok(Some(decode(CF_BLOCK_BLOOMS, key, &bytes)?)),
+            None => Ok(None),
         }
     }
 
+    fn get_latest_height(&self) -> Result<u64, StorageError> {
+        self.get_meta_u64(META_LATEST_HEIGHT)
+    }
+
     fn next_tx_seq(&self) -> Result<u64, StorageError> {
-        let cf = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
-        let current = self
-            .db
-            .get_cf(&cf, b"tx_seq")
-            .map_err(|_| StorageError::Database)?
-            .map(|b| {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(&b);
-                u64::from_be_bytes(arr)
-            })
-            .unwrap_or(0);
-        let next = current + 1;
-        self.db
-            .put_cf(&cf, b"tx_seq", next.to_be_bytes())
-            .map_err(|_| StorageError::Database)?;
+        let next = self.get_meta_u64(META_TX_SEQ)? + 1;
+        self.put_meta_u64(META_TX_SEQ, next)?;
         Ok(next)
     }
 
     fn put_tx_seq_index(&self, seq: u64, hash: &Hash) -> Result<(), StorageError> {
-        let cf = self.db.cf_handle(CF_TX_SEQ_INDEX).ok_or(StorageError::Database)?;
+        let cf = self.cf(CF_TX_SEQ_INDEX)?;
         self.db
-            .put_cf(&cf, seq.to_be_bytes(), hash.0)
-            .map_err(|_| StorageError::Database)
+            .put_cf(cf, seq.to_be_bytes(), hash.0)
+            .map_err(db_err(CF_TX_SEQ_INDEX, KeyKind::TxSeq(seq)))
     }
 
     fn get_tx_hash_by_seq(&self, seq: u64) -> Result<Option<Hash>, StorageError> {
-        let cf = self.db.cf_handle(CF_TX_SEQ_INDEX).ok_or(StorageError::Database)?;
-        match self.db.get_cf(&cf, seq.to_be_bytes()).map_err(|_| StorageError::Database)? {
-            Some(b) => {
-                let mut arr = [0u8; 32];
-                arr.copy_from_slice(&b);
-                Ok(Some(Hash(arr)))
-            }
+        let cf = self.cf(CF_TX_SEQ_INDEX)?;
+        let key = KeyKind::TxSeq(seq);
+        match self.db.get_cf(cf, seq.to_be_bytes()).map_err(db_err(CF_TX_SEQ_INDEX, key))? {
+            Some(b) => Ok(Some(Hash(fixed(CF_TX_SEQ_INDEX, key, &b)?))),
             None => Ok(None),
         }
     }
 
-    fn get_last_included_tx_seq(&self) -> Result<u64, StorageError> {
-        let cf = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
-        match self
+    fn get_address_transactions(
+        &self,
+        address: &Address,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AddressTxEntry>, StorageError> {
+        let Some(seek) = address_tx_seek_key(address, before) else {
+            return Ok(Vec::new());
+        };
+        let cf = self.cf(CF_ADDRESS_TX_INDEX)?;
+        let key = KeyKind::AddressTx(*address, before.unwrap_or(u64::MAX));
+        let entries = self
             .db
-            .get_cf(&cf, b"last_included_tx_seq")
-            .map_err(|_| StorageError::Database)?
-        {
-            Some(b) => {
-                let mut arr = [0u8; 8];
-                arr.copy_from_slice(&b);
-                Ok(u64::from_be_bytes(arr))
-            }
-            None => Ok(0),
+            .iterator_cf(cf, IteratorMode::From(&seek, Direction::Reverse))
+            .map(|entry| entry.map_err(db_err(CF_ADDRESS_TX_INDEX, key)));
+        address_tx_page(CF_ADDRESS_TX_INDEX, address, limit, entries)
+    }
+
+    fn get_code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, StorageError> {
+        let cf = self.cf(CF_CODE)?;
+        self.db
+            .get_cf(cf, code_hash.0)
+            .map_err(db_err(CF_CODE, KeyKind::Code(*code_hash)))
+    }
+
+    fn get_contract(&self, address: &Address) -> Result<Option<Hash>, StorageError> {
+        let cf = self.cf(CF_CONTRACTS)?;
+        let key = KeyKind::Contract(*address);
+        match self.db.get_cf(cf, address.0).map_err(db_err(CF_CONTRACTS, key))? {
+            Some(b) => Ok(Some(Hash(fixed(CF_CONTRACTS, key, &b)?))),
+            None => Ok(None),
         }
     }
 
-    fn set_last_included_tx_seq(&self, seq: u64) -> Result<(), StorageError> {
-        let cf = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
+    fn get_contract_storage(
+        &self,
+        contract: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StorageError> {
+        let cf = self.cf(CF_CONTRACT_STORAGE)?;
+        let kind = KeyKind::ContractStorage(*contract);
         self.db
-            .put_cf(&cf, b"last_included_tx_seq", seq.to_be_bytes())
-            .map_err(|_| StorageError::Database)
+            .get_cf(cf, contract_storage_key(contract, key))
+            .map_err(db_err(CF_CONTRACT_STORAGE, kind))
+    }
+
+    fn get_last_included_tx_seq(&self) -> Result<u64, StorageError> {
+        self.get_meta_u64(META_LAST_INCLUDED_TX_SEQ)
+    }
+
+    fn set_last_included_tx_seq(&self, seq: u64) -> Result<(), StorageError> {
+        self.put_meta_u64(META_LAST_INCLUDED_TX_SEQ, seq)
     }
 
     fn write_batch(&self, ops: Vec<BatchOp>) -> Result<(), StorageError> {
-        let cf_accounts = self.db.cf_handle(CF_ACCOUNTS).ok_or(StorageError::Database)?;
-        let cf_blocks = self.db.cf_handle(CF_BLOCKS).ok_or(StorageError::Database)?;
-        let cf_transactions = self.db.cf_handle(CF_TRANSACTIONS).ok_or(StorageError::Database)?;
-        let cf_meta = self.db.cf_handle(CF_META).ok_or(StorageError::Database)?;
-        let cf_height_index = self.db.cf_handle(CF_HEIGHT_INDEX).ok_or(StorageError::Database)?;
-        let cf_tx_seq_index = self.db.cf_handle(CF_TX_SEQ_INDEX).ok_or(StorageError::Database)?;
+        let cf_accounts = self.cf(CF_ACCOUNTS)?;
+        let cf_blocks = self.cf(CF_BLOCKS)?;
+        let cf_transactions = self.cf(CF_TRANSACTIONS)?;
+        let cf_receipts = self.cf(CF_RECEIPTS)?;
+        let cf_meta = self.cf(CF_META)?;
+        let cf_height_index = self.cf(CF_HEIGHT_INDEX)?;
+        let cf_block_blooms = self.cf(CF_BLOCK_BLOOMS)?;
+        let cf_tx_seq_index = self.cf(CF_TX_SEQ_INDEX)?;
+        let cf_address_tx_index = self.cf(CF_ADDRESS_TX_INDEX)?;
+        let cf_code = self.cf(CF_CODE)?;
+        let cf_contracts = self.cf(CF_CONTRACTS)?;
+        let cf_contract_storage = self.cf(CF_CONTRACT_STORAGE)?;
 
         let mut batch = WriteBatchWithTransaction::<false>::default();
 
@@ -242,50 +707,73 @@ impl Storage for RocksDbStorage {
         for op in ops {
             match op {
                 BatchOp::PutAccount(address, account) => {
-                    batch.put_cf(&cf_accounts, address.0, account.encode());
+                    batch.put_cf(cf_accounts, address.0, account.encode());
                 }
                 BatchOp::PutBlock(hash, block) => {
-                    batch.put_cf(&cf_blocks, hash.0, block.encode());
+                    batch.put_cf(cf_blocks, hash.0, block.encode());
                 }
                 BatchOp::PutTransaction(hash, tx) => {
-                    batch.put_cf(&cf_transactions, hash.0, tx.encode());
+                    batch.put_cf(cf_transactions, hash.0, tx.encode());
+                }
+                BatchOp::PutReceipt(hash, receipt) => {
+                    batch.put_cf(cf_receipts, hash.0, receipt.encode());
                 }
                 BatchOp::PutBlockHeightIndex(height, hash) => {
-                    batch.put_cf(&cf_height_index, height.to_be_bytes(), hash.0);
+                    batch.put_cf(cf_height_index, height.to_be_bytes(), hash.0);
                     max_height = Some(match max_height {
                         Some(h) if h >= height => h,
                         _ => height,
                     });
                 }
+                BatchOp::PutBlockBloom(height, bloom) => {
+                    batch.put_cf(cf_block_blooms, height.to_be_bytes(), bloom.encode());
+                }
                 BatchOp::PutTxSeqIndex(seq, hash) => {
-                    batch.put_cf(&cf_tx_seq_index, seq.to_be_bytes(), hash.0);
+                    batch.put_cf(cf_tx_seq_index, seq.to_be_bytes(), hash.0);
+                }
+                BatchOp::PutAddressTxIndex(address, entry) => {
+                    batch.put_cf(
+                        cf_address_tx_index,
+                        address_tx_key(&address, entry.height, entry.index),
+                        entry.tx_hash.0,
+                    );
+                }
+                BatchOp::PutCode(hash, code) => {
+                    batch.put_cf(cf_code, hash.0, code);
+                }
+                BatchOp::PutContract(address, hash) => {
+                    batch.put_cf(cf_contracts, address.0, hash.0);
+                }
+                BatchOp::PutContractStorage(contract, key, value) => {
+                    batch.put_cf(
+                        cf_contract_storage,
+                        contract_storage_key(&contract, &key),
+                        value,
+                    );
+                }
+                BatchOp::DeleteContractStorage(contract, key) => {
+                    batch.delete_cf(cf_contract_storage, contract_storage_key(&contract, &key));
                 }
                 BatchOp::SetTxSeq(seq) => {
-                    batch.put_cf(&cf_meta, b"tx_seq", seq.to_be_bytes());
+                    batch.put_cf(cf_meta, META_TX_SEQ, seq.to_be_bytes());
                 }
                 BatchOp::SetLastIncludedTxSeq(seq) => {
-                    batch.put_cf(&cf_meta, b"last_included_tx_seq", seq.to_be_bytes());
+                    batch.put_cf(cf_meta, META_LAST_INCLUDED_TX_SEQ, seq.to_be_bytes());
                 }
             }
         }
 
         // Update latest_height if any height index entries were written.
         if let Some(height) = max_height {
-            let current = self
-                .db
-                .get_cf(&cf_meta, b"latest_height")
-                .map_err(|_| StorageError::Database)?
-                .map(|b| {
-                    let mut arr = [0u8; 8];
-                    arr.copy_from_slice(&b);
-                    u64::from_be_bytes(arr)
-                })
-                .unwrap_or(0);
+            let current = self.get_meta_u64(META_LATEST_HEIGHT)?;
             if height > current {
-                batch.put_cf(&cf_meta, b"latest_height", height.to_be_bytes());
+                batch.put_cf(cf_meta, META_LATEST_HEIGHT, height.to_be_bytes());
             }
         }
 
-        self.db.write(batch).map_err(|_| StorageError::Database)
+        self.db.write(batch).map_err(|e| StorageError::Batch {
+            kind: classify(&e),
+            source: Box::new(e),
+        })
     }
 }
@@ -0,0 +1,135 @@
+//! On-disk schema versioning and migrations for [`RocksDbStorage`].
+//!
+//! The schema version is stored as a big-endian `u32` under the
+//! [`META_SCHEMA_VERSION`] key of the `meta` column family. Databases
+//! written before versioning existed carry no such key and are treated as
+//! version 0.
+//!
+//! Every change to the encoding of a stored value, or to the set of column
+//! families, must bump [`SCHEMA_VERSION`] and append a [`Migration`] to
+//! [`MIGRATIONS`] that upgrades a database from the previous version.
+//!
+//! [`RocksDbStorage`]: crate::RocksDbStorage
+
+use crate::rocksdb::{
+    add_receipt_blooms, add_receipt_gas, backfill_address_index, backfill_block_blooms,
+    backfill_receipts, RocksDbStorage,
+};
+use crate::storage::StorageError;
+
+/// Current on-disk schema version written by this build.
+pub const SCHEMA_VERSION: u32 = 7;
+
+/// Metadata key holding the on-disk schema version.
+pub const META_SCHEMA_VERSION: &str = "schema_version";
+
+/// A single upgrade step from schema version `from` to `from + 1`.
+pub struct Migration {
+    /// Schema version this step upgrades from.
+    pub from: u32,
+    /// Short human-readable summary of the change.
+    pub description: &'static str,
+    /// Rewrites the database in place. Runs with every column family open.
+    pub run: fn(&RocksDbStorage) -> Result<(), StorageError>,
+}
+
+/// Registry of migration steps, ordered by [`Migration::from`].
+///
+/// Step `i` upgrades from version `i` to version `i + 1`.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        from: 0,
+        description: "record schema version in meta column family",
+        run: |_| Ok(()),
+    },
+    Migration {
+        from: 1,
+        description: "add receipts column family and backfill receipts from blocks",
+        run: backfill_receipts,
+    },
+    Migration {
+        from: 2,
+        description: "add address_tx_index column family and backfill it from blocks",
+        run: backfill_address_index,
+    },
+    Migration {
+        from: 3,
+        description: "add code, contracts and contract_storage column families",
+        run: |_| Ok(()),
+    },
+    Migration {
+        from: 4,
+        description: "add log blooms to receipts",
+        run: add_receipt_blooms,
+    },
+    Migration {
+        from: 5,
+        description: "record gas used and failure reason in receipts",
+        run: add_receipt_gas,
+    },
+    Migration {
+        from: 6,
+        description: "add block_blooms column family and backfill it from receipts",
+        run: backfill_block_blooms,
+    },
+];
+
+/// Outcome of bringing a database up to [`SCHEMA_VERSION`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Schema version found on disk before migrating.
+    pub from: u32,
+    /// Schema version on disk after migrating.
+    pub to: u32,
+    /// Descriptions of the steps that were applied, in order.
+    pub applied: Vec<&'static str>,
+    /// Whether the database was freshly created by this open.
+    pub created: bool,
+}
+
+impl MigrationReport {
+    /// Returns `true` if any migration step was applied.
+    #[must_use]
+    pub fn migrated(&self) -> bool {
+        !self.applied.is_empty()
+    }
+}
+
+/// Returns the migration steps needed to upgrade from `version` to
+/// [`SCHEMA_VERSION`].
+///
+/// # Errors
+///
+/// Returns [`StorageError::UnsupportedSchemaVersion`] if `version` is newer
+/// than this build supports.
+pub fn pending(version: u32) -> Result<&'static [Migration], StorageError> {
+    if version > SCHEMA_VERSION {
+        return Err(StorageError::UnsupportedSchemaVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    Ok(&MIGRATIONS[version as usize..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_covers_every_version() {
+        assert_eq!(MIGRATIONS.len(), SCHEMA_VERSION as usize);
+        for (i, m) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(m.from as usize, i, "migration {i} is out of order");
+        }
+    }
+
+    #[test]
+    fn pending_rejects_newer_version() {
+        assert!(pending(SCHEMA_VERSION).unwrap().is_empty());
+        assert!(matches!(
+            pending(SCHEMA_VERSION + 1),
+            Err(StorageError::UnsupportedSchemaVersion { .. })
+        ));
+    }
+}
@@ -1,16 +1,327 @@
 //! Storage trait and error types for Mbongo Chain persistence.
 
-use mbongo_core::{Account, Address, Block, Hash, Transaction};
+use mbongo_core::{Account, Address, Block, Bloom, Hash, Receipt, Transaction};
+use parity_scale_codec::Decode;
+
+/// Identifies the record a storage operation was acting on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    /// An account keyed by address.
+    Account(Address),
+    /// A block keyed by hash.
+    Block(Hash),
+    /// A transaction keyed by hash.
+    Transaction(Hash),
+    /// A transaction receipt keyed by transaction hash.
+    Receipt(Hash),
+    /// An entry in the height → block-hash index.
+    Height(u64),
+    /// The aggregate log bloom of the block at a height.
+    BlockBloom(u64),
+    /// An entry in the tx sequence → tx hash index.
+    TxSeq(u64),
+    /// An entry in the address → transaction index at a block height.
+    AddressTx(Address, u64),
+    /// Contract code keyed by its hash.
+    Code(Hash),
+    /// The code hash of a deployed contract.
+    Contract(Address),
+    /// A storage slot of a contract.
+    ContractStorage(Address),
+    /// A fixed metadata key (e.g. `latest_height`).
+    Meta(&'static str),
+}
+
+impl std::fmt::Display for KeyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Account(address) => write!(f, "account {address}"),
+            Self::Block(hash) => write!(f, "block {hash}"),
+            Self::Transaction(hash) => write!(f, "transaction {hash}"),
+            Self::Receipt(hash) => write!(f, "receipt {hash}"),
+            Self::Height(height) => write!(f, "height {height}"),
+            Self::BlockBloom(height) => write!(f, "block bloom at height {height}"),
+            Self::TxSeq(seq) => write!(f, "tx seq {seq}"),
+            Self::AddressTx(address, height) => {
+                write!(f, "address index {address} at height {height}")
+            }
+            Self::Code(hash) => write!(f, "code {hash}"),
+            Self::Contract(address) => write!(f, "contract {address}"),
+            Self::ContractStorage(address) => write!(f, "storage slot of contract {address}"),
+            Self::Meta(key) => write!(f, "meta key `{key}`"),
+        }
+    }
+}
+
+/// Broad category of a database engine failure.
+///
+/// Lets operators distinguish on-disk corruption from transient lock
+/// contention and from I/O problems such as a full disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbErrorKind {
+    /// The engine detected corrupted data on disk.
+    Corruption,
+    /// The operation conflicted with a lock or timed out; retrying may succeed.
+    Busy,
+    /// An I/O error (disk full, permission denied, missing files, ...).
+    Io,
+    /// The engine rejected the arguments or options it was given.
+    InvalidArgument,
+    /// Any other engine failure.
+    Other,
+}
+
+impl std::fmt::Display for DbErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Corruption => "corruption",
+            Self::Busy => "busy",
+            Self::Io => "io",
+            Self::InvalidArgument => "invalid argument",
+            Self::Other => "other",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Boxed underlying error from a storage engine.
+pub type BoxedSource = Box<dyn std::error::Error + Send + Sync>;
 
 /// Errors returned by storage operations.
 #[derive(Debug, thiserror::Error)]
 pub enum StorageError {
-    /// An error occurred in the underlying database engine.
-    #[error("Database error")]
-    Database,
-    /// Serialization or deserialization failed.
-    #[error("Serialization error")]
-    Serialization,
+    /// The database could not be opened.
+    #[error("failed to open database at {path} ({kind}): {source}")]
+    Open {
+        /// Filesystem path of the database.
+        path: String,
+        /// Category of the engine failure.
+        kind: DbErrorKind,
+        /// Underlying engine error.
+        #[source]
+        source: BoxedSource,
+    },
+    /// A column family expected by this backend is missing.
+    #[error("missing column family `{0}`")]
+    MissingColumnFamily(&'static str),
+    /// A read or write of a single record failed in the database engine.
+    #[error("database error on {key} in column family `{cf}` ({kind}): {source}")]
+    Database {
+        /// Column family being accessed.
+        cf: &'static str,
+        /// Record being accessed.
+        key: KeyKind,
+        /// Category of the engine failure.
+        kind: DbErrorKind,
+        /// Underlying engine error.
+        #[source]
+        source: BoxedSource,
+    },
+    /// An atomic write batch failed to commit.
+    #[error("write batch failed ({kind}): {source}")]
+    Batch {
+        /// Category of the engine failure.
+        kind: DbErrorKind,
+        /// Underlying engine error.
+        #[source]
+        source: BoxedSource,
+    },
+    /// A stored value could not be SCALE-decoded.
+    #[error("failed to decode {key} in column family `{cf}`: {source}")]
+    Decode {
+        /// Column family the value was read from.
+        cf: &'static str,
+        /// Record that failed to decode.
+        key: KeyKind,
+        /// Underlying SCALE codec error.
+        #[source]
+        source: parity_scale_codec::Error,
+    },
+    /// A fixed-size stored value (index entry or counter) has the wrong length.
+    #[error("corrupt {key} in column family `{cf}`: expected {expected} bytes, got {got}")]
+    CorruptValue {
+        /// Column family the value was read from.
+        cf: &'static str,
+        /// Record with the unexpected length.
+        key: KeyKind,
+        /// Expected length in bytes.
+        expected: usize,
+        /// Actual length in bytes.
+        got: usize,
+    },
+    /// An in-memory lock was poisoned by a panicking writer.
+    #[error("lock poisoned for column family `{0}`")]
+    LockPoisoned(&'static str),
+    /// The database was written by a newer build with an unknown schema.
+    #[error(
+        "database schema version {found} is newer than the supported version {supported}; \
+         upgrade mbongo-node to open it"
+    )]
+    UnsupportedSchemaVersion {
+        /// Schema version recorded on disk.
+        found: u32,
+        /// Latest schema version this build understands.
+        supported: u32,
+    },
+    /// A schema migration step failed; the database is left at version `from`.
+    #[error("schema migration from version {from} ({description}) failed: {source}")]
+    Migration {
+        /// Schema version the failing step upgrades from.
+        from: u32,
+        /// Description of the failing step.
+        description: &'static str,
+        /// Underlying storage error.
+        #[source]
+        source: Box<StorageError>,
+    },
+}
+
+impl StorageError {
+    /// Returns the column family involved in the failure, if any.
+    #[must_use]
+    pub fn column_family(&self) -> Option<&'static str> {
+        match self {
+            Self::MissingColumnFamily(cf) | Self::LockPoisoned(cf) => Some(cf),
+            Self::Database { cf, .. } | Self::Decode { cf, .. } | Self::CorruptValue { cf, .. } => {
+                Some(cf)
+            }
+            Self::Migration { source, .. } => source.column_family(),
+            Self::Open { .. } | Self::Batch { .. } | Self::UnsupportedSchemaVersion { .. } => None,
+        }
+    }
+
+    /// Returns the record involved in the failure, if any.
+    #[must_use]
+    pub fn key(&self) -> Option<KeyKind> {
+        match self {
+            Self::Database { key, .. }
+            | Self::Decode { key, .. }
+            | Self::CorruptValue { key, .. } => Some(*key),
+            Self::Migration { source, .. } => source.key(),
+            _ => None,
+        }
+    }
+
+    /// Returns the engine failure category, if the error came from the database engine.
+    #[must_use]
+    pub fn db_kind(&self) -> Option<DbErrorKind> {
+        match self {
+            Self::Open { kind, .. } | Self::Database { kind, .. } | Self::Batch { kind, .. } => {
+                Some(*kind)
+            }
+            Self::Migration { source, .. } => source.db_kind(),
+            _ => None,
+        }
+    }
+}
+
+/// SCALE-decodes a stored value, attaching the column family and key on failure.
+pub(crate) fn decode<T: Decode>(
+    cf: &'static str,
+    key: KeyKind,
+    bytes: &[u8],
+) -> Result<T, StorageError> {
+    T::decode(&mut &bytes[..]).map_err(|source| StorageError::Decode { cf, key, source })
+}
+
+/// Reads a fixed-size value (hash or big-endian counter), rejecting wrong lengths.
+pub(crate) fn fixed<const N: usize>(
+    cf: &'static str,
+    key: KeyKind,
+    bytes: &[u8],
+) -> Result<[u8; N], StorageError> {
+    bytes.try_into().map_err(|_| StorageError::CorruptValue {
+        cf,
+        key,
+        expected: N,
+        got: bytes.len(),
+    })
+}
+
+/// Length of a key in the address → transaction index: address, then
+/// big-endian block height, then big-endian position in the block.
+const ADDRESS_TX_KEY_LEN: usize = 32 + 8 + 4;
+
+/// One transaction sent or received by an address, as recorded in the
+/// address → transaction index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressTxEntry {
+    /// Height of the block that included the transaction.
+    pub height: u64,
+    /// Position of the transaction in the block body.
+    pub index: u32,
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+}
+
+/// Builds the address → transaction index key for `address` at
+/// (`height`, `index`). Keys sort by address, then height, then index.
+pub(crate) fn address_tx_key(address: &Address, height: u64, index: u32) -> Vec<u8> {
+    let mut key = Vec::with_capacity(ADDRESS_TX_KEY_LEN);
+    key.extend_from_slice(&address.0);
+    key.extend_from_slice(&height.to_be_bytes());
+    key.extend_from_slice(&index.to_be_bytes());
+    key
+}
+
+/// Key from which to scan the address → transaction index backwards to
+/// list `address`'s entries below height `before`, or `None` if there are
+/// none.
+pub(crate) fn address_tx_seek_key(address: &Address, before: Option<u64>) -> Option<Vec<u8>> {
+    match before {
+        Some(0) => None,
+        Some(before) => Some(address_tx_key(address, before - 1, u32::MAX)),
+        None => Some(address_tx_key(address, u64::MAX, u32::MAX)),
+    }
+}
+
+/// Collects one page of [`Storage::get_address_transactions`] from
+/// `entries`, an iterator over the address → transaction index walking
+/// backwards from [`address_tx_seek_key`].
+pub(crate) fn address_tx_page<K, V>(
+    cf: &'static str,
+    address: &Address,
+    limit: usize,
+    entries: impl Iterator<Item = Result<(K, V), StorageError>>,
+) -> Result<Vec<AddressTxEntry>, StorageError>
+where
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut page: Vec<AddressTxEntry> = Vec::new();
+    for entry in entries {
+        let (key, value) = entry?;
+        let key = key.as_ref();
+        if !key.starts_with(&address.0) {
+            break;
+        }
+        let suffix: [u8; 12] = fixed(cf, KeyKind::AddressTx(*address, 0), &key[32..])?;
+        let height = u64::from_be_bytes(suffix[..8].try_into().expect("8-byte slice"));
+        let index = u32::from_be_bytes(suffix[8..].try_into().expect("4-byte slice"));
+        if page.len() >= limit && page.last().is_some_and(|last| last.height != height) {
+            break;
+        }
+        let tx_hash = Hash(fixed(
+            cf,
+            KeyKind::AddressTx(*address, height),
+            value.as_ref(),
+        )?);
+        page.push(AddressTxEntry {
+            height,
+            index,
+            tx_hash,
+        });
+    }
+    Ok(page)
+}
+
+/// Builds the contract storage key for `key` in `contract`: the contract
+/// address followed by the raw slot key.
+pub(crate) fn contract_storage_key(contract: &Address, key: &[u8]) -> Vec<u8> {
+    let mut composite = Vec::with_capacity(32 + key.len());
+    composite.extend_from_slice(&contract.0);
+    composite.extend_from_slice(key);
+    composite
 }
 
 /// A single atomic operation within a [`Storage::write_batch`] call.
@@ -21,10 +332,24 @@ pub enum BatchOp {
     PutBlock(Hash, Block),
     /// Persist a transaction keyed by its hash.
     PutTransaction(Hash, Transaction),
+    /// Persist a transaction receipt keyed by the transaction hash.
+    PutReceipt(Hash, Receipt),
     /// Store a mapping from block height to block hash and update latest height.
     PutBlockHeightIndex(u64, Hash),
+    /// Store the union of the receipt blooms of the block at a height.
+    PutBlockBloom(u64, Bloom),
     /// Store a mapping from sequence number to transaction hash.
     PutTxSeqIndex(u64, Hash),
+    /// Record that the address sent or received the entry's transaction.
+    PutAddressTxIndex(Address, AddressTxEntry),
+    /// Persist contract code keyed by its blake3 hash.
+    PutCode(Hash, Vec<u8>),
+    /// Record the code hash of a contract deployed at the address.
+    PutContract(Address, Hash),
+    /// Set a storage slot of a contract.
+    PutContractStorage(Address, Vec<u8>, Vec<u8>),
+    /// Clear a storage slot of a contract.
+    DeleteContractStorage(Address, Vec<u8>),
     /// Set the transaction sequence counter to a specific value.
     SetTxSeq(u64),
     /// Set the last transaction sequence number included in a block.
@@ -37,7 +362,10 @@ pub enum BatchOp {
 /// deserialize them on retrieval. Keys are derived from the domain
 /// identifiers ([`Address`] for accounts, [`Hash`] for blocks and
 /// transactions).
-pub trait Storage {
+///
+/// Implementations must be shareable across threads: block execution reads
+/// accounts from several threads at once.
+pub trait Storage: Send + Sync {
     /// Retrieve an account by address.
     ///
     /// # Errors
@@ -80,6 +408,13 @@ pub trait Storage {
     /// Returns [`StorageError`] on database or serialization failure.
     fn put_transaction(&self, hash: &Hash, tx: &Transaction) -> Result<(), StorageError>;
 
+    /// Retrieve the receipt of an included transaction by transaction hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database or deserialization failure.
+    fn get_receipt(&self, tx_hash: &Hash) -> Result<Option<Receipt>, StorageError>;
+
     /// Retrieve a block by its height via the height→hash index.
     ///
     /// # Errors
@@ -94,6 +429,16 @@ pub trait Storage {
     /// Returns [`StorageError`] on database or serialization failure.
     fn put_block_height_index(&self, height: u64, hash: Hash) -> Result<(), StorageError>;
 
+    /// Retrieve the union of the receipt blooms of the block at `height`.
+    ///
+    /// `None` means no bloom was recorded for the height, not that the
+    /// block has no logs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database or deserialization failure.
+    fn get_block_bloom(&self, height: u64) -> Result<Option<Bloom>, StorageError>;
+
     /// Return the latest persisted block height, or 0 if no blocks exist.
     ///
     /// # Errors
@@ -124,6 +469,48 @@ pub trait Storage {
     /// Returns [`StorageError`] on database failure.
     fn get_tx_hash_by_seq(&self, seq: u64) -> Result<Option<Hash>, StorageError>;
 
+    /// Return the transactions `address` sent or received, newest first.
+    ///
+    /// Only blocks below height `before` are listed, or all blocks if
+    /// `None`. Whole blocks are returned until at least `limit` entries have
+    /// been collected, so a page never ends partway through a block and may
+    /// hold more than `limit` entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database failure or a malformed entry.
+    fn get_address_transactions(
+        &self,
+        address: &Address,
+        before: Option<u64>,
+        limit: usize,
+    ) -> Result<Vec<AddressTxEntry>, StorageError>;
+
+    /// Retrieve contract code by its blake3 hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database failure.
+    fn get_code(&self, code_hash: &Hash) -> Result<Option<Vec<u8>>, StorageError>;
+
+    /// Retrieve the code hash of the contract deployed at `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database failure or a malformed entry.
+    fn get_contract(&self, address: &Address) -> Result<Option<Hash>, StorageError>;
+
+    /// Retrieve the value stored under `key` by the contract at `contract`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`StorageError`] on database failure.
+    fn get_contract_storage(
+        &self,
+        contract: &Address,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, StorageError>;
+
     /// Return the last transaction sequence number included in a block, or 0 if none.
     ///
     /// # Errors
@@ -25,6 +25,8 @@ fn main() {
         amount: 100,
         nonce: 0,
         signature: [0u8; 64],
+        multisig: None,
+        data: Vec::new(),
     };
 
     // Sign the SCALE-encoded payload.
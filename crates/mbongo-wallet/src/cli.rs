@@ -0,0 +1,730 @@
+//! `mbongo-wallet` command-line interface.
+//!
+//! Keys live in an encrypted [`Keystore`] directory, `~/.mbongo/wallets` by
+//! default. Commands that talk to the chain use the node's JSON-RPC endpoint
+//! given by `--rpc-url`. Amounts are in the chain's base unit, exactly as
+//! stored in [`Transaction::amount`](mbongo_core::Transaction::amount).
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use mbongo_core::{Address, Multisig, Transaction, TransactionType};
+use zeroize::Zeroizing;
+
+use crate::hd::{self, DerivationPath};
+use crate::keystore::{KeyInfo, Keystore, KeystoreFile};
+use crate::mnemonic::Mnemonic;
+use crate::offline::{ChainInfo, TransactionFile};
+use crate::rpc::{RpcClient, DEFAULT_RPC_URL};
+use crate::signing;
+
+/// How often `--wait` polls the node.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Top-level command-line arguments.
+#[derive(Parser, Debug)]
+#[command(name = "mbongo-wallet")]
+#[command(author = "Mbongo Chain Contributors")]
+#[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(about = "Mbongo Chain wallet", long_about = None)]
+pub struct Cli {
+    /// Keystore directory (default: ~/.mbongo/wallets)
+    #[arg(long, global = true)]
+    pub wallet_dir: Option<PathBuf>,
+
+    /// Node JSON-RPC endpoint
+    #[arg(long, global = true, default_value = DEFAULT_RPC_URL)]
+    pub rpc_url: String,
+
+    /// Command to run
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Wallet commands.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a wallet from a fresh recovery phrase
+    Create {
+        /// Wallet name
+        #[arg(short, long, default_value = "default")]
+        name: String,
+        /// Recovery phrase length (12, 15, 18, 21 or 24 words)
+        #[arg(long, default_value_t = 24)]
+        words: usize,
+        /// Read the password from this file instead of prompting
+        #[arg(long)]
+        password_file: Option<PathBuf>,
+    },
+    /// Restore a wallet from a recovery phrase
+    Restore {
+        /// Wallet name
+        #[arg(short, long, default_value = "restored")]
+        name: String,
+        /// Read the recovery phrase from this file instead of prompting
+        #[arg(long)]
+        mnemonic_file: Option<PathBuf>,
+        /// Read the password from this file instead of prompting
+        #[arg(long)]
+        password_file: Option<PathBuf>,
+        /// HD derivation path; every component must be hardened
+        #[arg(long, default_value_t = DerivationPath::default())]
+        derivation_path: DerivationPath,
+    },
+    /// Show wallet addresses
+    Address {
+        /// Wallet name
+        #[arg(short, long, default_value = "default")]
+        name: String,
+        /// List every wallet in the keystore
+        #[arg(long)]
+        all: bool,
+    },
+    /// Show the balance and next nonce of a wallet or address
+    Balance {
+        /// Wallet name
+        #[arg(short, long, default_value = "default")]
+        name: String,
+        /// Query this address instead of a wallet
+        #[arg(short, long)]
+        address: Option<Address>,
+    },
+    /// Send funds to another address
+    Transfer {
+        /// Recipient address
+        #[arg(short, long)]
+        to: Address,
+        /// Signing and submission options
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Stake funds with a validator
+    Stake {
+        /// Validator address
+        #[arg(long)]
+        validator: Address,
+        /// Signing and submission options
+        #[command(flatten)]
+        tx: TxArgs,
+    },
+    /// Build an unsigned transaction file for offline signing
+    Build {
+        /// Transaction type
+        #[arg(long = "type", value_enum, default_value_t = TxKind::Transfer)]
+        kind: TxKind,
+        /// Sender address; its key signs the file offline
+        #[arg(long, required_unless_present = "threshold")]
+        sender: Option<Address>,
+        /// Send from a multisig account instead of `--sender`
+        #[command(flatten)]
+        multisig: MultisigArgs,
+        /// Recipient, or validator for stake transactions
+        #[arg(short, long)]
+        to: Address,
+        /// Amount in base units
+        #[arg(short, long)]
+        amount: u128,
+        /// Nonce to use instead of asking the node
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Where to write the unsigned transaction file
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Sign a transaction file without contacting the node
+    Sign {
+        /// Unsigned transaction file written by `build`
+        file: PathBuf,
+        /// Wallet holding the sender's key, or a multisig member's key
+        #[arg(short, long, default_value = "default")]
+        from: String,
+        /// Where to write the signed transaction file
+        #[arg(short, long)]
+        out: PathBuf,
+        /// Read the password from this file instead of prompting
+        #[arg(long)]
+        password_file: Option<PathBuf>,
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Merge multisig member signatures from several transaction files
+    Combine {
+        /// Files for the same transaction, each signed by some members
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<PathBuf>,
+        /// Where to write the combined transaction file
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+    /// Print the address of a multisig account
+    MultisigAddress {
+        /// Number of member signatures required
+        #[arg(long)]
+        threshold: u8,
+        /// Member address; repeat once per member
+        #[arg(long = "member", required = true)]
+        members: Vec<Address>,
+    },
+    /// Submit a transaction file signed by `sign`
+    Broadcast {
+        /// Signed transaction file
+        file: PathBuf,
+        /// Wait until the transaction is included in a block
+        #[arg(long)]
+        wait: bool,
+        /// Seconds to wait for inclusion with --wait
+        #[arg(long, default_value_t = 60)]
+        timeout: u64,
+    },
+}
+
+/// Transaction types the wallet can build.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxKind {
+    /// Transfer funds
+    Transfer,
+    /// Stake with a validator
+    Stake,
+}
+
+impl From<TxKind> for TransactionType {
+    fn from(kind: TxKind) -> Self {
+        match kind {
+            TxKind::Transfer => Self::Transfer,
+            TxKind::Stake => Self::Stake,
+        }
+    }
+}
+
+/// Multisig account to build a transaction from.
+#[derive(Args, Debug)]
+pub struct MultisigArgs {
+    /// Number of member signatures required
+    #[arg(long, requires = "members")]
+    pub threshold: Option<u8>,
+    /// Member address; repeat once per member
+    #[arg(long = "member", requires = "threshold")]
+    pub members: Vec<Address>,
+}
+
+/// Arguments shared by commands that sign and submit a transaction.
+#[derive(Args, Debug)]
+pub struct TxArgs {
+    /// Wallet to send from
+    #[arg(short, long, default_value = "default")]
+    pub from: String,
+    /// Amount in base units
+    #[arg(short, long)]
+    pub amount: u128,
+    /// Nonce to use instead of asking the node
+    #[arg(long)]
+    pub nonce: Option<u64>,
+    /// Read the password from this file instead of prompting
+    #[arg(long)]
+    pub password_file: Option<PathBuf>,
+    /// Skip the confirmation prompt
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Wait until the transaction is included in a block
+    #[arg(long)]
+    pub wait: bool,
+    /// Seconds to wait for inclusion with --wait
+    #[arg(long, default_value_t = 60)]
+    pub timeout: u64,
+}
+
+/// Runs a parsed command line.
+///
+/// # Errors
+///
+/// Returns an error describing why the command failed.
+pub async fn run(cli: Cli) -> anyhow::Result<()> {
+    let wallet_dir = match cli.wallet_dir {
+        Some(dir) => dir,
+        None => default_wallet_dir()?,
+    };
+    let keystore = || {
+        Keystore::open(&wallet_dir)
+            .with_context(|| format!("cannot open keystore {}", wallet_dir.display()))
+    };
+    let rpc = RpcClient::new(cli.rpc_url);
+
+    match cli.command {
+        Command::Create {
+            name,
+            words,
+            password_file,
+        } => create(&keystore()?, &name, words, password_file.as_deref()),
+        Command::Restore {
+            name,
+            mnemonic_file,
+            password_file,
+            derivation_path,
+        } => restore(
+            &keystore()?,
+            &name,
+            mnemonic_file.as_deref(),
+            password_file.as_deref(),
+            &derivation_path,
+        ),
+        Command::Address { name, all } => address(&keystore()?, &name, all),
+        Command::Balance { name, address } => {
+            let address = match address {
+                Some(address) => address,
+                None => keystore()?.load(&name)?.address,
+            };
+            balance(&rpc, address).await
+        }
+        Command::Transfer { to, tx } => {
+            send(&keystore()?, &rpc, TransactionType::Transfer, to, tx).await
+        }
+        Command::Stake { validator, tx } => {
+            send(&keystore()?, &rpc, TransactionType::Stake, validator, tx).await
+        }
+        Command::Build {
+            kind,
+            sender,
+            multisig,
+            to,
+            amount,
+            nonce,
+            out,
+        } => {
+            let tx = draft(kind.into(), sender, multisig, to, amount)?;
+            build(&rpc, tx, nonce, &out).await
+        }
+        Command::Sign {
+            file,
+            from,
+            out,
+            password_file,
+            yes,
+        } => sign(
+            &keystore()?,
+            &file,
+            &from,
+            &out,
+            password_file.as_deref(),
+            yes,
+        ),
+        Command::Combine { files, out } => combine(&files, &out),
+        Command::MultisigAddress { threshold, members } => {
+            let config = Multisig::new(threshold, members)?;
+            println!("{}", config.address());
+            Ok(())
+        }
+        Command::Broadcast {
+            file,
+            wait,
+            timeout,
+        } => broadcast(&rpc, &file, wait, timeout).await,
+    }
+}
+
+fn create(
+    keystore: &Keystore,
+    name: &str,
+    words: usize,
+    password_file: Option<&Path>,
+) -> anyhow::Result<()> {
+    let mnemonic = Mnemonic::generate(words)?;
+    let path = DerivationPath::default();
+    let password = read_password(password_file, true)?;
+    let info = store_derived(keystore, name, &mnemonic, &path, &password)?;
+
+    println!("IMPORTANT: Write down your recovery phrase and store it safely.");
+    println!("           Anyone with this phrase can access your funds.");
+    println!();
+    println!("Recovery Phrase ({words} words):");
+    println!("{}", mnemonic.phrase().as_str());
+    println!();
+    print_wallet("Wallet Created", &info, &path);
+    Ok(())
+}
+
+fn restore(
+    keystore: &Keystore,
+    name: &str,
+    mnemonic_file: Option<&Path>,
+    password_file: Option<&Path>,
+    path: &DerivationPath,
+) -> anyhow::Result<()> {
+    let phrase = match mnemonic_file {
+        Some(file) => Zeroizing::new(
+            fs::read_to_string(file).with_context(|| format!("cannot read {}", file.display()))?,
+        ),
+        None => prompt_hidden("Enter your recovery phrase: ")?,
+    };
+    let mnemonic = Mnemonic::parse(&phrase).context("invalid recovery phrase")?;
+    let password = read_password(password_file, true)?;
+    let info = store_derived(keystore, name, &mnemonic, path, &password)?;
+    print_wallet("Wallet Restored", &info, path);
+    Ok(())
+}
+
+fn store_derived(
+    keystore: &Keystore,
+    name: &str,
+    mnemonic: &Mnemonic,
+    path: &DerivationPath,
+    password: &str,
+) -> anyhow::Result<KeyInfo> {
+    let key = hd::derive_signing_key(mnemonic.to_seed("").as_slice(), path);
+    Ok(keystore.import(name, &key, password)?)
+}
+
+fn print_wallet(title: &str, info: &KeyInfo, path: &DerivationPath) {
+    println!("{title}");
+    println!("  Name:        {}", info.name);
+    println!("  Address:     {}", info.address);
+    println!("  Derivation:  {path}");
+    println!("  Keystore:    {}", info.path.display());
+}
+
+fn address(keystore: &Keystore, name: &str, all: bool) -> anyhow::Result<()> {
+    if all {
+        let keys = keystore.list()?;
+        if keys.is_empty() {
+            println!("No wallets in {}", keystore.dir().display());
+        }
+        let width = keys.iter().map(|key| key.name.len()).max().unwrap_or(0);
+        for key in keys {
+            println!("{:width$}  {}", key.name, key.address);
+        }
+    } else {
+        println!("{}", keystore.load(name)?.address);
+    }
+    Ok(())
+}
+
+async fn balance(rpc: &RpcClient, address: Address) -> anyhow::Result<()> {
+    let balance = rpc.balance(address).await?;
+    let nonce = rpc.next_nonce(address).await?;
+    println!("Address:     {address}");
+    println!("Balance:     {balance}");
+    println!("Next nonce:  {nonce}");
+    Ok(())
+}
+
+async fn send(
+    keystore: &Keystore,
+    rpc: &RpcClient,
+    tx_type: TransactionType,
+    receiver: Address,
+    args: TxArgs,
+) -> anyhow::Result<()> {
+    let file = keystore.load(&args.from)?;
+    let nonce = match args.nonce {
+        Some(nonce) => nonce,
+        None => rpc.next_nonce(file.address).await?,
+    };
+    let mut tx = signing::unsigned_transaction(tx_type, file.address, receiver, args.amount, nonce);
+
+    println!("{}", signing::describe(&tx));
+    if !args.yes && !confirm("Submit this transaction? [y/N]: ")? {
+        bail!("aborted");
+    }
+
+    let key = unlock(&file, args.password_file.as_deref())?;
+    signing::sign_transaction(&mut tx, &key)?;
+    submit(rpc, &tx, args.wait, args.timeout).await
+}
+
+/// Builds an unsigned transaction from `sender` or from the multisig account
+/// in `multisig`, with nonce 0 until [`build`] fills it in.
+fn draft(
+    tx_type: TransactionType,
+    sender: Option<Address>,
+    multisig: MultisigArgs,
+    receiver: Address,
+    amount: u128,
+) -> anyhow::Result<Transaction> {
+    let Some(threshold) = multisig.threshold else {
+        let sender = sender.context("either --sender or --threshold is required")?;
+        return Ok(signing::unsigned_transaction(
+            tx_type, sender, receiver, amount, 0,
+        ));
+    };
+    let config = Multisig::new(threshold, multisig.members)?;
+    if let Some(sender) = sender {
+        if sender != config.address() {
+            bail!(
+                "--sender is {sender}, but the multisig account's address is {}",
+                config.address()
+            );
+        }
+    }
+    Ok(signing::multisig_transaction(
+        tx_type, config, receiver, amount, 0,
+    ))
+}
+
+async fn build(
+    rpc: &RpcClient,
+    mut tx: Transaction,
+    nonce: Option<u64>,
+    out: &Path,
+) -> anyhow::Result<()> {
+    tx.nonce = match nonce {
+        Some(nonce) => nonce,
+        None => rpc.next_nonce(tx.sender).await?,
+    };
+    let chain = ChainInfo {
+        genesis_hash: rpc.genesis_hash().await?,
+        height: rpc.block_height().await?,
+    };
+    let file = TransactionFile::unsigned(tx, chain);
+    file.write(out)?;
+    println!("{}", file.summary);
+    println!("Unsigned transaction written to {}", out.display());
+    Ok(())
+}
+
+/// Signs a transaction file. Deliberately takes no [`RpcClient`]: this runs
+/// on the offline machine.
+fn sign(
+    keystore: &Keystore,
+    path: &Path,
+    from: &str,
+    out: &Path,
+    password_file: Option<&Path>,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let mut file = TransactionFile::read(path)?;
+    let keys = keystore.load(from)?;
+    match &file.transaction.multisig {
+        Some(witness) if !witness.config.is_member(&keys.address) => bail!(
+            "wallet `{from}` is {}, which is not a member of multisig account {}",
+            keys.address,
+            file.transaction.sender
+        ),
+        None if keys.address != file.transaction.sender => bail!(
+            "wallet `{from}` is {}, but the transaction is sent from {}",
+            keys.address,
+            file.transaction.sender
+        ),
+        _ => {}
+    }
+
+    println!("{}", file.summary);
+    if !yes && !confirm("Sign this transaction? [y/N]: ")? {
+        bail!("aborted");
+    }
+    file.sign(&unlock(&keys, password_file)?)?;
+    file.write(out)?;
+    print_signing_state(&file, out);
+    Ok(())
+}
+
+/// Merges the multisig signatures of `files` into one file. Needs neither
+/// keys nor the node.
+fn combine(files: &[PathBuf], out: &Path) -> anyhow::Result<()> {
+    let (first, rest) = files.split_first().context("no transaction files given")?;
+    let mut combined =
+        TransactionFile::read(first).with_context(|| format!("cannot read {}", first.display()))?;
+    for path in rest {
+        let other = TransactionFile::read(path)
+            .with_context(|| format!("cannot read {}", path.display()))?;
+        combined
+            .combine(&other)
+            .with_context(|| format!("cannot combine {}", path.display()))?;
+    }
+    combined.write(out)?;
+    println!("{}", combined.summary);
+    print_signing_state(&combined, out);
+    Ok(())
+}
+
+fn print_signing_state(file: &TransactionFile, out: &Path) {
+    match file.hash {
+        Some(hash) => {
+            println!("Signed:      {hash}");
+            println!("Signed transaction written to {}", out.display());
+        }
+        None => println!(
+            "More member signatures are needed; partially signed transaction written to {}",
+            out.display()
+        ),
+    }
+}
+
+async fn broadcast(rpc: &RpcClient, path: &Path, wait: bool, timeout: u64) -> anyhow::Result<()> {
+    let file = TransactionFile::read(path)?;
+    let tx = file.signed_transaction()?;
+    let genesis = rpc.genesis_hash().await?;
+    if genesis != file.chain.genesis_hash {
+        bail!(
+            "transaction was built for the chain with genesis {}, but {} serves genesis {genesis}",
+            file.chain.genesis_hash,
+            rpc.url()
+        );
+    }
+    println!("{}", file.summary);
+    submit(rpc, tx, wait, timeout).await
+}
+
+/// Submits a signed transaction, printing its hash, and optionally waits
+/// for it to be included.
+async fn submit(rpc: &RpcClient, tx: &Transaction, wait: bool, timeout: u64) -> anyhow::Result<()> {
+    let hash = rpc.submit_transaction(tx).await?;
+    println!("Submitted:   {hash}");
+
+    if wait {
+        println!("Waiting for inclusion...");
+        let lookup = rpc
+            .wait_for_inclusion(hash, Duration::from_secs(timeout), POLL_INTERVAL)
+            .await?;
+        match lookup.block_height {
+            Some(height) => println!("Included in block #{height}"),
+            None => println!("Included"),
+        }
+    }
+    Ok(())
+}
+
+fn unlock(
+    file: &KeystoreFile,
+    password_file: Option<&Path>,
+) -> anyhow::Result<ed25519_dalek::SigningKey> {
+    let password = read_password(password_file, false)?;
+    Ok(file.decrypt(&password)?)
+}
+
+/// Home of the default keystore, `~/.mbongo/wallets`.
+fn default_wallet_dir() -> anyhow::Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .context("cannot find the home directory; pass --wallet-dir")?;
+    Ok(PathBuf::from(home).join(".mbongo").join("wallets"))
+}
+
+/// Reads a password from `file`, or prompts for it (twice if `confirm`).
+fn read_password(file: Option<&Path>, confirm: bool) -> anyhow::Result<Zeroizing<String>> {
+    if let Some(file) = file {
+        let contents = Zeroizing::new(
+            fs::read_to_string(file).with_context(|| format!("cannot read {}", file.display()))?,
+        );
+        return Ok(Zeroizing::new(
+            contents.trim_end_matches(['\r', '\n']).to_string(),
+        ));
+    }
+    let password = prompt_hidden("Password: ")?;
+    if confirm && *prompt_hidden("Confirm password: ")? != *password {
+        bail!("passwords do not match");
+    }
+    Ok(password)
+}
+
+/// Asks a yes/no question on the terminal; anything but `y` or `yes` is no.
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    eprint!("{question}");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// Reads a line from stdin without echoing it when stdin is a terminal.
+fn prompt_hidden(prompt: &str) -> anyhow::Result<Zeroizing<String>> {
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+    let echo_disabled = set_echo(false);
+    let mut line = Zeroizing::new(String::new());
+    let read = io::stdin().lock().read_line(&mut line);
+    if echo_disabled {
+        set_echo(true);
+        eprintln!();
+    }
+    read?;
+    Ok(Zeroizing::new(
+        line.trim_end_matches(['\r', '\n']).to_string(),
+    ))
+}
+
+/// Turns terminal echo on or off; returns whether it succeeded.
+#[cfg(unix)]
+fn set_echo(on: bool) -> bool {
+    use std::io::IsTerminal;
+    use std::process::{Command as Process, Stdio};
+
+    io::stdin().is_terminal()
+        && Process::new("stty")
+            .arg(if on { "echo" } else { "-echo" })
+            .stdin(Stdio::inherit())
+            .status()
+            .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(unix))]
+fn set_echo(_on: bool) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_transfer_flags() {
+        let cli = Cli::try_parse_from([
+            "mbongo-wallet",
+            "--rpc-url",
+            "http://node:9944/rpc",
+            "transfer",
+            "-t",
+            "0x2222222222222222222222222222222222222222222222222222222222222222",
+            "-a",
+            "100",
+            "--from",
+            "alice",
+            "--wait",
+            "-y",
+        ])
+        .unwrap();
+        assert_eq!(cli.rpc_url, "http://node:9944/rpc");
+        let Command::Transfer { to, tx } = cli.command else {
+            panic!("expected transfer, got {:?}", cli.command);
+        };
+        assert_eq!(to, Address([0x22; 32]));
+        assert_eq!(
+            (tx.from.as_str(), tx.amount, tx.nonce),
+            ("alice", 100, None)
+        );
+        assert!(tx.wait && tx.yes);
+    }
+
+    #[test]
+    fn rejects_unhardened_derivation_paths() {
+        let err = Cli::try_parse_from([
+            "mbongo-wallet",
+            "restore",
+            "--derivation-path",
+            "m/44'/60'/0'/0/0",
+        ])
+        .unwrap_err();
+        assert!(err.to_string().contains("hardened"), "{err}");
+
+        let cli = Cli::try_parse_from(["mbongo-wallet", "restore"]).unwrap();
+        let Command::Restore {
+            derivation_path, ..
+        } = cli.command
+        else {
+            panic!("expected restore");
+        };
+        assert_eq!(derivation_path, DerivationPath::default());
+    }
+}
@@ -0,0 +1,296 @@
+//! SLIP-0010 hierarchical deterministic derivation for ed25519.
+//!
+//! ed25519 only supports hardened derivation, so every path component must
+//! be hardened (`44'`). Mbongo accounts live under
+//! `m/44'/9797'/<account>'/0'/<index>'`; see [`DerivationPath::mbongo`].
+
+use std::fmt;
+use std::str::FromStr;
+
+use ed25519_dalek::SigningKey;
+use hmac::{Hmac, Mac};
+use mbongo_core::Address;
+use sha2::Sha512;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Offset added to an index to mark it hardened.
+pub const HARDENED: u32 = 1 << 31;
+/// Mbongo's BIP-44 coin type. It is not registered in SLIP-0044.
+pub const MBONGO_COIN_TYPE: u32 = 9797;
+
+/// HMAC key for the SLIP-0010 ed25519 master key.
+const MASTER_KEY: &[u8] = b"ed25519 seed";
+
+/// Errors returned when parsing a derivation path.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum HdError {
+    /// The path is not of the form `m/44'/...`.
+    #[error("invalid derivation path `{0}`")]
+    InvalidPath(String),
+    /// A component is not hardened, which ed25519 cannot derive.
+    #[error("ed25519 derivation requires hardened indices, `{0}` is not hardened")]
+    NotHardened(String),
+    /// An index is too large to harden.
+    #[error("derivation index {0} is out of range")]
+    IndexOutOfRange(u32),
+}
+
+/// A derivation path of hardened indices, such as `m/44'/9797'/0'/0'/0'`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// Builds a path from unhardened indices, hardening each one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HdError::IndexOutOfRange`] for indices of `2^31` or more.
+    pub fn new(indices: &[u32]) -> Result<Self, HdError> {
+        indices
+            .iter()
+            .map(|&index| {
+                if index >= HARDENED {
+                    Err(HdError::IndexOutOfRange(index))
+                } else {
+                    Ok(index | HARDENED)
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    /// The Mbongo path for key `index` of `account`:
+    /// `m/44'/9797'/<account>'/0'/<index>'`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HdError::IndexOutOfRange`] if `account` or `index` is `2^31`
+    /// or more.
+    pub fn mbongo(account: u32, index: u32) -> Result<Self, HdError> {
+        Self::new(&[44, MBONGO_COIN_TYPE, account, 0, index])
+    }
+
+    /// The hardened indices, including the [`HARDENED`] bit.
+    #[must_use]
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl Default for DerivationPath {
+    /// The first key of the first Mbongo account, `m/44'/9797'/0'/0'/0'`.
+    fn default() -> Self {
+        Self::mbongo(0, 0).expect("small indices are in range")
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index - HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdError;
+
+    /// Parses `m/44'/9797'/0'/0'/0'`; `h` and `H` are accepted in place of
+    /// `'`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('/');
+        if parts.next() != Some("m") {
+            return Err(HdError::InvalidPath(s.to_string()));
+        }
+        let indices = parts
+            .map(|part| {
+                let index = part
+                    .strip_suffix(['\'', 'h', 'H'])
+                    .ok_or_else(|| HdError::NotHardened(part.to_string()))?;
+                index.parse::<u32>().map_err(|_| HdError::InvalidPath(s.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(&indices)
+    }
+}
+
+/// An ed25519 private key with its SLIP-0010 chain code.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    secret: Zeroizing<[u8; 32]>,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// The master key for `seed`, usually a BIP-39 seed from
+    /// [`crate::mnemonic::Mnemonic::to_seed`].
+    #[must_use]
+    pub fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(MASTER_KEY, &[seed])
+    }
+
+    /// Derives the hardened child at `index`, which must already include the
+    /// [`HARDENED`] bit.
+    fn child(&self, index: u32) -> Self {
+        Self::from_hmac(
+            &self.chain_code,
+            &[&[0], self.secret.as_slice(), &index.to_be_bytes()],
+        )
+    }
+
+    /// Derives the key at `path` below this key.
+    #[must_use]
+    pub fn derive(&self, path: &DerivationPath) -> Self {
+        path.indices().iter().fold(self.clone(), |key, &index| key.child(index))
+    }
+
+    /// The ed25519 signing key.
+    #[must_use]
+    pub fn signing_key(&self) -> SigningKey {
+        SigningKey::from_bytes(&self.secret)
+    }
+
+    /// The address of the signing key.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        Address(self.signing_key().verifying_key().to_bytes())
+    }
+
+    /// The chain code used to derive children.
+    #[must_use]
+    pub fn chain_code(&self) -> [u8; 32] {
+        self.chain_code
+    }
+
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Self {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        for part in data {
+            mac.update(part);
+        }
+        let output = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+        let mut secret = Zeroizing::new([0u8; 32]);
+        let mut chain_code = [0u8; 32];
+        secret.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+        Self { secret, chain_code }
+    }
+}
+
+impl fmt::Debug for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedKey")
+            .field("address", &self.address())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Derives the signing key at `path` from `seed`.
+#[must_use]
+pub fn derive_signing_key(seed: &[u8], path: &DerivationPath) -> SigningKey {
+    ExtendedKey::master(seed).derive(path).signing_key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mnemonic::Mnemonic;
+
+    fn check(key: &ExtendedKey, chain_code: &str, secret: &str, public: &str) {
+        assert_eq!(hex::encode(key.chain_code()), chain_code);
+        assert_eq!(hex::encode(key.signing_key().to_bytes()), secret);
+        assert_eq!(hex::encode(key.address().0), public);
+    }
+
+    // SLIP-0010, "Test vector 1 for ed25519".
+    #[test]
+    fn slip10_vector_1() {
+        let master = ExtendedKey::master(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap());
+        check(
+            &master,
+            "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+            "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+        );
+        check(
+            &master.derive(&"m/0'/1'/2'".parse().unwrap()),
+            "2e69929e00b5ab250f49c3fb1c12f252de4fed2c1db88387094a0f8c4c9ccd6c",
+            "92a5b23c0b8a99e37d07df3fb9966917f5d06e02ddbd909c7e184371463e9fc9",
+            "ae98736566d30ed0e9d2f4486a64bc95740d89c7db33f52121f8ea8f76ff0fc1",
+        );
+        check(
+            &master.derive(&"m/0H/1H/2H/2H/1000000000H".parse().unwrap()),
+            "68789923a0cac2cd5a29172a475fe9e0fb14cd6adb5ad98a3fa70333e7afa230",
+            "8f94d394a8e8fd6b1bc2f3f49f5c47e385281d5c17e65324b0f62483e37e8793",
+            "3c24da049451555d51a7014a37337aa4e12d41e485abccfa46b47dfb2af54b7a",
+        );
+    }
+
+    // SLIP-0010, "Test vector 2 for ed25519".
+    #[test]
+    fn slip10_vector_2() {
+        let seed = hex::decode(
+            "fffcf9f6f3f0edeae7e4e1dedbd8d5d2cfccc9c6c3c0bdbab7b4b1aeaba8a5a2\
+             9f9c999693908d8a8784817e7b7875726f6c696663605d5a5754514e4b484542",
+        )
+        .unwrap();
+        let master = ExtendedKey::master(&seed);
+        check(
+            &master,
+            "ef70a74db9c3a5af931b5fe73ed8e1a53464133654fd55e7a66f8570b8e33c3b",
+            "171cb88b1b3c1db25add599712e36245d75bc65a1a5c9e18d76f9f2b1eab4012",
+            "8fe9693f8fa62a4305a140b9764c5ee01e455963744fe18204b4fb948249308a",
+        );
+        check(
+            &master.derive(&"m/0'/2147483647'/1'/2147483646'/2'".parse().unwrap()),
+            "5d70af781f3a37b829f0d060924d5e960bdc02e85423494afc0b1a41bbe196d4",
+            "551d333177df541ad876a60ea71f00447931c0a9da16f227c11ea080d7391b8d",
+            "47150c75db263559a70d5778bf36abbab30fb061ad69f69ece61a72b0cfa4fc0",
+        );
+    }
+
+    // The BIP-39 "abandon ... about" phrase with an empty passphrase, on the
+    // default Mbongo path. Cross-checked with an independent Python
+    // implementation of BIP-39 and SLIP-0010.
+    #[test]
+    fn mbongo_path_vector() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let path = DerivationPath::default();
+        assert_eq!(path.to_string(), "m/44'/9797'/0'/0'/0'");
+        let key = derive_signing_key(mnemonic.to_seed("").as_slice(), &path);
+        assert_eq!(
+            hex::encode(key.verifying_key().to_bytes()),
+            "9dfe016c99741dc4f74ec03d519abaa0a15b629484f9bd682ba03a2fd682821d"
+        );
+    }
+
+    #[test]
+    fn paths_parse_and_display() {
+        let path: DerivationPath = "m/44h/9797H/3'/0'/7'".parse().unwrap();
+        assert_eq!(path, DerivationPath::mbongo(3, 7).unwrap());
+        assert_eq!(path.to_string(), "m/44'/9797'/3'/0'/7'");
+        assert_eq!(
+            "m".parse::<DerivationPath>().unwrap().indices(),
+            &[] as &[u32]
+        );
+
+        assert_eq!(
+            "m/44'/60'/0'/0/0".parse::<DerivationPath>(),
+            Err(HdError::NotHardened("0".to_string()))
+        );
+        assert!(matches!(
+            "44'/0'".parse::<DerivationPath>(),
+            Err(HdError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            "m/x'".parse::<DerivationPath>(),
+            Err(HdError::InvalidPath(_))
+        ));
+        assert_eq!(
+            "m/2147483648'".parse::<DerivationPath>(),
+            Err(HdError::IndexOutOfRange(HARDENED))
+        );
+    }
+}
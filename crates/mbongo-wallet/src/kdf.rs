@@ -0,0 +1,213 @@
+//! Password-based key derivation: PBKDF2 and scrypt.
+//!
+//! scrypt (RFC 7914) protects keystore files; PBKDF2-HMAC-SHA512 turns
+//! BIP-39 mnemonics into seeds. Both come from the `scrypt` and `pbkdf2`
+//! crates; this module adds the parameter limits a keystore file is checked
+//! against before any memory is allocated.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use thiserror::Error;
+
+/// Largest amount of memory scrypt parameters may ask for (1 GiB), so a
+/// hostile keystore file cannot exhaust the machine that opens it.
+pub const MAX_SCRYPT_MEMORY: u64 = 1 << 30;
+
+/// Invalid scrypt parameters.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum KdfError {
+    /// `n` is not a power of two greater than 1 and below `2^(16 r)`.
+    #[error("scrypt n must be a power of two greater than 1 and below 2^(16 r), got {0}")]
+    InvalidCost(u64),
+    /// `r` or `p` is zero, or `r * p` is too large.
+    #[error("scrypt r and p must be non-zero with r * p < 2^30, got r={r} p={p}")]
+    InvalidBlockParams {
+        /// Block size parameter.
+        r: u32,
+        /// Parallelism parameter.
+        p: u32,
+    },
+    /// The parameters need more than [`MAX_SCRYPT_MEMORY`] bytes.
+    #[error("scrypt parameters need {0} bytes of memory")]
+    TooMuchMemory(u64),
+    /// scrypt cannot derive a key of this many bytes.
+    #[error("scrypt cannot derive a {0}-byte key")]
+    InvalidOutputLength(usize),
+}
+
+/// scrypt cost parameters, as stored in keystore files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScryptParams {
+    /// CPU/memory cost; a power of two.
+    pub n: u64,
+    /// Block size.
+    pub r: u32,
+    /// Parallelism.
+    pub p: u32,
+}
+
+impl ScryptParams {
+    /// Parameters for new keystores: `n = 2^18, r = 8, p = 1` (256 MiB).
+    pub const RECOMMENDED: Self = Self {
+        n: 1 << 18,
+        r: 8,
+        p: 1,
+    };
+
+    /// Checks that the parameters are well formed and within
+    /// [`MAX_SCRYPT_MEMORY`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KdfError`] describing the first problem found.
+    pub fn validate(&self) -> Result<(), KdfError> {
+        if self.n < 2 || !self.n.is_power_of_two() {
+            return Err(KdfError::InvalidCost(self.n));
+        }
+        if self.r == 0 || self.p == 0 || u64::from(self.r) * u64::from(self.p) >= 1 << 30 {
+            return Err(KdfError::InvalidBlockParams {
+                r: self.r,
+                p: self.p,
+            });
+        }
+        // RFC 7914 requires n < 2^(128 r / 8).
+        if u64::from(self.n.trailing_zeros()) >= 16 * u64::from(self.r) {
+            return Err(KdfError::InvalidCost(self.n));
+        }
+        let memory = 128 * u64::from(self.r) * self.n.saturating_add(u64::from(self.p));
+        if memory > MAX_SCRYPT_MEMORY {
+            return Err(KdfError::TooMuchMemory(memory));
+        }
+        Ok(())
+    }
+}
+
+/// Fills `out` with PBKDF2-HMAC-SHA256 of `password` and `salt`.
+pub fn pbkdf2_sha256(password: &[u8], salt: &[u8], rounds: u32, out: &mut [u8]) {
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, rounds, out);
+}
+
+/// Fills `out` with PBKDF2-HMAC-SHA512 of `password` and `salt`.
+pub fn pbkdf2_sha512(password: &[u8], salt: &[u8], rounds: u32, out: &mut [u8]) {
+    pbkdf2::pbkdf2_hmac::<Sha512>(password, salt, rounds, out);
+}
+
+/// Fills `out` with scrypt of `password` and `salt`.
+///
+/// # Errors
+///
+/// Returns [`KdfError`] if `params` fail [`ScryptParams::validate`] or `out`
+/// is empty.
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    params: &ScryptParams,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    params.validate()?;
+    let log_n =
+        u8::try_from(params.n.trailing_zeros()).map_err(|_| KdfError::InvalidCost(params.n))?;
+    // The key length recorded in `Params` only matters to the crate's
+    // password-hash API; the length of `out` decides what is derived.
+    let scrypt_params =
+        scrypt::Params::new(log_n, params.r, params.p, scrypt::Params::RECOMMENDED_LEN).map_err(
+            |_| KdfError::InvalidBlockParams {
+                r: params.r,
+                p: params.p,
+            },
+        )?;
+    scrypt::scrypt(password, salt, &scrypt_params, out)
+        .map_err(|_| KdfError::InvalidOutputLength(out.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrypt_hex(password: &str, salt: &str, n: u64, r: u32, p: u32) -> String {
+        let mut out = [0u8; 64];
+        scrypt(
+            password.as_bytes(),
+            salt.as_bytes(),
+            &ScryptParams { n, r, p },
+            &mut out,
+        )
+        .unwrap();
+        hex::encode(out)
+    }
+
+    // RFC 7914, section 11.
+    #[test]
+    fn pbkdf2_sha256_rfc7914_vectors() {
+        let mut out = [0u8; 64];
+        pbkdf2_sha256(b"passwd", b"salt", 1, &mut out);
+        assert_eq!(
+            hex::encode(out),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+    }
+
+    // Matches Python's `hashlib.pbkdf2_hmac("sha512", b"password", b"salt", 1)`.
+    #[test]
+    fn pbkdf2_sha512_vector() {
+        let mut out = [0u8; 64];
+        pbkdf2_sha512(b"password", b"salt", 1, &mut out);
+        assert_eq!(
+            hex::encode(out),
+            "867f70cf1ade02cff3752599a3a53dc4af34c7a669815ae5d513554e1c8cf252\
+             c02d470a285a0501bad999bfe943c08f050235d7d68b1da55e63f73b60a57fce"
+        );
+    }
+
+    // RFC 7914, section 12.
+    #[test]
+    fn scrypt_rfc7914_vectors() {
+        assert_eq!(
+            scrypt_hex("", "", 16, 1, 1),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede214\
+             42fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+        assert_eq!(
+            scrypt_hex("password", "NaCl", 1024, 8, 16),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162\
+             2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn scrypt_params_are_validated() {
+        let valid = ScryptParams { n: 16, r: 1, p: 1 };
+        assert_eq!(valid.validate(), Ok(()));
+        assert_eq!(ScryptParams::RECOMMENDED.validate(), Ok(()));
+        assert_eq!(
+            ScryptParams { n: 15, ..valid }.validate(),
+            Err(KdfError::InvalidCost(15))
+        );
+        assert_eq!(
+            ScryptParams {
+                n: 1 << 16,
+                ..valid
+            }
+            .validate(),
+            Err(KdfError::InvalidCost(1 << 16))
+        );
+        assert!(matches!(
+            ScryptParams { r: 0, ..valid }.validate(),
+            Err(KdfError::InvalidBlockParams { .. })
+        ));
+        assert!(matches!(
+            ScryptParams {
+                n: 1 << 24,
+                r: 8,
+                p: 1
+            }
+            .validate(),
+            Err(KdfError::TooMuchMemory(_))
+        ));
+        assert_eq!(
+            scrypt(b"", b"", &valid, &mut []),
+            Err(KdfError::InvalidOutputLength(0))
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! Encrypted keystore for ed25519 signing keys.
+//!
+//! Each key lives in its own JSON file, `<name>.json`, inside a keystore
+//! directory. The file format follows the layout of Web3 Secret Storage but
+//! uses an AEAD cipher in place of a separate MAC:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "address": "0x…",
+//!   "crypto": {
+//!     "cipher": "chacha20-poly1305",
+//!     "cipherparams": { "nonce": "0x…" },
+//!     "ciphertext": "0x…",
+//!     "kdf": "scrypt",
+//!     "kdfparams": { "n": 262144, "r": 8, "p": 1, "salt": "0x…" }
+//!   }
+//! }
+//! ```
+//!
+//! The 32-byte key is derived from the password with scrypt and encrypts the
+//! 32-byte ed25519 secret. The address is authenticated as associated data,
+//! so editing it makes decryption fail instead of yielding a mislabelled key.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce};
+use ed25519_dalek::SigningKey;
+use mbongo_core::Address;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::kdf::{self, KdfError, ScryptParams};
+
+/// Keystore file format version written by this crate.
+pub const KEYSTORE_VERSION: u32 = 1;
+/// Shortest password accepted for new or re-encrypted keys.
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+const CIPHER: &str = "chacha20-poly1305";
+const KDF: &str = "scrypt";
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Errors returned by keystore operations.
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    /// A key with this name already exists.
+    #[error("key `{0}` already exists")]
+    Exists(String),
+    /// No key with this name exists.
+    #[error("key `{0}` not found")]
+    NotFound(String),
+    /// The name cannot be used as a file name.
+    #[error("invalid key name `{0}`: use letters, digits, `-`, `_` and `.`")]
+    InvalidName(String),
+    /// The new password is shorter than [`MIN_PASSWORD_LEN`].
+    #[error("password must be at least {MIN_PASSWORD_LEN} characters")]
+    WeakPassword,
+    /// The password does not decrypt the key, or the file was tampered with.
+    #[error("wrong password or corrupted keystore")]
+    WrongPassword,
+    /// The file uses a version, cipher or KDF this build does not support.
+    #[error("unsupported keystore: {0}")]
+    Unsupported(String),
+    /// The file is structurally invalid.
+    #[error("corrupt keystore: {0}")]
+    Corrupt(String),
+    /// The KDF parameters are invalid.
+    #[error(transparent)]
+    Kdf(#[from] KdfError),
+    /// Reading or writing the keystore failed.
+    #[error("keystore I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid keystore JSON.
+    #[error("invalid keystore JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// An encrypted ed25519 key, as stored on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreFile {
+    /// File format version; [`KEYSTORE_VERSION`] for files written now.
+    pub version: u32,
+    /// Address of the encrypted key.
+    pub address: Address,
+    /// Cipher, KDF and encrypted key material.
+    pub crypto: CryptoSection,
+}
+
+/// The `crypto` section of a [`KeystoreFile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CryptoSection {
+    /// AEAD cipher name; `chacha20-poly1305`.
+    pub cipher: String,
+    /// Cipher parameters.
+    pub cipherparams: CipherParams,
+    /// `0x`-prefixed hex of the encrypted secret key followed by the tag.
+    pub ciphertext: String,
+    /// Key derivation function name; `scrypt`.
+    pub kdf: String,
+    /// Key derivation parameters.
+    pub kdfparams: KdfParams,
+}
+
+/// Parameters of the AEAD cipher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CipherParams {
+    /// `0x`-prefixed hex of the 12-byte nonce.
+    pub nonce: String,
+}
+
+/// Parameters of the scrypt KDF.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// scrypt cost parameters.
+    #[serde(flatten)]
+    pub scrypt: ScryptParams,
+    /// `0x`-prefixed hex of the salt.
+    pub salt: String,
+}
+
+impl KeystoreFile {
+    /// Encrypts `key` under `password` with fresh random salt and nonce.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::WeakPassword`] for short passwords and
+    /// [`KeystoreError::Kdf`] for invalid `params`.
+    pub fn encrypt(
+        key: &SigningKey,
+        password: &str,
+        params: ScryptParams,
+    ) -> Result<Self, KeystoreError> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+        Self::encrypt_with(key, password, params, &salt, &nonce)
+    }
+
+    /// [`KeystoreFile::encrypt`] with caller-chosen salt and nonce, for
+    /// reproducible test vectors.
+    fn encrypt_with(
+        key: &SigningKey,
+        password: &str,
+        params: ScryptParams,
+        salt: &[u8],
+        nonce: &[u8; NONCE_LEN],
+    ) -> Result<Self, KeystoreError> {
+        if password.chars().count() < MIN_PASSWORD_LEN {
+            return Err(KeystoreError::WeakPassword);
+        }
+        let address = Address(key.verifying_key().to_bytes());
+        let cipher = derive_cipher(password, salt, &params)?;
+        let secret = Zeroizing::new(key.to_bytes());
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: secret.as_slice(),
+                    aad: &address.0,
+                },
+            )
+            .expect("encrypting 32 bytes cannot fail");
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            crypto: CryptoSection {
+                cipher: CIPHER.to_string(),
+                cipherparams: CipherParams {
+                    nonce: to_hex(nonce),
+                },
+                ciphertext: to_hex(&ciphertext),
+                kdf: KDF.to_string(),
+                kdfparams: KdfParams {
+                    scrypt: params,
+                    salt: to_hex(salt),
+                },
+            },
+        })
+    }
+
+    /// Decrypts the key with `password`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::WrongPassword`] if the password is wrong or
+    /// the file was modified, [`KeystoreError::Unsupported`] for unknown
+    /// versions or algorithms, and [`KeystoreError::Corrupt`] for malformed
+    /// fields.
+    pub fn decrypt(&self, password: &str) -> Result<SigningKey, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::Unsupported(format!(
+                "version {}",
+                self.version
+            )));
+        }
+        if self.crypto.cipher != CIPHER {
+            return Err(KeystoreError::Unsupported(format!(
+                "cipher {}",
+                self.crypto.cipher
+            )));
+        }
+        if self.crypto.kdf != KDF {
+            return Err(KeystoreError::Unsupported(format!(
+                "kdf {}",
+                self.crypto.kdf
+            )));
+        }
+        let salt = from_hex("salt", &self.crypto.kdfparams.salt)?;
+        let nonce = from_hex("nonce", &self.crypto.cipherparams.nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(KeystoreError::Corrupt(format!(
+                "nonce must be {NONCE_LEN} bytes, got {}",
+                nonce.len()
+            )));
+        }
+        let ciphertext = from_hex("ciphertext", &self.crypto.ciphertext)?;
+
+        let cipher = derive_cipher(password, &salt, &self.crypto.kdfparams.scrypt)?;
+        let secret = Zeroizing::new(
+            cipher
+                .decrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &ciphertext,
+                        aad: &self.address.0,
+                    },
+                )
+                .map_err(|_| KeystoreError::WrongPassword)?,
+        );
+        let secret: &[u8; 32] = secret.as_slice().try_into().map_err(|_| {
+            KeystoreError::Corrupt(format!("secret key must be 32 bytes, got {}", secret.len()))
+        })?;
+        let key = SigningKey::from_bytes(secret);
+        if key.verifying_key().to_bytes() != self.address.0 {
+            return Err(KeystoreError::Corrupt(
+                "decrypted key does not match address".to_string(),
+            ));
+        }
+        Ok(key)
+    }
+
+    /// Reads a keystore file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Io`] or [`KeystoreError::Json`] if the file
+    /// cannot be read or parsed.
+    pub fn read(path: &Path) -> Result<Self, KeystoreError> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Writes the keystore to `path`, readable only by the owner on Unix.
+    ///
+    /// The file is written under a temporary name and renamed into place,
+    /// so an interrupted write never leaves a truncated keystore behind.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Io`] if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), KeystoreError> {
+        let tmp = path.with_extension("json.tmp");
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(self)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+fn derive_cipher(
+    password: &str,
+    salt: &[u8],
+    params: &ScryptParams,
+) -> Result<ChaCha20Poly1305, KeystoreError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    kdf::scrypt(password.as_bytes(), salt, params, key.as_mut_slice())?;
+    Ok(ChaCha20Poly1305::new(key.as_slice().into()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn from_hex(field: &str, s: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s))
+        .map_err(|e| KeystoreError::Corrupt(format!("{field}: {e}")))
+}
+
+/// A key stored in a [`Keystore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    /// Name the key is stored under.
+    pub name: String,
+    /// Address of the key.
+    pub address: Address,
+    /// Path of the keystore file.
+    pub path: PathBuf,
+}
+
+/// A directory of named, encrypted keys.
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    dir: PathBuf,
+    params: ScryptParams,
+}
+
+impl Keystore {
+    /// Opens the keystore in `dir`, creating the directory if needed. New
+    /// keys are encrypted with [`ScryptParams::RECOMMENDED`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Io`] if the directory cannot be created.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, KeystoreError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            params: ScryptParams::RECOMMENDED,
+        })
+    }
+
+    /// Uses `params` instead of the recommended scrypt cost for keys
+    /// encrypted from now on.
+    #[must_use]
+    pub fn with_scrypt_params(mut self, params: ScryptParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Directory holding the keystore files.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the file for the key called `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::InvalidName`] if `name` is not a plain file
+    /// name.
+    pub fn path(&self, name: &str) -> Result<PathBuf, KeystoreError> {
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !valid {
+            return Err(KeystoreError::InvalidName(name.to_string()));
+        }
+        Ok(self.dir.join(format!("{name}.json")))
+    }
+
+    /// Generates a new random key and stores it as `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Exists`] if `name` is taken, plus the errors
+    /// of [`KeystoreFile::encrypt`].
+    pub fn create(&self, name: &str, password: &str) -> Result<KeyInfo, KeystoreError> {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        OsRng.fill_bytes(secret.as_mut_slice());
+        self.import(name, &SigningKey::from_bytes(&secret), password)
+    }
+
+    /// Stores an existing `key` as `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Exists`] if `name` is taken, plus the errors
+    /// of [`KeystoreFile::encrypt`].
+    pub fn import(
+        &self,
+        name: &str,
+        key: &SigningKey,
+        password: &str,
+    ) -> Result<KeyInfo, KeystoreError> {
+        let path = self.path(name)?;
+        if path.exists() {
+            return Err(KeystoreError::Exists(name.to_string()));
+        }
+        let file = KeystoreFile::encrypt(key, password, self.params)?;
+        file.write(&path)?;
+        Ok(KeyInfo {
+            name: name.to_string(),
+            address: file.address,
+            path,
+        })
+    }
+
+    /// Copies a keystore file from elsewhere into the keystore as `name`,
+    /// after checking that `password` opens it. The file keeps its
+    /// password.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Exists`] if `name` is taken, plus the errors
+    /// of [`KeystoreFile::read`] and [`KeystoreFile::decrypt`].
+    pub fn import_file(
+        &self,
+        name: &str,
+        source: &Path,
+        password: &str,
+    ) -> Result<KeyInfo, KeystoreError> {
+        let path = self.path(name)?;
+        if path.exists() {
+            return Err(KeystoreError::Exists(name.to_string()));
+        }
+        let file = KeystoreFile::read(source)?;
+        file.decrypt(password)?;
+        file.write(&path)?;
+        Ok(KeyInfo {
+            name: name.to_string(),
+            address: file.address,
+            path,
+        })
+    }
+
+    /// Reads the encrypted file of the key called `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::NotFound`] if there is no such key.
+    pub fn load(&self, name: &str) -> Result<KeystoreFile, KeystoreError> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(KeystoreError::NotFound(name.to_string()));
+        }
+        KeystoreFile::read(&path)
+    }
+
+    /// Decrypts the key called `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Keystore::load`] and [`KeystoreFile::decrypt`].
+    pub fn unlock(&self, name: &str, password: &str) -> Result<SigningKey, KeystoreError> {
+        self.load(name)?.decrypt(password)
+    }
+
+    /// Re-encrypts the key called `name` under `export_password` for backup
+    /// or transfer to another machine. The stored file is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Keystore::unlock`] and
+    /// [`KeystoreFile::encrypt`].
+    pub fn export(
+        &self,
+        name: &str,
+        password: &str,
+        export_password: &str,
+    ) -> Result<KeystoreFile, KeystoreError> {
+        let key = self.unlock(name, password)?;
+        KeystoreFile::encrypt(&key, export_password, self.params)
+    }
+
+    /// Lists the stored keys, sorted by name. Files that are not valid
+    /// keystores are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`KeystoreError::Io`] if the directory cannot be read.
+    pub fn list(&self) -> Result<Vec<KeyInfo>, KeystoreError> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if let Ok(file) = KeystoreFile::read(&path) {
+                keys.push(KeyInfo {
+                    name: name.to_string(),
+                    address: file.address,
+                    path,
+                });
+            }
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+
+    /// Re-encrypts the key called `name` under `new_password`, with a fresh
+    /// salt and nonce.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`Keystore::unlock`] and
+    /// [`KeystoreFile::encrypt`].
+    pub fn change_password(
+        &self,
+        name: &str,
+        old_password: &str,
+        new_password: &str,
+    ) -> Result<(), KeystoreError> {
+        let key = self.unlock(name, old_password)?;
+        let file = KeystoreFile::encrypt(&key, new_password, self.params)?;
+        file.write(&self.path(name)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cheap scrypt cost so tests run quickly in debug builds.
+    const TEST_PARAMS: ScryptParams = ScryptParams { n: 16, r: 8, p: 1 };
+
+    fn test_keystore() -> (tempfile::TempDir, Keystore) {
+        let dir = tempfile::tempdir().unwrap();
+        let keystore =
+            Keystore::open(dir.path().join("keys")).unwrap().with_scrypt_params(TEST_PARAMS);
+        (dir, keystore)
+    }
+
+    #[test]
+    fn encryption_is_reproducible_with_fixed_salt_and_nonce() {
+        let key = SigningKey::from_bytes(&[0xAA; 32]);
+        let file =
+            KeystoreFile::encrypt_with(&key, "correct horse", TEST_PARAMS, &[1u8; 32], &[2u8; 12])
+                .unwrap();
+        let again =
+            KeystoreFile::encrypt_with(&key, "correct horse", TEST_PARAMS, &[1u8; 32], &[2u8; 12])
+                .unwrap();
+        assert_eq!(file, again);
+        assert_eq!(file.address, Address(key.verifying_key().to_bytes()));
+        // 32-byte secret plus 16-byte tag.
+        assert_eq!(file.crypto.ciphertext.len(), 2 + 2 * 48);
+        assert_eq!(
+            file.decrypt("correct horse").unwrap().to_bytes(),
+            [0xAA; 32]
+        );
+    }
+
+    /// Secret key `00 01 .. 1f` under password `mbongo test vector`, salt
+    /// `5a * 32`, nonce `3c * 12` and scrypt `n = 1024, r = 8, p = 1`.
+    /// Cross-checked against Python's `hashlib.scrypt` and the
+    /// `cryptography` package's `ChaCha20Poly1305`.
+    const VECTOR: &str = include_str!("../tests/vectors/keystore-v1.json");
+
+    #[test]
+    fn decrypts_reference_vector() {
+        let file: KeystoreFile = serde_json::from_str(VECTOR).unwrap();
+        let secret: [u8; 32] = core::array::from_fn(|i| u8::try_from(i).unwrap());
+        assert_eq!(
+            file.decrypt("mbongo test vector").unwrap().to_bytes(),
+            secret
+        );
+
+        let key = SigningKey::from_bytes(&secret);
+        let params = file.crypto.kdfparams.scrypt;
+        let encrypted = KeystoreFile::encrypt_with(
+            &key,
+            "mbongo test vector",
+            params,
+            &[0x5a; 32],
+            &[0x3c; 12],
+        )
+        .unwrap();
+        assert_eq!(encrypted, file);
+    }
+
+    #[test]
+    fn wrong_password_and_tampering_are_rejected() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let file = KeystoreFile::encrypt(&key, "password1", TEST_PARAMS).unwrap();
+        assert!(matches!(
+            file.decrypt("password2"),
+            Err(KeystoreError::WrongPassword)
+        ));
+
+        let mut relabelled = file.clone();
+        relabelled.address = Address([9u8; 32]);
+        assert!(matches!(
+            relabelled.decrypt("password1"),
+            Err(KeystoreError::WrongPassword)
+        ));
+
+        let mut newer = file.clone();
+        newer.version = 2;
+        assert!(matches!(
+            newer.decrypt("password1"),
+            Err(KeystoreError::Unsupported(_))
+        ));
+
+        let mut hostile = file;
+        hostile.crypto.kdfparams.scrypt.n = 1 << 40;
+        assert!(matches!(
+            hostile.decrypt("password1"),
+            Err(KeystoreError::Kdf(KdfError::TooMuchMemory(_)))
+        ));
+    }
+
+    #[test]
+    fn short_passwords_are_refused() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        assert!(matches!(
+            KeystoreFile::encrypt(&key, "short", TEST_PARAMS),
+            Err(KeystoreError::WeakPassword)
+        ));
+    }
+
+    #[test]
+    fn create_list_unlock_and_change_password() {
+        let (_dir, keystore) = test_keystore();
+        let alice = keystore.create("alice", "alice-password").unwrap();
+        let bob_key = SigningKey::from_bytes(&[0xBB; 32]);
+        let bob = keystore.import("bob", &bob_key, "bob-password").unwrap();
+        assert_eq!(bob.address, Address(bob_key.verifying_key().to_bytes()));
+
+        assert!(matches!(
+            keystore.create("alice", "alice-password"),
+            Err(KeystoreError::Exists(_))
+        ));
+        assert!(matches!(
+            keystore.create("../escape", "alice-password"),
+            Err(KeystoreError::InvalidName(_))
+        ));
+        assert!(matches!(
+            keystore.unlock("carol", "whatever1"),
+            Err(KeystoreError::NotFound(_))
+        ));
+
+        let listed = keystore.list().unwrap();
+        assert_eq!(listed, vec![alice.clone(), bob]);
+
+        let key = keystore.unlock("alice", "alice-password").unwrap();
+        assert_eq!(key.verifying_key().to_bytes(), alice.address.0);
+
+        keystore.change_password("alice", "alice-password", "new-password").unwrap();
+        assert!(matches!(
+            keystore.unlock("alice", "alice-password"),
+            Err(KeystoreError::WrongPassword)
+        ));
+        assert_eq!(
+            keystore.unlock("alice", "new-password").unwrap().to_bytes(),
+            key.to_bytes()
+        );
+    }
+
+    #[test]
+    fn export_and_import_file_round_trip() {
+        let (dir, keystore) = test_keystore();
+        let original = keystore.create("hot", "hot-password").unwrap();
+
+        let exported = keystore.export("hot", "hot-password", "backup-password").unwrap();
+        let backup = dir.path().join("backup.json");
+        exported.write(&backup).unwrap();
+
+        let (_other_dir, other) = test_keystore();
+        assert!(matches!(
+            other.import_file("cold", &backup, "hot-password"),
+            Err(KeystoreError::WrongPassword)
+        ));
+        let imported = other.import_file("cold", &backup, "backup-password").unwrap();
+        assert_eq!(imported.address, original.address);
+        assert!(other.unlock("cold", "backup-password").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keystore_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (_dir, keystore) = test_keystore();
+        let info = keystore.create("alice", "alice-password").unwrap();
+        let mode = fs::metadata(&info.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
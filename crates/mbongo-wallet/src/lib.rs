@@ -9,17 +9,43 @@
 //! # Examples
 //!
 //! ```
-//! // TODO: Add examples once wallet is implemented
+//! use mbongo_core::{Address, TransactionType};
+//! use mbongo_wallet::hd::derive_signing_key;
+//! use mbongo_wallet::signing::{address_of, sign_transaction, unsigned_transaction};
+//! use mbongo_wallet::{DerivationPath, Mnemonic};
+//!
+//! let mnemonic = Mnemonic::generate(24).unwrap();
+//! let key = derive_signing_key(mnemonic.to_seed("").as_slice(), &DerivationPath::default());
+//!
+//! let mut tx = unsigned_transaction(
+//!     TransactionType::Transfer,
+//!     address_of(&key),
+//!     Address([0x22; 32]),
+//!     100,
+//!     0,
+//! );
+//! sign_transaction(&mut tx, &key).unwrap();
+//! assert!(tx.verify_signature());
 //! ```
 
 #![warn(missing_docs)]
 #![warn(clippy::all)]
 #![warn(clippy::pedantic)]
 
-// Module structure (to be implemented)
-// pub mod keystore;
-// pub mod signing;
-// pub mod cli;
+pub mod cli;
+pub mod hd;
+pub mod kdf;
+pub mod keystore;
+pub mod mnemonic;
+pub mod offline;
+pub mod rpc;
+pub mod signing;
+
+pub use hd::{DerivationPath, ExtendedKey, HdError};
+pub use keystore::{KeyInfo, Keystore, KeystoreError, KeystoreFile};
+pub use mnemonic::{Mnemonic, MnemonicError};
+pub use offline::{ChainInfo, OfflineError, TransactionFile};
+pub use rpc::{RpcClient, RpcClientError};
 
 #[cfg(test)]
 mod tests {
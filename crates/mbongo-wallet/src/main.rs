@@ -0,0 +1,26 @@
+//! Mbongo Chain wallet binary.
+//!
+//! # Usage
+//!
+//! ```bash
+//! # Create a wallet and write down the recovery phrase
+//! mbongo-wallet create --name alice
+//!
+//! # Restore it elsewhere from the phrase
+//! mbongo-wallet restore --name alice --mnemonic-file phrase.txt
+//!
+//! # Check the balance and send funds, waiting for inclusion
+//! mbongo-wallet balance --name alice
+//! mbongo-wallet transfer --from alice --to 0x… --amount 1000 --wait
+//!
+//! # Stake with a validator through a remote node
+//! mbongo-wallet --rpc-url http://node:9944/rpc stake --from alice --validator 0x… --amount 500
+//! ```
+
+use clap::Parser;
+use mbongo_wallet::cli::{self, Cli};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    cli::run(Cli::parse()).await
+}
@@ -0,0 +1,304 @@
+//! BIP-39 mnemonic phrases.
+//!
+//! A mnemonic encodes 128–256 bits of entropy plus a SHA-256 checksum as
+//! 12–24 words from the English BIP-39 wordlist. [`Mnemonic::to_seed`]
+//! stretches the phrase and an optional passphrase into the 64-byte seed
+//! that [`crate::hd`] derives keys from.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::Zeroizing;
+
+use crate::kdf;
+
+/// Word counts allowed by BIP-39.
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// The English BIP-39 wordlist, one word per line.
+const ENGLISH: &str = include_str!("wordlist/english.txt");
+const SEED_ROUNDS: u32 = 2048;
+
+/// Errors returned when building or parsing a mnemonic.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MnemonicError {
+    /// The phrase does not have 12, 15, 18, 21 or 24 words.
+    #[error("mnemonic must have 12, 15, 18, 21 or 24 words, got {0}")]
+    InvalidWordCount(usize),
+    /// The entropy is not 16, 20, 24, 28 or 32 bytes.
+    #[error("mnemonic entropy must be 16, 20, 24, 28 or 32 bytes, got {0}")]
+    InvalidEntropyLength(usize),
+    /// A word is not in the English wordlist.
+    #[error("`{0}` is not a BIP-39 word")]
+    UnknownWord(String),
+    /// The checksum bits do not match the entropy.
+    #[error("mnemonic checksum mismatch")]
+    InvalidChecksum,
+}
+
+/// A BIP-39 mnemonic. The entropy is wiped from memory on drop and never
+/// printed by `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    entropy: Zeroizing<Vec<u8>>,
+}
+
+impl Mnemonic {
+    /// Generates a mnemonic of `word_count` words from the OS random number
+    /// generator.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MnemonicError::InvalidWordCount`] unless `word_count` is one
+    /// of [`WORD_COUNTS`].
+    pub fn generate(word_count: usize) -> Result<Self, MnemonicError> {
+        if !WORD_COUNTS.contains(&word_count) {
+            return Err(MnemonicError::InvalidWordCount(word_count));
+        }
+        let mut entropy = Zeroizing::new(vec![0u8; word_count * 4 / 3]);
+        OsRng.fill_bytes(&mut entropy);
+        Self::from_entropy(&entropy)
+    }
+
+    /// Builds the mnemonic for `entropy`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MnemonicError::InvalidEntropyLength`] unless `entropy` is
+    /// 16, 20, 24, 28 or 32 bytes long.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, MnemonicError> {
+        if !(16..=32).contains(&entropy.len()) || entropy.len() % 4 != 0 {
+            return Err(MnemonicError::InvalidEntropyLength(entropy.len()));
+        }
+        Ok(Self {
+            entropy: Zeroizing::new(entropy.to_vec()),
+        })
+    }
+
+    /// Parses a phrase. Case and surrounding or repeated whitespace are
+    /// ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MnemonicError`] if the word count, a word or the checksum
+    /// is invalid.
+    pub fn parse(phrase: &str) -> Result<Self, MnemonicError> {
+        let phrase = Zeroizing::new(phrase.nfkd().collect::<String>().to_lowercase());
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        if !WORD_COUNTS.contains(&words.len()) {
+            return Err(MnemonicError::InvalidWordCount(words.len()));
+        }
+
+        // Each word carries 11 bits; the last `words / 3` bits are checksum.
+        let mut bits = Zeroizing::new(Vec::with_capacity(words.len() * 11));
+        for word in &words {
+            let index = wordlist()
+                .binary_search(word)
+                .map_err(|_| MnemonicError::UnknownWord((*word).to_string()))?;
+            bits.extend((0..11).rev().map(|bit| (index >> bit) & 1 == 1));
+        }
+        let entropy_bits = words.len() * 32 / 3;
+        let mut entropy = Zeroizing::new(vec![0u8; entropy_bits / 8]);
+        for (i, &bit) in bits[..entropy_bits].iter().enumerate() {
+            entropy[i / 8] |= u8::from(bit) << (7 - i % 8);
+        }
+
+        let mnemonic = Self::from_entropy(&entropy)?;
+        if checksum_bits(&entropy).ne(bits[entropy_bits..].iter().copied()) {
+            return Err(MnemonicError::InvalidChecksum);
+        }
+        Ok(mnemonic)
+    }
+
+    /// The entropy the mnemonic encodes.
+    #[must_use]
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    /// Number of words in the phrase.
+    #[must_use]
+    pub fn word_count(&self) -> usize {
+        self.entropy.len() * 3 / 4
+    }
+
+    /// The phrase, words separated by single spaces.
+    #[must_use]
+    pub fn phrase(&self) -> Zeroizing<String> {
+        let bits: Vec<bool> = self
+            .entropy
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+            .chain(checksum_bits(&self.entropy))
+            .collect();
+        let words: Vec<&str> = bits
+            .chunks(11)
+            .map(|chunk| {
+                let index = chunk.iter().fold(0usize, |acc, &bit| acc << 1 | usize::from(bit));
+                wordlist()[index]
+            })
+            .collect();
+        Zeroizing::new(words.join(" "))
+    }
+
+    /// Derives the 64-byte BIP-39 seed, protected by `passphrase` (empty for
+    /// none).
+    #[must_use]
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; 64]> {
+        let salt = Zeroizing::new(format!("mnemonic{passphrase}").nfkd().collect::<String>());
+        let mut seed = Zeroizing::new([0u8; 64]);
+        kdf::pbkdf2_sha512(
+            self.phrase().as_bytes(),
+            salt.as_bytes(),
+            SEED_ROUNDS,
+            seed.as_mut_slice(),
+        );
+        seed
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mnemonic")
+            .field("word_count", &self.word_count())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The first `entropy.len() / 4` bits of SHA-256 of `entropy`.
+fn checksum_bits(entropy: &[u8]) -> impl Iterator<Item = bool> {
+    let hash = Sha256::digest(entropy);
+    let first = hash[0];
+    (0..entropy.len() / 4).map(move |i| (first >> (7 - i)) & 1 == 1)
+}
+
+/// The English wordlist, in its (alphabetical) BIP-39 order.
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| ENGLISH.lines().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Official BIP-39 vectors (trezor/python-mnemonic), passphrase `TREZOR`.
+    const VECTORS: [(&str, &str, &str); 6] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553\
+             1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6f\
+             a457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+        ),
+        (
+            "ffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+            "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13\
+             332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+        ),
+        (
+            "808080808080808080808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+             amount doctor acoustic avoid letter always",
+            "107d7c02a5aa6f38c58083ff74f04c607c2d2c0ecc55501dadd72d025b751bc2\
+             7fe913ffb796f841c49b1d33b610cf0e91d3aa239027f5e99fe4ce9e5088cd65",
+        ),
+        (
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon art",
+            "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd30971\
+             70af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+        ),
+        (
+            "8080808080808080808080808080808080808080808080808080808080808080",
+            "letter advice cage absurd amount doctor acoustic avoid letter advice cage absurd \
+             amount doctor acoustic avoid letter advice cage absurd amount doctor acoustic bless",
+            "c0c519bd0e91a2ed54357d9d1ebef6f5af218a153624cf4f2da911a0ed8f7a09\
+             e2ef61af0aca007096df430022f7a2b6fb91661a9589097069720d015e4e982f",
+        ),
+    ];
+
+    #[test]
+    fn wordlist_is_complete_and_sorted() {
+        let words = wordlist();
+        assert_eq!(words.len(), 2048);
+        assert!(words.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!((words[0], words[2047]), ("abandon", "zoo"));
+    }
+
+    #[test]
+    fn bip39_reference_vectors() {
+        for (entropy, phrase, seed) in VECTORS {
+            let entropy = hex::decode(entropy).unwrap();
+            let mnemonic = Mnemonic::from_entropy(&entropy).unwrap();
+            assert_eq!(mnemonic.phrase().as_str(), phrase);
+            assert_eq!(hex::encode(mnemonic.to_seed("TREZOR").as_slice()), seed);
+
+            let parsed = Mnemonic::parse(phrase).unwrap();
+            assert_eq!(parsed.entropy(), entropy.as_slice());
+        }
+    }
+
+    #[test]
+    fn parse_normalises_case_and_whitespace() {
+        let mnemonic = Mnemonic::parse(
+            "  Legal WINNER thank year wave sausage\n worth useful legal winner thank yellow ",
+        )
+        .unwrap();
+        assert_eq!(mnemonic.entropy(), [0x7f; 16]);
+    }
+
+    #[test]
+    fn parse_rejects_bad_phrases() {
+        assert_eq!(
+            Mnemonic::parse("abandon abandon abandon"),
+            Err(MnemonicError::InvalidWordCount(3))
+        );
+        assert_eq!(
+            Mnemonic::parse("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo mbongo"),
+            Err(MnemonicError::UnknownWord("mbongo".to_string()))
+        );
+        assert_eq!(
+            Mnemonic::parse("zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo"),
+            Err(MnemonicError::InvalidChecksum)
+        );
+        assert_eq!(
+            Mnemonic::from_entropy(&[0u8; 17]),
+            Err(MnemonicError::InvalidEntropyLength(17))
+        );
+    }
+
+    #[test]
+    fn generated_mnemonics_round_trip() {
+        for count in WORD_COUNTS {
+            let mnemonic = Mnemonic::generate(count).unwrap();
+            assert_eq!(mnemonic.word_count(), count);
+            assert_eq!(Mnemonic::parse(&mnemonic.phrase()).unwrap(), mnemonic);
+        }
+        assert_eq!(
+            Mnemonic::generate(13),
+            Err(MnemonicError::InvalidWordCount(13))
+        );
+    }
+
+    #[test]
+    fn debug_does_not_leak_the_phrase() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let debug = format!("{mnemonic:?}");
+        assert!(!debug.contains("abandon"), "{debug}");
+    }
+}
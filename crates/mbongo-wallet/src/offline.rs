@@ -0,0 +1,449 @@
+//! Transaction files for air-gapped signing.
+//!
+//! The flow has three steps:
+//!
+//! 1. An online machine builds an unsigned [`TransactionFile`], filling in
+//!    the nonce and chain info from a node.
+//! 2. An offline machine holding the key reviews the summary and signs the
+//!    file, without any network access.
+//! 3. The online machine broadcasts the signed file.
+//!
+//! Both stages use the same JSON format; `kind` says which one a file is.
+//!
+//! Multisig transactions stay `unsigned` while members sign them one at a
+//! time, possibly on different machines, and become `signed` once the
+//! threshold is met. Files signed by different members are merged with
+//! [`TransactionFile::combine`].
+//!
+//! The summary is for people reading the file. It is recomputed and checked
+//! on load, so an edited summary cannot misdescribe the transaction.
+
+use std::fs;
+use std::path::Path;
+
+use ed25519_dalek::SigningKey;
+use mbongo_core::{Hash, MultisigError, MultisigWitness, Transaction};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::signing::{self, SigningError};
+
+/// Transaction file format version written by this crate.
+pub const TRANSACTION_FILE_VERSION: u32 = 1;
+
+/// Errors returned when reading, signing or validating a transaction file.
+#[derive(Debug, Error)]
+pub enum OfflineError {
+    /// The file uses a format version this build does not support.
+    #[error("unsupported transaction file version {0}")]
+    UnsupportedVersion(u32),
+    /// The stored summary does not describe the stored transaction.
+    #[error("transaction file summary does not match its transaction")]
+    SummaryMismatch,
+    /// A signed file was expected.
+    #[error("transaction file is not signed")]
+    NotSigned,
+    /// An unsigned file was expected.
+    #[error("transaction file is already signed")]
+    AlreadySigned,
+    /// The file is marked signed, but the signature or hash is wrong.
+    #[error("transaction file has an invalid signature or hash")]
+    InvalidSignature,
+    /// The partial multisig signatures in an unsigned file are invalid.
+    #[error("transaction file has invalid multisig signatures: {0}")]
+    InvalidMultisig(#[from] MultisigError),
+    /// Only multisig transaction files can be combined.
+    #[error("transaction file is not from a multisig account")]
+    NotMultisig,
+    /// Files being combined are for different transactions or chains.
+    #[error("transaction files describe different transactions")]
+    Mismatch,
+    /// The signing key is not the transaction's sender.
+    #[error(transparent)]
+    Signing(#[from] SigningError),
+    /// Reading or writing the file failed.
+    #[error("transaction file I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid transaction file JSON.
+    #[error("invalid transaction file JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Whether a [`TransactionFile`] has been signed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    /// Built online, waiting to be signed offline.
+    Unsigned,
+    /// Signed offline, ready to broadcast.
+    Signed,
+}
+
+/// The chain a transaction was built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainInfo {
+    /// Hash of the genesis block, identifying the network.
+    pub genesis_hash: Hash,
+    /// Chain height when the transaction was built.
+    pub height: u64,
+}
+
+/// A transaction on its way through the offline signing flow.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionFile {
+    /// File format version; [`TRANSACTION_FILE_VERSION`] for files written
+    /// now.
+    pub version: u32,
+    /// Signing stage.
+    pub kind: FileKind,
+    /// Human-readable description of the transaction and chain.
+    pub summary: String,
+    /// Network the transaction is meant for.
+    pub chain: ChainInfo,
+    /// The transaction; its signature is all zeros until signed.
+    pub transaction: Transaction,
+    /// Hash of the signed transaction, as the node will report it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<Hash>,
+}
+
+impl TransactionFile {
+    /// Wraps an unsigned transaction built for `chain`. Any signature on
+    /// `transaction`, including multisig member signatures, is cleared.
+    #[must_use]
+    pub fn unsigned(mut transaction: Transaction, chain: ChainInfo) -> Self {
+        transaction.signature = [0u8; 64];
+        if let Some(witness) = &mut transaction.multisig {
+            witness.signatures.clear();
+        }
+        Self {
+            version: TRANSACTION_FILE_VERSION,
+            kind: FileKind::Unsigned,
+            summary: summary(&transaction, &chain),
+            chain,
+            transaction,
+            hash: None,
+        }
+    }
+
+    /// Signs the transaction with `key` and marks the file signed. For a
+    /// multisig transaction this adds one member signature, and the file is
+    /// only marked signed once the threshold is met.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OfflineError::AlreadySigned`] for signed files and
+    /// [`OfflineError::Signing`] if `key` is not the sender's key or, for
+    /// multisig, cannot add a member signature.
+    pub fn sign(&mut self, key: &SigningKey) -> Result<(), OfflineError> {
+        if self.kind == FileKind::Signed {
+            return Err(OfflineError::AlreadySigned);
+        }
+        if self.transaction.multisig.is_some() {
+            signing::sign_multisig(&mut self.transaction, key)?;
+        } else {
+            signing::sign_transaction(&mut self.transaction, key)?;
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Merges the multisig member signatures of `other`, a file for the
+    /// same transaction, into this one. Signatures beyond the threshold are
+    /// dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OfflineError::NotMultisig`] for single-key transactions and
+    /// [`OfflineError::Mismatch`] if `other` is for a different transaction
+    /// or chain.
+    pub fn combine(&mut self, other: &Self) -> Result<(), OfflineError> {
+        let (Some(ours), Some(theirs)) = (&self.transaction.multisig, &other.transaction.multisig)
+        else {
+            return Err(OfflineError::NotMultisig);
+        };
+        let unsigned = |tx: &Transaction| {
+            let mut tx = tx.clone();
+            if let Some(witness) = &mut tx.multisig {
+                witness.signatures.clear();
+            }
+            tx
+        };
+        if self.chain != other.chain
+            || ours.config != theirs.config
+            || unsigned(&self.transaction) != unsigned(&other.transaction)
+        {
+            return Err(OfflineError::Mismatch);
+        }
+
+        let theirs = theirs.signatures.clone();
+        let witness = self.transaction.multisig.as_mut().ok_or(OfflineError::NotMultisig)?;
+        for s in theirs {
+            if witness.is_complete() {
+                break;
+            }
+            if witness.signatures.iter().all(|ours| ours.signer != s.signer) {
+                witness.add_signature(s.signer, s.signature)?;
+            }
+        }
+        self.refresh();
+        Ok(())
+    }
+
+    /// Recomputes the kind, hash and summary after signatures change.
+    fn refresh(&mut self) {
+        let complete =
+            self.transaction.multisig.as_ref().map_or(true, MultisigWitness::is_complete);
+        if complete {
+            self.kind = FileKind::Signed;
+            self.hash = Some(signing::transaction_hash(&self.transaction));
+        }
+        self.summary = summary(&self.transaction, &self.chain);
+    }
+
+    /// Checks that the file is internally consistent: a supported version,
+    /// a summary matching the transaction, and a valid signature and hash
+    /// if signed (or none if unsigned, apart from valid partial multisig
+    /// signatures).
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`OfflineError`] describing the first problem found.
+    pub fn validate(&self) -> Result<(), OfflineError> {
+        if self.version != TRANSACTION_FILE_VERSION {
+            return Err(OfflineError::UnsupportedVersion(self.version));
+        }
+        if self.summary != summary(&self.transaction, &self.chain) {
+            return Err(OfflineError::SummaryMismatch);
+        }
+        match self.kind {
+            FileKind::Unsigned
+                if self.transaction.signature != [0u8; 64]
+                    || self.hash.is_some()
+                    || self
+                        .transaction
+                        .multisig
+                        .as_ref()
+                        .is_some_and(MultisigWitness::is_complete) =>
+            {
+                Err(OfflineError::AlreadySigned)
+            }
+            FileKind::Unsigned => match &self.transaction.multisig {
+                Some(witness) => Ok(witness.verify_partial(&self.transaction)?),
+                None => Ok(()),
+            },
+            FileKind::Signed
+                if !self.transaction.verify_signature()
+                    || self.hash != Some(signing::transaction_hash(&self.transaction)) =>
+            {
+                Err(OfflineError::InvalidSignature)
+            }
+            FileKind::Signed => Ok(()),
+        }
+    }
+
+    /// Returns the signed transaction, ready to submit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OfflineError::NotSigned`] for unsigned files.
+    pub fn signed_transaction(&self) -> Result<&Transaction, OfflineError> {
+        match self.kind {
+            FileKind::Signed => Ok(&self.transaction),
+            FileKind::Unsigned => Err(OfflineError::NotSigned),
+        }
+    }
+
+    /// Reads and validates a transaction file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OfflineError`] if the file cannot be read or parsed, or
+    /// fails [`TransactionFile::validate`].
+    pub fn read(path: &Path) -> Result<Self, OfflineError> {
+        let file: Self = serde_json::from_slice(&fs::read(path)?)?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Writes the file as pretty-printed JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OfflineError::Io`] if the file cannot be written.
+    pub fn write(&self, path: &Path) -> Result<(), OfflineError> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// The summary stored in a [`TransactionFile`]: the transaction description
+/// followed by the network it targets.
+#[must_use]
+pub fn summary(tx: &Transaction, chain: &ChainInfo) -> String {
+    format!(
+        "{}\n  Genesis:   {}\n  Built at:  height {}",
+        signing::describe(tx),
+        chain.genesis_hash,
+        chain.height
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mbongo_core::{Address, Multisig, TransactionType};
+
+    fn key() -> SigningKey {
+        SigningKey::from_bytes(&[0xAA; 32])
+    }
+
+    fn unsigned_file() -> TransactionFile {
+        let tx = signing::unsigned_transaction(
+            TransactionType::Transfer,
+            signing::address_of(&key()),
+            Address([0x22; 32]),
+            250,
+            4,
+        );
+        let chain = ChainInfo {
+            genesis_hash: Hash([0x99; 32]),
+            height: 12,
+        };
+        TransactionFile::unsigned(tx, chain)
+    }
+
+    #[test]
+    fn sign_and_round_trip_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tx.json");
+        let file = unsigned_file();
+        assert!(file.summary.contains("Amount:    250"), "{}", file.summary);
+        file.write(&path).unwrap();
+
+        let mut loaded = TransactionFile::read(&path).unwrap();
+        assert_eq!(loaded, file);
+        assert!(matches!(
+            loaded.signed_transaction(),
+            Err(OfflineError::NotSigned)
+        ));
+
+        loaded.sign(&key()).unwrap();
+        loaded.write(&path).unwrap();
+        let signed = TransactionFile::read(&path).unwrap();
+        assert_eq!(signed.kind, FileKind::Signed);
+        let tx = signed.signed_transaction().unwrap();
+        assert!(tx.verify_signature());
+        assert_eq!(signed.hash, Some(signing::transaction_hash(tx)));
+        assert!(matches!(
+            signed.clone().sign(&key()),
+            Err(OfflineError::AlreadySigned)
+        ));
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let mut file = unsigned_file();
+
+        let mut edited_summary = file.clone();
+        edited_summary.summary = edited_summary.summary.replace("250", "25");
+        assert!(matches!(
+            edited_summary.validate(),
+            Err(OfflineError::SummaryMismatch)
+        ));
+
+        file.sign(&key()).unwrap();
+        let mut edited_amount = file.clone();
+        edited_amount.transaction.amount = 251;
+        edited_amount.summary = summary(&edited_amount.transaction, &edited_amount.chain);
+        assert!(matches!(
+            edited_amount.validate(),
+            Err(OfflineError::InvalidSignature)
+        ));
+
+        let mut unsigned_with_signature = file;
+        unsigned_with_signature.kind = FileKind::Unsigned;
+        assert!(matches!(
+            unsigned_with_signature.validate(),
+            Err(OfflineError::AlreadySigned)
+        ));
+    }
+
+    #[test]
+    fn multisig_members_sign_separately_and_combine() {
+        let dir = tempfile::tempdir().unwrap();
+        let keys: Vec<SigningKey> = (1u8..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let config = Multisig::new(2, keys.iter().map(signing::address_of).collect()).unwrap();
+        let tx = signing::multisig_transaction(
+            TransactionType::Transfer,
+            config,
+            Address([0x22; 32]),
+            250,
+            0,
+        );
+        let chain = ChainInfo {
+            genesis_hash: Hash([0x99; 32]),
+            height: 12,
+        };
+        let unsigned = TransactionFile::unsigned(tx, chain);
+
+        let mut first = unsigned.clone();
+        first.sign(&keys[0]).unwrap();
+        assert_eq!(first.kind, FileKind::Unsigned);
+        assert!(
+            first.summary.contains("2 of 3, 1 signed"),
+            "{}",
+            first.summary
+        );
+        let path = dir.path().join("first.json");
+        first.write(&path).unwrap();
+        let first = TransactionFile::read(&path).unwrap();
+        assert!(matches!(
+            first.signed_transaction(),
+            Err(OfflineError::NotSigned)
+        ));
+
+        let mut second = unsigned.clone();
+        second.sign(&keys[2]).unwrap();
+        let mut combined = first.clone();
+        combined.combine(&second).unwrap();
+        assert_eq!(combined.kind, FileKind::Signed);
+        combined.validate().unwrap();
+        let tx = combined.signed_transaction().unwrap().clone();
+        assert!(tx.verify_signature());
+        assert_eq!(combined.hash, Some(signing::transaction_hash(&tx)));
+
+        // Re-combining is a no-op; other transactions are refused.
+        combined.combine(&first).unwrap();
+        assert_eq!(combined.transaction, tx);
+        let mut other = second.clone();
+        other.transaction.amount = 1;
+        assert!(matches!(
+            first.clone().combine(&other),
+            Err(OfflineError::Mismatch)
+        ));
+        assert!(matches!(
+            first.clone().combine(&unsigned_file()),
+            Err(OfflineError::NotMultisig)
+        ));
+
+        let mut forged = first;
+        forged.transaction.multisig.as_mut().unwrap().signatures[0].signature = [7; 64];
+        assert!(matches!(
+            forged.validate(),
+            Err(OfflineError::InvalidMultisig(
+                MultisigError::InvalidSignature(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn signing_with_the_wrong_key_is_refused() {
+        let mut file = unsigned_file();
+        let other = SigningKey::from_bytes(&[0xBB; 32]);
+        assert!(matches!(file.sign(&other), Err(OfflineError::Signing(_))));
+        assert_eq!(file.kind, FileKind::Unsigned);
+    }
+}
@@ -0,0 +1,250 @@
+//! Minimal JSON-RPC client for the node methods the wallet needs.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use mbongo_core::{Address, Block, Hash, Transaction};
+use parity_scale_codec::Encode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+/// Default node JSON-RPC endpoint.
+pub const DEFAULT_RPC_URL: &str = "http://127.0.0.1:9944/rpc";
+
+/// Errors returned by [`RpcClient`].
+#[derive(Debug, Error)]
+pub enum RpcClientError {
+    /// The node could not be reached or returned a non-JSON body.
+    #[error("cannot reach node at {url}: {source}")]
+    Transport {
+        /// Endpoint that was called.
+        url: String,
+        /// Underlying HTTP error.
+        #[source]
+        source: reqwest::Error,
+    },
+    /// The node answered with a JSON-RPC error.
+    #[error("node rejected {method}: {message} (code {code})")]
+    Rpc {
+        /// Method that failed.
+        method: String,
+        /// JSON-RPC error code.
+        code: i64,
+        /// Error message.
+        message: String,
+        /// Extra error data, if any.
+        data: Option<Value>,
+    },
+    /// The response did not have the expected shape.
+    #[error("unexpected response to {method}: {detail}")]
+    InvalidResponse {
+        /// Method that was called.
+        method: String,
+        /// What was wrong with the response.
+        detail: String,
+    },
+    /// [`RpcClient::wait_for_inclusion`] gave up.
+    #[error("transaction {0} was not included within the timeout")]
+    Timeout(Hash),
+}
+
+/// Where a transaction is in its lifecycle, as reported by
+/// `chain_get_transaction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Waiting in the mempool.
+    Pending,
+    /// Included in a committed block.
+    Included,
+    /// Neither pending nor included.
+    Unknown,
+}
+
+/// Result of [`RpcClient::transaction_status`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TransactionLookup {
+    /// Lifecycle status.
+    pub status: TransactionStatus,
+    /// Hash of the including block, once included.
+    pub block_hash: Option<Hash>,
+    /// Height of the including block, once included.
+    pub block_height: Option<u64>,
+}
+
+/// JSON-RPC client bound to one node endpoint.
+#[derive(Debug)]
+pub struct RpcClient {
+    url: String,
+    http: reqwest::Client,
+    next_id: AtomicU64,
+}
+
+impl RpcClient {
+    /// Creates a client for the endpoint at `url`, e.g. [`DEFAULT_RPC_URL`].
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            http: reqwest::Client::new(),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// The endpoint this client calls.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Calls `method` with named `params` and decodes the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RpcClientError`] if the request fails, the node returns an
+    /// error, or the result does not decode as `T`.
+    pub async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, RpcClientError> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+        });
+        let transport = |source| RpcClientError::Transport {
+            url: self.url.clone(),
+            source,
+        };
+        let response: Value = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(transport)?
+            .json()
+            .await
+            .map_err(transport)?;
+
+        let invalid = |detail: String| RpcClientError::InvalidResponse {
+            method: method.to_string(),
+            detail,
+        };
+        if let Some(error) = response.get("error") {
+            return Err(RpcClientError::Rpc {
+                method: method.to_string(),
+                code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                data: error.get("data").cloned(),
+            });
+        }
+        let result = response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| invalid("missing `result`".to_string()))?;
+        serde_json::from_value(result).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// Current chain height.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`].
+    pub async fn block_height(&self) -> Result<u64, RpcClientError> {
+        self.call("chain_get_block_height", json!({})).await
+    }
+
+    /// Hash of the genesis block, which identifies the network.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`].
+    pub async fn genesis_hash(&self) -> Result<Hash, RpcClientError> {
+        let genesis: Block = self.call("chain_get_block_by_height", json!({ "height": 0 })).await?;
+        Ok(Hash(*blake3::hash(&genesis.encode()).as_bytes()))
+    }
+
+    /// Committed balance of `address`.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`].
+    pub async fn balance(&self, address: Address) -> Result<u128, RpcClientError> {
+        let balance: String = self.call("state_get_balance", json!({ "address": address })).await?;
+        balance.parse().map_err(|_| RpcClientError::InvalidResponse {
+            method: "state_get_balance".to_string(),
+            detail: format!("balance `{balance}` is not a decimal integer"),
+        })
+    }
+
+    /// Nonce the next transaction from `address` must carry, counting its
+    /// pending transactions.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`].
+    pub async fn next_nonce(&self, address: Address) -> Result<u64, RpcClientError> {
+        self.call("state_get_next_nonce", json!({ "address": address })).await
+    }
+
+    /// Submits a signed transaction as raw SCALE bytes and returns its hash.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`]; validation failures such as a bad nonce or
+    /// insufficient balance come back as [`RpcClientError::Rpc`].
+    pub async fn submit_transaction(&self, tx: &Transaction) -> Result<Hash, RpcClientError> {
+        self.call(
+            "author_submit_raw_transaction",
+            json!({ "raw": tx.encode_hex() }),
+        )
+        .await
+    }
+
+    /// Looks up a transaction by hash.
+    ///
+    /// # Errors
+    ///
+    /// See [`RpcClient::call`].
+    pub async fn transaction_status(
+        &self,
+        hash: Hash,
+    ) -> Result<TransactionLookup, RpcClientError> {
+        self.call("chain_get_transaction", json!({ "hash": hash })).await
+    }
+
+    /// Polls every `interval` until `hash` is included, returning the
+    /// lookup of the including block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RpcClientError::Timeout`] if the transaction is not
+    /// included within `timeout`, plus the errors of [`RpcClient::call`].
+    pub async fn wait_for_inclusion(
+        &self,
+        hash: Hash,
+        timeout: Duration,
+        interval: Duration,
+    ) -> Result<TransactionLookup, RpcClientError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let lookup = self.transaction_status(hash).await?;
+            if lookup.status == TransactionStatus::Included {
+                return Ok(lookup);
+            }
+            if Instant::now() + interval > deadline {
+                return Err(RpcClientError::Timeout(hash));
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Transaction construction and signing.
+//!
+//! Transactions are signed over [`Transaction::signing_payload`] and
+//! identified by the blake3 hash of their full SCALE encoding, the same hash
+//! the node returns from `author_submit_transaction`.
+//!
+//! Transactions from a multisig account are built with
+//! [`multisig_transaction`] and collect one [`sign_multisig`] call per
+//! member until the threshold is met.
+
+use ed25519_dalek::{Signer, SigningKey};
+use mbongo_core::{
+    Address, Hash, Multisig, MultisigError, MultisigWitness, Transaction, TransactionType,
+};
+use parity_scale_codec::Encode;
+use thiserror::Error;
+
+/// Errors returned when signing a transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SigningError {
+    /// The transaction's sender is not the signing key's address.
+    #[error("transaction sender {sender} does not match signing key {signer}")]
+    WrongSigner {
+        /// Sender named in the transaction.
+        sender: Address,
+        /// Address of the signing key.
+        signer: Address,
+    },
+    /// A multisig signature was requested for a single-key transaction.
+    #[error("transaction is not from a multisig account")]
+    NotMultisig,
+    /// The key cannot sign for the multisig account.
+    #[error(transparent)]
+    Multisig(#[from] MultisigError),
+}
+
+/// Returns the address of `key`.
+#[must_use]
+pub fn address_of(key: &SigningKey) -> Address {
+    Address(key.verifying_key().to_bytes())
+}
+
+/// Builds an unsigned transaction from `sender`. The signature is all zeros
+/// until [`sign_transaction`] fills it in.
+#[must_use]
+pub fn unsigned_transaction(
+    tx_type: TransactionType,
+    sender: Address,
+    receiver: Address,
+    amount: u128,
+    nonce: u64,
+) -> Transaction {
+    Transaction {
+        tx_type,
+        sender,
+        receiver,
+        amount,
+        nonce,
+        signature: [0u8; 64],
+        multisig: None,
+        data: Vec::new(),
+    }
+}
+
+/// Builds an unsigned transaction from the multisig account `config`,
+/// carrying an empty witness for [`sign_multisig`] to fill in.
+#[must_use]
+pub fn multisig_transaction(
+    tx_type: TransactionType,
+    config: Multisig,
+    receiver: Address,
+    amount: u128,
+    nonce: u64,
+) -> Transaction {
+    Transaction {
+        sender: config.address(),
+        multisig: Some(MultisigWitness::new(config)),
+        ..unsigned_transaction(tx_type, Address::zero(), receiver, amount, nonce)
+    }
+}
+
+/// Adds `key`'s member signature to the multisig witness of `tx`.
+///
+/// # Errors
+///
+/// Returns [`SigningError::NotMultisig`] if `tx` has no witness, and
+/// [`SigningError::Multisig`] if `key` is not a member, has already signed,
+/// or the threshold is already met.
+pub fn sign_multisig(tx: &mut Transaction, key: &SigningKey) -> Result<(), SigningError> {
+    let signature = key.sign(&tx.signing_payload()).to_bytes();
+    tx.multisig
+        .as_mut()
+        .ok_or(SigningError::NotMultisig)?
+        .add_signature(address_of(key), signature)?;
+    Ok(())
+}
+
+/// Signs `tx` with `key`, replacing any existing signature.
+///
+/// # Errors
+///
+/// Returns [`SigningError::WrongSigner`] if `tx.sender` is not the address of
+/// `key`.
+pub fn sign_transaction(tx: &mut Transaction, key: &SigningKey) -> Result<(), SigningError> {
+    let signer = address_of(key);
+    if tx.sender != signer {
+        return Err(SigningError::WrongSigner {
+            sender: tx.sender,
+            signer,
+        });
+    }
+    tx.signature = key.sign(&tx.signing_payload()).to_bytes();
+    Ok(())
+}
+
+/// Returns the hash the node identifies `tx` by: blake3 over its SCALE
+/// encoding, signature included.
+#[must_use]
+pub fn transaction_hash(tx: &Transaction) -> Hash {
+    Hash(*blake3::hash(&tx.encode()).as_bytes())
+}
+
+/// Describes `tx` for a confirmation prompt, one field per line.
+#[must_use]
+pub fn describe(tx: &Transaction) -> String {
+    let (title, label, receiver) = match tx.tx_type {
+        TransactionType::Transfer => ("Transfer", "To:       ", tx.receiver),
+        TransactionType::Stake => ("Stake", "Validator:", tx.receiver),
+        TransactionType::ComputeTask => ("Compute task", "Provider: ", tx.receiver),
+        TransactionType::Deploy => ("Deploy contract", "Contract: ", tx.deployed_address()),
+        TransactionType::Call => ("Contract call", "Contract: ", tx.receiver),
+    };
+    let multisig = tx.multisig.as_ref().map_or_else(String::new, |witness| {
+        format!(
+            "\n  Multisig:  {} of {}, {} signed",
+            witness.config.threshold,
+            witness.config.members.len(),
+            witness.signatures.len()
+        )
+    });
+    format!(
+        "{title}\n  From:      {}\n  {label} {receiver}\n  Amount:    {}\n  Nonce:     {}{multisig}",
+        tx.sender, tx.amount, tx.nonce
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_transactions_verify() {
+        let key = SigningKey::from_bytes(&[0xAA; 32]);
+        let mut tx = unsigned_transaction(
+            TransactionType::Transfer,
+            address_of(&key),
+            Address([0x22; 32]),
+            100,
+            0,
+        );
+        assert!(!tx.verify_signature());
+
+        sign_transaction(&mut tx, &key).unwrap();
+        assert!(tx.verify_signature());
+
+        tx.amount += 1;
+        assert!(!tx.verify_signature());
+    }
+
+    #[test]
+    fn signing_for_another_sender_is_refused() {
+        let key = SigningKey::from_bytes(&[0xAA; 32]);
+        let mut tx = unsigned_transaction(
+            TransactionType::Stake,
+            Address([0x11; 32]),
+            Address([0x22; 32]),
+            5,
+            3,
+        );
+        assert_eq!(
+            sign_transaction(&mut tx, &key),
+            Err(SigningError::WrongSigner {
+                sender: Address([0x11; 32]),
+                signer: address_of(&key),
+            })
+        );
+        assert_eq!(tx.signature, [0u8; 64]);
+    }
+
+    #[test]
+    fn multisig_members_sign_until_the_threshold() {
+        let keys: Vec<SigningKey> = (1u8..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let config = Multisig::new(2, keys.iter().map(address_of).collect()).unwrap();
+        let mut tx = multisig_transaction(
+            TransactionType::Transfer,
+            config.clone(),
+            Address([0x22; 32]),
+            100,
+            0,
+        );
+        assert_eq!(tx.sender, config.address());
+        assert!(describe(&tx).ends_with("Multisig:  2 of 3, 0 signed"));
+
+        sign_multisig(&mut tx, &keys[0]).unwrap();
+        assert!(!tx.verify_signature());
+        sign_multisig(&mut tx, &keys[2]).unwrap();
+        assert!(tx.verify_signature());
+        assert_eq!(
+            sign_multisig(&mut tx, &keys[1]),
+            Err(SigningError::Multisig(MultisigError::ThresholdReached(2)))
+        );
+
+        let mut single = unsigned_transaction(
+            TransactionType::Transfer,
+            address_of(&keys[0]),
+            Address([0x22; 32]),
+            1,
+            0,
+        );
+        assert_eq!(
+            sign_multisig(&mut single, &keys[0]),
+            Err(SigningError::NotMultisig)
+        );
+    }
+
+    #[test]
+    fn hash_covers_the_signature() {
+        let key = SigningKey::from_bytes(&[0xAA; 32]);
+        let mut tx = unsigned_transaction(
+            TransactionType::Transfer,
+            address_of(&key),
+            Address([0x22; 32]),
+            100,
+            0,
+        );
+        let unsigned = transaction_hash(&tx);
+        sign_transaction(&mut tx, &key).unwrap();
+        assert_ne!(transaction_hash(&tx), unsigned);
+        assert_eq!(
+            transaction_hash(&tx).0,
+            *blake3::hash(&tx.encode()).as_bytes()
+        );
+    }
+}
@@ -0,0 +1,463 @@
+//! End-to-end tests of the wallet commands against a fake JSON-RPC node.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use clap::Parser;
+use ed25519_dalek::SigningKey;
+use mbongo_core::{Address, Block, BlockBody, BlockHeader, Hash, Transaction, TransactionType};
+use mbongo_wallet::cli::{self, Cli};
+use mbongo_wallet::hd::derive_signing_key;
+use mbongo_wallet::kdf::ScryptParams;
+use mbongo_wallet::offline::FileKind;
+use mbongo_wallet::signing::{address_of, transaction_hash};
+use mbongo_wallet::{
+    DerivationPath, Keystore, Mnemonic, RpcClient, RpcClientError, TransactionFile,
+};
+use parity_scale_codec::Encode;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+
+const PHRASE: &str = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+const PASSWORD: &str = "hunter2hunter2";
+
+/// Records submitted transactions and reports them as included at height 7.
+#[derive(Clone, Default)]
+struct FakeNode {
+    submitted: Arc<Mutex<Vec<Transaction>>>,
+}
+
+fn genesis() -> Block {
+    Block {
+        header: BlockHeader {
+            parent_hash: Hash([0; 32]),
+            state_root: Hash([0x5e; 32]),
+            transactions_root: Hash([0; 32]),
+            timestamp: 1_700_000_000,
+            height: 0,
+        },
+        body: BlockBody::default(),
+    }
+}
+
+async fn handle(State(node): State<FakeNode>, Json(request): Json<Value>) -> Json<Value> {
+    let params = &request["params"];
+    let result = match request["method"].as_str().unwrap() {
+        "state_get_balance" => json!("1000"),
+        "state_get_next_nonce" => json!(4),
+        "chain_get_block_height" => json!(12),
+        "chain_get_block_by_height" => {
+            assert_eq!(params["height"], 0);
+            json!(genesis())
+        }
+        "author_submit_raw_transaction" => {
+            let tx = Transaction::decode_hex(params["raw"].as_str().unwrap()).unwrap();
+            if !tx.verify_signature() {
+                return Json(json!({
+                    "jsonrpc": "2.0",
+                    "error": { "code": -32010, "message": "invalid signature" },
+                    "id": request["id"],
+                }));
+            }
+            let hash = transaction_hash(&tx);
+            node.submitted.lock().unwrap().push(tx);
+            json!(hash)
+        }
+        "chain_get_transaction" => json!({
+            "hash": params["hash"],
+            "status": "included",
+            "transaction": null,
+            "block_hash": format!("0x{}", "ab".repeat(32)),
+            "block_height": 7,
+        }),
+        method => panic!("unexpected method {method}"),
+    };
+    Json(json!({ "jsonrpc": "2.0", "result": result, "id": request["id"] }))
+}
+
+async fn spawn_node() -> (FakeNode, String) {
+    let node = FakeNode::default();
+    let app = Router::new().route("/rpc", post(handle)).with_state(node.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/rpc", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (node, url)
+}
+
+async fn wallet(dir: &Path, url: &str, args: &[&str]) -> anyhow::Result<()> {
+    let mut argv = vec![
+        "mbongo-wallet",
+        "--wallet-dir",
+        dir.to_str().unwrap(),
+        "--rpc-url",
+        url,
+    ];
+    argv.extend_from_slice(args);
+    cli::run(Cli::try_parse_from(argv)?).await
+}
+
+/// Stores `key` as `name` with a cheap scrypt cost, so tests that sign do not
+/// pay for the recommended 256 MiB derivation on every decryption.
+fn cheap_key(dir: &Path, name: &str, key: &SigningKey) -> Address {
+    Keystore::open(dir)
+        .unwrap()
+        .with_scrypt_params(ScryptParams { n: 16, r: 8, p: 1 })
+        .import(name, key, PASSWORD)
+        .unwrap()
+        .address
+}
+
+#[tokio::test]
+async fn restore_derives_the_default_path() {
+    let (_node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let keys = tmp.path().join("keys");
+    let phrase_file = tmp.path().join("phrase.txt");
+    let password_file = tmp.path().join("password.txt");
+    std::fs::write(&phrase_file, format!("{PHRASE}\n")).unwrap();
+    std::fs::write(&password_file, format!("{PASSWORD}\n")).unwrap();
+
+    wallet(
+        &keys,
+        &url,
+        &[
+            "restore",
+            "--name",
+            "alice",
+            "--mnemonic-file",
+            phrase_file.to_str().unwrap(),
+            "--password-file",
+            password_file.to_str().unwrap(),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let seed = Mnemonic::parse(PHRASE).unwrap().to_seed("");
+    let expected = derive_signing_key(seed.as_slice(), &DerivationPath::default());
+    let keystore = Keystore::open(&keys).unwrap();
+    assert_eq!(
+        keystore.load("alice").unwrap().address,
+        address_of(&expected)
+    );
+    assert!(wallet(&keys, &url, &["address", "--all"]).await.is_ok());
+}
+
+#[tokio::test]
+async fn transfer_and_stake_are_signed_and_submitted() {
+    let (node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let keys = tmp.path().join("keys");
+    let password_file = tmp.path().join("password.txt");
+    std::fs::write(&password_file, PASSWORD).unwrap();
+    let password = password_file.to_str().unwrap();
+    let alice = cheap_key(&keys, "alice", &SigningKey::from_bytes(&[0xAA; 32]));
+
+    wallet(&keys, &url, &["balance", "--name", "alice"]).await.unwrap();
+
+    let bob = format!("0x{}", "22".repeat(32));
+    wallet(
+        &keys,
+        &url,
+        &[
+            "transfer",
+            "--from",
+            "alice",
+            "--to",
+            &bob,
+            "--amount",
+            "250",
+            "--password-file",
+            password,
+            "--yes",
+            "--wait",
+        ],
+    )
+    .await
+    .unwrap();
+    wallet(
+        &keys,
+        &url,
+        &[
+            "stake",
+            "--from",
+            "alice",
+            "--validator",
+            &bob,
+            "--amount",
+            "100",
+            "--nonce",
+            "9",
+            "--password-file",
+            password,
+            "--yes",
+        ],
+    )
+    .await
+    .unwrap();
+
+    let submitted = node.submitted.lock().unwrap().clone();
+    assert_eq!(submitted.len(), 2);
+    let (transfer, stake) = (&submitted[0], &submitted[1]);
+    assert_eq!(transfer.tx_type, TransactionType::Transfer);
+    assert_eq!(
+        (
+            transfer.sender,
+            transfer.receiver,
+            transfer.amount,
+            transfer.nonce
+        ),
+        (alice, Address([0x22; 32]), 250, 4)
+    );
+    assert_eq!(stake.tx_type, TransactionType::Stake);
+    assert_eq!((stake.amount, stake.nonce), (100, 9));
+}
+
+#[tokio::test]
+async fn wrong_password_submits_nothing() {
+    let (node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let keys = tmp.path().join("keys");
+    let wrong_file = tmp.path().join("wrong.txt");
+    std::fs::write(&wrong_file, "not-the-password").unwrap();
+    cheap_key(&keys, "default", &SigningKey::from_bytes(&[0xAA; 32]));
+
+    let err = wallet(
+        &keys,
+        &url,
+        &[
+            "transfer",
+            "--to",
+            &format!("0x{}", "22".repeat(32)),
+            "--amount",
+            "1",
+            "--password-file",
+            wrong_file.to_str().unwrap(),
+            "--yes",
+        ],
+    )
+    .await
+    .unwrap_err();
+    assert!(err.to_string().contains("wrong password"), "{err}");
+    assert!(node.submitted.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn rpc_errors_are_reported() {
+    let (_node, url) = spawn_node().await;
+    let client = RpcClient::new(url);
+    let unsigned = Transaction {
+        tx_type: TransactionType::Transfer,
+        sender: Address([1; 32]),
+        receiver: Address([2; 32]),
+        amount: 1,
+        nonce: 0,
+        signature: [0; 64],
+        multisig: None,
+        data: Vec::new(),
+    };
+    let err = client.submit_transaction(&unsigned).await.unwrap_err();
+    assert!(
+        matches!(err, RpcClientError::Rpc { code: -32010, .. }),
+        "{err:?}"
+    );
+    assert_eq!(client.balance(Address([1; 32])).await.unwrap(), 1000);
+}
+
+#[tokio::test]
+async fn offline_build_sign_and_broadcast() {
+    let (node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let keys = tmp.path().join("keys");
+    let password_file = tmp.path().join("password.txt");
+    std::fs::write(&password_file, PASSWORD).unwrap();
+    let unsigned = tmp.path().join("tx.json");
+    let signed = tmp.path().join("tx.signed.json");
+    let alice = cheap_key(&keys, "cold", &SigningKey::from_bytes(&[0xAA; 32]));
+
+    wallet(
+        &keys,
+        &url,
+        &[
+            "build",
+            "--type",
+            "stake",
+            "--sender",
+            &alice.to_string(),
+            "--to",
+            &format!("0x{}", "22".repeat(32)),
+            "--amount",
+            "500",
+            "--out",
+            unsigned.to_str().unwrap(),
+        ],
+    )
+    .await
+    .unwrap();
+    let file = TransactionFile::read(&unsigned).unwrap();
+    assert_eq!(file.kind, FileKind::Unsigned);
+    assert_eq!(file.transaction.nonce, 4);
+    assert_eq!(file.chain.height, 12);
+    assert_eq!(
+        file.chain.genesis_hash,
+        Hash(*blake3::hash(&genesis().encode()).as_bytes())
+    );
+
+    // Signing must work with no node at all.
+    wallet(
+        &keys,
+        "http://127.0.0.1:1/rpc",
+        &[
+            "sign",
+            unsigned.to_str().unwrap(),
+            "--from",
+            "cold",
+            "--out",
+            signed.to_str().unwrap(),
+            "--password-file",
+            password_file.to_str().unwrap(),
+            "--yes",
+        ],
+    )
+    .await
+    .unwrap();
+
+    let err = wallet(&keys, &url, &["broadcast", unsigned.to_str().unwrap()])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not signed"), "{err}");
+
+    wallet(
+        &keys,
+        &url,
+        &["broadcast", signed.to_str().unwrap(), "--wait"],
+    )
+    .await
+    .unwrap();
+    let submitted = node.submitted.lock().unwrap().clone();
+    assert_eq!(submitted.len(), 1);
+    assert_eq!(submitted[0].tx_type, TransactionType::Stake);
+    assert_eq!((submitted[0].sender, submitted[0].amount), (alice, 500));
+    assert_eq!(
+        TransactionFile::read(&signed).unwrap().hash,
+        Some(transaction_hash(&submitted[0]))
+    );
+}
+
+#[tokio::test]
+async fn broadcast_refuses_another_network() {
+    let (node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let key = SigningKey::from_bytes(&[0xAA; 32]);
+    let tx = mbongo_wallet::signing::unsigned_transaction(
+        TransactionType::Transfer,
+        address_of(&key),
+        Address([0x22; 32]),
+        1,
+        0,
+    );
+    let chain = mbongo_wallet::ChainInfo {
+        genesis_hash: Hash([0x77; 32]),
+        height: 3,
+    };
+    let mut file = TransactionFile::unsigned(tx, chain);
+    file.sign(&key).unwrap();
+    let path = tmp.path().join("tx.json");
+    file.write(&path).unwrap();
+
+    let err = wallet(tmp.path(), &url, &["broadcast", path.to_str().unwrap()])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("genesis"), "{err}");
+    assert!(node.submitted.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn multisig_members_sign_separately_then_combine_and_broadcast() {
+    let (node, url) = spawn_node().await;
+    let tmp = tempfile::tempdir().unwrap();
+    let keys = tmp.path().join("keys");
+    let password_file = tmp.path().join("password.txt");
+    std::fs::write(&password_file, PASSWORD).unwrap();
+    let password = password_file.to_str().unwrap();
+    let path = |name: &str| tmp.path().join(name).to_str().unwrap().to_string();
+
+    let members: Vec<Address> = (1u8..=3)
+        .map(|i| cheap_key(&keys, &format!("m{i}"), &SigningKey::from_bytes(&[i; 32])))
+        .collect();
+    cheap_key(&keys, "outsider", &SigningKey::from_bytes(&[9; 32]));
+    let member_args: Vec<String> =
+        members.iter().flat_map(|m| ["--member".to_string(), m.to_string()]).collect();
+    let member_args: Vec<&str> = member_args.iter().map(String::as_str).collect();
+
+    let mut args = vec!["multisig-address", "--threshold", "2"];
+    args.extend(&member_args);
+    wallet(&keys, &url, &args).await.unwrap();
+
+    let bob = format!("0x{}", "22".repeat(32));
+    let unsigned = path("tx.json");
+    let mut args = vec!["build", "--threshold", "2"];
+    args.extend(&member_args);
+    args.extend(["--to", &bob, "--amount", "75", "--out", &unsigned]);
+    wallet(&keys, &url, &args).await.unwrap();
+
+    let sign = |from: &'static str, out: String| {
+        let (keys, unsigned) = (keys.clone(), unsigned.clone());
+        async move {
+            wallet(
+                &keys,
+                "http://127.0.0.1:1/rpc",
+                &[
+                    "sign",
+                    &unsigned,
+                    "--from",
+                    from,
+                    "--out",
+                    &out,
+                    "--password-file",
+                    password,
+                    "--yes",
+                ],
+            )
+            .await
+        }
+    };
+    let err = sign("outsider", path("x.json")).await.unwrap_err();
+    assert!(err.to_string().contains("not a member"), "{err}");
+    sign("m1", path("a.json")).await.unwrap();
+    sign("m3", path("b.json")).await.unwrap();
+
+    let err = wallet(&keys, &url, &["broadcast", &path("a.json")]).await.unwrap_err();
+    assert!(err.to_string().contains("not signed"), "{err}");
+
+    let combined = path("combined.json");
+    wallet(
+        &keys,
+        &url,
+        &[
+            "combine",
+            &path("a.json"),
+            &path("b.json"),
+            "--out",
+            &combined,
+        ],
+    )
+    .await
+    .unwrap();
+    wallet(&keys, &url, &["broadcast", &combined]).await.unwrap();
+
+    let submitted = node.submitted.lock().unwrap().clone();
+    assert_eq!(submitted.len(), 1);
+    let tx = &submitted[0];
+    let witness = tx.multisig.as_ref().unwrap();
+    assert_eq!(tx.sender, witness.config.address());
+    assert_eq!((tx.amount, tx.nonce), (75, 4));
+    let mut signers: Vec<Address> = witness.signatures.iter().map(|s| s.signer).collect();
+    signers.sort_by_key(|a| a.0);
+    let mut expected = vec![members[0], members[2]];
+    expected.sort_by_key(|a| a.0);
+    assert_eq!(signers, expected);
+}